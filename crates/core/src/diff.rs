@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+
+/// Edit distance past which `diff_lines` stops looking for the shortest edit script
+///
+/// Beyond it the changed middle of the texts is reported as a single hunk, which keeps time and
+/// memory bounded when a document is mostly rewritten.
+pub const MAX_DIFF_EDITS: usize = 2_000;
+
+/// A run of changed lines between two texts
+///
+/// `old_start`/`new_start` are 1-based line numbers where the hunk begins in each text. A hunk that
+/// only adds lines has `old_count == 0`, and `old_start` is the old line the additions go before
+/// (one past the last line when appending); likewise for `new_start` when lines are only removed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+/// Diffs `old` against `new` line by line and returns the changed runs in order
+///
+/// CRLF and lone CR line endings are treated as LF, and a missing final newline is not a change, so
+/// texts that differ only in those ways give no hunks. Lines shared at the start and end are skipped
+/// before the Myers diff runs on the rest.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffHunk> {
+    let old = normalize_line_endings(old);
+    let new = normalize_line_endings(new);
+    if old == new {
+        return Vec::new();
+    }
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+    let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+    let mut matches = shortest_edit_matches(old_middle, new_middle).unwrap_or_default();
+    matches.push((old_middle.len(), new_middle.len()));
+
+    let mut hunks = Vec::new();
+    let (mut old_index, mut new_index) = (0, 0);
+    for (old_match, new_match) in matches {
+        if old_match > old_index || new_match > new_index {
+            hunks.push(DiffHunk {
+                old_start: prefix + old_index + 1,
+                old_count: old_match - old_index,
+                new_start: prefix + new_index + 1,
+                new_count: new_match - new_index,
+                removed: old_middle[old_index..old_match]
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect(),
+                added: new_middle[new_index..new_match]
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect(),
+            });
+        }
+        (old_index, new_index) = (old_match + 1, new_match + 1);
+    }
+
+    hunks
+}
+
+fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Pairs of equal lines (old index, new index) on a shortest edit script, or `None` past [`MAX_DIFF_EDITS`]
+///
+/// Myers' O((N+M)D) algorithm. Each round only keeps the diagonals it can reach, so the saved
+/// history for backtracking is O(D²) rather than O((N+M)D).
+fn shortest_edit_matches(old: &[&str], new: &[&str]) -> Option<Vec<(usize, usize)>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max_edits = (old.len() + new.len()).min(MAX_DIFF_EDITS) as isize;
+    let offset = max_edits + 1;
+    let mut furthest = vec![0isize; 2 * offset as usize + 1];
+    let mut history: Vec<Vec<isize>> = Vec::new();
+
+    let at = |furthest: &[isize], k: isize| furthest[(k + offset) as usize];
+    let follows_down =
+        |furthest: &[isize], d: isize, k: isize| k == -d || (k != d && at(furthest, k - 1) < at(furthest, k + 1));
+
+    let mut edits = None;
+    'search: for d in 0..=max_edits {
+        history.push(furthest[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if follows_down(&furthest, d, k) { at(&furthest, k + 1) } else { at(&furthest, k - 1) + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[(k + offset) as usize] = x;
+            if x >= n && y >= m {
+                edits = Some(d);
+                break 'search;
+            }
+        }
+    }
+    edits?;
+
+    let mut matches = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, window) in history.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let saved = |k: isize| window[(k + d) as usize];
+        let down = k == -d || (k != d && saved(k - 1) < saved(k + 1));
+        let previous_k = if down { k + 1 } else { k - 1 };
+        let previous_x = if d == 0 { 0 } else { saved(previous_k) };
+        let previous_y = if d == 0 { 0 } else { previous_x - previous_k };
+
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+        (x, y) = (previous_x, previous_y);
+    }
+
+    matches.reverse();
+    Some(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_and_line_ending_only_changes() {
+        assert!(diff_lines("a\nb\n", "a\nb\n").is_empty());
+        assert!(diff_lines("a\r\nb\r\n", "a\nb").is_empty());
+        assert!(diff_lines("", "").is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_and_changed_lines() {
+        let old = "one\ntwo\nthree\nfour\nfive\n";
+        let new = "zero\none\nthree\nFOUR\nfive\nsix\n";
+
+        let hunks = diff_lines(old, new);
+        assert_eq!(
+            hunks
+                .iter()
+                .map(|hunk| (hunk.old_start, hunk.old_count, hunk.new_start, hunk.new_count))
+                .collect::<Vec<_>>(),
+            vec![(1, 0, 1, 1), (2, 1, 3, 0), (4, 1, 4, 1), (6, 0, 6, 1)]
+        );
+        assert_eq!(hunks[0].added, vec!["zero"]);
+        assert_eq!(hunks[1].removed, vec!["two"]);
+        assert_eq!(hunks[2].removed, vec!["four"]);
+        assert_eq!(hunks[2].added, vec!["FOUR"]);
+        assert_eq!(hunks[3].added, vec!["six"]);
+    }
+
+    #[test]
+    fn test_rewrite_past_edit_limit_is_one_hunk() {
+        let old: String = (0..MAX_DIFF_EDITS).map(|line| format!("old {}\n", line)).collect();
+        let new: String = (0..MAX_DIFF_EDITS).map(|line| format!("new {}\n", line)).collect();
+        let text = |body: &str| format!("header\n{}footer\n", body);
+
+        let hunks = diff_lines(&text(&old), &text(&new));
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].old_start, hunks[0].old_count), (2, MAX_DIFF_EDITS));
+        assert_eq!((hunks[0].new_start, hunks[0].new_count), (2, MAX_DIFF_EDITS));
+    }
+}
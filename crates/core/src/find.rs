@@ -0,0 +1,303 @@
+use super::{AppError, ErrorCode};
+use regex::{Match, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on the matches a single find returns, whatever `max_matches` asks for
+pub const MAX_FIND_MATCHES: usize = 10_000;
+
+/// How `find_in_text` interprets its query
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FindOptions {
+    pub case_sensitive: bool,
+    /// Only match when the neighbouring characters are not letters, digits, or `_`
+    pub whole_word: bool,
+    /// Treat the query as a regular expression instead of literal text
+    pub regex: bool,
+    /// Stop after this many matches (clamped to 1..=[`MAX_FIND_MATCHES`])
+    pub max_matches: usize,
+}
+
+impl Default for FindOptions {
+    fn default() -> Self {
+        Self { case_sensitive: false, whole_word: false, regex: false, max_matches: 1_000 }
+    }
+}
+
+/// A match found in a document
+///
+/// `start`/`end` are byte offsets; `line` and `column` are 1-based, with columns counted in characters.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FindMatch {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// How `replace_in_text` finds matches, and whether it only previews the replacements
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReplaceOptions {
+    #[serde(flatten)]
+    pub find: FindOptions,
+    pub preview_only: bool,
+}
+
+/// A replacement made (or, in a preview, proposed) by `replace_in_text`
+///
+/// `start`/`end` are byte offsets into the original text; `line` and `column` are 1-based.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReplacedRange {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub replacement: String,
+}
+
+/// Outcome of `replace_in_text`; a preview returns the original text unchanged
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReplaceResult {
+    pub text: String,
+    pub replacements: Vec<ReplacedRange>,
+}
+
+/// Compiles the query into a regex, or `None` for an empty query
+fn compile_query(query: &str, options: &FindOptions) -> Result<Option<Regex>, AppError> {
+    if query.is_empty() {
+        return Ok(None);
+    }
+
+    let pattern = if options.regex { query.to_string() } else { regex::escape(query) };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(true)
+        .build()
+        .map(Some)
+        .map_err(|e| AppError::new(ErrorCode::Parse, format!("Invalid search pattern: {}", e)))
+}
+
+fn accepts_match(text: &str, found: &Match<'_>, options: &FindOptions) -> bool {
+    !found.is_empty() && (!options.whole_word || is_whole_word(text, found.start(), found.end()))
+}
+
+/// Finds the non-overlapping matches of `query` in `text`, in order
+///
+/// An empty query finds nothing, and empty regex matches are skipped. An invalid regex is a
+/// `Parse` error.
+pub fn find_in_text(text: &str, query: &str, options: &FindOptions) -> Result<Vec<FindMatch>, AppError> {
+    let Some(regex) = compile_query(query, options)? else {
+        return Ok(Vec::new());
+    };
+
+    let limit = options.max_matches.clamp(1, MAX_FIND_MATCHES);
+    let mut matches = Vec::new();
+    let mut position = Position::default();
+
+    for found in regex.find_iter(text) {
+        if !accepts_match(text, &found, options) {
+            continue;
+        }
+
+        position.advance(text, found.start());
+        matches.push(FindMatch {
+            start: found.start(),
+            end: found.end(),
+            line: position.line,
+            column: position.column,
+        });
+
+        if matches.len() == limit {
+            break;
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Replaces every match of `query` in `text`, leaving everything between matches byte-for-byte intact
+///
+/// Matches are found as in [`find_in_text`] and never overlap; `max_matches` does not apply, so a
+/// replace covers the whole document. In regex mode the replacement may use `$1`/`${name}` capture
+/// references (`$$` for a literal `$`); otherwise it is inserted as-is. With `preview_only` the
+/// ranges and their replacements are computed but the returned text is the original.
+pub fn replace_in_text(
+    text: &str, query: &str, replacement: &str, options: &ReplaceOptions,
+) -> Result<ReplaceResult, AppError> {
+    let ReplaceOptions { find: options, preview_only } = options;
+    let Some(regex) = compile_query(query, options)? else {
+        return Ok(ReplaceResult { text: text.to_string(), replacements: Vec::new() });
+    };
+
+    let mut output = String::with_capacity(text.len());
+    let mut replacements = Vec::new();
+    let mut position = Position::default();
+    let mut copied = 0;
+
+    for captures in regex.captures_iter(text) {
+        let Some(found) = captures.get(0).filter(|found| accepts_match(text, found, options)) else {
+            continue;
+        };
+
+        let mut replaced = String::new();
+        if options.regex {
+            captures.expand(replacement, &mut replaced);
+        } else {
+            replaced.push_str(replacement);
+        }
+
+        output.push_str(&text[copied..found.start()]);
+        output.push_str(&replaced);
+        copied = found.end();
+
+        position.advance(text, found.start());
+        replacements.push(ReplacedRange {
+            start: found.start(),
+            end: found.end(),
+            line: position.line,
+            column: position.column,
+            replacement: replaced,
+        });
+    }
+    output.push_str(&text[copied..]);
+
+    let text = if *preview_only { text.to_string() } else { output };
+    Ok(ReplaceResult { text, replacements })
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    !text[..start].chars().next_back().is_some_and(is_word_char)
+        && !text[end..].chars().next().is_some_and(is_word_char)
+}
+
+/// Line and column of a byte offset, advanced incrementally as matches move forward
+struct Position {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self { offset: 0, line: 1, column: 1 }
+    }
+}
+
+impl Position {
+    fn advance(&mut self, text: &str, offset: usize) {
+        for ch in text[self.offset..offset].chars() {
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.offset = offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(matches: &[FindMatch]) -> Vec<(usize, usize)> {
+        matches.iter().map(|found| (found.line, found.column)).collect()
+    }
+
+    #[test]
+    fn test_literal_find_reports_lines_and_char_columns() {
+        let text = "Café au lait\nnaïve café (café)\n";
+        let options = FindOptions { case_sensitive: true, ..FindOptions::default() };
+        let matches = find_in_text(text, "café", &options).unwrap();
+
+        assert_eq!(spans(&matches), vec![(2, 7), (2, 13)]);
+        assert_eq!(&text[matches[0].start..matches[0].end], "café");
+        assert!(find_in_text(text, "", &options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_case_insensitive_and_whole_word_find() {
+        let text = "Cat catalog CAT\nbobcat cat_nap cat.";
+        let matches = find_in_text(text, "cat", &FindOptions::default()).unwrap();
+        assert_eq!(matches.len(), 6);
+
+        let whole_word = FindOptions { whole_word: true, ..FindOptions::default() };
+        let matches = find_in_text(text, "cat", &whole_word).unwrap();
+        assert_eq!(spans(&matches), vec![(1, 1), (1, 13), (2, 16)]);
+
+        let capped = FindOptions { max_matches: 2, ..FindOptions::default() };
+        assert_eq!(find_in_text(text, "cat", &capped).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_regex_find() {
+        let text = "v1.2 and v10.0\n- [ ] todo\n";
+        let options = FindOptions { regex: true, ..FindOptions::default() };
+
+        let matches = find_in_text(text, r"v\d+\.\d+", &options).unwrap();
+        assert_eq!(spans(&matches), vec![(1, 1), (1, 10)]);
+
+        let matches = find_in_text(text, r"^- \[ \]", &options).unwrap();
+        assert_eq!(spans(&matches), vec![(2, 1)]);
+
+        assert!(find_in_text(text, "x*", &options).unwrap().is_empty());
+        let error = find_in_text(text, "(unclosed", &options).unwrap_err();
+        assert_eq!(error.code, ErrorCode::Parse);
+    }
+
+    #[test]
+    fn test_literal_replace_preserves_surrounding_text() {
+        let text = "Teh cat.\r\nThe  dog $1 teh\n";
+        let options =
+            ReplaceOptions { find: FindOptions { whole_word: true, ..FindOptions::default() }, preview_only: false };
+        let result = replace_in_text(text, "teh", "$1", &options).unwrap();
+
+        assert_eq!(result.text, "$1 cat.\r\nThe  dog $1 $1\n");
+        assert_eq!(
+            result
+                .replacements
+                .iter()
+                .map(|range| (range.start, range.end, range.line, range.column))
+                .collect::<Vec<_>>(),
+            vec![(0, 3, 1, 1), (22, 25, 2, 13)]
+        );
+    }
+
+    #[test]
+    fn test_regex_replace_expands_captures_without_overlap() {
+        let text = "2024-06-15 and 2023-01-02; aaaa";
+        let find = FindOptions { regex: true, case_sensitive: true, ..FindOptions::default() };
+        let options = ReplaceOptions { find, preview_only: false };
+
+        let result = replace_in_text(text, r"(\d{4})-(\d{2})-(\d{2})", "$3/$2/${1}", &options).unwrap();
+        assert_eq!(result.text, "15/06/2024 and 02/01/2023; aaaa");
+        assert_eq!(result.replacements[1].replacement, "02/01/2023");
+
+        let result = replace_in_text(text, "aa", "b", &options).unwrap();
+        assert!(result.text.ends_with("; bb"));
+        assert_eq!(result.replacements.len(), 2);
+    }
+
+    #[test]
+    fn test_replace_preview_leaves_text_unchanged() {
+        let text = "one two one";
+        let options = ReplaceOptions { preview_only: true, ..ReplaceOptions::default() };
+        let result = replace_in_text(text, "ONE", "1", &options).unwrap();
+
+        assert_eq!(result.text, text);
+        assert_eq!(
+            result
+                .replacements
+                .iter()
+                .map(|range| (range.start, range.end, range.replacement.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(0, 3, "1"), (8, 11, "1")]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Common English words `word_frequencies` leaves out unless [`FreqOptions::stopwords`] replaces them
+pub static DEFAULT_STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "because", "been", "but", "by",
+    "can", "could", "did", "do", "does", "for", "from", "had", "has", "have", "he", "her", "him", "his", "how", "i",
+    "if", "in", "into", "is", "it", "its", "just", "me", "more", "my", "no", "not", "of", "on", "one", "or", "our",
+    "out", "she", "so", "some", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this", "to",
+    "up", "us", "was", "we", "were", "what", "when", "which", "who", "will", "with", "would", "you", "your",
+];
+
+/// How `word_frequencies` filters the words it counts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FreqOptions {
+    /// Shortest word counted, in characters
+    pub min_length: usize,
+    /// Words to leave out, matched case-insensitively; `None` uses [`DEFAULT_STOPWORDS`]
+    pub stopwords: Option<Vec<String>>,
+    /// Return at most this many words; 0 returns them all
+    pub limit: usize,
+}
+
+impl Default for FreqOptions {
+    fn default() -> Self {
+        Self { min_length: 3, stopwords: None, limit: 0 }
+    }
+}
+
+/// Counts how often each word occurs, most frequent first
+///
+/// Words are split on Unicode word boundaries (so `don't` and `naïve` stay whole) and lowercased.
+/// Tokens without a letter, such as numbers, are skipped. Words with the same count are ordered
+/// alphabetically, so the result does not depend on hashing.
+pub fn word_frequencies(text: &str, options: &FreqOptions) -> Vec<(String, usize)> {
+    word_frequencies_outside(text, &[], options)
+}
+
+/// Like [`word_frequencies`], but ignores words that start inside any of the `skip` byte ranges
+///
+/// Used to leave code blocks out of the counts.
+pub fn word_frequencies_outside(text: &str, skip: &[Range<usize>], options: &FreqOptions) -> Vec<(String, usize)> {
+    let stopwords: HashSet<String> = match &options.stopwords {
+        Some(words) => words.iter().map(|word| word.trim().to_lowercase()).collect(),
+        None => DEFAULT_STOPWORDS.iter().map(|word| word.to_string()).collect(),
+    };
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (start, word) in text.unicode_word_indices() {
+        if skip.iter().any(|range| range.contains(&start)) || !word.chars().any(char::is_alphabetic) {
+            continue;
+        }
+
+        let word = word.to_lowercase();
+        if word.chars().count() < options.min_length || stopwords.contains(&word) {
+            continue;
+        }
+        *counts.entry(word).or_insert(0) += 1;
+    }
+
+    let mut frequencies: Vec<(String, usize)> = counts.into_iter().collect();
+    frequencies.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if options.limit > 0 {
+        frequencies.truncate(options.limit);
+    }
+    frequencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_word_ranks_first() {
+        let text = "The garden was quiet. Really quiet, the kind of Quiet that makes a garden feel \
+                    enormous. Réa walked the garden's edge; 42 birds didn't sing.";
+
+        let frequencies = word_frequencies(text, &FreqOptions::default());
+        assert_eq!(frequencies[0], ("quiet".to_string(), 3));
+        assert_eq!(frequencies[1], ("garden".to_string(), 2));
+        assert_eq!(frequencies[2], ("birds".to_string(), 1));
+        assert!(frequencies.iter().any(|(word, _)| word == "didn't"));
+        assert!(frequencies.iter().any(|(word, _)| word == "réa"));
+        assert!(
+            !frequencies
+                .iter()
+                .any(|(word, _)| word == "the" || word == "42" || word == "of")
+        );
+    }
+
+    #[test]
+    fn test_options_and_skip_ranges() {
+        let text = "Alpha beta alpha. ```let alpha = beta;```";
+        let code = text.find("```").unwrap()..text.len();
+        let options = FreqOptions { min_length: 5, stopwords: Some(vec!["Beta".to_string()]), limit: 0 };
+
+        assert_eq!(word_frequencies(text, &options), vec![("alpha".to_string(), 3)]);
+        assert_eq!(
+            word_frequencies_outside(text, &[code], &FreqOptions { min_length: 1, ..FreqOptions::default() }),
+            vec![("alpha".to_string(), 2), ("beta".to_string(), 1)]
+        );
+        assert_eq!(
+            word_frequencies(text, &FreqOptions { limit: 1, ..FreqOptions::default() }),
+            vec![("alpha".to_string(), 3)]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use ammonia::Builder;
+use html2md::common::get_tag_attr;
+use html2md::{Handle, StructuredPrinter, TagHandler, TagHandlerFactory};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+/// URL schemes allowed in links and images; relative URLs are always allowed
+pub const SAFE_URL_SCHEMES: &[&str] = &["http", "https", "mailto", "tel"];
+
+/// Elements html2md has a Markdown form for; anything else is unwrapped (its tags dropped, its text kept)
+const CONVERTED_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "del",
+    "div",
+    "em",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "i",
+    "img",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "section",
+    "strong",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// Elements removed together with everything inside them
+const DROPPED_TAGS: &[&str] = &[
+    "head", "script", "style", "iframe", "object", "embed", "template", "noscript", "textarea", "title", "svg", "math",
+    "select", "button", "form", "canvas", "audio", "video",
+];
+
+/// Attributes html2md reads; layout attributes like `width` are dropped so images stay Markdown
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[("a", &["href"]), ("img", &["src", "alt", "title"])];
+
+/// Strips pasted HTML down to what converts cleanly before html2md sees it, so no raw HTML or
+/// unsafe URL can pass through to the Markdown
+static PASTE_CLEANER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::empty();
+    builder
+        .tags(CONVERTED_TAGS.iter().copied().collect())
+        .clean_content_tags(DROPPED_TAGS.iter().copied().collect())
+        .tag_attributes(
+            TAG_ATTRIBUTES
+                .iter()
+                .map(|(tag, attributes)| (*tag, attributes.iter().copied().collect::<HashSet<_>>()))
+                .collect::<HashMap<_, _>>(),
+        )
+        .attribute_filter(safe_url_attribute)
+        .link_rel(None);
+    builder
+});
+
+/// Whether `url` is relative or uses one of [`SAFE_URL_SCHEMES`]
+///
+/// Whitespace and control characters are ignored and the scheme is matched case-insensitively, so
+/// `" JavaScript:"` and `"java\tscript:"` are both rejected.
+pub fn is_safe_url(url: &str) -> bool {
+    let compact: String = url
+        .chars()
+        .filter(|ch| !ch.is_whitespace() && !ch.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    match compact.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => SAFE_URL_SCHEMES.contains(&scheme),
+        _ => true,
+    }
+}
+
+/// Ammonia attribute filter that drops URL attributes failing [`is_safe_url`]
+pub fn safe_url_attribute<'u>(_element: &str, attribute: &str, value: &'u str) -> Option<Cow<'u, str>> {
+    match attribute {
+        "href" | "src" | "cite" if !is_safe_url(value) => None,
+        _ => Some(Cow::Borrowed(value)),
+    }
+}
+
+/// Converts HTML (typically rich text pasted from a browser) to Markdown
+///
+/// The HTML is cleaned with ammonia and converted with html2md. Headings, paragraphs, emphasis,
+/// links, images, code, block quotes, lists, and tables are converted; other elements keep only
+/// their text. Scripts, styles, and the document head are dropped with their content, and links
+/// and images with `javascript:` or other unlisted schemes lose their URL (links keep their text).
+pub fn html_to_markdown(html: &str) -> String {
+    let cleaned = PASTE_CLEANER.clean(html).to_string();
+    let handlers: HashMap<String, Box<dyn TagHandlerFactory>> = HashMap::from([
+        (
+            "a".to_string(),
+            Box::new(LinkHandlerFactory) as Box<dyn TagHandlerFactory>,
+        ),
+        ("img".to_string(), Box::new(ImageHandlerFactory)),
+    ]);
+    let mut output = html2md::parse_html_custom(&cleaned, &handlers);
+    if output.trim().is_empty() {
+        return String::new();
+    }
+
+    output.push('\n');
+    output
+}
+
+struct LinkHandlerFactory;
+
+impl TagHandlerFactory for LinkHandlerFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(LinkHandler::default())
+    }
+}
+
+/// Writes `[text](href)`, or just the text when the cleaner removed the `href`
+#[derive(Default)]
+struct LinkHandler {
+    start: usize,
+    href: Option<String>,
+}
+
+impl TagHandler for LinkHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        self.start = printer.data.len();
+        self.href = get_tag_attr(tag, "href").filter(|href| !href.trim().is_empty());
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        if let Some(href) = &self.href {
+            printer.insert_str(self.start, "[");
+            printer.append_str(&format!("]({})", href));
+        }
+    }
+}
+
+struct ImageHandlerFactory;
+
+impl TagHandlerFactory for ImageHandlerFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(ImageHandler)
+    }
+}
+
+/// Writes `![alt](src "title")`, or nothing when the cleaner removed the `src`
+struct ImageHandler;
+
+impl TagHandler for ImageHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        let Some(src) = get_tag_attr(tag, "src").filter(|src| !src.trim().is_empty()) else {
+            return;
+        };
+
+        let alt = get_tag_attr(tag, "alt").unwrap_or_default();
+        let title = get_tag_attr(tag, "title")
+            .map(|title| format!(" \"{}\"", title.replace('"', "\\\"")))
+            .unwrap_or_default();
+        printer.append_str(&format!("![{}]({}{})", alt, src.trim().replace(' ', "%20"), title));
+    }
+
+    fn after_handle(&mut self, _printer: &mut StructuredPrinter) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings_paragraphs_and_inline_formatting() {
+        let html = r#"<html><head><title>Ignored</title><style>p { color: red }</style></head><body>
+            <h1>Release notes</h1>
+            <p>This is <b>bold</b>, <em>italic</em> and <code>code</code>.</p>
+            <script>alert("no")</script>
+            <p>A <a href="https://example.com/a" onclick="x()">link</a> and <a href="javascript:alert(1)">unsafe</a></p>
+        </body></html>"#;
+
+        let markdown = html_to_markdown(html);
+        assert!(markdown.starts_with("Release notes\n==========\n\n"), "{markdown}");
+        assert!(
+            markdown.contains("This is **bold**, *italic* and `code`."),
+            "{markdown}"
+        );
+        assert!(
+            markdown.contains("A [link](https://example.com/a) and unsafe"),
+            "{markdown}"
+        );
+        assert!(!markdown.contains("Ignored") && !markdown.contains("color") && !markdown.contains("alert"));
+        assert!(markdown.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_lists_quotes_and_images() {
+        let html = "<ul><li>One</li><li>Two</li></ul><blockquote><p>Quoted</p></blockquote>\
+                    <img src=\"https://example.com/i.png\" alt=\"Pic\" width=\"20\">\
+                    <img src=\"data:image/png;base64,AAAA\" alt=\"Inline\">";
+
+        let markdown = html_to_markdown(html);
+        assert!(markdown.contains("* One\n* Two"), "{markdown}");
+        assert!(markdown.contains("> Quoted"), "{markdown}");
+        assert!(markdown.contains("![Pic](https://example.com/i.png)"), "{markdown}");
+        assert!(!markdown.contains("<img") && !markdown.contains("Inline"), "{markdown}");
+    }
+
+    #[test]
+    fn test_is_safe_url() {
+        assert!(is_safe_url("https://example.com"));
+        assert!(is_safe_url("mailto:me@example.com"));
+        assert!(is_safe_url("docs/page.md#part"));
+        assert!(is_safe_url("?q=a:b"));
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url(" Java\tScript:alert(1)"));
+        assert!(!is_safe_url("data:text/html,x"));
+    }
+
+    #[test]
+    fn test_empty_and_text_only_input() {
+        assert_eq!(html_to_markdown(""), "");
+        assert_eq!(html_to_markdown("<p>  </p><script>x</script>"), "");
+        assert_eq!(html_to_markdown("a &lt; b &amp; c"), "a \\< b & c\n");
+    }
+}
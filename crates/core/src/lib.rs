@@ -2,11 +2,37 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "atproto")]
 pub mod atproto;
+mod diff;
+mod find;
+mod frequency;
+mod html;
+mod link_title;
 mod nlp;
+mod plaintext;
+mod typography;
+mod whitespace;
+pub use diff::{DiffHunk, MAX_DIFF_EDITS, diff_lines};
+pub use find::{
+    FindMatch, FindOptions, MAX_FIND_MATCHES, ReplaceOptions, ReplaceResult, ReplacedRange, find_in_text,
+    replace_in_text,
+};
+pub use frequency::{DEFAULT_STOPWORDS, FreqOptions, word_frequencies, word_frequencies_outside};
+pub use html::{SAFE_URL_SCHEMES, html_to_markdown, is_safe_url, safe_url_attribute};
+#[cfg(feature = "link-titles")]
+pub use link_title::{LINK_TITLE_TIMEOUT, MAX_LINK_TITLE_BYTES, fetch_link_title};
+pub use link_title::{extract_html_title, is_fetchable_url};
 pub use nlp::{
-    PatternCategory, PatternMatcher, StyleCategorySettings, StyleMatch, StylePattern, StylePatternInput,
-    StyleScanInput, scan_style_matches,
+    PatternCategory, PatternMatcher, RegexPatternMatcher, StyleCategorySettings, StyleMatch, StylePattern,
+    StylePatternError, StylePatternInput, StyleScanInput, StyleScanReport, StyleSummary, scan_style_matches,
+    scan_style_report, style_summary,
+};
+pub use plaintext::{code_span_len, plaintext_to_markdown};
+pub use typography::{TypographyIssue, TypographyIssueKind, typography_fix, typography_scan, typography_scan_outside};
+pub use whitespace::{
+    WhitespaceIssue, WhitespaceIssueKind, scan_whitespace_issues, scan_whitespace_issues_outside,
+    trim_trailing_whitespace,
 };
 
 /// Unique identifier for a document within a location
@@ -65,6 +91,8 @@ pub enum Encoding {
     Utf8WithBom,
     Utf16Le,
     Utf16Be,
+    /// ISO-8859-1; never detected, only used when a document is opened with an explicit encoding
+    Latin1,
 }
 
 impl From<Encoding> for i32 {
@@ -74,6 +102,19 @@ impl From<Encoding> for i32 {
             Encoding::Utf8WithBom => 1,
             Encoding::Utf16Le => 2,
             Encoding::Utf16Be => 3,
+            Encoding::Latin1 => 4,
+        }
+    }
+}
+
+impl From<i32> for Encoding {
+    fn from(val: i32) -> Self {
+        match val {
+            1 => Encoding::Utf8WithBom,
+            2 => Encoding::Utf16Le,
+            3 => Encoding::Utf16Be,
+            4 => Encoding::Latin1,
+            _ => Encoding::Utf8,
         }
     }
 }
@@ -106,11 +147,40 @@ impl From<LineEnding> for i32 {
     }
 }
 
+impl From<i32> for LineEnding {
+    fn from(val: i32) -> Self {
+        match val {
+            1 => LineEnding::CrLf,
+            2 => LineEnding::Auto,
+            _ => LineEnding::Lf,
+        }
+    }
+}
+
 /// Document content with metadata for opening
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DocContent {
     pub text: String,
     pub meta: DocMeta,
+    /// Set when the file exceeded the open size limit and `text` holds only its leading portion
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// A document's bytes as stored on disk, for raw views and encoding troubleshooting
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RawDocContent {
+    pub bytes: Vec<u8>,
+    /// Encoding detected from the byte order mark, as `doc_open` would decode with
+    pub encoding: Encoding,
+    pub line_ending: LineEnding,
+    /// Whether the bytes decode as `encoding` without invalid sequences
+    pub is_valid_text: bool,
+    /// Set when the bytes do not decode cleanly or contain a NUL character
+    pub is_binary: bool,
+    /// Set when the file exceeded the open size limit and `bytes` holds only its leading portion
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 /// Options for listing documents
@@ -120,6 +190,13 @@ pub struct DocListOptions {
     pub extensions: Option<Vec<String>>,
     pub sort_by: Option<DocSortField>,
     pub sort_order: SortOrder,
+    /// Follow symlinks that resolve inside the location root; otherwise symlinks are skipped
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// When recursive, how many directory levels below the location root to descend into; `Some(0)`
+    /// lists the root only (like `recursive: false`) and `None` has no limit
+    #[serde(default)]
+    pub max_depth: Option<usize>,
 }
 
 /// Sort fields for document listing
@@ -149,12 +226,127 @@ pub enum SavePolicy {
     InPlace,
 }
 
+/// How hard a save works to survive a crash or power loss
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// Fsync the written file only
+    #[default]
+    Fast,
+    /// Also fsync the parent directory so the rename itself is on disk (a no-op on Windows)
+    Full,
+}
+
+/// Word-count goal progress computed after a save
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GoalProgress {
+    pub current_words: usize,
+    pub target_words: usize,
+}
+
+impl GoalProgress {
+    pub fn is_reached(&self) -> bool {
+        self.current_words >= self.target_words
+    }
+}
+
 /// Result of a save operation
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SaveResult {
     pub success: bool,
     pub new_meta: Option<DocMeta>,
     pub conflict_detected: bool,
+    /// Set when the document has a word-count goal that has not been reached before this save
+    #[serde(default)]
+    pub goal_progress: Option<GoalProgress>,
+    /// Set when trim-on-save changed the text, so the written content differs from what was sent
+    #[serde(default)]
+    pub whitespace_trimmed: bool,
+    /// Set when `stamp_updated_on_save` wrote a new `updated` front matter value
+    #[serde(default)]
+    pub updated_stamped: bool,
+}
+
+/// Changes a location reconcile would make to the catalog, computed without applying them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ReconcilePlan {
+    /// Files on disk without a catalog entry
+    pub added: Vec<PathBuf>,
+    /// Catalogued files whose size or mtime differs on disk
+    pub updated: Vec<PathBuf>,
+    /// Catalog entries whose file no longer exists
+    pub removed: Vec<PathBuf>,
+    /// Catalogued files that are unchanged on disk
+    pub unchanged: usize,
+}
+
+/// Disagreements between a location's files, catalog, and search index, found without fixing them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct IndexAudit {
+    /// Catalog entries whose file no longer exists on disk
+    pub missing_from_disk: Vec<PathBuf>,
+    /// Files on disk without a catalog entry
+    pub missing_from_catalog: Vec<PathBuf>,
+    /// Search index entries without a catalog entry
+    pub orphaned_search_entries: Vec<PathBuf>,
+    /// Catalogued text documents that should be searchable but have no search index entry
+    pub missing_from_search: Vec<PathBuf>,
+}
+
+impl IndexAudit {
+    /// True when the files, catalog, and search index all agree
+    pub fn is_consistent(&self) -> bool {
+        self.missing_from_disk.is_empty()
+            && self.missing_from_catalog.is_empty()
+            && self.orphaned_search_entries.is_empty()
+            && self.missing_from_search.is_empty()
+    }
+}
+
+/// What an archive import does with a file whose path already exists in the location
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ImportCollision {
+    /// Keep the existing file and leave the archived one out
+    #[default]
+    Skip,
+    Overwrite,
+    /// Import under the first free `name-N.ext`
+    Rename,
+}
+
+/// Options for importing an archive into a location
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub on_collision: ImportCollision,
+}
+
+/// Outcome of an archive import
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ImportReport {
+    /// Paths written, after any collision renames
+    pub imported: Vec<PathBuf>,
+    /// Paths left out because a file already existed there
+    pub skipped: Vec<PathBuf>,
+    /// Archive entry names refused because they are absolute or escape the location root
+    pub rejected: Vec<String>,
+}
+
+/// A document a bulk tag edit could not apply to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagBulkFailure {
+    pub doc_id: DocId,
+    pub error: AppError,
+}
+
+/// Outcome of a bulk tag edit, with each list in the order the documents were given
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TagBulkReport {
+    /// Documents rewritten
+    pub updated: Vec<DocId>,
+    /// Documents that already had the requested tags
+    pub unchanged: Vec<DocId>,
+    /// Documents left as they were because reading or saving them failed
+    pub failed: Vec<TagBulkFailure>,
 }
 
 /// Unique identifier for a location
@@ -180,6 +372,19 @@ pub struct LocationDescriptor {
     pub name: String,
     pub root_path: PathBuf,
     pub added_at: DateTime<Utc>,
+    /// Lowercase extensions (without the dot) treated as indexable text; `None` uses the default set
+    #[serde(default)]
+    pub indexable_extensions: Option<Vec<String>>,
+    /// Gitignore-style patterns, matched against paths relative to the root, excluded from indexing
+    #[serde(default = "default_ignore_globs")]
+    pub ignore_globs: Vec<String>,
+}
+
+/// Ignore patterns applied to locations that have not configured their own (hidden directories)
+pub const DEFAULT_IGNORE_GLOBS: &[&str] = &[".*/"];
+
+pub fn default_ignore_globs() -> Vec<String> {
+    DEFAULT_IGNORE_GLOBS.iter().map(|glob| glob.to_string()).collect()
 }
 
 /// Reference to a document within a location
@@ -234,6 +439,7 @@ pub enum ErrorCode {
     Parse,
     Index,
     Conflict,
+    InvalidUrl,
 }
 
 impl std::fmt::Display for ErrorCode {
@@ -246,6 +452,7 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::Parse => write!(f, "PARSE_ERROR"),
             ErrorCode::Index => write!(f, "INDEX_ERROR"),
             ErrorCode::Conflict => write!(f, "CONFLICT"),
+            ErrorCode::InvalidUrl => write!(f, "INVALID_URL"),
         }
     }
 }
@@ -282,6 +489,10 @@ impl AppError {
         Self::new(ErrorCode::InvalidPath, message)
     }
 
+    pub fn invalid_url(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidUrl, message)
+    }
+
     pub fn io(message: impl Into<String>) -> Self {
         Self::new(ErrorCode::Io, message)
     }
@@ -358,6 +569,25 @@ pub enum BackendEvent {
     DocModifiedExternally { doc_id: DocId, new_mtime: DateTime<Utc> },
     /// Emitted when save status changes (for UI feedback)
     SaveStatusChanged { doc_id: DocId, status: SaveStatus },
+    /// Emitted after saving a document with a word-count goal, until the goal is first reached
+    GoalProgress {
+        doc_id: DocId,
+        current_words: usize,
+        target_words: usize,
+    },
+    /// Emitted periodically while a background reindex works through a location's files
+    ReconcileProgress {
+        location_id: LocationId,
+        processed: usize,
+        total: usize,
+    },
+    /// Emitted once when a background reindex stops, whether it finished, was cancelled, or failed
+    ReconcileFinished {
+        location_id: LocationId,
+        indexed: usize,
+        cancelled: bool,
+        error: Option<String>,
+    },
     /// Emitted when the filesystem watcher detects file or directory changes.
     FilesystemChanged {
         location_id: LocationId,
@@ -411,6 +641,58 @@ pub struct SearchDateRange {
     pub to: Option<String>,
 }
 
+/// Largest snippet length (in tokens) FTS5's `snippet()` honors
+pub const MAX_SNIPPET_TOKENS: usize = 64;
+
+/// Snippet shape for full-text search results
+///
+/// The highlight markers only delimit matches inside SQLite; they are stripped from the returned
+/// snippet and reported as [`SearchMatch`] ranges, so pick markers that do not occur in documents.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SearchOptions {
+    pub snippet_tokens: usize,
+    pub ellipsis: String,
+    pub highlight_open: String,
+    pub highlight_close: String,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            snippet_tokens: 12,
+            ellipsis: " ... ".to_string(),
+            highlight_open: "<<".to_string(),
+            highlight_close: ">>".to_string(),
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Checks the snippet length against FTS5 limits and that the markers can be told apart
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !(1..=MAX_SNIPPET_TOKENS).contains(&self.snippet_tokens) {
+            return Err(AppError::new(
+                ErrorCode::Parse,
+                format!(
+                    "Snippet length must be between 1 and {} tokens, got {}",
+                    MAX_SNIPPET_TOKENS, self.snippet_tokens
+                ),
+            ));
+        }
+
+        if self.highlight_open.is_empty() || self.highlight_close.is_empty() {
+            return Err(AppError::new(ErrorCode::Parse, "Highlight markers must not be empty"));
+        }
+
+        if self.highlight_open == self.highlight_close {
+            return Err(AppError::new(ErrorCode::Parse, "Highlight markers must differ"));
+        }
+
+        Ok(())
+    }
+}
+
 /// Highlight range in a snippet
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SearchMatch {
@@ -428,6 +710,12 @@ pub struct SearchHit {
     pub line: usize,
     pub column: usize,
     pub matches: Vec<SearchMatch>,
+    /// Rendered id (`heading-setup`) of the last heading above `line`, or `None` before the first heading
+    #[serde(default)]
+    pub nearest_heading_anchor: Option<String>,
+    /// Start of the document as plain prose, without Markdown syntax, stored when it was last indexed
+    #[serde(default)]
+    pub preview: Option<String>,
 }
 
 /// Normalizes a relative path and rejects any path traversal attempts
@@ -467,6 +755,69 @@ pub fn normalize_relative_path(path: &Path) -> Result<PathBuf, PathError> {
     Ok(normalized)
 }
 
+/// Longest filename accepted by `validate_filename`, in UTF-8 bytes
+///
+/// 255 bytes is the usual Linux limit; since no character takes more UTF-16 units than UTF-8
+/// bytes, it also keeps names within the 255-unit limit on Windows and macOS.
+pub const MAX_FILENAME_BYTES: usize = 255;
+
+/// Device names Windows reserves with or without an extension (`nul.md` is as unusable as `NUL`)
+const RESERVED_WINDOWS_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2",
+    "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Checks that `name` is a single file or directory name usable on Windows, macOS, and Linux
+///
+/// Rejects empty names, `.`/`..`, path separators, characters Windows forbids, reserved device
+/// names, trailing dots or spaces, and names longer than [`MAX_FILENAME_BYTES`]. The error says which
+/// rule failed.
+pub fn validate_filename(name: &str) -> Result<(), PathError> {
+    if name.trim().is_empty() {
+        return Err(PathError::EmptyPath);
+    }
+    if name == "." || name == ".." {
+        return Err(PathError::InvalidPath(format!("'{}' is not a valid name", name)));
+    }
+    if name.contains(['/', '\\']) {
+        return Err(PathError::InvalidPath(
+            "Name cannot contain path separators".to_string(),
+        ));
+    }
+    if let Some(ch) = name
+        .chars()
+        .find(|ch| ch.is_control() || matches!(ch, '<' | '>' | ':' | '"' | '|' | '?' | '*'))
+    {
+        return Err(PathError::InvalidPath(format!("Name cannot contain {:?}", ch)));
+    }
+    if name.ends_with(['.', ' ']) {
+        return Err(PathError::InvalidPath(
+            "Name cannot end with a dot or space".to_string(),
+        ));
+    }
+
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_WINDOWS_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+    {
+        return Err(PathError::InvalidPath(format!(
+            "'{}' is a reserved name on Windows",
+            stem
+        )));
+    }
+
+    if name.len() > MAX_FILENAME_BYTES {
+        return Err(PathError::InvalidPath(format!(
+            "Name is too long ({} bytes, maximum {})",
+            name.len(),
+            MAX_FILENAME_BYTES
+        )));
+    }
+
+    Ok(())
+}
+
 /// Validates that a resolved path is within the location root
 ///
 /// This is a defense-in-depth check to ensure that even if path resolution
@@ -502,14 +853,98 @@ pub static CONFLICT_PATTERNS: &[&str] = &[
 
 /// Detects if a filename indicates a conflicted copy from cloud providers
 pub fn is_conflicted_filename(filename: &str) -> bool {
+    is_conflicted_filename_with(filename, &[])
+}
+
+/// Like [`is_conflicted_filename`], but also matches `extra_patterns` (e.g. Syncthing's `sync-conflict`)
+///
+/// Extra patterns are matched case-insensitively as substrings alongside [`CONFLICT_PATTERNS`];
+/// blank ones are ignored.
+pub fn is_conflicted_filename_with(filename: &str, extra_patterns: &[String]) -> bool {
     let lower = filename.to_lowercase();
     CONFLICT_PATTERNS.iter().any(|pattern| lower.contains(pattern))
+        || extra_patterns.iter().any(|pattern| {
+            let pattern = pattern.trim().to_lowercase();
+            !pattern.is_empty() && lower.contains(&pattern)
+        })
+}
+
+/// Counts words as whitespace-separated tokens, the rule behind `DocumentMetadata.word_count`
+pub fn count_words(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Counts words within a byte range of `text`
+///
+/// Out-of-range offsets are clamped to the text, reversed ranges are swapped, and offsets inside a
+/// multi-byte character move back to its start. A word cut by the range counts once.
+pub fn count_words_in_range(text: &str, start_byte: usize, end_byte: usize) -> usize {
+    let floor_boundary = |offset: usize| {
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    };
+
+    let (start, end) = (floor_boundary(start_byte), floor_boundary(end_byte));
+    count_words(&text[start.min(end)..start.max(end)])
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_validate_filename_accepts_ordinary_names() {
+        assert!(validate_filename("Chapter 1.md").is_ok());
+        assert!(validate_filename(".gitignore").is_ok());
+        assert!(validate_filename("console.md").is_ok());
+        assert!(validate_filename("日本語のメモ.md").is_ok());
+    }
+
+    #[test]
+    fn test_validate_filename_rejects_invalid_names() {
+        assert_eq!(validate_filename(""), Err(PathError::EmptyPath));
+        assert_eq!(validate_filename("   "), Err(PathError::EmptyPath));
+
+        for name in [
+            "..",
+            "notes/todo.md",
+            "notes\\todo.md",
+            "what?.md",
+            "a:b",
+            "tab\there",
+            "draft.",
+            "draft ",
+        ] {
+            assert!(
+                matches!(validate_filename(name), Err(PathError::InvalidPath(_))),
+                "{:?} should be rejected",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_filename_rejects_reserved_windows_names() {
+        for name in ["CON", "nul.md", "Com1.txt", "lpt9", "aux .md"] {
+            let error = validate_filename(name).unwrap_err();
+            assert!(error.to_string().contains("reserved"), "{:?}: {}", name, error);
+        }
+        assert!(validate_filename("COM10.md").is_ok());
+    }
+
+    #[test]
+    fn test_validate_filename_limits_length_in_bytes() {
+        let ascii = format!("{}.md", "a".repeat(MAX_FILENAME_BYTES - 3));
+        assert!(validate_filename(&ascii).is_ok());
+
+        let wide = format!("{}.md", "é".repeat(MAX_FILENAME_BYTES / 2));
+        assert!(wide.chars().count() < MAX_FILENAME_BYTES);
+        assert!(validate_filename(&wide).unwrap_err().to_string().contains("too long"));
+    }
+
     #[test]
     fn test_location_id_conversions() {
         let id = LocationId(42);
@@ -624,6 +1059,20 @@ mod tests {
         assert!(!is_conflicted_filename("regular-file.txt"));
     }
 
+    #[test]
+    fn test_is_conflicted_filename_with_extra_patterns() {
+        let extra = vec!["Sync-Conflict".to_string(), "  ".to_string()];
+        assert!(is_conflicted_filename_with(
+            "notes.sync-conflict-20240115-101500-ABCDEFG.md",
+            &extra
+        ));
+        assert!(is_conflicted_filename_with("My Doc (conflict).md", &extra));
+        assert!(!is_conflicted_filename_with("My Doc.md", &extra));
+        assert!(!is_conflicted_filename(
+            "notes.sync-conflict-20240115-101500-ABCDEFG.md"
+        ));
+    }
+
     #[test]
     fn test_default_encoding() {
         let enc: Encoding = Default::default();
@@ -647,4 +1096,18 @@ mod tests {
         let order: SortOrder = Default::default();
         assert!(matches!(order, SortOrder::Descending));
     }
+
+    #[test]
+    fn test_count_words_in_range_partial_and_full() {
+        let text = "The quick brown fox — naïve café jumps.";
+        assert_eq!(count_words_in_range(text, 0, text.len()), count_words(text));
+        assert_eq!(count_words_in_range(text, 0, usize::MAX), 8);
+        assert_eq!(count_words_in_range(text, 4, 15), 2);
+        assert_eq!(count_words_in_range(text, 15, 4), 2);
+        assert_eq!(count_words_in_range(text, 5, 5), 0);
+
+        let cafe = text.find("café").unwrap();
+        assert_eq!(count_words_in_range(text, cafe + 4, text.len()), 2);
+        assert_eq!(count_words_in_range(text, 1000, 2000), 0);
+    }
 }
@@ -0,0 +1,190 @@
+/// How long `fetch_link_title` waits for a page before giving up
+#[cfg(feature = "link-titles")]
+pub const LINK_TITLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(8);
+
+/// How much of a page `fetch_link_title` reads while looking for its `<title>`
+#[cfg(feature = "link-titles")]
+pub const MAX_LINK_TITLE_BYTES: usize = 256 * 1024;
+
+/// Whether `url` is an absolute `http` or `https` URL with a host, the only kind worth fetching
+pub fn is_fetchable_url(url: &str) -> bool {
+    let url = url.trim();
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return false;
+    };
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    (scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https"))
+        && !host.is_empty()
+        && !host.chars().any(char::is_whitespace)
+}
+
+/// The text of the first `<title>` element in an HTML page, entity-decoded and whitespace-collapsed
+///
+/// Returns `None` when there is no title or it is blank.
+pub fn extract_html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut search_from = 0;
+
+    let start = loop {
+        let open = search_from + lower[search_from..].find("<title")?;
+        let after_name = open + "<title".len();
+        match lower[after_name..].chars().next() {
+            Some('>') | Some('/') => break after_name,
+            Some(ch) if ch.is_ascii_whitespace() => break after_name,
+            _ => search_from = after_name,
+        }
+    };
+
+    let content_start = start + lower[start..].find('>')? + 1;
+    let content_end = content_start + lower[content_start..].find("</title")?;
+    let title = decode_entities(&html[content_start..content_end])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(title).filter(|title| !title.is_empty())
+}
+
+/// Decodes common named and all numeric character references; `&nbsp;` becomes a plain space
+fn decode_entities(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let end = rest[1..]
+            .find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '#')
+            .map_or(rest.len(), |end| end + 1);
+        let entity = &rest[1..end];
+        let replacement = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "copy" => Some('©'),
+            "reg" => Some('®'),
+            "trade" => Some('™'),
+            "hellip" => Some('…'),
+            "mdash" => Some('—'),
+            "ndash" => Some('–'),
+            "lsquo" => Some('‘'),
+            "rsquo" => Some('’'),
+            "ldquo" => Some('“'),
+            "rdquo" => Some('”'),
+            "laquo" => Some('«'),
+            "raquo" => Some('»'),
+            "bull" => Some('•'),
+            "middot" => Some('·'),
+            "times" => Some('×'),
+            "euro" => Some('€'),
+            _ => entity.strip_prefix('#').and_then(|number| {
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                };
+                code.and_then(char::from_u32)
+                    .map(|ch| if ch == '\u{a0}' { ' ' } else { ch })
+            }),
+        };
+
+        match replacement {
+            Some(ch) => {
+                decoded.push(ch);
+                rest = rest[end..].strip_prefix(';').unwrap_or(&rest[end..]);
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Fetches a web page and returns its `<title>`
+///
+/// Only `http(s)` URLs are fetched; anything else is an `InvalidUrl` error. The request times out
+/// after [`LINK_TITLE_TIMEOUT`] and at most [`MAX_LINK_TITLE_BYTES`] of the body are read. Pages
+/// without a title are `NotFound` errors.
+#[cfg(feature = "link-titles")]
+pub async fn fetch_link_title(url: &str) -> Result<String, crate::AppError> {
+    use crate::{AppError, ErrorCode};
+
+    let url = url.trim();
+    if !is_fetchable_url(url) {
+        return Err(AppError::invalid_url("Only http and https links can be fetched").with_context(url));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(LINK_TITLE_TIMEOUT)
+        .build()
+        .map_err(|e| AppError::io(format!("Failed to build HTTP client: {}", e)))?;
+    let mut response = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "text/html,application/xhtml+xml")
+        .send()
+        .await
+        .map_err(|e| AppError::io(format!("Failed to fetch link: {}", e)).with_context(url))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::io(format!("Link returned HTTP {}", response.status())).with_context(url));
+    }
+
+    let mut body = Vec::new();
+    while body.len() < MAX_LINK_TITLE_BYTES {
+        let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| AppError::io(format!("Failed to read link body: {}", e)).with_context(url))?
+        else {
+            break;
+        };
+        // Only the new bytes, plus enough of the old ones to catch a tag split across chunks
+        let search_from = body.len().saturating_sub(b"</title>".len() - 1);
+        body.extend_from_slice(&chunk);
+        if body[search_from..]
+            .windows(b"</title>".len())
+            .any(|window| window.eq_ignore_ascii_case(b"</title>"))
+        {
+            break;
+        }
+    }
+
+    extract_html_title(&String::from_utf8_lossy(&body))
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Link has no title").with_context(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_html_title() {
+        let html = "<html><head><meta charset=utf-8><TITLE data-x=\"1\">\n  Rust &amp; Writing\n  Notes </TITLE>\
+                    </head><body><h1>Ignored</h1><title>Second</title></body></html>";
+        assert_eq!(extract_html_title(html).as_deref(), Some("Rust & Writing Notes"));
+        assert_eq!(
+            extract_html_title("<titles>x</titles><title>Real</title>").as_deref(),
+            Some("Real")
+        );
+        assert_eq!(extract_html_title("<title>   </title>"), None);
+        assert_eq!(extract_html_title("<p>No title here</p>"), None);
+    }
+
+    #[test]
+    fn test_only_http_urls_are_fetchable() {
+        assert!(is_fetchable_url("https://example.com/post?id=1"));
+        assert!(is_fetchable_url("HTTP://example.com"));
+        assert!(!is_fetchable_url("file:///etc/passwd"));
+        assert!(!is_fetchable_url("javascript:alert(1)"));
+        assert!(!is_fetchable_url("https:///missing-host"));
+        assert!(!is_fetchable_url("example.com"));
+    }
+}
@@ -1,4 +1,5 @@
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::sync::OnceLock;
@@ -44,6 +45,9 @@ pub struct StylePatternInput {
     pub category: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub replacement: Option<String>,
+    /// Treats `text` as a (case-insensitive) regular expression instead of a literal phrase
+    #[serde(default)]
+    pub is_regex: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -79,6 +83,29 @@ pub struct StyleMatch {
     pub replacement: Option<String>,
 }
 
+/// A custom pattern that could not be compiled
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StylePatternError {
+    pub pattern: String,
+    pub message: String,
+}
+
+/// Matches from a style scan along with any custom patterns that were skipped
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct StyleScanReport {
+    pub matches: Vec<StyleMatch>,
+    pub errors: Vec<StylePatternError>,
+}
+
+/// Match counts from a style scan, per category and overall
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct StyleSummary {
+    pub filler: usize,
+    pub redundancy: usize,
+    pub cliche: usize,
+    pub total: usize,
+}
+
 #[derive(Debug, Clone)]
 struct IndexedPattern {
     normalized_text: String,
@@ -157,23 +184,127 @@ impl PatternMatcher {
             matches.push(StyleMatch { from, to, category: dedupe_key.2, replacement: dedupe_key.3 });
         }
 
-        matches.sort_by(|left, right| {
-            left.from
-                .cmp(&right.from)
-                .then(left.to.cmp(&right.to))
-                .then(left.category.as_str().cmp(right.category.as_str()))
-        });
+        sort_matches(&mut matches);
+        matches
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRegexPattern {
+    regex: Regex,
+    category: PatternCategory,
+    replacement: Option<String>,
+}
+
+/// Matches regex-based style patterns against the original (un-normalized) text
+///
+/// Each distinct pattern source is compiled once; patterns that fail to compile are
+/// collected in `errors` and skipped so the rest of the scan still runs.
+pub struct RegexPatternMatcher {
+    patterns: Vec<CompiledRegexPattern>,
+    errors: Vec<StylePatternError>,
+}
+
+impl RegexPatternMatcher {
+    pub fn new(patterns: Vec<StylePattern>) -> Self {
+        let mut compiled: HashMap<String, Result<Regex, String>> = HashMap::new();
+        let mut compiled_patterns = Vec::new();
+        let mut errors = Vec::new();
+
+        for pattern in patterns {
+            if pattern.text.is_empty() {
+                continue;
+            }
+
+            let is_new = !compiled.contains_key(&pattern.text);
+            let result = compiled.entry(pattern.text.clone()).or_insert_with(|| {
+                RegexBuilder::new(&pattern.text)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|error| error.to_string())
+            });
+
+            match result {
+                Ok(regex) => compiled_patterns.push(CompiledRegexPattern {
+                    regex: regex.clone(),
+                    category: pattern.category,
+                    replacement: pattern.replacement,
+                }),
+                Err(message) if is_new => {
+                    errors.push(StylePatternError { pattern: pattern.text, message: message.clone() })
+                }
+                Err(_) => {}
+            }
+        }
+
+        Self { patterns: compiled_patterns, errors }
+    }
+
+    pub fn errors(&self) -> &[StylePatternError] {
+        &self.errors
+    }
+
+    pub fn scan(&self, text: &str) -> Vec<StyleMatch> {
+        if self.patterns.is_empty() || text.is_empty() {
+            return Vec::new();
+        }
+
+        let index = TextIndex::build(text);
+        let mut matches = Vec::new();
+        let mut seen = HashSet::new();
+
+        for pattern in &self.patterns {
+            for found in pattern.regex.find_iter(text) {
+                if found.is_empty() {
+                    continue;
+                }
+
+                let Some(from) = index.utf16_offset(found.start()) else {
+                    continue;
+                };
+                let Some(to) = index.utf16_offset(found.end()) else {
+                    continue;
+                };
+
+                if !seen.insert((from, to, pattern.category, pattern.replacement.clone())) {
+                    continue;
+                }
 
+                matches.push(StyleMatch {
+                    from,
+                    to,
+                    category: pattern.category,
+                    replacement: pattern.replacement.clone(),
+                });
+            }
+        }
+
+        sort_matches(&mut matches);
         matches
     }
 }
 
+fn sort_matches(matches: &mut [StyleMatch]) {
+    matches.sort_by(|left, right| {
+        left.from
+            .cmp(&right.from)
+            .then(left.to.cmp(&right.to))
+            .then(left.category.as_str().cmp(right.category.as_str()))
+    });
+}
+
 pub fn scan_style_matches(input: &StyleScanInput) -> Vec<StyleMatch> {
+    scan_style_report(input).matches
+}
+
+/// Scans text with built-in and custom patterns, reporting custom regexes that failed to compile
+pub fn scan_style_report(input: &StyleScanInput) -> StyleScanReport {
     let mut patterns: Vec<StylePattern> = builtin_patterns()
         .iter()
         .filter(|pattern| input.categories.allows(pattern.category))
         .cloned()
         .collect();
+    let mut regex_patterns = Vec::new();
 
     for pattern in &input.custom_patterns {
         let category = PatternCategory::from_raw(pattern.category.trim().to_lowercase().as_str());
@@ -181,10 +312,41 @@ pub fn scan_style_matches(input: &StyleScanInput) -> Vec<StyleMatch> {
             continue;
         };
 
-        patterns.push(StylePattern { text: pattern.text.clone(), category, replacement: pattern.replacement.clone() });
+        let style_pattern =
+            StylePattern { text: pattern.text.clone(), category, replacement: pattern.replacement.clone() };
+        if pattern.is_regex {
+            regex_patterns.push(style_pattern);
+        } else {
+            patterns.push(style_pattern);
+        }
     }
 
-    PatternMatcher::new(patterns).scan(&input.text)
+    let mut matches = PatternMatcher::new(patterns).scan(&input.text);
+    let regex_matcher = RegexPatternMatcher::new(regex_patterns);
+    if !regex_matcher.patterns.is_empty() {
+        matches.extend(regex_matcher.scan(&input.text));
+        sort_matches(&mut matches);
+    }
+
+    StyleScanReport { matches, errors: regex_matcher.errors().to_vec() }
+}
+
+/// Counts style matches per category, for badges such as "5 filler words"
+///
+/// Counts the same matches [`scan_style_matches`] returns, so they always agree with the detailed
+/// scan; only the counts are kept, so nothing per match is returned to the caller.
+pub fn style_summary(input: &StyleScanInput) -> StyleSummary {
+    scan_style_matches(input)
+        .iter()
+        .fold(StyleSummary::default(), |mut summary, found| {
+            match found.category {
+                PatternCategory::Filler => summary.filler += 1,
+                PatternCategory::Redundancy => summary.redundancy += 1,
+                PatternCategory::Cliche => summary.cliche += 1,
+            }
+            summary.total += 1;
+            summary
+        })
 }
 
 #[derive(Deserialize)]
@@ -427,6 +589,28 @@ mod tests {
         assert!(matches.iter().any(|m| m.category == PatternCategory::Cliche));
     }
 
+    #[test]
+    fn style_summary_counts_match_the_detailed_scan() {
+        let input = StyleScanInput {
+            text: "Basically, the plan is actually fine. It was plain as day, so we beat around the bush, \
+                   and honestly, basically nothing changed."
+                .to_string(),
+            categories: StyleCategorySettings { filler: true, redundancy: false, cliche: true },
+            custom_patterns: vec![],
+        };
+
+        let summary = style_summary(&input);
+        let matches = scan_style_matches(&input);
+        let count = |category| matches.iter().filter(|m| m.category == category).count();
+
+        assert_eq!(summary.filler, count(PatternCategory::Filler));
+        assert_eq!(summary.cliche, count(PatternCategory::Cliche));
+        assert_eq!(summary.redundancy, 0);
+        assert_eq!(summary.total, matches.len());
+        assert!(summary.filler >= 4);
+        assert!(summary.cliche >= 2);
+    }
+
     #[test]
     fn style_scan_ignores_invalid_custom_categories() {
         let matches = scan_style_matches(&StyleScanInput {
@@ -436,6 +620,7 @@ mod tests {
                 text: "unique phrase".to_string(),
                 category: "unknown".to_string(),
                 replacement: None,
+                is_regex: false,
             }],
         });
 
@@ -451,10 +636,89 @@ mod tests {
                 text: "actually".to_string(),
                 category: "filler".to_string(),
                 replacement: Some("".to_string()),
+                is_regex: false,
             }],
         });
 
         assert_eq!(matches.len(), 1);
         assert_eq!(matches[0].category, PatternCategory::Filler);
     }
+
+    #[test]
+    fn style_scan_applies_custom_regex_patterns() {
+        let text = "Écoute: it was VERY good and  very   tired.";
+        let report = scan_style_report(&StyleScanInput {
+            text: text.to_string(),
+            categories: StyleCategorySettings::default(),
+            custom_patterns: vec![
+                StylePatternInput {
+                    text: r"\bvery\s+\w+".to_string(),
+                    category: "filler".to_string(),
+                    replacement: None,
+                    is_regex: true,
+                },
+                StylePatternInput {
+                    text: "  ".to_string(),
+                    category: "redundancy".to_string(),
+                    replacement: Some(" ".to_string()),
+                    is_regex: true,
+                },
+            ],
+        });
+
+        assert!(report.errors.is_empty());
+
+        let fillers: Vec<&StyleMatch> = report
+            .matches
+            .iter()
+            .filter(|m| m.category == PatternCategory::Filler)
+            .collect();
+        assert_eq!(fillers.len(), 2);
+
+        let first_start = text.find("VERY").unwrap();
+        assert_eq!(fillers[0].from, text[..first_start].encode_utf16().count());
+        assert_eq!(fillers[0].to, fillers[0].from + "VERY good".len());
+
+        let double_spaces = report
+            .matches
+            .iter()
+            .filter(|m| m.category == PatternCategory::Redundancy)
+            .count();
+        assert_eq!(double_spaces, 2);
+    }
+
+    #[test]
+    fn style_scan_reports_invalid_regex_without_aborting() {
+        let report = scan_style_report(&StyleScanInput {
+            text: "Actually we can proceed.".to_string(),
+            categories: StyleCategorySettings::default(),
+            custom_patterns: vec![
+                StylePatternInput {
+                    text: "(unclosed".to_string(),
+                    category: "filler".to_string(),
+                    replacement: None,
+                    is_regex: true,
+                },
+                StylePatternInput {
+                    text: "(unclosed".to_string(),
+                    category: "cliche".to_string(),
+                    replacement: None,
+                    is_regex: true,
+                },
+                StylePatternInput {
+                    text: "actually".to_string(),
+                    category: "filler".to_string(),
+                    replacement: None,
+                    is_regex: false,
+                },
+            ],
+        });
+
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].pattern, "(unclosed");
+        assert!(!report.errors[0].message.is_empty());
+        assert_eq!(report.matches.len(), 1);
+        assert_eq!(report.matches[0].from, 0);
+        assert_eq!(report.matches[0].to, 8);
+    }
 }
@@ -0,0 +1,242 @@
+/// Lightly marks up pasted plain text as Markdown
+///
+/// Bare `http(s)://` URLs become `<autolinks>`, `*` and `•` bullets become `-` list items set off
+/// from a preceding paragraph by a blank line, and runs of blank lines collapse to one. Front
+/// matter, fenced code, indented code, and existing links are left alone, and running the
+/// conversion on its own output changes nothing.
+pub fn plaintext_to_markdown(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut output: Vec<String> = Vec::with_capacity(lines.len());
+    let mut idx = front_matter_len(&lines);
+    output.extend(lines[..idx].iter().map(|line| line.to_string()));
+
+    let mut fence: Option<(char, usize)> = None;
+    let mut in_list = false;
+    let mut in_indented_code = false;
+    let mut previous_blank = true;
+
+    while idx < lines.len() {
+        let line = lines[idx];
+        idx += 1;
+
+        if let Some((marker, length)) = fence {
+            if fence_marker(line).is_some_and(|(ch, run)| ch == marker && run >= length) {
+                fence = None;
+            }
+            output.push(line.to_string());
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            if !previous_blank {
+                output.push(String::new());
+            }
+            previous_blank = true;
+            continue;
+        }
+
+        let indent = indent_width(line);
+        in_indented_code = indent >= 4 && !in_list && (in_indented_code || previous_blank);
+
+        if in_indented_code {
+            output.push(line.to_string());
+        } else if let Some(opening) = fence_marker(line) {
+            fence = Some(opening);
+            output.push(line.to_string());
+        } else if let Some(item) = bullet_item(line) {
+            if !previous_blank && !in_list {
+                output.push(String::new());
+            }
+            let leading = &line[..line.len() - line.trim_start().len()];
+            output.push(format!("{}- {}", leading, link_urls(item)));
+            in_list = true;
+        } else {
+            // Indented lines continue a list item, as does a lazy line directly after one
+            in_list &= indent > 0 || !previous_blank;
+            output.push(if is_reference_definition(line) { line.to_string() } else { link_urls(line) });
+        }
+        previous_blank = false;
+    }
+
+    let mut result = output.join("\n");
+    if text.ends_with('\n') && !result.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+/// Number of leading lines that form a `---` (YAML) or `+++` (TOML) front matter block
+fn front_matter_len(lines: &[&str]) -> usize {
+    let Some(delimiter) = lines
+        .first()
+        .map(|line| line.trim_end())
+        .filter(|line| *line == "---" || *line == "+++")
+    else {
+        return 0;
+    };
+
+    lines
+        .iter()
+        .skip(1)
+        .position(|line| {
+            let line = line.trim_end();
+            line == delimiter || (delimiter == "---" && line == "...")
+        })
+        .map_or(0, |closing| closing + 2)
+}
+
+/// Returns the marker character and run length when a line opens or closes a code fence
+pub(crate) fn fence_marker(line: &str) -> Option<(char, usize)> {
+    if indent_width(line) > 3 {
+        return None;
+    }
+
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|ch| *ch == '`' || *ch == '~')?;
+    let length = trimmed.chars().take_while(|ch| *ch == marker).count();
+    (length >= 3).then_some((marker, length))
+}
+
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|ch| ch.is_whitespace())
+        .map(|ch| if ch == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Returns the item text of a `- `, `* `, or `• ` bullet line, skipping thematic breaks like `* * *`
+fn bullet_item(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let is_break = trimmed.chars().filter(|ch| !ch.is_whitespace()).count() >= 3
+        && trimmed.chars().all(|ch| ch.is_whitespace() || ch == '-' || ch == '*');
+    if is_break {
+        return None;
+    }
+
+    let item = ["- ", "* ", "• "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))?;
+    let item = item.trim_start();
+    (!item.is_empty()).then_some(item)
+}
+
+/// Matches `[label]: destination` link reference definitions
+fn is_reference_definition(line: &str) -> bool {
+    indent_width(line) <= 3
+        && line
+            .trim_start()
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("]:"))
+            .is_some_and(|(label, _)| !label.is_empty() && !label.contains(']'))
+}
+
+/// Wraps bare `http(s)://` URLs in angle brackets, skipping code spans and URLs already in links
+fn link_urls(line: &str) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut cursor = 0;
+    let mut copied = 0;
+
+    while let Some(ch) = line[cursor..].chars().next() {
+        if ch == '`' {
+            cursor += code_span_len(line, cursor);
+            continue;
+        }
+
+        if (line[cursor..].starts_with("http://") || line[cursor..].starts_with("https://"))
+            && starts_bare_url(&line[..cursor])
+        {
+            let length = url_len(&line[cursor..]);
+            if line[cursor..cursor + length].contains("://") && !line[cursor..cursor + length].ends_with("://") {
+                output.push_str(&line[copied..cursor]);
+                output.push('<');
+                output.push_str(&line[cursor..cursor + length]);
+                output.push('>');
+                copied = cursor + length;
+            }
+            cursor += length;
+            continue;
+        }
+
+        cursor += ch.len_utf8();
+    }
+
+    output.push_str(&line[copied..]);
+    output
+}
+
+/// A URL is bare when it starts the line or follows whitespace or an opening paren not closing a `[label]`
+fn starts_bare_url(before: &str) -> bool {
+    let mut chars = before.chars().rev();
+    match chars.next() {
+        None => true,
+        Some(ch) if ch.is_whitespace() => true,
+        Some('(') => chars.next() != Some(']'),
+        Some(_) => false,
+    }
+}
+
+/// Byte length of the URL at the start of `text`, dropping trailing punctuation and unbalanced `)`
+fn url_len(text: &str) -> usize {
+    let end = text
+        .find(|ch: char| ch.is_whitespace() || matches!(ch, '<' | '>' | '"' | '`'))
+        .unwrap_or(text.len());
+    let mut url = &text[..end];
+
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '*', '_']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(rest) if trimmed.matches('(').count() < trimmed.matches(')').count() => rest,
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url.len();
+        }
+        url = trimmed;
+    }
+}
+
+/// Returns the byte length of the inline code span starting at `start`, or of the unmatched backtick run
+pub fn code_span_len(line: &str, start: usize) -> usize {
+    let run = line[start..].bytes().take_while(|byte| *byte == b'`').count();
+    let closing = "`".repeat(run);
+    match line[start + run..].find(&closing) {
+        Some(offset) => run + offset + run,
+        None => run,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links_bare_urls_and_is_idempotent() {
+        let text = "See https://example.com/docs. Or (https://en.wikipedia.org/wiki/Rust_(language)).\n\
+                    Already <https://a.dev>, [site](https://b.dev), `https://code.dev` and [ref]: https://c.dev\n";
+        let converted = plaintext_to_markdown(text);
+
+        assert!(converted.contains("See <https://example.com/docs>."));
+        assert!(converted.contains("(<https://en.wikipedia.org/wiki/Rust_(language)>)."));
+        assert!(converted.contains("Already <https://a.dev>, [site](https://b.dev), `https://code.dev`"));
+        assert!(!converted.contains("<<"));
+        assert_eq!(plaintext_to_markdown(&converted), converted);
+    }
+
+    #[test]
+    fn test_normalizes_lists_and_blank_lines() {
+        let text = "Groceries:\n* eggs\n• milk https://shop.example\n  - oat\n\n\n\nDone.\n\n* * *\n";
+        let converted = plaintext_to_markdown(text);
+
+        assert_eq!(
+            converted,
+            "Groceries:\n\n- eggs\n- milk <https://shop.example>\n  - oat\n\nDone.\n\n* * *\n"
+        );
+        assert_eq!(plaintext_to_markdown(&converted), converted);
+    }
+
+    #[test]
+    fn test_leaves_code_and_front_matter_untouched() {
+        let text = "---\ntitle: https://meta.example\n---\n```\n* not a list\n\n\n\nhttps://x.example\n```\n\n    https://indented.example\n";
+        assert_eq!(plaintext_to_markdown(text), text);
+    }
+}
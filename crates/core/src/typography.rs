@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Kind of typography problem found by `typography_scan`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TypographyIssueKind {
+    /// `"` that should be “ or ”
+    StraightDoubleQuote,
+    /// `'` that should be ‘ or ’ (apostrophes included)
+    StraightSingleQuote,
+    /// `--` that should be an em dash
+    DoubleHyphen,
+    /// `...` that should be an ellipsis
+    ThreeDots,
+}
+
+/// A straight-typed character run and its typographic replacement, as a byte range into the text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TypographyIssue {
+    pub start: usize,
+    pub end: usize,
+    pub kind: TypographyIssueKind,
+    pub replacement: String,
+}
+
+/// Words that start with an apostrophe standing in for dropped letters, so it is ’ rather than ‘
+const ELISIONS: &[&str] = &["cause", "em", "n", "round", "til", "tis", "twas"];
+
+/// URL prefixes whose text runs to the next whitespace or closing bracket
+const URL_PREFIXES: &[&str] = &["http://", "https://", "ftp://", "mailto:", "www."];
+
+/// Finds straight quotes, double hyphens, and three-dot runs that have typographic equivalents
+///
+/// Quotes open after whitespace or an opening bracket and close otherwise, so an apostrophe inside
+/// a word (`don't`, `Ada's`) becomes ’. Quotes after a digit are taken as primes (`5'10"`) and left
+/// alone, as are `---` rules, `--flags`, and longer dot runs. Inline code, URLs, link destinations,
+/// and HTML tags are skipped.
+pub fn typography_scan(text: &str) -> Vec<TypographyIssue> {
+    typography_scan_outside(text, &[])
+}
+
+/// Like [`typography_scan`], but ignores issues that start inside any of the `skip` byte ranges
+///
+/// Used to leave code blocks and front matter alone.
+pub fn typography_scan_outside(text: &str, skip: &[Range<usize>]) -> Vec<TypographyIssue> {
+    let mut protected = protected_ranges(text);
+    protected.extend(skip.iter().cloned());
+
+    let mut issues = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = text.char_indices();
+
+    while let Some((start, ch)) = chars.next() {
+        if protected.iter().any(|range| range.contains(&start)) {
+            prev = Some(ch);
+            continue;
+        }
+
+        let rest = &text[start + ch.len_utf8()..];
+        let next = rest.chars().next();
+        let issue = match ch {
+            '"' if !prev.is_some_and(|prev| prev.is_ascii_digit()) => {
+                let replacement = if opens_quote(prev) { "“" } else { "”" };
+                Some((start + 1, TypographyIssueKind::StraightDoubleQuote, replacement))
+            }
+            '\'' if !prev.is_some_and(|prev| prev.is_ascii_digit()) => {
+                let elided = next.is_some_and(|next| next.is_ascii_digit()) || starts_with_elision(rest);
+                let replacement = if opens_quote(prev) && !elided { "‘" } else { "’" };
+                Some((start + 1, TypographyIssueKind::StraightSingleQuote, replacement))
+            }
+            '-' | '.' => {
+                let run = rest.len() - rest.trim_start_matches(ch).len() + 1;
+                for _ in 1..run {
+                    chars.next();
+                }
+                let after = text[start + run..].chars().next();
+
+                match (ch, run) {
+                    ('.', 3) => Some((start + 3, TypographyIssueKind::ThreeDots, "…")),
+                    ('-', 2) if !is_flag(prev, after) && after != Some('>') => {
+                        Some((start + 2, TypographyIssueKind::DoubleHyphen, "—"))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some((end, kind, replacement)) = issue {
+            issues.push(TypographyIssue { start, end, kind, replacement: replacement.to_string() });
+            prev = text[..end].chars().next_back();
+        } else {
+            prev = Some(ch);
+        }
+    }
+
+    issues
+}
+
+/// Applies every suggestion from [`typography_scan_outside`] to the text
+pub fn typography_fix(text: &str, skip: &[Range<usize>]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut copied = 0;
+
+    for issue in typography_scan_outside(text, skip) {
+        output.push_str(&text[copied..issue.start]);
+        output.push_str(&issue.replacement);
+        copied = issue.end;
+    }
+
+    output.push_str(&text[copied..]);
+    output
+}
+
+/// Whether a quote after `prev` opens rather than closes
+fn opens_quote(prev: Option<char>) -> bool {
+    prev.is_none_or(|prev| prev.is_whitespace() || matches!(prev, '(' | '[' | '{' | '‘' | '“' | '—' | '–' | '/'))
+}
+
+/// Whether `--` between `prev` and `after` reads as a command-line flag (`run --verbose`)
+fn is_flag(prev: Option<char>, after: Option<char>) -> bool {
+    prev.is_none_or(char::is_whitespace) && after.is_some_and(|after| after.is_ascii_alphabetic())
+}
+
+fn starts_with_elision(rest: &str) -> bool {
+    let word_len = rest.find(|ch: char| !ch.is_alphabetic()).unwrap_or(rest.len());
+    ELISIONS
+        .iter()
+        .any(|elision| rest[..word_len].eq_ignore_ascii_case(elision))
+}
+
+/// Byte ranges of inline code, URLs, link destinations, and HTML tags, which keep their characters as typed
+fn protected_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut idx = 0;
+
+    while idx < text.len() {
+        let rest = &text[idx..];
+        let after = rest.get(1..).unwrap_or_default();
+        let end = if rest.starts_with('`') {
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            let fence = &rest[..ticks];
+            let close = rest[ticks..]
+                .match_indices(fence)
+                .map(|(at, _)| ticks + at + ticks)
+                .find(|&end| !rest[end..].starts_with('`'));
+            match close {
+                Some(end) => Some(idx + end),
+                None => {
+                    idx += ticks;
+                    continue;
+                }
+            }
+        } else if rest.starts_with("<!--") {
+            Some(rest.find("-->").map_or(text.len(), |at| idx + at + 3))
+        } else if rest.starts_with('<') && after.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '/') {
+            rest.find('>').map(|at| idx + at + 1)
+        } else if rest.starts_with("](") {
+            rest.find(')').map(|at| idx + at + 1)
+        } else if starts_url(text, idx) {
+            let len = rest
+                .find(|ch: char| ch.is_whitespace() || matches!(ch, ')' | ']' | '>' | '"'))
+                .unwrap_or(rest.len());
+            Some(idx + len)
+        } else {
+            None
+        };
+
+        match end {
+            Some(end) => {
+                ranges.push(idx..end);
+                idx = end;
+            }
+            None => idx += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    ranges
+}
+
+fn starts_url(text: &str, idx: usize) -> bool {
+    let boundary = text[..idx]
+        .chars()
+        .next_back()
+        .is_none_or(|prev| !prev.is_alphanumeric());
+    boundary
+        && URL_PREFIXES.iter().any(|prefix| {
+            text.get(idx..idx + prefix.len())
+                .is_some_and(|candidate| candidate.eq_ignore_ascii_case(prefix))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replacements(text: &str) -> Vec<(String, String)> {
+        typography_scan(text)
+            .into_iter()
+            .map(|issue| (text[issue.start..issue.end].to_string(), issue.replacement))
+            .collect()
+    }
+
+    #[test]
+    fn test_quotes_dashes_and_dots() {
+        let text = "\"Don't go,\" she said -- and waited... 'Fine.' It's the '90s' sound, 'tis true. Café 'naïve'.";
+        assert_eq!(
+            typography_fix(text, &[]),
+            "“Don’t go,” she said — and waited… ‘Fine.’ It’s the ’90s’ sound, ’tis true. Café ‘naïve’."
+        );
+
+        let issues = typography_scan(text);
+        assert_eq!(issues[0].kind, TypographyIssueKind::StraightDoubleQuote);
+        assert_eq!((issues[0].start, issues[0].end), (0, 1));
+        let dash = issues
+            .iter()
+            .find(|issue| issue.kind == TypographyIssueKind::DoubleHyphen)
+            .unwrap();
+        assert_eq!(&text[dash.start..dash.end], "--");
+        assert_eq!(dash.replacement, "—");
+    }
+
+    #[test]
+    fn test_skips_code_urls_and_markup() {
+        let text = "Run `say \"hi\" -- now` with --verbose, see [docs](https://x.io/a--b \"T\") or \
+                    https://x.io/it's <a href=\"y\">link</a> <!-- it's -- hidden -->\n---\n5'10\" tall....";
+        assert!(replacements(text).is_empty(), "{:?}", replacements(text));
+
+        let fenced = "```\nsay \"hi\" -- now\n```\n\"Done\"";
+        let block = 0..fenced.rfind("```").unwrap() + 4;
+        let starts: Vec<usize> = typography_scan_outside(fenced, std::slice::from_ref(&block))
+            .iter()
+            .map(|issue| issue.start)
+            .collect();
+        assert_eq!(starts, vec![block.end, fenced.len() - 1]);
+    }
+}
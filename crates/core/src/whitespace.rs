@@ -0,0 +1,258 @@
+use crate::plaintext::fence_marker;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Kind of whitespace problem found by `scan_whitespace_issues`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WhitespaceIssueKind {
+    /// Spaces, tabs, or non-breaking spaces before a line ending (or a whitespace-only line)
+    TrailingSpace,
+    HardTab,
+    /// U+00A0 or U+202F
+    NonBreakingSpace,
+    /// Two or more spaces between words; leading indentation is not counted
+    MultipleSpaces,
+}
+
+/// A run of problem whitespace, as a byte range into the scanned text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WhitespaceIssue {
+    pub start: usize,
+    pub end: usize,
+    pub kind: WhitespaceIssueKind,
+}
+
+fn is_non_breaking_space(ch: char) -> bool {
+    matches!(ch, '\u{a0}' | '\u{202f}')
+}
+
+fn is_inline_space(ch: char) -> bool {
+    ch == ' ' || ch == '\t' || is_non_breaking_space(ch)
+}
+
+/// Finds trailing whitespace, hard tabs, non-breaking spaces, and runs of spaces between words
+///
+/// Each run is reported once, in text order. Whitespace at the end of a line counts only as
+/// trailing, whatever it is made of.
+pub fn scan_whitespace_issues(text: &str) -> Vec<WhitespaceIssue> {
+    scan_whitespace_issues_outside(text, &[])
+}
+
+/// Like [`scan_whitespace_issues`], but ignores issues that start inside any of the `skip` byte ranges
+///
+/// Used to leave code blocks alone, where indentation and alignment are deliberate.
+pub fn scan_whitespace_issues_outside(text: &str, skip: &[Range<usize>]) -> Vec<WhitespaceIssue> {
+    let mut issues = Vec::new();
+    let mut line_start = 0;
+
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let trimmed = content.trim_end_matches(is_inline_space);
+        let indent = trimmed.len() - trimmed.trim_start_matches(' ').len();
+
+        scan_line(&trimmed[indent..], line_start + indent, &mut issues);
+        if trimmed.len() < content.len() {
+            issues.push(WhitespaceIssue {
+                start: line_start + trimmed.len(),
+                end: line_start + content.len(),
+                kind: WhitespaceIssueKind::TrailingSpace,
+            });
+        }
+
+        line_start += line.len();
+    }
+
+    issues.retain(|issue| !skip.iter().any(|range| range.contains(&issue.start)));
+    issues
+}
+
+/// Strips trailing whitespace from every line and ends the text with exactly one line ending
+///
+/// Lines starting inside a `skip` range are left as they are. A Markdown hard break (two or more
+/// trailing spaces on a line followed by a non-blank line) is kept, except inside fenced code where
+/// it means nothing. Empty text stays empty.
+pub fn trim_trailing_whitespace(text: &str, skip: &[Range<usize>]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut line_start = 0;
+    let mut fence: Option<(char, usize)> = None;
+    let mut lines = text.split_inclusive('\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let in_fence = match (fence, fence_marker(content)) {
+            (Some((marker, length)), Some((ch, run))) if ch == marker && run >= length => {
+                fence = None;
+                true
+            }
+            (Some(_), _) => true,
+            (None, Some(opening)) => {
+                fence = Some(opening);
+                true
+            }
+            (None, None) => false,
+        };
+
+        let trimmed = content.trim_end_matches(is_inline_space);
+        let hard_break = !in_fence
+            && !trimmed.trim().is_empty()
+            && content.len() - content.trim_end_matches(' ').len() >= 2
+            && lines.peek().is_some_and(|next| !next.trim().is_empty());
+
+        if hard_break || skip.iter().any(|range| range.contains(&line_start)) {
+            output.push_str(line);
+        } else {
+            output.push_str(trimmed);
+            output.push_str(&line[content.len()..]);
+        }
+        line_start += line.len();
+    }
+
+    let ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    output.truncate(output.trim_end_matches(['\r', '\n']).len());
+    if !output.is_empty() {
+        output.push_str(ending);
+    }
+    output
+}
+
+/// Reports the tab, non-breaking space, and multiple-space runs in a line with its indentation and
+/// trailing whitespace already removed
+fn scan_line(line: &str, offset: usize, issues: &mut Vec<WhitespaceIssue>) {
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        let kind = match ch {
+            '\t' => WhitespaceIssueKind::HardTab,
+            ch if is_non_breaking_space(ch) => WhitespaceIssueKind::NonBreakingSpace,
+            ' ' => WhitespaceIssueKind::MultipleSpaces,
+            _ => continue,
+        };
+
+        let mut end = start + ch.len_utf8();
+        let mut count = 1;
+        while let Some(&(idx, next)) = chars.peek() {
+            let same = match kind {
+                WhitespaceIssueKind::NonBreakingSpace => is_non_breaking_space(next),
+                _ => next == ch,
+            };
+            if !same {
+                break;
+            }
+            end = idx + next.len_utf8();
+            count += 1;
+            chars.next();
+        }
+
+        if kind != WhitespaceIssueKind::MultipleSpaces || count > 1 {
+            issues.push(WhitespaceIssue { start: offset + start, end: offset + end, kind });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str) -> Vec<(WhitespaceIssueKind, &str)> {
+        scan_whitespace_issues(text)
+            .into_iter()
+            .map(|issue| (issue.kind, &text[issue.start..issue.end]))
+            .collect()
+    }
+
+    #[test]
+    fn test_trailing_space() {
+        let text = "One  \r\nTwo\t\nthree\u{a0}\n   \nfour";
+        let issues = scan_whitespace_issues(text);
+
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.kind == WhitespaceIssueKind::TrailingSpace)
+        );
+        assert_eq!(
+            issues.iter().map(|issue| (issue.start, issue.end)).collect::<Vec<_>>(),
+            vec![(3, 5), (10, 11), (17, 19), (20, 23)]
+        );
+    }
+
+    #[test]
+    fn test_hard_tab() {
+        assert_eq!(
+            found("\tIndented\tand\t\ttabbed."),
+            vec![
+                (WhitespaceIssueKind::HardTab, "\t"),
+                (WhitespaceIssueKind::HardTab, "\t"),
+                (WhitespaceIssueKind::HardTab, "\t\t"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_breaking_space() {
+        let text = "Mr.\u{a0}Smith paid 10\u{202f}€ — café\u{a0}\u{a0}au lait";
+        let issues = scan_whitespace_issues(text);
+
+        assert_eq!(issues.len(), 3);
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.kind == WhitespaceIssueKind::NonBreakingSpace)
+        );
+        assert_eq!(&text[issues[0].start..issues[0].end], "\u{a0}");
+        assert_eq!(&text[issues[1].start..issues[1].end], "\u{202f}");
+        assert_eq!(&text[issues[2].start..issues[2].end], "\u{a0}\u{a0}");
+    }
+
+    #[test]
+    fn test_multiple_spaces_skip_indentation() {
+        assert_eq!(
+            found("    - item  with   gaps\nsingle spaces only\n"),
+            vec![
+                (WhitespaceIssueKind::MultipleSpaces, "  "),
+                (WhitespaceIssueKind::MultipleSpaces, "   ")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace() {
+        let text = "Title  \r\n\r\n```\ncode  \n```\nEnd\t\r\n\r\n\r\n";
+        let code = text.find("```").unwrap()..text.find("End").unwrap();
+
+        assert_eq!(
+            trim_trailing_whitespace(text, &[code]),
+            "Title\r\n\r\n```\ncode  \n```\nEnd\r\n"
+        );
+        assert_eq!(trim_trailing_whitespace("No newline ", &[]), "No newline\n");
+        assert_eq!(trim_trailing_whitespace(" \n\n", &[]), "");
+    }
+
+    #[test]
+    fn test_trim_keeps_hard_breaks() {
+        let text = "Roses are red  \nViolets are blue   \nLast line  \n\nSpaced \t\nout\n";
+        assert_eq!(
+            trim_trailing_whitespace(text, &[]),
+            "Roses are red  \nViolets are blue   \nLast line\n\nSpaced\nout\n"
+        );
+
+        let fenced = "```\ncode  \nmore\n```\nAfter  \nend\n";
+        assert_eq!(
+            trim_trailing_whitespace(fenced, &[]),
+            "```\ncode\nmore\n```\nAfter  \nend\n"
+        );
+    }
+
+    #[test]
+    fn test_skip_ranges() {
+        let text = "Prose  here\n```\nlet  x\t= 1;\n```\n";
+        let code = text.find("```").unwrap()..text.len();
+        let issues = scan_whitespace_issues_outside(text, &[code]);
+
+        assert_eq!(
+            issues,
+            vec![WhitespaceIssue { start: 5, end: 7, kind: WhitespaceIssueKind::MultipleSpaces }]
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use super::DocumentMetadata;
+use super::{DocumentMetadata, Heading, parser::MarkdownParser, utils};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use unicode_normalization::UnicodeNormalization;
 
 /// Severity level for diagnostics
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -116,38 +119,93 @@ impl Diagnostics {
         self.items.iter().filter(|d| d.severity == severity).collect()
     }
 
+    /// Keeps only diagnostics positioned on a line within `lines`
+    ///
+    /// Document-wide diagnostics (those without a line) are dropped.
+    pub fn within_lines(mut self, lines: &RangeInclusive<usize>) -> Self {
+        self.items
+            .retain(|diagnostic| diagnostic.line.is_some_and(|line| lines.contains(&line)));
+        self
+    }
+
     /// Runs all diagnostic checks on the document
     pub fn run(text: &str, metadata: &DocumentMetadata) -> Self {
         let mut diagnostics = Self::new();
 
-        diagnostics.check_duplicate_heading_ids(metadata);
+        diagnostics.check_duplicate_heading_anchors(metadata);
+        diagnostics.check_heading_level_skips(metadata);
         diagnostics.check_malformed_links(metadata);
         diagnostics.check_mixed_line_endings(text);
+        diagnostics.check_footnotes(text);
 
         diagnostics
     }
 
-    /// Checks for duplicate heading IDs
-    fn check_duplicate_heading_ids(&mut self, metadata: &DocumentMetadata) {
-        let mut seen_anchors: std::collections::HashMap<String, Vec<usize>> = std::collections::HashMap::new();
+    /// Checks for headings whose text slugifies to the same anchor
+    ///
+    /// Slugs use the renderer's rule on NFC-normalized text, so precomposed and decomposed spellings
+    /// of a heading collide too. The first heading using an anchor is reported once, naming the line
+    /// of its first repeat, and each repeat is reported naming the first heading's line and the
+    /// suffixed anchor it renders with.
+    fn check_duplicate_heading_anchors(&mut self, metadata: &DocumentMetadata) {
+        let mut first_headings: HashMap<String, &Heading> = HashMap::new();
+        let mut reported: HashSet<String> = HashSet::new();
 
-        for (idx, heading) in metadata.outline.iter().enumerate() {
-            if let Some(anchor) = &heading.anchor {
-                seen_anchors.entry(anchor.clone()).or_default().push(idx);
+        for heading in &metadata.outline {
+            let slug = utils::heading_anchor(&heading.text.nfc().collect::<String>());
+            let Some(first) = first_headings.get(&slug).copied() else {
+                first_headings.insert(slug, heading);
+                continue;
+            };
+
+            if reported.insert(slug.clone()) {
+                self.push(
+                    Diagnostic::warning(
+                        "duplicate-heading",
+                        format!("Heading anchor #{} is used again on line {}", slug, heading.line),
+                    )
+                    .at_position(first.line, 1)
+                    .with_source(format!("{} {}", "#".repeat(first.level as usize), first.text)),
+                );
             }
+
+            let rendered = heading.anchor.as_deref().unwrap_or(&slug);
+            self.push(
+                Diagnostic::warning(
+                    "duplicate-heading",
+                    format!(
+                        "Heading anchor #{} is already used on line {}; this heading renders as #{}",
+                        slug, first.line, rendered
+                    ),
+                )
+                .at_position(heading.line, 1)
+                .with_source(format!("{} {}", "#".repeat(heading.level as usize), heading.text)),
+            );
         }
+    }
 
-        for (anchor, indices) in seen_anchors {
-            if indices.len() > 1 {
-                for idx in &indices {
-                    if let Some(heading) = metadata.outline.get(*idx) {
-                        self.push(
-                            Diagnostic::warning("dup-heading-id", format!("Duplicate heading ID: {}", anchor))
-                                .at_position(*idx + 1, 1)
-                                .with_source(format!("{} {}", "#".repeat(heading.level as usize), heading.text)),
-                        );
-                    }
-                }
+    /// Checks for headings more than one level deeper than the heading before them
+    ///
+    /// The first heading may use any level, and a heading may always return to a shallower level.
+    fn check_heading_level_skips(&mut self, metadata: &DocumentMetadata) {
+        for pair in metadata.outline.windows(2) {
+            let (previous, heading) = (&pair[0], &pair[1]);
+            if heading.level > previous.level + 1 {
+                self.push(
+                    Diagnostic::warning(
+                        "heading-level-skip",
+                        format!(
+                            "Heading level skips from H{} to H{}: {}",
+                            previous.level, heading.level, heading.text
+                        ),
+                    )
+                    .at_position(heading.line, 1)
+                    .with_source(format!(
+                        "{} {}",
+                        "#".repeat(heading.level as usize),
+                        heading.text
+                    )),
+                );
             }
         }
     }
@@ -155,17 +213,17 @@ impl Diagnostics {
     /// Checks for malformed links (empty URLs, invalid protocols)
     fn check_malformed_links(&mut self, metadata: &DocumentMetadata) {
         for link in &metadata.links {
-            if link.url.is_empty() {
-                self.push(
-                    Diagnostic::warning("empty-link-url", "Link has empty URL")
-                        .with_source(format!("[{}]", link.title.as_deref().unwrap_or("text"))),
-                );
+            let diagnostic = if link.url.is_empty() {
+                Diagnostic::warning("empty-link-url", "Link has empty URL")
+                    .with_source(format!("[{}]", link.title.as_deref().unwrap_or("text")))
             } else if link.url.starts_with("javascript:") {
-                self.push(
-                    Diagnostic::error("javascript-link", format!("JavaScript URL detected: {}", link.url))
-                        .with_source(link.url.clone()),
-                );
-            }
+                Diagnostic::error("javascript-link", format!("JavaScript URL detected: {}", link.url))
+                    .with_source(link.url.clone())
+            } else {
+                continue;
+            };
+
+            self.push(if link.line > 0 { diagnostic.at_position(link.line, 1) } else { diagnostic });
         }
     }
 
@@ -181,4 +239,106 @@ impl Diagnostics {
             ));
         }
     }
+
+    /// Checks that every `[^id]` reference has a `[^id]:` definition and vice versa
+    ///
+    /// Ids are compared the way Comrak matches labels (case-insensitively, with inner whitespace
+    /// collapsed). Front matter, fenced code blocks, and inline code spans are skipped.
+    fn check_footnotes(&mut self, text: &str) {
+        let body_start = text.len() - MarkdownParser::extract_front_matter(text).0.len();
+        let line_offset = text[..body_start].matches('\n').count();
+
+        let mut definitions: Vec<(String, usize)> = Vec::new();
+        let mut references: Vec<(String, usize, usize)> = Vec::new();
+        let mut fence = utils::FenceTracker::default();
+
+        for (idx, line) in text[body_start..].lines().enumerate() {
+            let line_number = line_offset + idx + 1;
+            if fence.is_code_line(line) {
+                continue;
+            }
+
+            let trimmed = line.trim_start();
+            let mut scan_from = 0;
+            let indent = line.len() - trimmed.len();
+            if indent <= 3
+                && let Some(rest) = trimmed.strip_prefix("[^")
+                && let Some(end) = rest.find("]:")
+                && !rest[..end].trim().is_empty()
+                && !rest[..end].contains(['[', ']'])
+            {
+                definitions.push((rest[..end].to_string(), line_number));
+                scan_from = indent + 2 + end + 2;
+            }
+
+            for (column, id) in footnote_references(&line[scan_from..]) {
+                let column = line[..scan_from].chars().count() + column;
+                references.push((id.to_string(), line_number, column));
+            }
+        }
+
+        let defined: HashMap<String, usize> = definitions
+            .iter()
+            .map(|(id, line)| (normalize_footnote_id(id), *line))
+            .collect();
+        let referenced: HashSet<String> = references.iter().map(|(id, _, _)| normalize_footnote_id(id)).collect();
+
+        for (id, line, column) in &references {
+            if !defined.contains_key(&normalize_footnote_id(id)) {
+                self.push(
+                    Diagnostic::error(
+                        "footnote-undefined",
+                        format!("Footnote reference has no definition: {}", id),
+                    )
+                    .at_position(*line, *column)
+                    .with_source(format!("[^{}]", id)),
+                );
+            }
+        }
+
+        for (id, line) in &definitions {
+            if !referenced.contains(&normalize_footnote_id(id)) {
+                self.push(
+                    Diagnostic::warning("footnote-unused", format!("Footnote is never referenced: {}", id))
+                        .at_position(*line, 1)
+                        .with_source(format!("[^{}]:", id)),
+                );
+            }
+        }
+    }
+}
+
+/// Finds `[^id]` references in a line, returning the 1-indexed character column and the id
+///
+/// Inline code spans are skipped.
+fn footnote_references(line: &str) -> Vec<(usize, &str)> {
+    let bytes = line.as_bytes();
+    let mut references = Vec::new();
+    let mut cursor = 0;
+
+    while cursor < bytes.len() {
+        if bytes[cursor] == b'`' {
+            cursor += writer_core::code_span_len(line, cursor);
+            continue;
+        }
+
+        if line[cursor..].starts_with("[^")
+            && let Some(end) = line[cursor + 2..].find(']')
+        {
+            let id = &line[cursor + 2..cursor + 2 + end];
+            if !id.trim().is_empty() && !id.contains('[') {
+                references.push((line[..cursor].chars().count() + 1, id));
+            }
+            cursor += 2 + end + 1;
+            continue;
+        }
+
+        cursor += line[cursor..].chars().next().map_or(1, char::len_utf8);
+    }
+
+    references
+}
+
+fn normalize_footnote_id(id: &str) -> String {
+    id.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
 }
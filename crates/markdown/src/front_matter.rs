@@ -0,0 +1,259 @@
+use super::{FrontMatterFormat, MarkdownError, parser::MarkdownParser};
+use serde_yaml::Value as YamlValue;
+use std::collections::HashMap;
+
+/// Sets scalar `fields` in the document's front matter, adding a `format` block when there is none
+///
+/// New fields are appended in key order; see [`upsert_entries`] for how the block is edited.
+pub(crate) fn upsert_front_matter(
+    text: &str, fields: &HashMap<String, String>, format: FrontMatterFormat,
+) -> Result<String, MarkdownError> {
+    let mut keys: Vec<&str> = fields.keys().map(String::as_str).collect();
+    keys.sort();
+    if let Some(key) = keys.iter().find(|key| !is_bare_key(key)) {
+        return Err(MarkdownError::ParseError(format!(
+            "Invalid front matter key: {:?}",
+            key
+        )));
+    }
+
+    upsert_entries(text, &keys, format, |format, key| {
+        vec![entry(format, key, &fields[key])]
+    })
+}
+
+/// Sets a front matter sequence field to `values`, adding a `format` block when there is none
+///
+/// YAML lists are written in flow style (`key: [a, b]`) unless the existing entry is a block list,
+/// which is rewritten as one with the same item indentation. TOML lists are arrays of strings.
+pub(crate) fn set_front_matter_list(
+    text: &str, key: &str, values: &[String], format: FrontMatterFormat,
+) -> Result<String, MarkdownError> {
+    let (_, existing) = MarkdownParser::extract_front_matter(text);
+    let block_indent = existing
+        .raw
+        .as_deref()
+        .filter(|_| existing.format == Some(FrontMatterFormat::Yaml))
+        .and_then(|raw| yaml_block_list_indent(raw, key));
+
+    upsert_entries(text, &[key], format, |format, key| match (format, &block_indent) {
+        (FrontMatterFormat::Yaml, Some(indent)) if !values.is_empty() => std::iter::once(format!("{}:", key))
+            .chain(values.iter().map(|value| format!("{}- {}", indent, yaml_scalar(value))))
+            .collect(),
+        (FrontMatterFormat::Yaml, _) => {
+            let items: Vec<String> = values.iter().map(|value| yaml_scalar(value)).collect();
+            vec![format!("{}: [{}]", key, items.join(", "))]
+        }
+        (FrontMatterFormat::Toml, _) => {
+            let items: Vec<String> = values.iter().map(|value| quoted(value)).collect();
+            vec![format!("{} = [{}]", key, items.join(", "))]
+        }
+    })
+}
+
+/// Writes each key's rendered lines into the front matter, replacing an existing entry in place
+///
+/// Existing blocks are edited line by line in their own format, so unrelated keys, comments, and
+/// ordering are untouched: a field already present has its value (including any indented or
+/// multi-line continuation) replaced, and new fields are appended in the order given. The body
+/// after the block is copied unchanged.
+fn upsert_entries(
+    text: &str, keys: &[&str], format: FrontMatterFormat, render: impl Fn(FrontMatterFormat, &str) -> Vec<String>,
+) -> Result<String, MarkdownError> {
+    let crlf = text.contains("\r\n");
+    let ending = if crlf { "\r\n" } else { "\n" };
+    let (_, existing) = MarkdownParser::extract_front_matter(text);
+
+    let (Some(format), Some(raw)) = (existing.format, existing.raw.as_deref()) else {
+        let mut output = String::new();
+        output.push_str(format.delimiter());
+        output.push_str(ending);
+        for key in keys {
+            for line in render(format, key) {
+                output.push_str(&line);
+                output.push_str(ending);
+            }
+        }
+        output.push_str(format.delimiter());
+        output.push_str(ending);
+        if !text.is_empty() && !text.starts_with(['\r', '\n']) {
+            output.push_str(ending);
+        }
+        output.push_str(text);
+        return Ok(output);
+    };
+
+    let open_end = text.len() - text.trim_start().len() + format.delimiter().len();
+    let block_end = open_end + raw.len();
+    let (opening_rest, block) = raw.split_once('\n').unwrap_or((raw, ""));
+    let mut lines: Vec<String> =
+        if block.is_empty() { Vec::new() } else { block.split('\n').map(str::to_string).collect() };
+
+    for key in keys {
+        let mut entry = render(format, key);
+        if crlf {
+            entry.iter_mut().for_each(|line| line.push('\r'));
+        }
+
+        match format {
+            FrontMatterFormat::Yaml => upsert_yaml_lines(&mut lines, key, entry),
+            FrontMatterFormat::Toml => upsert_toml_lines(&mut lines, key, entry),
+        }
+    }
+
+    validate(&lf_only(&lines), format)?;
+    let updated = lines.join("\n");
+
+    let mut output = String::with_capacity(text.len() + updated.len());
+    output.push_str(&text[..open_end]);
+    output.push_str(opening_rest);
+    output.push('\n');
+    output.push_str(&updated);
+    output.push_str(&text[block_end..]);
+    Ok(output)
+}
+
+/// Indentation before the `-` of the first item when `key` holds a YAML block list
+fn yaml_block_list_indent(raw: &str, key: &str) -> Option<String> {
+    let mut lines = raw.lines();
+    lines.find(|line| {
+        line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| rest.trim().is_empty())
+    })?;
+    let item = lines.next()?;
+    let trimmed = item.trim_start();
+    trimmed
+        .starts_with('-')
+        .then(|| item[..item.len() - trimmed.len()].to_string())
+}
+
+/// Keys written without quoting in both YAML and TOML
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+}
+
+fn entry(format: FrontMatterFormat, key: &str, value: &str) -> String {
+    match format {
+        FrontMatterFormat::Yaml => format!("{}: {}", key, yaml_scalar(value)),
+        FrontMatterFormat::Toml => format!("{} = {}", key, quoted(value)),
+    }
+}
+
+/// Writes the value bare when YAML would read it back as the same string, and double-quoted otherwise
+///
+/// Quoting keeps values such as `true`, `42`, `a: b`, or `x # y` from changing type or being cut short.
+fn yaml_scalar(value: &str) -> String {
+    let plain = serde_yaml::from_str::<YamlValue>(&format!("key: {}", value))
+        .ok()
+        .and_then(|parsed| {
+            parsed
+                .get("key")
+                .and_then(YamlValue::as_str)
+                .map(|parsed| parsed == value)
+        })
+        .unwrap_or(false);
+
+    if plain && !value.contains(['\n', '\r']) { value.to_string() } else { quoted(value) }
+}
+
+/// A double-quoted string using the escapes YAML and TOML basic strings share
+fn quoted(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            ch if ch.is_control() => output.push_str(&format!("\\u{:04X}", ch as u32)),
+            ch => output.push(ch),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Replaces a top-level `key:` and its indented continuation lines, or appends the entry
+fn upsert_yaml_lines(lines: &mut Vec<String>, key: &str, entry: Vec<String>) {
+    let position = lines.iter().position(|existing| {
+        existing
+            .strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r']))
+    });
+
+    match position {
+        Some(start) => {
+            let mut end = start + 1;
+            while lines
+                .get(end)
+                .is_some_and(|next| next.starts_with([' ', '\t']) || next.starts_with('-'))
+            {
+                end += 1;
+            }
+            lines.splice(start..end, entry);
+        }
+        None => lines.extend(entry),
+    }
+}
+
+/// Replaces a top-level `key = ...` entry (however many lines its value spans), or inserts the entry
+/// after the last top-level line, since keys after a `[table]` header would belong to that table
+fn upsert_toml_lines(lines: &mut Vec<String>, key: &str, entry: Vec<String>) {
+    let top_level_end = lines
+        .iter()
+        .position(|existing| existing.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+
+    let position = lines[..top_level_end].iter().position(|existing| {
+        existing
+            .trim_start()
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    });
+
+    match position {
+        Some(start) => {
+            let mut end = start + 1;
+            while end < top_level_end && toml::from_str::<toml::Table>(&lf_only(&lines[start..end])).is_err() {
+                end += 1;
+            }
+            lines.splice(start..end, entry);
+        }
+        None => {
+            let insert_at = lines[..top_level_end]
+                .iter()
+                .rposition(|existing| !existing.trim().is_empty())
+                .map_or(0, |last| last + 1);
+            lines.splice(insert_at..insert_at, entry);
+        }
+    }
+}
+
+/// Joins block lines with `\n`, dropping the `\r` of CRLF endings (a lone `\r` is invalid TOML)
+fn lf_only(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| line.trim_end_matches('\r'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn validate(block: &str, format: FrontMatterFormat) -> Result<(), MarkdownError> {
+    let result = match format {
+        FrontMatterFormat::Yaml => serde_yaml::from_str::<YamlValue>(block)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        FrontMatterFormat::Toml => toml::from_str::<toml::Table>(block)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+    };
+
+    result.map_err(|e| MarkdownError::ParseError(format!("Front matter is not valid after update: {}", e)))
+}
@@ -0,0 +1,143 @@
+use comrak::Arena;
+use comrak::nodes::{AstNode, NodeValue};
+
+/// A heading's position in the parsed source (1-indexed lines and byte columns)
+struct HeadingSpan {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    level: u8,
+    setext: bool,
+}
+
+/// Prefixes each heading at `start_level` or deeper with its hierarchical number (`1`, `1.1`, ...)
+///
+/// Numbers follow nesting rather than absolute levels, as in the OPML outline: a heading nests under
+/// the closest preceding shallower one, so H1 then H3 numbers `1` then `1.1` instead of leaving an
+/// empty `0` component. A heading above `start_level` restarts the numbering. Only the parsed tree
+/// is changed, never the source text.
+pub(crate) fn number_headings<'a>(arena: &'a Arena<'a>, root: &'a AstNode<'a>, start_level: u8) {
+    number_headings_with(arena, root, start_level, |number| NodeValue::Text(number.into()));
+}
+
+/// Numbers headings like [`number_headings`] for HTML output, keeping heading anchors unchanged
+///
+/// The number is inserted as raw output rather than heading text, so the renderer's `header_ids`
+/// still slugify only the written heading and existing `#anchor` links keep resolving.
+pub(crate) fn number_headings_html<'a>(arena: &'a Arena<'a>, root: &'a AstNode<'a>, start_level: u8) {
+    number_headings_with(arena, root, start_level, NodeValue::Raw);
+}
+
+fn number_headings_with<'a>(
+    arena: &'a Arena<'a>, root: &'a AstNode<'a>, start_level: u8, number_node: impl Fn(String) -> NodeValue,
+) {
+    let start_level = start_level.clamp(1, 6);
+    let mut open_levels: Vec<u8> = Vec::new();
+    let mut counters: Vec<usize> = Vec::new();
+
+    let headings: Vec<(&'a AstNode<'a>, u8)> = root
+        .descendants()
+        .filter_map(|node| match &node.data.borrow().value {
+            NodeValue::Heading(heading) => Some((node, heading.level)),
+            _ => None,
+        })
+        .collect();
+
+    for (node, level) in headings {
+        if level < start_level {
+            open_levels.clear();
+            counters.clear();
+            continue;
+        }
+
+        while open_levels.last().is_some_and(|open| *open >= level) {
+            open_levels.pop();
+        }
+        open_levels.push(level);
+        counters.truncate(open_levels.len());
+        counters.resize(open_levels.len(), 0);
+        if let Some(last) = counters.last_mut() {
+            *last += 1;
+        }
+
+        let number = counters.iter().map(usize::to_string).collect::<Vec<_>>().join(".");
+        node.prepend(arena.alloc(number_node(format!("{} ", number)).into()));
+    }
+}
+
+/// Rewrites every heading in `body` to `level + delta`, clamped to 1..=6
+///
+/// ATX headings only have their `#` run replaced; setext headings are converted to ATX with their
+/// content lines joined. All other lines are copied byte-for-byte.
+pub(crate) fn shift_headings<'a>(root: &'a AstNode<'a>, body: &str, delta: i8) -> String {
+    let headings: Vec<HeadingSpan> = root
+        .descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            match &data.value {
+                NodeValue::Heading(heading) => Some(HeadingSpan {
+                    start_line: data.sourcepos.start.line,
+                    start_column: data.sourcepos.start.column,
+                    end_line: data.sourcepos.end.line,
+                    level: heading.level,
+                    setext: heading.setext,
+                }),
+                _ => None,
+            }
+        })
+        .collect();
+
+    let lines: Vec<&str> = body.split_inclusive('\n').collect();
+    let mut output = String::with_capacity(body.len());
+    let mut next_line = 1;
+
+    for heading in headings {
+        if heading.start_line < next_line || heading.start_line > lines.len() {
+            continue;
+        }
+        for line in &lines[next_line - 1..heading.start_line - 1] {
+            output.push_str(line);
+        }
+
+        let level = (heading.level as i16 + delta as i16).clamp(1, 6) as usize;
+        let first = lines[heading.start_line - 1];
+        let prefix_len = (heading.start_column - 1).min(first.len());
+        let (prefix, rest) = first.split_at(prefix_len);
+
+        if heading.setext {
+            let end_line = heading.end_line.clamp(heading.start_line, lines.len());
+            let content_lines = &lines[heading.start_line - 1..end_line - 1];
+            let ending = &first[first.trim_end_matches(['\r', '\n']).len()..];
+            let text = content_lines
+                .iter()
+                .map(|line| line.get(prefix_len..).unwrap_or(line).trim())
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            output.push_str(prefix);
+            output.push_str(&"#".repeat(level));
+            output.push(' ');
+            output.push_str(&text);
+            output.push_str(if ending.is_empty() { "\n" } else { ending });
+            if end_line == lines.len() && !body.ends_with('\n') {
+                output.truncate(output.trim_end_matches(['\r', '\n']).len());
+            }
+            next_line = end_line + 1;
+        } else {
+            let indent = rest.len() - rest.trim_start_matches(' ').len();
+            let hashes = rest[indent..].bytes().take_while(|byte| *byte == b'#').count();
+            output.push_str(prefix);
+            output.push_str(&rest[..indent]);
+            output.push_str(&"#".repeat(level));
+            output.push_str(&rest[indent + hashes..]);
+            next_line = heading.start_line + 1;
+        }
+    }
+
+    for line in lines.iter().skip(next_line - 1) {
+        output.push_str(line);
+    }
+
+    output
+}
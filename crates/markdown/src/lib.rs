@@ -1,14 +1,25 @@
 use comrak::{Arena, Options, parse_document};
-use diagnostics::Diagnostics;
 use parser::MarkdownParser;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::{Range, RangeInclusive};
+use std::time::Instant;
 use transformer::{DocxTransformer, MarkdownTransformer};
+use unicode_width::UnicodeWidthStr;
 
 mod diagnostics;
+mod front_matter;
+mod headings;
 mod parser;
+mod sanitize;
+mod tables;
+mod tags;
 mod transformer;
 mod utils;
+mod wikilinks;
+
+pub use diagnostics::{Diagnostic, DiagnosticSeverity, Diagnostics};
 
 /// Front matter format for documents
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -38,6 +49,10 @@ pub enum MarkdownProfile {
     /// Disables: raw HTML (treated as untrusted)
     #[default]
     GfmSafe,
+    /// GitHub Flavored Markdown that keeps raw HTML, filtered through an allowlist sanitizer
+    /// Keeps harmless tags such as `<mark>`, `<sub>`, `<sup>`, and `<br>`; drops scripts,
+    /// event handlers, styles, and `javascript:` URLs
+    GfmSanitized,
     /// Extended profile with all safe features including front matter
     /// Enables: GFM features + footnotes + description lists + front matter
     Extended,
@@ -60,7 +75,7 @@ impl MarkdownProfile {
                     autolink: true,
                     tasklist: true,
                     superscript: false,
-                    header_ids: Some("heading-".to_string()),
+                    header_ids: Some(utils::HEADING_ID_PREFIX.to_string()),
                     footnotes: true,
                     description_lists: true,
                     front_matter_delimiter: None,
@@ -78,6 +93,12 @@ impl MarkdownProfile {
                 parse: comrak::options::Parse::default(),
                 render: comrak::options::Render { r#unsafe: false, sourcepos: true, ..Default::default() },
             },
+            MarkdownProfile::GfmSanitized => {
+                let mut options = MarkdownProfile::GfmSafe.to_options();
+                options.extension.tagfilter = false;
+                options.render.r#unsafe = true;
+                options
+            }
             MarkdownProfile::Extended => Options {
                 extension: comrak::options::Extension {
                     strikethrough: true,
@@ -86,7 +107,7 @@ impl MarkdownProfile {
                     autolink: true,
                     tasklist: true,
                     superscript: false,
-                    header_ids: Some("heading-".to_string()),
+                    header_ids: Some(utils::HEADING_ID_PREFIX.to_string()),
                     footnotes: true,
                     description_lists: true,
                     front_matter_delimiter: Some("---".to_string()),
@@ -107,6 +128,11 @@ impl MarkdownProfile {
         }
     }
 
+    /// Returns true if rendered HTML must pass through the allowlist sanitizer
+    pub fn sanitizes_html(&self) -> bool {
+        matches!(self, MarkdownProfile::GfmSanitized)
+    }
+
     /// Returns true if this profile supports front matter
     pub fn supports_front_matter(&self) -> bool {
         matches!(self, MarkdownProfile::Extended)
@@ -119,6 +145,9 @@ pub struct Heading {
     pub level: u8,
     pub text: String,
     pub anchor: Option<String>,
+    /// Source line (1-indexed, counting front matter) where the heading starts
+    #[serde(default)]
+    pub line: usize,
 }
 
 /// Parsed front matter data
@@ -127,6 +156,45 @@ pub struct FrontMatter {
     pub raw: Option<String>,
     pub format: Option<FrontMatterFormat>,
     pub fields: HashMap<String, String>,
+    /// Sequence fields whose items are scalars (e.g. `aliases`, `tags`)
+    #[serde(default)]
+    pub lists: HashMap<String, Vec<String>>,
+}
+
+impl FrontMatter {
+    /// Returns the values declared for `key`, accepting either a list or a single scalar
+    pub fn list(&self, key: &str) -> Vec<String> {
+        if let Some(values) = self.lists.get(key) {
+            return values.clone();
+        }
+
+        self.fields
+            .get(key)
+            .map(|value| vec![value.clone()])
+            .unwrap_or_default()
+    }
+
+    /// Returns the trimmed, de-duplicated `aliases` declared in the front matter
+    pub fn aliases(&self) -> Vec<String> {
+        let mut aliases: Vec<String> = Vec::new();
+        for alias in self.list("aliases") {
+            let alias = alias.trim();
+            if !alias.is_empty() && !aliases.iter().any(|existing| existing.eq_ignore_ascii_case(alias)) {
+                aliases.push(alias.to_string());
+            }
+        }
+        aliases
+    }
+
+    /// Whether the `draft` field is truthy (`true`, `yes`, `on`, or `1`); a missing field is not a draft
+    pub fn is_draft(&self) -> bool {
+        self.fields.get("draft").is_some_and(|value| {
+            matches!(
+                value.trim().trim_matches(['"', '\'']).to_ascii_lowercase().as_str(),
+                "true" | "yes" | "on" | "1"
+            )
+        })
+    }
 }
 
 /// Extracted document metadata from Markdown parsing
@@ -138,10 +206,22 @@ pub struct DocumentMetadata {
     pub outline: Vec<Heading>,
     /// All link references found in the document
     pub links: Vec<LinkRef>,
+    /// All images embedded in the document
+    #[serde(default)]
+    pub images: Vec<ImageRef>,
     /// Number of task list items (checked and unchecked)
     pub task_items: TaskStats,
     /// Estimated word count
     pub word_count: usize,
+    /// Sentences across paragraph prose (code blocks and headings excluded)
+    #[serde(default)]
+    pub sentence_count: usize,
+    /// Words across paragraph prose (code blocks and headings excluded)
+    #[serde(default)]
+    pub prose_word_count: usize,
+    /// Number of paragraphs, including those nested in lists and blockquotes
+    #[serde(default)]
+    pub paragraph_count: usize,
     /// Front matter data if present
     pub front_matter: FrontMatter,
 }
@@ -153,15 +233,90 @@ pub struct TaskStats {
     pub completed: usize,
 }
 
+/// Prose word count for a heading's section, including its subsections
+///
+/// The preamble (prose before the first heading) is reported as level 0 with no heading.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionStat {
+    /// Heading text, or `None` for the preamble
+    pub heading: Option<String>,
+    pub level: u8,
+    pub word_count: usize,
+}
+
+/// Task list items under one heading, not counting its subsections
+///
+/// Tasks before the first heading are reported as level 0 with no heading.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionTaskStats {
+    /// Heading text, or `None` for the preamble
+    pub heading: Option<String>,
+    pub level: u8,
+    pub total: usize,
+    pub completed: usize,
+}
+
+/// Reading speed behind `DocStats.reading_time_minutes`
+pub const READING_WORDS_PER_MINUTE: usize = 200;
+
+/// Document statistics returned by [`MarkdownEngine::stats`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocStats {
+    pub word_count: usize,
+    pub prose_word_count: usize,
+    pub sentence_count: usize,
+    pub paragraph_count: usize,
+    /// Minutes to read the body at [`READING_WORDS_PER_MINUTE`], rounded up
+    pub reading_time_minutes: usize,
+    pub heading_count: usize,
+    pub task_items: TaskStats,
+    /// Completed share of task items, or `None` when the document has none
+    pub task_completion: Option<f64>,
+    pub link_count: usize,
+    pub tags: Vec<String>,
+}
+
 /// A link reference found in the document
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LinkRef {
     pub url: String,
     pub title: Option<String>,
+    /// True when the URL points at a file path rather than a remote resource or fragment
+    #[serde(default)]
+    pub is_local: bool,
+    /// Source line (1-indexed, counting front matter) where the link starts
+    #[serde(default)]
+    pub line: usize,
 }
 
-/// Result of rendering Markdown to HTML with metadata and diagnostics
+/// An image embedded in the document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageRef {
+    /// Alt text, flattened to plain text
+    pub alt: String,
+    pub url: String,
+    /// True when the URL points at a file path rather than a remote resource or data URI
+    pub is_local: bool,
+    /// Source line (1-indexed, counting front matter) where the image starts
+    #[serde(default)]
+    pub line: usize,
+}
+
+/// A fenced or indented code block
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// First word of the fence's info string; `None` for indented blocks and bare fences
+    pub language: Option<String>,
+    /// Block content exactly as parsed, including its trailing newline
+    pub content: String,
+    pub fenced: bool,
+    /// First and last source lines (1-indexed, counting front matter), fences included
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Result of rendering Markdown to HTML with metadata and diagnostics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RenderResult {
     /// The rendered HTML output
     pub html: String,
@@ -169,6 +324,18 @@ pub struct RenderResult {
     pub metadata: DocumentMetadata,
     /// Diagnostics (lint warnings/errors) found in the document
     pub diagnostics: Diagnostics,
+    /// Per-stage timings, present only when requested via [`MarkdownEngine::render_with_timings`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<RenderTimings>,
+}
+
+/// Wall-clock duration of each render stage in milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RenderTimings {
+    pub parse_ms: f64,
+    pub metadata_ms: f64,
+    pub html_ms: f64,
+    pub diagnostics_ms: f64,
 }
 
 /// Errors that can occur during Markdown processing
@@ -196,6 +363,93 @@ pub enum PdfNode {
     Footnote { id: String, content: String },
 }
 
+/// A node of the untransformed Comrak parse tree
+///
+/// Serialized shape: `{ "type", "sourcepos", "literal"?, "level"?, "children" }`. `type` is the
+/// Comrak `NodeValue` variant name, so it tracks the parser rather than Writer's own node types.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawAstNode {
+    /// Comrak node variant name (e.g. `Heading`, `Paragraph`, `Text`)
+    #[serde(rename = "type")]
+    pub node_type: Cow<'static, str>,
+    /// Source span as `[start_line, start_column, end_line, end_column]` (1-indexed)
+    pub sourcepos: [usize; 4],
+    /// Literal content for text, code, HTML, and front matter nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub literal: Option<String>,
+    /// Heading level (1-6) for heading nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<u8>,
+    pub children: Vec<RawAstNode>,
+}
+
+/// Source span of a [`MarkdownAstNode`], 1-indexed with inclusive end, in lines and byte columns
+/// of the original document text (front matter included)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AstSourcepos {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// Node kinds of Writer's Markdown syntax tree
+///
+/// Owned by Writer rather than mirrored from Comrak, so the serialized names stay stable across
+/// parser upgrades. Nodes without a dedicated kind are reported as `other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkdownNodeKind {
+    Document,
+    FrontMatter,
+    Heading,
+    Paragraph,
+    BlockQuote,
+    List,
+    Item,
+    TaskItem,
+    CodeBlock,
+    HtmlBlock,
+    ThematicBreak,
+    Table,
+    TableRow,
+    TableCell,
+    FootnoteDefinition,
+    FootnoteReference,
+    Text,
+    Code,
+    HtmlInline,
+    Emphasis,
+    Strong,
+    Strikethrough,
+    Link,
+    Image,
+    SoftBreak,
+    LineBreak,
+    Other,
+}
+
+/// A node of Writer's Markdown syntax tree
+///
+/// Serialized shape: `{ "type", "sourcepos", "level"?, "ordered"?, "text"?, "children"? }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkdownAstNode {
+    #[serde(rename = "type")]
+    pub kind: MarkdownNodeKind,
+    pub sourcepos: AstSourcepos,
+    /// Heading level (1-6) for heading nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<u8>,
+    /// Ordering flag for list nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ordered: Option<bool>,
+    /// Literal content for text, code, HTML, and front matter nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<MarkdownAstNode>,
+}
+
 /// Result of rendering Markdown for PDF export
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PdfRenderResult {
@@ -236,6 +490,19 @@ pub struct PdfListItem {
     pub content: String,
 }
 
+/// How local images referenced by an HTML export are packaged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetMode {
+    /// Leave image URLs as written
+    #[default]
+    None,
+    /// Embed local images as base64 `data:` URIs
+    Inline,
+    /// Copy local images into an `assets/` folder next to the export and point at the copies
+    CopyFolder,
+}
+
 /// Options for HTML export
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExportOptions {
@@ -255,6 +522,12 @@ pub struct ExportOptions {
     pub custom_css: Option<String>,
     /// External CSS URLs to link
     pub external_css_urls: Vec<String>,
+    /// How local images are bundled with the export
+    pub asset_mode: AssetMode,
+    /// Prefix headings with hierarchical numbers (`1`, `1.1`, ...); the source is not changed
+    pub number_headings: bool,
+    /// Shallowest heading level that gets a number when `number_headings` is set (1-6)
+    pub number_headings_from: u8,
 }
 
 impl Default for ExportOptions {
@@ -268,10 +541,98 @@ impl Default for ExportOptions {
             include_metadata: true,
             custom_css: None,
             external_css_urls: Vec::new(),
+            asset_mode: AssetMode::None,
+            number_headings: false,
+            number_headings_from: 1,
         }
     }
 }
 
+/// Paper size of a DOCX export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageSize {
+    #[default]
+    A4,
+    Letter,
+    Legal,
+}
+
+impl PageSize {
+    /// Width and height in twips (1/1440 inch)
+    pub fn twips(self) -> (u32, u32) {
+        match self {
+            PageSize::A4 => (11906, 16838),
+            PageSize::Letter => (12240, 15840),
+            PageSize::Legal => (12240, 20160),
+        }
+    }
+}
+
+/// Page margins in twips (1/1440 inch)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Margins {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+impl Default for Margins {
+    /// The margins docx-rs writes when none are set
+    fn default() -> Self {
+        Self { top: 1985, right: 1701, bottom: 1701, left: 1701 }
+    }
+}
+
+/// Page setup and base font for DOCX export; the defaults reproduce the plain docx-rs output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocxOptions {
+    pub page_size: PageSize,
+    pub margins_twips: Margins,
+    /// Font family for body text; headings and lists inherit it
+    pub base_font: Option<String>,
+    /// Body text size in half-points (24 = 12pt); 0 keeps Word's default
+    pub base_font_size: usize,
+    /// Prefix headings with hierarchical numbers, as in [`ExportOptions::number_headings`]
+    pub number_headings: bool,
+    /// Shallowest heading level that gets a number when `number_headings` is set (1-6)
+    pub number_headings_from: u8,
+}
+
+impl Default for DocxOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::default(),
+            margins_twips: Margins::default(),
+            base_font: None,
+            base_font_size: 0,
+            number_headings: false,
+            number_headings_from: 1,
+        }
+    }
+}
+
+/// Page geometry used by [`MarkdownEngine::estimate_pages`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageEstimateConfig {
+    /// Body lines that fit on one page
+    pub lines_per_page: usize,
+    /// Columns per line before text wraps; wide (CJK) characters take two
+    pub chars_per_line: usize,
+    /// Extra lines charged per heading for its larger type and surrounding space
+    pub heading_extra_lines: usize,
+}
+
+impl Default for PageEstimateConfig {
+    /// Roughly A4 with 2.5cm margins and 11pt body text
+    fn default() -> Self {
+        Self { lines_per_page: 46, chars_per_line: 80, heading_extra_lines: 1 }
+    }
+}
+
 impl ExportOptions {
     /// Creates options for standalone export with all features
     pub fn standalone() -> Self {
@@ -284,6 +645,9 @@ impl ExportOptions {
             include_metadata: true,
             custom_css: None,
             external_css_urls: Vec::new(),
+            asset_mode: AssetMode::None,
+            number_headings: false,
+            number_headings_from: 1,
         }
     }
 
@@ -298,6 +662,9 @@ impl ExportOptions {
             include_metadata: false,
             custom_css: None,
             external_css_urls: Vec::new(),
+            asset_mode: AssetMode::None,
+            number_headings: false,
+            number_headings_from: 1,
         }
     }
 }
@@ -329,14 +696,366 @@ impl MarkdownEngine {
         };
 
         let root = parse_document(&arena, body_text, &options);
-        Ok(MarkdownParser::build_metadata(root, body_text, front_matter))
+        Ok(MarkdownParser::build_metadata(root, text, body_text, front_matter))
+    }
+
+    /// Extracts document metadata and the plaintext rendering of the body from a single parse
+    ///
+    /// For callers such as the indexer that need both; the plaintext is the same as
+    /// [`MarkdownEngine::render_for_text`] produces.
+    pub fn metadata_with_text(
+        &self, text: &str, profile: MarkdownProfile,
+    ) -> Result<(DocumentMetadata, String), MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let (body_text, front_matter) = if profile.supports_front_matter() {
+            MarkdownParser::extract_front_matter(text)
+        } else {
+            (text, FrontMatter::default())
+        };
+
+        let root = parse_document(&arena, body_text, &options);
+        let metadata = MarkdownParser::build_metadata(root, text, body_text, front_matter);
+        Ok((metadata, MarkdownTransformer::transform_to_plaintext(root)))
+    }
+
+    /// Collects word, structure, task, link, and tag statistics for the document
+    ///
+    /// Word, structure, task, and link counts come from one parse. Tags are gathered by a separate
+    /// line scan of the text, the same one the tag index uses, so the two always agree.
+    pub fn stats(&self, text: &str, profile: MarkdownProfile) -> Result<DocStats, MarkdownError> {
+        let metadata = self.metadata(text, profile)?;
+        let task_completion = (metadata.task_items.total > 0)
+            .then(|| metadata.task_items.completed as f64 / metadata.task_items.total as f64);
+
+        Ok(DocStats {
+            word_count: metadata.word_count,
+            prose_word_count: metadata.prose_word_count,
+            sentence_count: metadata.sentence_count,
+            paragraph_count: metadata.paragraph_count,
+            reading_time_minutes: metadata.word_count.div_ceil(READING_WORDS_PER_MINUTE),
+            heading_count: metadata.outline.len(),
+            task_completion,
+            task_items: metadata.task_items,
+            link_count: metadata.links.len(),
+            tags: tags::collect_tags(text),
+        })
+    }
+
+    /// Counts prose words per section, where a section runs until the next heading of the same or higher level
+    ///
+    /// Words are counted as in `prose_word_count`, so code blocks and heading text are left out. A
+    /// preamble section is included only when there is prose before the first heading.
+    pub fn section_word_counts(&self, text: &str, profile: MarkdownProfile) -> Result<Vec<SectionStat>, MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let body = if profile.supports_front_matter() { self.body(text) } else { text };
+        let root = parse_document(&arena, body, &options);
+        Ok(MarkdownParser::section_word_counts(root))
+    }
+
+    /// Counts task list items per heading section, for per-section progress
+    ///
+    /// Each task, nested or not, counts once toward the nearest heading above it, so the totals add up
+    /// to `DocStats.task_items`. Every heading gets an entry; a preamble entry is included only when
+    /// there are tasks before the first heading.
+    pub fn task_rollup(&self, text: &str, profile: MarkdownProfile) -> Result<Vec<SectionTaskStats>, MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let body = if profile.supports_front_matter() { self.body(text) } else { text };
+        let root = parse_document(&arena, body, &options);
+        Ok(MarkdownParser::task_rollup(root))
+    }
+
+    /// Flips the checkbox of the task list item that starts on `line` (1-indexed, counting front matter)
+    ///
+    /// `[ ]` becomes `[x]`, and a checked box (`[x]` or `[X]`) becomes `[ ]`; nothing else in the text
+    /// changes. A line that does not start a task list item, such as a plain list item or a checkbox
+    /// inside a code block, is an error.
+    pub fn toggle_task(&self, text: &str, line: usize) -> Result<String, MarkdownError> {
+        let arena = Arena::new();
+        let options = MarkdownProfile::Extended.to_options();
+
+        let body = self.body(text);
+        let front_matter_lines = text[..text.len() - body.len()].matches('\n').count();
+        let not_a_task = || MarkdownError::ParseError(format!("Line {} is not a task list item", line));
+
+        let body_line = line
+            .checked_sub(front_matter_lines)
+            .filter(|line| *line > 0)
+            .ok_or_else(not_a_task)?;
+        let root = parse_document(&arena, body, &options);
+        let column = MarkdownParser::task_item_column(root, body_line).ok_or_else(not_a_task)?;
+
+        let line_start = match line - 1 {
+            0 => 0,
+            skip => text
+                .match_indices('\n')
+                .nth(skip - 1)
+                .map_or(text.len(), |(at, _)| at + 1),
+        };
+        let line_text = text[line_start..].split('\n').next().unwrap_or_default();
+        let marker = line_text
+            .get(column.saturating_sub(1)..)
+            .and_then(|item| item.find('['))
+            .map(|offset| line_start + column.saturating_sub(1) + offset)
+            .filter(|at| text[*at..].len() >= 3 && text.as_bytes()[*at + 2] == b']')
+            .ok_or_else(not_a_task)?;
+
+        let checked = match &text[marker + 1..marker + 2] {
+            " " => "x",
+            "x" | "X" => " ",
+            _ => return Err(not_a_task()),
+        };
+
+        let mut toggled = String::with_capacity(text.len());
+        toggled.push_str(&text[..marker + 1]);
+        toggled.push_str(checked);
+        toggled.push_str(&text[marker + 2..]);
+        Ok(toggled)
+    }
+
+    /// Inserts `content` at the end of the section whose heading has the anchor `heading_anchor`
+    ///
+    /// The anchor may be given with or without the leading `#` and `heading-` prefix. The section runs
+    /// until the next heading of the same or higher level, so the content lands after any subsections.
+    /// It is separated from the section's last line by one blank line, and the blank lines that
+    /// followed the section are kept after it. When no heading matches, the content is appended at the
+    /// end of the document if `append_if_missing` is set, and an error is returned otherwise.
+    pub fn append_to_section(
+        &self, text: &str, heading_anchor: &str, content: &str, append_if_missing: bool,
+    ) -> Result<String, MarkdownError> {
+        let outline = self.metadata(text, MarkdownProfile::Extended)?.outline;
+        let wanted = heading_anchor.trim().trim_start_matches('#');
+        let wanted = wanted.strip_prefix(utils::HEADING_ID_PREFIX).unwrap_or(wanted);
+
+        let position = outline.iter().position(|heading| {
+            heading
+                .anchor
+                .as_deref()
+                .and_then(|anchor| anchor.strip_prefix(utils::HEADING_ID_PREFIX))
+                == Some(wanted)
+        });
+        let section_end = match position {
+            Some(idx) => outline[idx + 1..]
+                .iter()
+                .find(|heading| heading.level <= outline[idx].level)
+                .and_then(|next| text.match_indices('\n').nth(next.line.checked_sub(2)?))
+                .map_or(text.len(), |(at, _)| at + 1),
+            None if append_if_missing => text.len(),
+            None => {
+                return Err(MarkdownError::ParseError(format!(
+                    "No heading with anchor '{}'",
+                    heading_anchor
+                )));
+            }
+        };
+
+        let section = &text[..section_end];
+        let kept = section.trim_end().len();
+        let last_line_end = section[kept..].find('\n').map_or(section_end, |at| kept + at);
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+
+        let mut updated = String::with_capacity(text.len() + content.len() + 4);
+        updated.push_str(&text[..last_line_end]);
+        if last_line_end > 0 {
+            updated.push_str(newline);
+            updated.push_str(newline);
+        }
+        updated.push_str(content.trim_matches(|ch| ch == '\n' || ch == '\r'));
+        match &text[last_line_end..section_end] {
+            "" => updated.push_str(newline),
+            gap => updated.push_str(gap),
+        }
+        updated.push_str(&text[section_end..]);
+        Ok(updated)
+    }
+
+    /// Extracts front matter without parsing the document body.
+    pub fn front_matter(&self, text: &str) -> FrontMatter {
+        MarkdownParser::extract_front_matter(text).1
+    }
+
+    /// Returns the document's tags: front matter `tags` entries followed by inline `#tags`
+    ///
+    /// Tags are de-duplicated ignoring case, keeping the first spelling seen.
+    pub fn tags(&self, text: &str) -> Vec<String> {
+        tags::collect_tags(text)
+    }
+
+    /// Returns the document body that follows its front matter block (the whole text when there is none)
+    pub fn body<'a>(&self, text: &'a str) -> &'a str {
+        MarkdownParser::extract_front_matter(text).0
+    }
+
+    /// Splits a document into its body and parsed front matter, for exporting the body alone
+    ///
+    /// Handles both `---` (YAML) and `+++` (TOML) blocks. Only the line ending that closes the block is
+    /// removed (`\n` or `\r\n`), so a blank line after it stays at the top of the body. Without front
+    /// matter the text is returned unchanged with an empty `FrontMatter`.
+    pub fn strip_front_matter(&self, text: &str) -> (String, FrontMatter) {
+        let (body, front_matter) = MarkdownParser::extract_front_matter(text);
+        if front_matter.format.is_none() {
+            return (text.to_string(), front_matter);
+        }
+
+        let body = body.strip_prefix("\r\n").unwrap_or(body);
+        (body.to_string(), front_matter)
+    }
+
+    /// Inserts or updates scalar front matter fields, leaving unrelated keys and the body unchanged
+    ///
+    /// An existing block keeps its own format; `format` is used when a new block has to be added.
+    /// Values are quoted when needed to stay strings, and new keys are appended in sorted order.
+    pub fn upsert_front_matter(
+        &self, text: &str, fields: &HashMap<String, String>, format: FrontMatterFormat,
+    ) -> Result<String, MarkdownError> {
+        front_matter::upsert_front_matter(text, fields, format)
+    }
+
+    /// Normalizes a tag name (trimming a leading `#`), returning `None` when it is not a valid tag
+    pub fn normalize_tag(&self, tag: &str) -> Option<String> {
+        tags::normalize_tag(tag).map(str::to_string)
+    }
+
+    /// Renames a tag (and its `tag/child` descendants) in front matter and inline `#tags`
+    ///
+    /// Matching ignores case and a leading `#` on either name. Returns `Ok(None)` when the text
+    /// does not use the tag; other text, including code, is never modified.
+    pub fn rename_tag(&self, text: &str, old_tag: &str, new_tag: &str) -> Result<Option<String>, MarkdownError> {
+        let old_tag = tags::normalize_tag(old_tag)
+            .ok_or_else(|| MarkdownError::ParseError(format!("Invalid tag name: {}", old_tag)))?;
+        let new_tag = tags::normalize_tag(new_tag)
+            .ok_or_else(|| MarkdownError::ParseError(format!("Invalid tag name: {}", new_tag)))?;
+
+        Ok(tags::rename_tag(text, old_tag, new_tag))
+    }
+
+    /// Adds and removes tags in front matter (or, with `inline`, as `#tags`), returning `Ok(None)` when nothing changes
+    ///
+    /// Adding a tag the document already has, in either place, is a no-op; removal takes the tag
+    /// out of both front matter and inline text. Code is never modified.
+    pub fn edit_tags(
+        &self, text: &str, add: &[String], remove: &[String], inline: bool,
+    ) -> Result<Option<String>, MarkdownError> {
+        tags::edit_tags(
+            text,
+            &tags::normalize_tags(add)?,
+            &tags::normalize_tags(remove)?,
+            inline,
+        )
+    }
+
+    /// Promotes (negative `delta`) or demotes (positive `delta`) every heading, clamping levels to 1..=6
+    ///
+    /// Heading lines are located by source position and only their markers change; setext headings
+    /// become ATX. Everything else, including front matter, is preserved byte-for-byte.
+    pub fn shift_headings(&self, text: &str, delta: i8, profile: MarkdownProfile) -> Result<String, MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let body = if profile.supports_front_matter() { self.body(text) } else { text };
+        let head = &text[..text.len() - body.len()];
+
+        let root = parse_document(&arena, body, &options);
+        Ok(format!("{}{}", head, headings::shift_headings(root, body, delta)))
+    }
+
+    /// Aligns the columns of every GFM table, leaving all other content byte-for-byte unchanged
+    ///
+    /// Tables are only recognised under profiles with the table extension, so `StrictCommonMark`
+    /// returns the text as-is.
+    pub fn format_tables(&self, text: &str, profile: MarkdownProfile) -> Result<String, MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let body = if profile.supports_front_matter() { self.body(text) } else { text };
+        let head = &text[..text.len() - body.len()];
+
+        let root = parse_document(&arena, body, &options);
+        Ok(format!("{}{}", head, tables::format_tables(root, body)))
+    }
+
+    /// Returns the byte ranges of the lines covered by fenced and indented code blocks
+    ///
+    /// Ranges are offsets into `text`, including any front matter before the body.
+    pub fn code_block_ranges(&self, text: &str, profile: MarkdownProfile) -> Result<Vec<Range<usize>>, MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let body = if profile.supports_front_matter() { self.body(text) } else { text };
+        let offset = text.len() - body.len();
+
+        let root = parse_document(&arena, body, &options);
+        Ok(utils::code_block_ranges(root, body)
+            .into_iter()
+            .map(|range| range.start + offset..range.end + offset)
+            .collect())
+    }
+
+    /// Lists the document's code blocks in order, with their language and source lines
+    pub fn extract_code_blocks(&self, text: &str, profile: MarkdownProfile) -> Result<Vec<CodeBlock>, MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let body = if profile.supports_front_matter() { self.body(text) } else { text };
+        let line_offset = text[..text.len() - body.len()].matches('\n').count();
+
+        let root = parse_document(&arena, body, &options);
+        Ok(utils::code_blocks(root)
+            .into_iter()
+            .map(|block| CodeBlock {
+                start_line: block.start_line + line_offset,
+                end_line: block.end_line + line_offset,
+                ..block
+            })
+            .collect())
+    }
+
+    /// Lists the targets of the document's `[[wikilinks]]` in order, outside code and front matter
+    ///
+    /// Heading and label parts are dropped, so `[[Note#Intro|intro]]` gives `Note`; links to a heading
+    /// in the same document are skipped.
+    pub fn wikilink_targets(&self, text: &str) -> Vec<String> {
+        wikilinks::wikilink_targets(text)
+    }
+
+    /// Rewrites `[[Note]]`-style wikilinks to standard Markdown links
+    ///
+    /// The resolver maps a wikilink target to an href; `[[Note|alias]]` uses the alias as link text
+    /// and `[[Note#Heading]]` appends the heading anchor. Unresolved links are left unchanged.
+    pub fn resolve_wikilinks(
+        &self, text: &str, resolver: impl Fn(&str) -> Option<String>,
+    ) -> Result<String, MarkdownError> {
+        Ok(wikilinks::rewrite_wikilinks(text, resolver))
     }
 
     /// Renders Markdown text to HTML using the specified profile
     pub fn render(&self, text: &str, profile: MarkdownProfile) -> Result<RenderResult, MarkdownError> {
+        self.render_timed(text, profile, false, None)
+    }
+
+    /// Renders Markdown to HTML and records how long each stage took
+    ///
+    /// Intended for diagnosing slow previews; the timings are returned in [`RenderResult::timings`].
+    pub fn render_with_timings(&self, text: &str, profile: MarkdownProfile) -> Result<RenderResult, MarkdownError> {
+        self.render_timed(text, profile, true, None)
+    }
+
+    /// Renders with optional stage timings and, when `number_headings_from` is set, numbered headings
+    ///
+    /// Numbers are added after metadata is built, so the title, outline, and word count match the source.
+    fn render_timed(
+        &self, text: &str, profile: MarkdownProfile, with_timings: bool, number_headings_from: Option<u8>,
+    ) -> Result<RenderResult, MarkdownError> {
+        let elapsed_ms = |start: Instant| start.elapsed().as_secs_f64() * 1000.0;
         let arena = Arena::new();
         let options = profile.to_options();
 
+        let parse_start = Instant::now();
         let (body_text, front_matter) = if profile.supports_front_matter() {
             MarkdownParser::extract_front_matter(text)
         } else {
@@ -344,13 +1063,40 @@ impl MarkdownEngine {
         };
 
         let root = parse_document(&arena, body_text, &options);
-        let metadata = MarkdownParser::build_metadata(root, body_text, front_matter);
+        let parse_ms = elapsed_ms(parse_start);
+
+        let metadata_start = Instant::now();
+        let metadata = MarkdownParser::build_metadata(root, text, body_text, front_matter);
+        let metadata_ms = elapsed_ms(metadata_start);
+
+        if let Some(start_level) = number_headings_from {
+            headings::number_headings_html(&arena, root, start_level);
+        }
 
+        let html_start = Instant::now();
         let mut html_output = String::new();
         comrak::format_html(root, &options, &mut html_output).map_err(|e| MarkdownError::ParseError(e.to_string()))?;
+        if profile.sanitizes_html() {
+            html_output = sanitize::sanitize_html(&html_output);
+        }
+        let html_ms = elapsed_ms(html_start);
 
+        let diagnostics_start = Instant::now();
         let diagnostics = Diagnostics::run(text, &metadata);
-        Ok(RenderResult { html: html_output, metadata, diagnostics })
+        let diagnostics_ms = elapsed_ms(diagnostics_start);
+
+        let timings = with_timings.then_some(RenderTimings { parse_ms, metadata_ms, html_ms, diagnostics_ms });
+        Ok(RenderResult { html: html_output, metadata, diagnostics, timings })
+    }
+
+    /// Runs diagnostics and keeps only those on lines within `line_range` (1-indexed, inclusive)
+    ///
+    /// Lets the editor refresh markers near an edit without replacing document-wide results.
+    pub fn lint_range(
+        &self, text: &str, profile: MarkdownProfile, line_range: RangeInclusive<usize>,
+    ) -> Result<Diagnostics, MarkdownError> {
+        let metadata = self.metadata(text, profile)?;
+        Ok(Diagnostics::run(text, &metadata).within_lines(&line_range))
     }
 
     /// Renders Markdown using the default GfmSafe profile
@@ -362,7 +1108,8 @@ impl MarkdownEngine {
     pub fn export_html(
         &self, text: &str, profile: MarkdownProfile, options: &ExportOptions,
     ) -> Result<String, MarkdownError> {
-        let render_result = self.render(text, profile)?;
+        let number_headings_from = options.number_headings.then_some(options.number_headings_from);
+        let render_result = self.render_timed(text, profile, false, number_headings_from)?;
 
         let mut output = String::new();
 
@@ -455,6 +1202,13 @@ impl MarkdownEngine {
         Ok(output)
     }
 
+    /// Rewrites local `<img src>` URLs in exported HTML, e.g. to inline or relocate images
+    ///
+    /// Remote and `data:` URLs are never passed to the callback; returning `None` keeps the original URL.
+    pub fn rewrite_image_sources(&self, html: &str, rewrite: impl FnMut(&str) -> Option<String>) -> String {
+        utils::rewrite_img_sources(html, rewrite)
+    }
+
     /// Exports only the body HTML without document wrapper
     ///
     /// This is useful for embedding the content in an existing HTML page
@@ -463,6 +1217,87 @@ impl MarkdownEngine {
         Ok(render_result.html)
     }
 
+    /// Exports the heading outline as an OPML 2.0 document for outliners and mind-mapping tools
+    ///
+    /// Each heading becomes an `<outline>` nested under the closest preceding heading of a lower
+    /// level, so a skipped level (H1 then H3) nests one step rather than adding empty parents. The
+    /// `<title>` is the document title, falling back to "Untitled".
+    pub fn export_opml(&self, text: &str, profile: MarkdownProfile) -> Result<String, MarkdownError> {
+        let metadata = self.metadata(text, profile)?;
+        let escape = |value: &str| {
+            let value: String = value
+                .chars()
+                .filter(|ch| !ch.is_control() || matches!(ch, '\t' | '\n'))
+                .collect();
+            utils::html_escape(&value)
+        };
+
+        let mut output = String::new();
+        output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        output.push_str("<opml version=\"2.0\">\n");
+        output.push_str("  <head>\n");
+        output.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape(metadata.title.as_deref().unwrap_or("Untitled"))
+        ));
+        output.push_str("  </head>\n");
+        output.push_str("  <body>\n");
+
+        let mut open_levels: Vec<u8> = Vec::new();
+        for (index, heading) in metadata.outline.iter().enumerate() {
+            while open_levels.last().is_some_and(|level| *level >= heading.level) {
+                open_levels.pop();
+                output.push_str(&format!("{}</outline>\n", "  ".repeat(open_levels.len() + 2)));
+            }
+
+            let indent = "  ".repeat(open_levels.len() + 2);
+            let has_children = metadata
+                .outline
+                .get(index + 1)
+                .is_some_and(|next| next.level > heading.level);
+            if has_children {
+                output.push_str(&format!("{}<outline text=\"{}\">\n", indent, escape(&heading.text)));
+                open_levels.push(heading.level);
+            } else {
+                output.push_str(&format!("{}<outline text=\"{}\"/>\n", indent, escape(&heading.text)));
+            }
+        }
+        while open_levels.pop().is_some() {
+            output.push_str(&format!("{}</outline>\n", "  ".repeat(open_levels.len() + 2)));
+        }
+
+        output.push_str("  </body>\n");
+        output.push_str("</opml>\n");
+        Ok(output)
+    }
+
+    /// Serializes the raw Comrak parse tree to JSON
+    ///
+    /// Front matter is left in the text so that profiles which parse it expose a `FrontMatter` node.
+    pub fn parse_to_ast_json(&self, text: &str, profile: MarkdownProfile) -> Result<String, MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let root = parse_document(&arena, text, &options);
+        let tree = MarkdownTransformer::transform_to_raw_ast(root);
+
+        serde_json::to_string(&tree).map_err(|e| MarkdownError::ParseError(format!("AST serialization failed: {}", e)))
+    }
+
+    /// Parses Markdown into Writer's own syntax tree and serializes it as JSON
+    ///
+    /// Unlike [`Self::parse_to_ast_json`], node types come from [`MarkdownNodeKind`] and payloads are
+    /// limited to levels, list ordering, and literal text. Source positions refer to `text` as given.
+    pub fn render_ast_json(&self, text: &str, profile: MarkdownProfile) -> Result<String, MarkdownError> {
+        let arena = Arena::new();
+        let options = profile.to_options();
+
+        let root = parse_document(&arena, text, &options);
+        let tree = MarkdownTransformer::transform_to_ast(root);
+
+        serde_json::to_string(&tree).map_err(|e| MarkdownError::ParseError(format!("AST serialization failed: {}", e)))
+    }
+
     /// Renders Markdown text to a PDF-compatible AST
     ///
     /// Parses the markdown and transforms it into a structured format
@@ -478,7 +1313,7 @@ impl MarkdownEngine {
         };
 
         let root = parse_document(&arena, body_text, &options);
-        let metadata = MarkdownParser::build_metadata(root, body_text, front_matter);
+        let metadata = MarkdownParser::build_metadata(root, text, body_text, front_matter);
         let nodes = MarkdownTransformer::transform_to_pdf_nodes(root);
 
         Ok(PdfRenderResult { nodes, title: metadata.title, word_count: metadata.word_count })
@@ -489,21 +1324,34 @@ impl MarkdownEngine {
     /// Parses the markdown and transforms it into plain text with preserved
     /// logical structure (paragraph breaks, list indentation, horizontal rules).
     pub fn render_for_text(&self, text: &str, profile: MarkdownProfile) -> Result<TextExportResult, MarkdownError> {
+        let (metadata, plain_text) = self.metadata_with_text(text, profile)?;
+        Ok(TextExportResult { text: plain_text, title: metadata.title, word_count: metadata.word_count })
+    }
+
+    /// Estimates how many pages the document fills when printed, for a "~12 pages" indicator
+    ///
+    /// Works from the plaintext render: each line wraps at `chars_per_line` columns, blank lines between
+    /// blocks count as one line each, and every heading costs `heading_extra_lines` more. Front matter is
+    /// not counted. The result depends only on the text and config, and is at least 1.
+    pub fn estimate_pages(&self, text: &str, profile: MarkdownProfile, config: PageEstimateConfig) -> usize {
         let arena = Arena::new();
         let options = profile.to_options();
-
-        let (body_text, front_matter) = if profile.supports_front_matter() {
-            MarkdownParser::extract_front_matter(text)
-        } else {
-            (text, FrontMatter::default())
-        };
+        let body_text = if profile.supports_front_matter() { self.body(text) } else { text };
 
         let root = parse_document(&arena, body_text, &options);
-        let metadata = MarkdownParser::build_metadata(root, body_text, front_matter);
+        let headings = root
+            .descendants()
+            .filter(|node| matches!(node.data.borrow().value, comrak::nodes::NodeValue::Heading(_)))
+            .count();
 
-        let plain_text = MarkdownTransformer::transform_to_plaintext(root);
+        let chars_per_line = config.chars_per_line.max(1);
+        let lines: usize = MarkdownTransformer::transform_to_plaintext(root)
+            .lines()
+            .map(|line| UnicodeWidthStr::width(line).div_ceil(chars_per_line).max(1))
+            .sum();
 
-        Ok(TextExportResult { text: plain_text, title: metadata.title, word_count: metadata.word_count })
+        let total = lines + headings * config.heading_extra_lines;
+        total.div_ceil(config.lines_per_page.max(1)).max(1)
     }
 
     /// Renders Markdown text to DOCX format
@@ -511,7 +1359,10 @@ impl MarkdownEngine {
     /// Parses the markdown and transforms it into a DOCX byte buffer
     /// using docx-rs, supporting headings, bold, italic, code font,
     /// ordered/unordered lists, blockquotes, and code blocks.
-    pub fn render_for_docx(&self, text: &str, profile: MarkdownProfile) -> Result<DocxExportResult, MarkdownError> {
+    /// Page size, margins, the base font, and heading numbering come from `docx_options`.
+    pub fn render_for_docx(
+        &self, text: &str, profile: MarkdownProfile, docx_options: &DocxOptions,
+    ) -> Result<DocxExportResult, MarkdownError> {
         let arena = Arena::new();
         let options = profile.to_options();
 
@@ -522,9 +1373,12 @@ impl MarkdownEngine {
         };
 
         let root = parse_document(&arena, body_text, &options);
-        let metadata = MarkdownParser::build_metadata(root, body_text, front_matter);
+        let metadata = MarkdownParser::build_metadata(root, text, body_text, front_matter);
+        if docx_options.number_headings {
+            headings::number_headings(&arena, root, docx_options.number_headings_from);
+        }
 
-        let data = DocxTransformer::transform_to_docx(root)
+        let data = DocxTransformer::transform_to_docx(root, docx_options)
             .map_err(|e| MarkdownError::ParseError(format!("DOCX generation failed: {}", e)))?;
 
         Ok(DocxExportResult { data, title: metadata.title, word_count: metadata.word_count })
@@ -548,75 +1402,558 @@ mod tests {
     }
 
     #[test]
-    fn test_strict_common_mark_basic() {
+    fn test_gfm_sanitized_keeps_safe_html() {
+        let engine = MarkdownEngine::new();
+        let markdown = "Some <mark>marked</mark> H<sub>2</sub>O x<sup>2</sup><br>\n\n\
+                        <script>alert('xss')</script>\n\n\
+                        <span onclick=\"alert(1)\" class=\"note\">hi</span> <a href=\"java&#x09;script:alert(1)\">x</a> \
+                        [link](javascript:alert(2)) [ok](https://example.com)";
+        let result = engine.render(markdown, MarkdownProfile::GfmSanitized).unwrap();
+
+        assert!(result.html.contains("<mark>marked</mark>"));
+        assert!(result.html.contains("<sub>2</sub>") && result.html.contains("<sup>2</sup>"));
+        assert!(result.html.contains("<br>"));
+        assert!(!result.html.contains("<script") && !result.html.contains("alert"));
+        assert!(result.html.contains("<span class=\"note\">hi</span>"));
+        assert!(!result.html.to_lowercase().contains("script:"));
+        assert!(result.html.contains("href=\"https://example.com\""));
+        assert!(result.html.contains("data-sourcepos"));
+
+        let xss = fs::read_to_string(render_fixtures_dir().join("xss-safety.md")).unwrap();
+        let result = engine.render(&xss, MarkdownProfile::GfmSanitized).unwrap();
+        assert!(!result.html.contains("<script") && !result.html.contains("<style"));
+        assert!(!result.html.contains("<img") && !result.html.contains("javascript:"));
+        assert!(result.html.contains("Click me"));
+
+        let strict = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+        assert!(!strict.html.contains("<mark>"));
+    }
+
+    #[test]
+    fn test_shift_headings_demotes_and_promotes() {
+        let engine = MarkdownEngine::new();
+        let text = "---\ntitle: Notes\n---\n# Title\n\nIntro with # hash.\n\nSetext Part\nsecond line\n---\n\n> ## Quoted ##\n\n###### Deep\n\n```\n# not a heading\n```\n";
+
+        let demoted = engine.shift_headings(text, 1, MarkdownProfile::Extended).unwrap();
+        assert_eq!(
+            demoted,
+            "---\ntitle: Notes\n---\n## Title\n\nIntro with # hash.\n\n### Setext Part second line\n\n> ### Quoted ##\n\n###### Deep\n\n```\n# not a heading\n```\n"
+        );
+
+        let promoted = engine.shift_headings(&demoted, -1, MarkdownProfile::Extended).unwrap();
+        assert_eq!(
+            promoted,
+            "---\ntitle: Notes\n---\n# Title\n\nIntro with # hash.\n\n## Setext Part second line\n\n> ## Quoted ##\n\n##### Deep\n\n```\n# not a heading\n```\n"
+        );
+
+        let clamped = engine
+            .shift_headings("# Top\n\n## Sub", -2, MarkdownProfile::GfmSafe)
+            .unwrap();
+        assert_eq!(clamped, "# Top\n\n# Sub");
+        assert_eq!(
+            engine
+                .shift_headings("Title\n=====", 0, MarkdownProfile::GfmSafe)
+                .unwrap(),
+            "# Title"
+        );
+    }
+
+    #[test]
+    fn test_format_tables_aligns_columns() {
+        let engine = MarkdownEngine::new();
+        let text = "Intro  | not a table
+
+| Name | Qty |Note|
+|:--|--:|:-:|
+| apple | 3 | a \\| b |
+kiwi|12
+| 東京 | 1 | ok |
+
+After.
+";
+
+        let formatted = engine.format_tables(text, MarkdownProfile::GfmSafe).unwrap();
+        assert_eq!(
+            formatted,
+            "Intro  | not a table
+
+| Name  | Qty |  Note  |
+| :---- | --: | :----: |
+| apple |   3 | a \\| b |
+| kiwi  |  12 |        |
+| 東京  |   1 |   ok   |
+
+After.
+"
+        );
+        assert_eq!(
+            engine.format_tables(&formatted, MarkdownProfile::GfmSafe).unwrap(),
+            formatted
+        );
+        assert_eq!(
+            engine.format_tables(text, MarkdownProfile::StrictCommonMark).unwrap(),
+            text
+        );
+    }
+
+    #[test]
+    fn test_export_opml_nests_headings() {
+        let engine = MarkdownEngine::new();
+        let text = "# Plan & Notes\n\n## Phase <1>\n\n### \"Setup\"\n\n### Build\n\n## Phase 2\n\n#### Deep\n";
+
+        let opml = engine.export_opml(text, MarkdownProfile::GfmSafe).unwrap();
+        assert_eq!(
+            opml,
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<opml version=\"2.0\">\n",
+                "  <head>\n",
+                "    <title>Plan &amp; Notes</title>\n",
+                "  </head>\n",
+                "  <body>\n",
+                "    <outline text=\"Plan &amp; Notes\">\n",
+                "      <outline text=\"Phase &lt;1&gt;\">\n",
+                "        <outline text=\"&quot;Setup&quot;\"/>\n",
+                "        <outline text=\"Build\"/>\n",
+                "      </outline>\n",
+                "      <outline text=\"Phase 2\">\n",
+                "        <outline text=\"Deep\"/>\n",
+                "      </outline>\n",
+                "    </outline>\n",
+                "  </body>\n",
+                "</opml>\n",
+            )
+        );
+
+        let empty = engine.export_opml("No headings.", MarkdownProfile::GfmSafe).unwrap();
+        assert!(empty.contains("<title>Untitled</title>\n  </head>\n  <body>\n  </body>"));
+    }
+
+    #[test]
+    fn test_code_block_ranges() {
+        let engine = MarkdownEngine::new();
+        let text = "---\ntitle: Code\n---\nProse\n\n```rust\nlet  x = 1;\n```\n\n    indented\tcode\n\nEnd";
+        let ranges = engine.code_block_ranges(text, MarkdownProfile::Extended).unwrap();
+
+        let blocks: Vec<&str> = ranges.iter().map(|range| &text[range.clone()]).collect();
+        assert_eq!(blocks, vec!["```rust\nlet  x = 1;\n```\n", "    indented\tcode\n"]);
+    }
+
+    #[test]
+    fn test_strict_common_mark_basic() {
+        let engine = MarkdownEngine::new();
+        let markdown = "# Hello\n\nThis is a paragraph.";
+        let result = engine.render(markdown, MarkdownProfile::StrictCommonMark).unwrap();
+
+        assert!(result.html.contains("<h1"));
+        assert!(result.html.contains("Hello"));
+        assert!(result.metadata.title == Some("Hello".to_string()));
+    }
+
+    #[test]
+    fn test_gfm_tables() {
+        let engine = MarkdownEngine::new();
+        let markdown = "| A | B |\n|---|---|\n| 1 | 2 |";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        assert!(result.html.contains("<table"));
+    }
+
+    #[test]
+    fn test_gfm_task_lists() {
+        let engine = MarkdownEngine::new();
+        let markdown = "- [x] Done\n- [ ] Not done";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        assert_eq!(result.metadata.task_items.total, 2);
+        assert_eq!(result.metadata.task_items.completed, 1);
+    }
+
+    #[test]
+    fn test_sourcepos_present() {
+        let engine = MarkdownEngine::new();
+        let markdown = "# Hello\n\nParagraph here.";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        assert!(utils::has_sourcepos(&result.html));
+        assert!(result.html.contains("data-sourcepos"));
+    }
+
+    #[test]
+    fn test_outline_extraction() {
+        let engine = MarkdownEngine::new();
+        let markdown = "# Title\n\n## Section 1\n\n### Subsection\n\n## Section 2";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        assert_eq!(result.metadata.outline.len(), 4);
+        assert_eq!(result.metadata.outline[0].level, 1);
+        assert_eq!(result.metadata.outline[0].text, "Title");
+        assert_eq!(result.metadata.outline[1].level, 2);
+        assert_eq!(result.metadata.outline[1].text, "Section 1");
+    }
+
+    #[test]
+    fn test_outline_mixes_setext_and_atx_headings() {
+        let engine = MarkdownEngine::new();
+        let markdown = "Field Notes\n===========\n\nIntro.\n\nFirst Trip\n----------\n\n### Day One\n\n## Second Trip\n\nLater\n=====\n";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        let outline: Vec<(u8, &str, usize, Option<&str>)> = result
+            .metadata
+            .outline
+            .iter()
+            .map(|heading| {
+                (
+                    heading.level,
+                    heading.text.as_str(),
+                    heading.line,
+                    heading.anchor.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            outline,
+            vec![
+                (1, "Field Notes", 1, Some("heading-field-notes")),
+                (2, "First Trip", 6, Some("heading-first-trip")),
+                (3, "Day One", 9, Some("heading-day-one")),
+                (2, "Second Trip", 11, Some("heading-second-trip")),
+                (1, "Later", 13, Some("heading-later")),
+            ]
+        );
+        assert_eq!(result.metadata.title, Some("Field Notes".to_string()));
+        assert!(result.html.contains(">Field Notes</h1>"));
+        assert!(result.html.contains(">First Trip</h2>"));
+    }
+
+    #[test]
+    fn test_sentence_and_paragraph_counts() {
+        let engine = MarkdownEngine::new();
+        let markdown = "# Heading\n\nDr. Smith arrived. He brought snacks, e.g. chips and dip! Was it enough?\n\nA second\nparagraph spans lines. It ends here\n\n```\nlet x = 1. Not prose.\n```\n\n- One item. Two sentences.\n";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        assert_eq!(result.metadata.paragraph_count, 3);
+        assert_eq!(result.metadata.sentence_count, 7);
+    }
+
+    #[test]
+    fn test_section_word_counts() {
+        let engine = MarkdownEngine::new();
+        let markdown = "---\ntitle: Book\n---\nA short opening line.\n\n# Chapter One\n\nThree words here.\n\n## Scene\n\nTwo more.\n\n```\nnot counted at all\n```\n\n# Chapter Two\n\n- One list item\n";
+        let sections = engine.section_word_counts(markdown, MarkdownProfile::Extended).unwrap();
+
+        let summary: Vec<(Option<&str>, u8, usize)> = sections
+            .iter()
+            .map(|section| (section.heading.as_deref(), section.level, section.word_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (None, 0, 4),
+                (Some("Chapter One"), 1, 5),
+                (Some("Scene"), 2, 2),
+                (Some("Chapter Two"), 1, 3)
+            ]
+        );
+
+        let sections = engine
+            .section_word_counts("# Only\n\nBody", MarkdownProfile::GfmSafe)
+            .unwrap();
+        assert_eq!(sections.len(), 1);
+
+        let sections = engine
+            .section_word_counts("Intro\n\n# preamble\n\nBody", MarkdownProfile::GfmSafe)
+            .unwrap();
+        assert_eq!(sections[0].heading, None);
+        assert_eq!(sections[1].heading.as_deref(), Some("preamble"));
+    }
+
+    #[test]
+    fn test_toggle_task_each_direction() {
+        let engine = MarkdownEngine::new();
+        let markdown = "---\ntitle: Tasks\n---\n- [ ] Open\n- [x] Done\n  - [X] Nested [ ] text\n> 1. [ ] Quoted\n";
+
+        let checked = engine.toggle_task(markdown, 4).unwrap();
+        assert_eq!(
+            checked,
+            "---\ntitle: Tasks\n---\n- [x] Open\n- [x] Done\n  - [X] Nested [ ] text\n> 1. [ ] Quoted\n"
+        );
+        assert_eq!(engine.toggle_task(&checked, 4).unwrap(), markdown);
+
+        let unchecked = engine.toggle_task(markdown, 6).unwrap();
+        assert_eq!(
+            unchecked,
+            "---\ntitle: Tasks\n---\n- [ ] Open\n- [x] Done\n  - [ ] Nested [ ] text\n> 1. [ ] Quoted\n"
+        );
+        assert!(engine.toggle_task(markdown, 5).unwrap().contains("- [ ] Done"));
+        assert!(engine.toggle_task(markdown, 7).unwrap().ends_with("> 1. [x] Quoted\n"));
+    }
+
+    #[test]
+    fn test_toggle_task_rejects_other_lines() {
+        let engine = MarkdownEngine::new();
+        let markdown = "# [ ] Heading\n\n- Plain item\n\n```\n- [ ] in code\n```\n\n- [ ] Real\n";
+
+        for line in [0, 1, 3, 6, 20] {
+            assert!(engine.toggle_task(markdown, line).is_err(), "line {}", line);
+        }
+        assert_eq!(
+            engine.toggle_task(markdown, 9).unwrap(),
+            "# [ ] Heading\n\n- Plain item\n\n```\n- [ ] in code\n```\n\n- [x] Real\n"
+        );
+    }
+
+    #[test]
+    fn test_append_to_section_in_middle() {
+        let engine = MarkdownEngine::new();
+        let markdown =
+            "---\ntitle: Log\n---\n# Log\n\n## Inbox\n\n- first\n\n### Later\n\nSomeday.\n\n\n## Done\n\nShipped.\n";
+
+        let updated = engine
+            .append_to_section(markdown, "heading-inbox", "- second\n", false)
+            .unwrap();
+        assert_eq!(
+            updated,
+            "---\ntitle: Log\n---\n# Log\n\n## Inbox\n\n- first\n\n### Later\n\nSomeday.\n\n- second\n\n\n## Done\n\nShipped.\n"
+        );
+
+        let updated = engine.append_to_section(markdown, "#later", "Maybe.", false).unwrap();
+        assert!(updated.contains("### Later\n\nSomeday.\n\nMaybe.\n\n\n## Done\n\nShipped.\n"));
+        assert!(updated.starts_with("---\ntitle: Log\n---\n# Log\n\n## Inbox\n\n- first\n\n### Later"));
+
+        let updated = engine.append_to_section(markdown, "done", "Again.", false).unwrap();
+        assert!(updated.ends_with("## Done\n\nShipped.\n\nAgain.\n"));
+    }
+
+    #[test]
+    fn test_append_to_missing_section() {
+        let engine = MarkdownEngine::new();
+        let markdown = "# Notes\n\nText.\n\n```\n## Not a heading\n```";
+
+        assert!(
+            engine
+                .append_to_section(markdown, "not-a-heading", "Lost.", false)
+                .is_err()
+        );
+        assert_eq!(
+            engine
+                .append_to_section(markdown, "not-a-heading", "Kept.", true)
+                .unwrap(),
+            "# Notes\n\nText.\n\n```\n## Not a heading\n```\n\nKept.\n"
+        );
+        assert_eq!(
+            engine.append_to_section("", "missing", "First.", true).unwrap(),
+            "First.\n"
+        );
+    }
+
+    #[test]
+    fn test_task_rollup_per_section() {
+        let engine = MarkdownEngine::new();
+        let markdown = "- [x] Loose task\n\n# Launch\n\n- [x] Draft post\n- [ ] Schedule\n  - [x] Pick a date\n\n## Assets\n\n- [ ] Banner\n\n# Cleanup\n\n- [X] Archive\n- [ ] Retro\n\n# Notes\n\nNo tasks here.\n";
+        let rollup = engine.task_rollup(markdown, MarkdownProfile::Extended).unwrap();
+
+        let summary: Vec<(Option<&str>, u8, usize, usize)> = rollup
+            .iter()
+            .map(|section| {
+                (
+                    section.heading.as_deref(),
+                    section.level,
+                    section.total,
+                    section.completed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (None, 0, 1, 1),
+                (Some("Launch"), 1, 3, 2),
+                (Some("Assets"), 2, 1, 0),
+                (Some("Cleanup"), 1, 2, 1),
+                (Some("Notes"), 1, 0, 0),
+            ]
+        );
+
+        let stats = engine.stats(markdown, MarkdownProfile::Extended).unwrap();
+        assert_eq!(
+            rollup.iter().map(|section| section.total).sum::<usize>(),
+            stats.task_items.total
+        );
+        assert_eq!(
+            rollup.iter().map(|section| section.completed).sum::<usize>(),
+            stats.task_items.completed
+        );
+        assert_eq!(
+            engine
+                .task_rollup("# Only\n\n- [ ] One", MarkdownProfile::Extended)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_stats_bundles_metrics_from_one_parse() {
+        let engine = MarkdownEngine::new();
+        let markdown = "---\ntitle: Plan\ntags: [draft]\n---\n# Plan\n\nWrite the intro. Then the #outline, see [docs](https://example.com).\n\n## Tasks\n\n- [x] Draft\n- [ ] Edit\n\n```rust\nlet x = 1;\n```\n";
+        let stats = engine.stats(markdown, MarkdownProfile::Extended).unwrap();
+
+        assert_eq!(
+            stats,
+            DocStats {
+                word_count: 25,
+                prose_word_count: 10,
+                sentence_count: 4,
+                paragraph_count: 3,
+                reading_time_minutes: 1,
+                heading_count: 2,
+                task_items: TaskStats { total: 2, completed: 1 },
+                task_completion: Some(0.5),
+                link_count: 1,
+                tags: vec!["draft".to_string(), "outline".to_string()],
+            }
+        );
+        assert_eq!(
+            engine.stats("", MarkdownProfile::Extended).unwrap().task_completion,
+            None
+        );
+    }
+
+    #[test]
+    fn test_word_count() {
         let engine = MarkdownEngine::new();
-        let markdown = "# Hello\n\nThis is a paragraph.";
-        let result = engine.render(markdown, MarkdownProfile::StrictCommonMark).unwrap();
+        let markdown = "This has five words total.";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
 
-        assert!(result.html.contains("<h1"));
-        assert!(result.html.contains("Hello"));
-        assert!(result.metadata.title == Some("Hello".to_string()));
+        assert_eq!(result.metadata.word_count, 5);
     }
 
     #[test]
-    fn test_gfm_tables() {
+    fn test_metadata_extracts_front_matter_title_without_rendering_html() {
         let engine = MarkdownEngine::new();
-        let markdown = "| A | B |\n|---|---|\n| 1 | 2 |";
-        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+        let markdown = "---\ntitle: Metadata Title\n---\n\nBody text only";
+        let metadata = engine.metadata(markdown, MarkdownProfile::Extended).unwrap();
 
-        assert!(result.html.contains("<table"));
+        assert_eq!(metadata.title, Some("Metadata Title".to_string()));
+        assert_eq!(metadata.word_count, 3);
     }
 
     #[test]
-    fn test_gfm_task_lists() {
+    fn test_tags_collects_front_matter_and_inline_tags_ignoring_case() {
         let engine = MarkdownEngine::new();
-        let markdown = "- [x] Done\n- [ ] Not done";
-        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+        let markdown = "---\ntags: [Writing, \"#drafts\"]\n---\n\n# Heading\n\n\
+                        Notes on #writing and #ideas/Plot, not #42 or a[link](#anchor).\n\n\
+                        ```\n#fenced\n```\n\nInline `#code` too. Café #été";
 
-        assert_eq!(result.metadata.task_items.total, 2);
-        assert_eq!(result.metadata.task_items.completed, 1);
+        assert_eq!(engine.tags(markdown), vec!["Writing", "drafts", "ideas/Plot", "été"]);
     }
 
     #[test]
-    fn test_sourcepos_present() {
+    fn test_rename_tag_rewrites_only_tag_tokens() {
         let engine = MarkdownEngine::new();
-        let markdown = "# Hello\n\nParagraph here.";
-        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+        let markdown = "---\ntitle: Draft notes\ntags:\n  - Draft\n  - \"draft/old\"\n  - keep\n---\n\n\
+                        A draft with #draft and #DRAFT/ch1 but not #drafty or `#draft`.\n";
 
-        assert!(utils::has_sourcepos(&result.html));
-        assert!(result.html.contains("data-sourcepos"));
+        let renamed = engine.rename_tag(markdown, "#draft", "wip").unwrap().unwrap();
+        assert_eq!(
+            renamed,
+            "---\ntitle: Draft notes\ntags:\n  - wip\n  - \"wip/old\"\n  - keep\n---\n\n\
+             A draft with #wip and #wip/ch1 but not #drafty or `#draft`.\n"
+        );
+
+        let toml = "+++\ntags = [\"draft\", \"keep\"]\n+++\nBody";
+        assert_eq!(
+            engine.rename_tag(toml, "draft", "wip").unwrap().unwrap(),
+            "+++\ntags = [\"wip\", \"keep\"]\n+++\nBody"
+        );
+
+        assert_eq!(engine.rename_tag("No tags here", "draft", "wip").unwrap(), None);
+        assert!(engine.rename_tag(markdown, "draft", "two words").is_err());
     }
 
     #[test]
-    fn test_outline_extraction() {
+    fn test_extract_code_blocks_distinguishes_fenced_and_indented() {
         let engine = MarkdownEngine::new();
-        let markdown = "# Title\n\n## Section 1\n\n### Subsection\n\n## Section 2";
-        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+        let markdown = "---\ntitle: Snippets\n---\n\nIntro\n\n```rust title=main\nfn main() {}\n\n```\n\n\
+                        Then:\n\n    $ cargo run\n\n~~~\nplain\n~~~\n";
+        let blocks = engine.extract_code_blocks(markdown, MarkdownProfile::Extended).unwrap();
 
-        assert_eq!(result.metadata.outline.len(), 4);
-        assert_eq!(result.metadata.outline[0].level, 1);
-        assert_eq!(result.metadata.outline[0].text, "Title");
-        assert_eq!(result.metadata.outline[1].level, 2);
-        assert_eq!(result.metadata.outline[1].text, "Section 1");
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].language.as_deref(), Some("rust"));
+        assert_eq!(blocks[0].content, "fn main() {}\n\n");
+        assert!(blocks[0].fenced);
+        assert_eq!((blocks[0].start_line, blocks[0].end_line), (7, 10));
+
+        assert_eq!(blocks[1].language, None);
+        assert_eq!(blocks[1].content, "$ cargo run\n");
+        assert!(!blocks[1].fenced);
+        assert_eq!(blocks[1].start_line, 14);
+
+        assert_eq!(blocks[2].language, None);
+        assert_eq!(blocks[2].content, "plain\n");
+        assert!(blocks[2].fenced);
     }
 
     #[test]
-    fn test_word_count() {
+    fn test_edit_tags_adds_once_and_removes_everywhere() {
         let engine = MarkdownEngine::new();
-        let markdown = "This has five words total.";
-        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+        let tags = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let markdown = "---\ntitle: Notes\ntags:\n  - draft\n  - keep\n---\n\nSome #Draft text #draft/ch1.\n#draft\n";
 
-        assert_eq!(result.metadata.word_count, 5);
+        let edited = engine
+            .edit_tags(markdown, &tags(&["Keep", "#new"]), &tags(&["draft"]), false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            edited,
+            "---\ntitle: Notes\ntags:\n  - keep\n  - new\n---\n\nSome text #draft/ch1.\n"
+        );
+        assert_eq!(
+            engine
+                .edit_tags(&edited, &tags(&["new"]), &tags(&["draft"]), false)
+                .unwrap(),
+            None
+        );
+
+        assert_eq!(
+            engine
+                .edit_tags("Body", &tags(&["a", "b"]), &[], false)
+                .unwrap()
+                .unwrap(),
+            "---\ntags: [a, b]\n---\n\nBody"
+        );
+        assert_eq!(
+            engine
+                .edit_tags("+++\ntags = [\"a\"]\n+++\nBody", &[], &tags(&["a"]), false)
+                .unwrap()
+                .unwrap(),
+            "+++\ntags = []\n+++\nBody"
+        );
+        assert_eq!(
+            engine
+                .edit_tags("Body\r\nMore", &tags(&["idea"]), &[], true)
+                .unwrap()
+                .unwrap(),
+            "Body\r\nMore\r\n#idea\r\n"
+        );
+        assert!(engine.edit_tags("Body", &tags(&["two words"]), &[], false).is_err());
     }
 
     #[test]
-    fn test_metadata_extracts_front_matter_title_without_rendering_html() {
+    fn test_metadata_collects_images_with_alt_text_and_locality() {
         let engine = MarkdownEngine::new();
-        let markdown = "---\ntitle: Metadata Title\n---\n\nBody text only";
+        let markdown = "---\ntitle: Images\n---\n\n![A *local* cat](img/cat%20one.png)\n\n\
+                        ![remote](https://example.com/a.png) ![inline](data:image/png;base64,AAAA)";
         let metadata = engine.metadata(markdown, MarkdownProfile::Extended).unwrap();
 
-        assert_eq!(metadata.title, Some("Metadata Title".to_string()));
-        assert_eq!(metadata.word_count, 3);
+        assert_eq!(metadata.images.len(), 3);
+        assert_eq!(metadata.images[0].alt, "A local cat");
+        assert_eq!(metadata.images[0].url, "img/cat%20one.png");
+        assert!(metadata.images[0].is_local);
+        assert_eq!(metadata.images[0].line, 5);
+        assert!(!metadata.images[1].is_local);
+        assert!(!metadata.images[2].is_local);
     }
 
     #[test]
@@ -641,6 +1978,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_lint_range_keeps_only_diagnostics_inside_range() {
+        let engine = MarkdownEngine::new();
+        let text =
+            "---\ntitle: Lint\n---\n[first](javascript:alert(1))\n\nPlain line.\n\n[second](javascript:alert(2))\n";
+
+        let all = engine
+            .lint_range(text, MarkdownProfile::Extended, 1..=usize::MAX)
+            .unwrap();
+        let lines: Vec<_> = all.items.iter().map(|diagnostic| diagnostic.line).collect();
+        assert_eq!(lines, vec![Some(4), Some(8)]);
+
+        let ranged = engine.lint_range(text, MarkdownProfile::Extended, 7..=9).unwrap();
+        assert_eq!(ranged.len(), 1);
+        assert_eq!(ranged.items[0].code, "javascript-link");
+        assert_eq!(ranged.items[0].line, Some(8));
+        assert_eq!(ranged.items[0].source.as_deref(), Some("javascript:alert(2)"));
+    }
+
+    #[test]
+    fn test_render_with_timings_populates_stage_durations() {
+        let engine = MarkdownEngine::new();
+        let text = "# Timings\n\nSome *body* text with a [link](https://example.com).\n\n".repeat(50);
+
+        let timed = engine.render_with_timings(&text, MarkdownProfile::GfmSafe).unwrap();
+        let timings = timed.timings.expect("timings should be present when requested");
+        assert!(timings.parse_ms > 0.0);
+        assert!(timings.html_ms > 0.0);
+        assert!(timings.metadata_ms >= 0.0);
+        assert!(timings.diagnostics_ms >= 0.0);
+
+        let untimed = engine.render(&text, MarkdownProfile::GfmSafe).unwrap();
+        assert!(untimed.timings.is_none());
+        assert_eq!(untimed.html, timed.html);
+        assert!(!serde_json::to_string(&untimed).unwrap().contains("\"timings\""));
+    }
+
+    #[test]
+    fn test_parse_to_ast_json_includes_heading_level_and_text_literal() {
+        let engine = MarkdownEngine::new();
+        let json = engine
+            .parse_to_ast_json("## Hello AST\n\nBody text.", MarkdownProfile::GfmSafe)
+            .unwrap();
+        let tree: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(tree["type"], "Document");
+
+        let heading = &tree["children"][0];
+        assert_eq!(heading["type"], "Heading");
+        assert_eq!(heading["level"], 2);
+        assert_eq!(heading["sourcepos"], serde_json::json!([1, 1, 1, 12]));
+
+        let text = &heading["children"][0];
+        assert_eq!(text["type"], "Text");
+        assert_eq!(text["literal"], "Hello AST");
+
+        let json = engine
+            .parse_to_ast_json("- [x] Done\n\n| A |\n|---|\n| `b` |\n", MarkdownProfile::Extended)
+            .unwrap();
+        let tree: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(tree["children"][0]["type"], "List");
+        assert_eq!(tree["children"][0]["children"][0]["type"], "TaskItem");
+        assert_eq!(tree["children"][1]["type"], "Table");
+        assert_eq!(
+            tree["children"][1]["children"][1]["children"][0]["children"][0]["type"],
+            "Code"
+        );
+    }
+
+    #[test]
+    fn test_render_ast_json_heading_carries_level_and_line_range() {
+        let engine = MarkdownEngine::new();
+        let text = "---\ntitle: AST\n---\n\nIntro\n\n### Third level\n\n1. one\n";
+        let json = engine.render_ast_json(text, MarkdownProfile::Extended).unwrap();
+        let tree: MarkdownAstNode = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(tree.kind, MarkdownNodeKind::Document);
+        assert_eq!(tree.children[0].kind, MarkdownNodeKind::FrontMatter);
+
+        let heading = tree
+            .children
+            .iter()
+            .find(|node| node.kind == MarkdownNodeKind::Heading)
+            .unwrap();
+        assert_eq!(heading.level, Some(3));
+        assert_eq!((heading.sourcepos.start_line, heading.sourcepos.end_line), (7, 7));
+        assert_eq!(heading.children[0].text.as_deref(), Some("Third level"));
+
+        let list = tree.children.last().unwrap();
+        assert_eq!(list.kind, MarkdownNodeKind::List);
+        assert_eq!(list.ordered, Some(true));
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["children"][2]["type"], "heading");
+    }
+
     #[test]
     fn test_render_for_pdf_handles_lists() {
         let engine = MarkdownEngine::new();
@@ -862,6 +2295,89 @@ mod tests {
         assert!(!result.metadata.front_matter.fields.contains_key("tags"));
     }
 
+    #[test]
+    fn test_strip_front_matter() {
+        let engine = MarkdownEngine::new();
+
+        let (body, front_matter) = engine.strip_front_matter("---\ntitle: Notes\n---\n\n# Notes\n");
+        assert_eq!(body, "\n# Notes\n");
+        assert_eq!(front_matter.format, Some(FrontMatterFormat::Yaml));
+        assert_eq!(front_matter.fields.get("title"), Some(&"Notes".to_string()));
+
+        let (body, front_matter) = engine.strip_front_matter("+++\r\ntitle = \"Notes\"\r\n+++\r\nBody\r\n");
+        assert_eq!(body, "Body\r\n");
+        assert_eq!(front_matter.format, Some(FrontMatterFormat::Toml));
+
+        let text = "# Heading\n\n---\n\nAfter a rule.\n";
+        let (body, front_matter) = engine.strip_front_matter(text);
+        assert_eq!(body, text);
+        assert_eq!(front_matter, FrontMatter::default());
+    }
+
+    #[test]
+    fn test_upsert_front_matter_adds_block_when_missing() {
+        let engine = MarkdownEngine::new();
+        let fields = HashMap::from([
+            ("updated".to_string(), "2024-06-01".to_string()),
+            ("title".to_string(), "Notes: Part #2".to_string()),
+            ("draft".to_string(), "true".to_string()),
+        ]);
+
+        let updated = engine
+            .upsert_front_matter("# Notes\n", &fields, FrontMatterFormat::Yaml)
+            .unwrap();
+        assert_eq!(
+            updated,
+            "---\ndraft: \"true\"\ntitle: \"Notes: Part #2\"\nupdated: 2024-06-01\n---\n\n# Notes\n"
+        );
+
+        let front_matter = engine.front_matter(&updated);
+        assert_eq!(front_matter.fields.get("title"), Some(&"Notes: Part #2".to_string()));
+        assert_eq!(front_matter.fields.get("draft"), Some(&"true".to_string()));
+        assert_eq!(engine.body(&updated), "\n# Notes\n");
+
+        let toml = engine
+            .upsert_front_matter("Body", &fields, FrontMatterFormat::Toml)
+            .unwrap();
+        assert!(
+            toml.starts_with(
+                "+++\ndraft = \"true\"\ntitle = \"Notes: Part #2\"\nupdated = \"2024-06-01\"\n+++\n\nBody"
+            )
+        );
+    }
+
+    #[test]
+    fn test_upsert_front_matter_updates_existing_block() {
+        let engine = MarkdownEngine::new();
+        let fields = HashMap::from([
+            ("updated".to_string(), "2024-06-01".to_string()),
+            ("tags".to_string(), "single".to_string()),
+        ]);
+
+        let yaml = "---\ntitle: Keep\ntags:\n  - a\n  - b\n# comment\n---\nBody ---\n";
+        assert_eq!(
+            engine
+                .upsert_front_matter(yaml, &fields, FrontMatterFormat::Toml)
+                .unwrap(),
+            "---\ntitle: Keep\ntags: single\n# comment\nupdated: 2024-06-01\n---\nBody ---\n"
+        );
+
+        let toml = "+++\r\ntitle = \"Keep\"\r\ntags = [\r\n  \"a\",\r\n]\r\n\r\n[extra]\r\nkey = 1\r\n+++\r\nBody\r\n";
+        assert_eq!(
+            engine
+                .upsert_front_matter(toml, &fields, FrontMatterFormat::Yaml)
+                .unwrap(),
+            "+++\r\ntitle = \"Keep\"\r\ntags = \"single\"\r\nupdated = \"2024-06-01\"\r\n\r\n[extra]\r\nkey = 1\r\n+++\r\nBody\r\n"
+        );
+
+        let invalid = HashMap::from([("bad key".to_string(), "x".to_string())]);
+        assert!(
+            engine
+                .upsert_front_matter(yaml, &invalid, FrontMatterFormat::Yaml)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_toml_front_matter_parses_scalar_fields_only() {
         let engine = MarkdownEngine::new();
@@ -881,6 +2397,61 @@ mod tests {
         assert!(!result.metadata.front_matter.fields.contains_key("nested"));
     }
 
+    #[test]
+    fn test_front_matter_collects_aliases_from_lists_and_scalars() {
+        let engine = MarkdownEngine::new();
+
+        let yaml = engine.front_matter("---\naliases:\n  - Foo\n  - Bar\n  - foo\n---\n\n# Body");
+        assert_eq!(yaml.aliases(), vec!["Foo".to_string(), "Bar".to_string()]);
+        assert!(!yaml.fields.contains_key("aliases"));
+
+        let toml = engine.front_matter("+++\naliases = [\"Baz\"]\n+++\n\n# Body");
+        assert_eq!(toml.aliases(), vec!["Baz".to_string()]);
+
+        let scalar = engine.front_matter("---\naliases: Qux\n---\n");
+        assert_eq!(scalar.aliases(), vec!["Qux".to_string()]);
+    }
+
+    #[test]
+    fn test_wikilink_targets() {
+        let engine = MarkdownEngine::new();
+        let text = "---\nrelated: \"[[Meta]]\"\n---\n[[Note#Intro|intro]], [[#Local]], ![[embed.png]]\n\n```\n[[Code]]\n```\n`[[Span]]` [[Other]]\n";
+
+        assert_eq!(engine.wikilink_targets(text), vec!["Note", "Other"]);
+    }
+
+    #[test]
+    fn test_resolve_wikilinks_uses_alias_text_and_resolved_href() {
+        let engine = MarkdownEngine::new();
+        let resolver = |target: &str| (target == "My Note").then(|| "my-note.html".to_string());
+
+        let result = engine
+            .resolve_wikilinks("See [[My Note|alias]] and [[Missing]].", resolver)
+            .unwrap();
+        assert_eq!(result, "See [alias](my-note.html) and [[Missing]].");
+
+        let result = engine
+            .resolve_wikilinks("Jump to [[My Note#Next Steps]].", resolver)
+            .unwrap();
+        assert_eq!(
+            result,
+            "Jump to [My Note > Next Steps](my-note.html#heading-next-steps)."
+        );
+    }
+
+    #[test]
+    fn test_resolve_wikilinks_skips_code_front_matter_and_embeds() {
+        let engine = MarkdownEngine::new();
+        let resolver = |target: &str| Some(format!("{}.html", target.to_lowercase()));
+        let markdown = "---\nrelated: \"[[Note]]\"\n---\n`[[Note]]` ![[Note]]\n\n```\n[[Note]]\n```\n[[Note]]\n";
+
+        let result = engine.resolve_wikilinks(markdown, resolver).unwrap();
+        assert_eq!(
+            result,
+            "---\nrelated: \"[[Note]]\"\n---\n`[[Note]]` ![[Note]]\n\n```\n[[Note]]\n```\n[Note](note.html)\n"
+        );
+    }
+
     #[test]
     fn test_diagnostics_empty_link_url() {
         let engine = MarkdownEngine::new();
@@ -896,6 +2467,53 @@ mod tests {
         assert!(!empty_link_warnings.is_empty(), "Should detect empty link URL");
     }
 
+    #[test]
+    fn test_diagnostics_heading_level_skip() {
+        let engine = MarkdownEngine::new();
+        let markdown = "### Intro\n\n## Setup\n\n#### Details\n\n##### Deeper\n\n## Next\n";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        let skips: Vec<_> = result
+            .diagnostics
+            .warnings()
+            .into_iter()
+            .filter(|d| d.code == "heading-level-skip")
+            .collect();
+        assert_eq!(skips.len(), 1, "Only the H2 to H4 jump is a skip: {:?}", skips);
+        assert!(skips[0].message.contains("Details"));
+        assert_eq!(skips[0].line, Some(5));
+
+        let clean = engine
+            .render("## Setup\n\n### Details\n\n## Next\n", MarkdownProfile::GfmSafe)
+            .unwrap();
+        assert!(clean.diagnostics.items.iter().all(|d| d.code != "heading-level-skip"));
+    }
+
+    #[test]
+    fn test_diagnostics_duplicate_heading() {
+        let engine = MarkdownEngine::new();
+        let markdown = "## Setup\n\nFirst.\n\n## Setup\n\n## Cafe\u{301}\n\n## Café\n";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        let duplicates: Vec<_> = result
+            .diagnostics
+            .warnings()
+            .into_iter()
+            .filter(|d| d.code == "duplicate-heading")
+            .collect();
+        let lines: Vec<_> = duplicates.iter().map(|d| d.line).collect();
+        assert_eq!(lines, [Some(1), Some(5), Some(7), Some(9)], "{:?}", duplicates);
+        assert!(duplicates[0].message.contains("line 5"));
+        assert!(duplicates[1].message.contains("line 1"));
+        assert!(duplicates[1].message.contains("#heading-setup-1"));
+        assert!(result.html.contains("id=\"heading-setup-1\""));
+        assert!(duplicates[2].message.contains("line 9"));
+        assert!(duplicates[3].message.contains("line 7"));
+
+        let anchors: Vec<_> = result.metadata.outline.iter().map(|h| h.anchor.as_deref()).collect();
+        assert_eq!(anchors[..2], [Some("heading-setup"), Some("heading-setup-1")]);
+    }
+
     #[test]
     fn test_diagnostics_mixed_line_endings() {
         let engine = MarkdownEngine::new();
@@ -926,6 +2544,47 @@ mod tests {
         assert!(!js_errors.is_empty(), "Should detect JavaScript URL");
     }
 
+    #[test]
+    fn test_diagnostics_footnote_undefined() {
+        let engine = MarkdownEngine::new();
+        let markdown = "Cited[^Ünïcode note] and noted[^ok].\n\n[^OK]: Defined.\n\n`[^code]` is ignored.";
+        let result = engine.render(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        let undefined: Vec<_> = result
+            .diagnostics
+            .errors()
+            .into_iter()
+            .filter(|d| d.code == "footnote-undefined")
+            .collect();
+        assert_eq!(
+            undefined.len(),
+            1,
+            "Only the unicode id lacks a definition: {:?}",
+            undefined
+        );
+        assert!(undefined[0].message.contains("Ünïcode note"));
+        assert_eq!((undefined[0].line, undefined[0].column), (Some(1), Some(6)));
+        assert!(result.diagnostics.items.iter().all(|d| d.code != "footnote-unused"));
+    }
+
+    #[test]
+    fn test_diagnostics_footnote_unused() {
+        let engine = MarkdownEngine::new();
+        let markdown = "---\ntitle: Notes\n---\n\nBody[^used].\n\n[^used]: Cited.\n[^spare id]: Never cited.";
+        let result = engine.render(markdown, MarkdownProfile::Extended).unwrap();
+
+        let unused: Vec<_> = result
+            .diagnostics
+            .warnings()
+            .into_iter()
+            .filter(|d| d.code == "footnote-unused")
+            .collect();
+        assert_eq!(unused.len(), 1, "Only the spare definition is unused: {:?}", unused);
+        assert!(unused[0].message.contains("spare id"));
+        assert_eq!(unused[0].line, Some(8));
+        assert!(result.diagnostics.errors().is_empty());
+    }
+
     #[test]
     fn test_frontmatter_yaml() {
         let engine = MarkdownEngine::new();
@@ -989,6 +2648,82 @@ mod tests {
         assert!(html.contains("</html>"));
     }
 
+    #[test]
+    fn test_export_html_numbered_headings() {
+        let engine = MarkdownEngine::new();
+        let markdown = "# Guide\n\n### Setup\n\n## Usage\n\n# Reference\n\n## API\n";
+        let options = ExportOptions { number_headings: true, ..ExportOptions::standalone() };
+        let html = engine
+            .export_html(markdown, MarkdownProfile::GfmSafe, &options)
+            .unwrap();
+
+        assert!(html.contains("<title>Guide</title>"));
+        assert!(html.contains(">1 Guide</h1>"));
+        assert!(html.contains(">1.1 Setup</h3>"));
+        assert!(html.contains(">1.2 Usage</h2>"));
+        assert!(html.contains(">2 Reference</h1>"));
+        assert!(html.contains(">2.1 API</h2>"));
+
+        let options = ExportOptions { number_headings: true, number_headings_from: 2, ..ExportOptions::embed() };
+        let html = engine
+            .export_html(markdown, MarkdownProfile::GfmSafe, &options)
+            .unwrap();
+        assert!(html.contains(">Guide</h1>"));
+        assert!(html.contains(">1 Setup</h3>"));
+        assert!(html.contains(">2 Usage</h2>"));
+        assert!(html.contains(">1 API</h2>"));
+
+        let plain = engine
+            .export_html(markdown, MarkdownProfile::GfmSafe, &ExportOptions::embed())
+            .unwrap();
+        assert!(plain.contains(">Guide</h1>"));
+    }
+
+    #[test]
+    fn test_export_html_numbered_headings_keep_anchors() {
+        let engine = MarkdownEngine::new();
+        let markdown = "# Guide\n\n[see](#guide)\n";
+        let options = ExportOptions { number_headings: true, ..ExportOptions::embed() };
+        let html = engine
+            .export_html(markdown, MarkdownProfile::GfmSafe, &options)
+            .unwrap();
+
+        assert!(html.contains(">1 Guide</h1>"));
+        assert!(html.contains("href=\"#guide\">see</a>"));
+        assert!(html.contains("id=\"heading-guide\""));
+        assert!(!html.contains("1-guide"));
+    }
+
+    #[test]
+    fn test_estimate_pages() {
+        let engine = MarkdownEngine::new();
+        let config = PageEstimateConfig { lines_per_page: 10, chars_per_line: 20, heading_extra_lines: 1 };
+        let paragraph = ["word"; 8].join(" ");
+        let markdown: String = (0..6)
+            .map(|part| format!("## Part {}\n\n{}\n\n", part, paragraph))
+            .collect();
+
+        // 6 headings + 6 two-line paragraphs + 11 blank lines = 29 lines, plus 6 for headings
+        assert_eq!(engine.estimate_pages(&markdown, MarkdownProfile::Extended, config), 4);
+        assert_eq!(
+            engine.estimate_pages(&markdown, MarkdownProfile::Extended, config),
+            engine.estimate_pages(&markdown, MarkdownProfile::Extended, config)
+        );
+
+        let heavier = PageEstimateConfig { heading_extra_lines: 3, ..config };
+        assert_eq!(engine.estimate_pages(&markdown, MarkdownProfile::Extended, heavier), 5);
+
+        let with_front_matter = format!("---\ntitle: Parts\ntags: [a, b]\n---\n{}", markdown);
+        assert_eq!(
+            engine.estimate_pages(&with_front_matter, MarkdownProfile::Extended, config),
+            4
+        );
+        assert_eq!(
+            engine.estimate_pages("", MarkdownProfile::Extended, PageEstimateConfig::default()),
+            1
+        );
+    }
+
     #[test]
     fn test_export_html_body_only() {
         let engine = MarkdownEngine::new();
@@ -1093,6 +2828,27 @@ mod tests {
         assert!(!html.to_lowercase().contains("<script") && !html.contains("</script>"));
     }
 
+    #[test]
+    fn test_rewrite_image_sources_skips_remote_urls() {
+        let engine = MarkdownEngine::new();
+        let markdown = "![Local](images/a%20b.png)\n\n![Remote](https://example.com/c.png)";
+        let html = engine.export_html_body(markdown, MarkdownProfile::GfmSafe).unwrap();
+
+        let mut seen = Vec::new();
+        let rewritten = engine.rewrite_image_sources(&html, |url| {
+            seen.push(url.to_string());
+            Some("assets/a b.png".to_string())
+        });
+
+        assert_eq!(seen, vec!["images/a%20b.png".to_string()]);
+        assert!(rewritten.contains("src=\"assets/a b.png\""));
+        assert!(rewritten.contains("src=\"https://example.com/c.png\""));
+
+        let custom = "<imgx src=\"images/a.png\"><img src=\"images/b.png\">";
+        let rewritten = engine.rewrite_image_sources(custom, |_| Some("assets/c.png".to_string()));
+        assert_eq!(rewritten, "<imgx src=\"images/a.png\"><img src=\"assets/c.png\">");
+    }
+
     #[test]
     fn test_export_options_embed() {
         let opts = ExportOptions::embed();
@@ -1404,7 +3160,9 @@ mod tests {
     fn test_render_for_docx_basic() {
         let engine = MarkdownEngine::new();
         let markdown = "# Hello World\n\nThis is a paragraph.";
-        let result = engine.render_for_docx(markdown, MarkdownProfile::GfmSafe).unwrap();
+        let result = engine
+            .render_for_docx(markdown, MarkdownProfile::GfmSafe, &DocxOptions::default())
+            .unwrap();
 
         assert!(!result.data.is_empty());
         assert_eq!(&result.data[0..2], b"PK");
@@ -1416,7 +3174,9 @@ mod tests {
     fn test_render_for_docx_with_front_matter() {
         let engine = MarkdownEngine::new();
         let markdown = "---\ntitle: My Document\n---\n\n# Content\n\nBody text.";
-        let result = engine.render_for_docx(markdown, MarkdownProfile::Extended).unwrap();
+        let result = engine
+            .render_for_docx(markdown, MarkdownProfile::Extended, &DocxOptions::default())
+            .unwrap();
 
         assert_eq!(&result.data[0..2], b"PK");
         assert_eq!(result.title, Some("My Document".to_string()));
@@ -1425,7 +3185,9 @@ mod tests {
     #[test]
     fn test_render_for_docx_empty() {
         let engine = MarkdownEngine::new();
-        let result = engine.render_for_docx("", MarkdownProfile::GfmSafe).unwrap();
+        let result = engine
+            .render_for_docx("", MarkdownProfile::GfmSafe, &DocxOptions::default())
+            .unwrap();
 
         assert_eq!(&result.data[0..2], b"PK");
         assert_eq!(result.title, None);
@@ -1458,7 +3220,9 @@ code block
 
 ---
 ";
-        let result = engine.render_for_docx(markdown, MarkdownProfile::GfmSafe).unwrap();
+        let result = engine
+            .render_for_docx(markdown, MarkdownProfile::GfmSafe, &DocxOptions::default())
+            .unwrap();
 
         assert_eq!(&result.data[0..2], b"PK");
         assert!(
@@ -1,4 +1,7 @@
-use super::{DocumentMetadata, FrontMatter, FrontMatterFormat, Heading, LinkRef, TaskStats, utils};
+use super::{
+    DocumentMetadata, FrontMatter, FrontMatterFormat, Heading, ImageRef, LinkRef, SectionStat, SectionTaskStats,
+    TaskStats, utils,
+};
 use comrak::nodes::NodeValue;
 use serde_yaml::Value as YamlValue;
 use std::collections::HashMap;
@@ -28,9 +31,10 @@ impl MarkdownParser {
         }
     }
 
-    /// Parses YAML-like front matter into key-value pairs
-    pub fn parse_yaml_like_front_matter(content: &str) -> HashMap<String, String> {
+    /// Parses YAML-like front matter into scalar key-value pairs and scalar lists
+    pub fn parse_yaml_like_front_matter(content: &str) -> (HashMap<String, String>, HashMap<String, Vec<String>>) {
         let mut fields = HashMap::new();
+        let mut lists = HashMap::new();
 
         if let Ok(parsed) = serde_yaml::from_str::<YamlValue>(content)
             && let YamlValue::Mapping(mapping) = parsed
@@ -39,34 +43,51 @@ impl MarkdownParser {
                 let Some(key_text) = key.as_str() else {
                     continue;
                 };
+                if key_text.is_empty() {
+                    continue;
+                }
+
+                if let YamlValue::Sequence(items) = &value {
+                    let values = items.iter().filter_map(Self::yaml_scalar_to_string).collect::<Vec<_>>();
+                    lists.insert(key_text.to_string(), values);
+                    continue;
+                }
+
                 let Some(value_text) = Self::yaml_scalar_to_string(&value) else {
                     continue;
                 };
-                if !key_text.is_empty() {
-                    fields.insert(key_text.to_string(), value_text);
-                }
+                fields.insert(key_text.to_string(), value_text);
             }
         }
 
-        fields
+        (fields, lists)
     }
 
-    /// Parses TOML-like front matter into key-value pairs
-    pub fn parse_toml_like_front_matter(content: &str) -> HashMap<String, String> {
+    /// Parses TOML-like front matter into scalar key-value pairs and scalar lists
+    pub fn parse_toml_like_front_matter(content: &str) -> (HashMap<String, String>, HashMap<String, Vec<String>>) {
         let mut fields = HashMap::new();
+        let mut lists = HashMap::new();
 
         if let Ok(table) = toml::from_str::<toml::Table>(content) {
             for (key, value) in table {
+                if key.is_empty() {
+                    continue;
+                }
+
+                if let TomlValue::Array(items) = &value {
+                    let values = items.iter().filter_map(Self::toml_scalar_to_string).collect::<Vec<_>>();
+                    lists.insert(key.to_string(), values);
+                    continue;
+                }
+
                 let Some(value_text) = Self::toml_scalar_to_string(&value) else {
                     continue;
                 };
-                if !key.is_empty() {
-                    fields.insert(key.to_string(), value_text);
-                }
+                fields.insert(key.to_string(), value_text);
             }
         }
 
-        fields
+        (fields, lists)
     }
 
     /// Extracts front matter from the beginning of the document
@@ -84,11 +105,11 @@ impl MarkdownParser {
                 .strip_prefix('\n')
                 .map_or(&rest[delimiter_end..], |value| value);
 
-            let fields = MarkdownParser::parse_yaml_like_front_matter(fm_content);
+            let (fields, lists) = MarkdownParser::parse_yaml_like_front_matter(fm_content);
 
             return (
                 body,
-                FrontMatter { raw: Some(fm_content.to_string()), format: Some(FrontMatterFormat::Yaml), fields },
+                FrontMatter { raw: Some(fm_content.to_string()), format: Some(FrontMatterFormat::Yaml), fields, lists },
             );
         }
 
@@ -101,11 +122,11 @@ impl MarkdownParser {
                 .strip_prefix('\n')
                 .map_or(&rest[delimiter_end..], |value| value);
 
-            let fields = MarkdownParser::parse_toml_like_front_matter(fm_content);
+            let (fields, lists) = MarkdownParser::parse_toml_like_front_matter(fm_content);
 
             return (
                 body,
-                FrontMatter { raw: Some(fm_content.to_string()), format: Some(FrontMatterFormat::Toml), fields },
+                FrontMatter { raw: Some(fm_content.to_string()), format: Some(FrontMatterFormat::Toml), fields, lists },
             );
         }
 
@@ -116,6 +137,8 @@ impl MarkdownParser {
     pub fn extract_metadata_from_node<'a>(
         node: &'a comrak::nodes::AstNode<'a>, metadata: &mut DocumentMetadata, first_h1: &mut bool,
     ) {
+        let line = node.data.borrow().sourcepos.start.line;
+
         match &node.data.borrow().value {
             NodeValue::Heading(heading) => {
                 let level = heading.level;
@@ -126,14 +149,30 @@ impl MarkdownParser {
                     *first_h1 = false;
                 }
 
-                metadata.outline.push(Heading { level, text, anchor: None });
+                metadata.outline.push(Heading { level, text, anchor: None, line });
             }
             NodeValue::Link(link) => {
                 metadata.links.push(LinkRef {
                     url: link.url.clone(),
                     title: if link.title.is_empty() { None } else { Some(link.title.clone()) },
+                    is_local: utils::is_local_url(&link.url),
+                    line,
+                });
+            }
+            NodeValue::Image(image) => {
+                metadata.images.push(ImageRef {
+                    alt: Self::extract_text_from_node(node),
+                    url: image.url.clone(),
+                    is_local: utils::is_local_url(&image.url),
+                    line,
                 });
             }
+            NodeValue::Paragraph => {
+                let prose = Self::extract_prose_from_node(node);
+                metadata.paragraph_count += 1;
+                metadata.sentence_count += utils::count_sentences(&prose);
+                metadata.prose_word_count += writer_core::count_words(&prose);
+            }
             NodeValue::TaskItem(task_item) => {
                 metadata.task_items.total += 1;
                 if let Some(symbol) = task_item.symbol
@@ -150,6 +189,87 @@ impl MarkdownParser {
         }
     }
 
+    /// Totals paragraph prose under each heading, nesting subsections into their parents
+    pub fn section_word_counts<'a>(root: &'a comrak::nodes::AstNode<'a>) -> Vec<SectionStat> {
+        let mut sections = vec![SectionStat { heading: None, level: 0, word_count: 0 }];
+
+        for node in root.descendants() {
+            match &node.data.borrow().value {
+                NodeValue::Heading(heading) => sections.push(SectionStat {
+                    heading: Some(Self::extract_text_from_node(node)),
+                    level: heading.level,
+                    word_count: 0,
+                }),
+                NodeValue::Paragraph => {
+                    let words = writer_core::count_words(&Self::extract_prose_from_node(node));
+                    if let Some(section) = sections.last_mut() {
+                        section.word_count += words;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let own_counts: Vec<usize> = sections.iter().map(|section| section.word_count).collect();
+        for idx in 1..sections.len() {
+            let level = sections[idx].level;
+            sections[idx].word_count += sections[idx + 1..]
+                .iter()
+                .zip(&own_counts[idx + 1..])
+                .take_while(|(section, _)| section.level > level)
+                .map(|(_, words)| words)
+                .sum::<usize>();
+        }
+
+        if sections[0].word_count == 0 {
+            sections.remove(0);
+        }
+        sections
+    }
+
+    /// Counts task items under each heading, leaving subsections to their own entries
+    pub fn task_rollup<'a>(root: &'a comrak::nodes::AstNode<'a>) -> Vec<SectionTaskStats> {
+        let mut sections = vec![SectionTaskStats { heading: None, level: 0, total: 0, completed: 0 }];
+
+        for node in root.descendants() {
+            match &node.data.borrow().value {
+                NodeValue::Heading(heading) => sections.push(SectionTaskStats {
+                    heading: Some(Self::extract_text_from_node(node)),
+                    level: heading.level,
+                    total: 0,
+                    completed: 0,
+                }),
+                NodeValue::TaskItem(task_item) => {
+                    if let Some(section) = sections.last_mut() {
+                        section.total += 1;
+                        if let Some(symbol) = task_item.symbol
+                            && (symbol == 'x' || symbol == 'X')
+                        {
+                            section.completed += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if sections[0].total == 0 {
+            sections.remove(0);
+        }
+        sections
+    }
+
+    /// Source column (1-indexed, in bytes) of the task list item that starts on `line`, if any
+    pub fn task_item_column<'a>(root: &'a comrak::nodes::AstNode<'a>, line: usize) -> Option<usize> {
+        root.descendants().find_map(|node| {
+            let data = node.data.borrow();
+            match data.value {
+                NodeValue::TaskItem(_) if data.sourcepos.start.line == line => Some(data.sourcepos.start.column),
+                _ => None,
+            }
+        })
+    }
+
     /// Extracts plain text from a node and its children
     fn extract_text_from_node<'a>(node: &'a comrak::nodes::AstNode<'a>) -> String {
         let mut text = String::new();
@@ -171,20 +291,55 @@ impl MarkdownParser {
         text
     }
 
+    /// Extracts inline text from a node, treating line breaks as word separators
+    fn extract_prose_from_node<'a>(node: &'a comrak::nodes::AstNode<'a>) -> String {
+        let mut text = String::new();
+
+        match &node.data.borrow().value {
+            NodeValue::Text(t) => text.push_str(t),
+            NodeValue::Code(code) => text.push_str(&code.literal),
+            NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+            _ => {
+                for child in node.children() {
+                    text.push_str(&Self::extract_prose_from_node(child));
+                }
+            }
+        }
+
+        text
+    }
+
+    /// Builds document metadata from a parsed tree
+    ///
+    /// `body_text` must be the suffix of `text` left after front matter extraction; source lines
+    /// are reported relative to the full `text`.
     pub fn build_metadata<'a>(
-        root: &'a comrak::nodes::AstNode<'a>, body_text: &str, front_matter: FrontMatter,
+        root: &'a comrak::nodes::AstNode<'a>, text: &str, body_text: &str, front_matter: FrontMatter,
     ) -> DocumentMetadata {
         let mut metadata = DocumentMetadata {
             title: None,
             outline: Vec::new(),
             links: Vec::new(),
+            images: Vec::new(),
             task_items: TaskStats::default(),
             word_count: 0,
+            sentence_count: 0,
+            prose_word_count: 0,
+            paragraph_count: 0,
             front_matter,
         };
 
         MarkdownParser::extract_metadata_from_node(root, &mut metadata, &mut true);
 
+        let line_offset = text[..text.len() - body_text.len()].matches('\n').count();
+        let mut anchors = utils::HeadingAnchors::default();
+        metadata.outline.iter_mut().for_each(|heading| {
+            heading.line += line_offset;
+            heading.anchor = Some(anchors.anchorize(&heading.text));
+        });
+        metadata.links.iter_mut().for_each(|link| link.line += line_offset);
+        metadata.images.iter_mut().for_each(|image| image.line += line_offset);
+
         if let Some(title) = metadata.front_matter.fields.get("title") {
             metadata.title = Some(title.clone());
         }
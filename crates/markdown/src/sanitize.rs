@@ -0,0 +1,135 @@
+use ammonia::Builder;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+/// Elements kept by the sanitizer; anything else is unwrapped (its tags dropped, its text kept)
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "section",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+    "var",
+];
+
+/// Elements removed together with everything inside them
+const DROPPED_TAGS: &[&str] = &[
+    "script",
+    "style",
+    "iframe",
+    "object",
+    "embed",
+    "template",
+    "noscript",
+    "textarea",
+    "title",
+    "xmp",
+    "noembed",
+    "noframes",
+    "plaintext",
+    "svg",
+    "math",
+    "select",
+    "button",
+    "form",
+];
+
+/// Attributes allowed on every kept element
+const GLOBAL_ATTRIBUTES: &[&str] = &["id", "class", "title", "lang", "dir", "inert"];
+
+/// Attribute name prefixes allowed on every kept element
+const GLOBAL_ATTRIBUTE_PREFIXES: &[&str] = &["data-", "aria-"];
+
+/// Attributes allowed only on specific elements
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("img", &["src", "alt", "width", "height"]),
+    ("input", &["type", "checked", "disabled"]),
+    ("ol", &["start", "type"]),
+    ("li", &["value"]),
+    ("td", &["align", "colspan", "rowspan"]),
+    ("th", &["align", "colspan", "rowspan"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("blockquote", &["cite"]),
+    ("q", &["cite"]),
+    ("del", &["cite"]),
+    ("ins", &["cite"]),
+    ("details", &["open"]),
+];
+
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::empty();
+    builder
+        .tags(ALLOWED_TAGS.iter().copied().collect())
+        .clean_content_tags(DROPPED_TAGS.iter().copied().collect())
+        .generic_attributes(GLOBAL_ATTRIBUTES.iter().copied().collect())
+        .generic_attribute_prefixes(GLOBAL_ATTRIBUTE_PREFIXES.iter().copied().collect())
+        .tag_attributes(
+            TAG_ATTRIBUTES
+                .iter()
+                .map(|(tag, attributes)| (*tag, attributes.iter().copied().collect::<HashSet<_>>()))
+                .collect::<HashMap<_, _>>(),
+        )
+        .attribute_filter(writer_core::safe_url_attribute)
+        .link_rel(None);
+    builder
+});
+
+/// Filters rendered HTML down to an allowlist of tags and attributes
+///
+/// Script-like elements are removed with their content, event handlers and `style` are dropped,
+/// and URL attributes must pass [`writer_core::is_safe_url`] (so `javascript:` links go).
+pub(crate) fn sanitize_html(html: &str) -> String {
+    SANITIZER.clean(html).to_string()
+}
@@ -0,0 +1,165 @@
+use comrak::nodes::{AstNode, NodeValue};
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+impl Alignment {
+    fn from_delimiter(cell: &str) -> Self {
+        match (cell.starts_with(':'), cell.ends_with(':') && cell.len() > 1) {
+            (true, true) => Alignment::Center,
+            (true, false) => Alignment::Left,
+            (false, true) => Alignment::Right,
+            (false, false) => Alignment::None,
+        }
+    }
+
+    fn delimiter(self, width: usize) -> String {
+        match self {
+            Alignment::None => "-".repeat(width),
+            Alignment::Left => format!(":{}", "-".repeat(width - 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+            Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+        }
+    }
+
+    fn pad(self, cell: &str, width: usize) -> String {
+        let gap = width.saturating_sub(cell.width());
+        let (before, after) = match self {
+            Alignment::Right => (gap, 0),
+            Alignment::Center => (gap / 2, gap - gap / 2),
+            Alignment::None | Alignment::Left => (0, gap),
+        };
+        format!("{}{}{}", " ".repeat(before), cell, " ".repeat(after))
+    }
+}
+
+/// Re-emits every GFM table in `body` with its pipes aligned
+///
+/// Columns are padded to their widest cell, measured in terminal columns so wide (CJK) characters
+/// count twice, and cells are placed according to the delimiter row's alignment markers. Escaped
+/// pipes stay inside their cell. Lines outside tables are copied byte-for-byte. A table is left alone
+/// when its lines do not share the container prefix of its first line (as in a lazy blockquote) or a
+/// row has more cells than the header, since re-emitting it would drop text.
+pub(crate) fn format_tables<'a>(root: &'a AstNode<'a>, body: &str) -> String {
+    let tables: Vec<(usize, usize, usize)> = root
+        .descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            match data.value {
+                NodeValue::Table(..) => Some((
+                    data.sourcepos.start.line,
+                    data.sourcepos.start.column,
+                    data.sourcepos.end.line,
+                )),
+                _ => None,
+            }
+        })
+        .collect();
+
+    let lines: Vec<&str> = body.split_inclusive('\n').collect();
+    let mut output = String::with_capacity(body.len());
+    let mut next_line = 1;
+
+    for (start_line, start_column, end_line) in tables {
+        if start_line < next_line || end_line > lines.len() || end_line < start_line + 1 {
+            continue;
+        }
+        for line in &lines[next_line - 1..start_line - 1] {
+            output.push_str(line);
+        }
+
+        let table_lines = &lines[start_line - 1..end_line];
+        match format_table(table_lines, start_column) {
+            Some(formatted) => output.push_str(&formatted),
+            None => table_lines.iter().for_each(|line| output.push_str(line)),
+        }
+        next_line = end_line + 1;
+    }
+
+    for line in lines.iter().skip(next_line - 1) {
+        output.push_str(line);
+    }
+
+    output
+}
+
+/// Aligns one table's lines (header, delimiter row, then body rows), or `None` to keep it as-is
+fn format_table(lines: &[&str], start_column: usize) -> Option<String> {
+    let prefix = lines[0].get(..start_column - 1)?;
+    let mut rows = Vec::with_capacity(lines.len());
+    for line in lines {
+        let content = line.strip_prefix(prefix)?.trim_end_matches(['\r', '\n']);
+        rows.push(split_row(content));
+    }
+
+    let columns = rows[0].len();
+    if rows.iter().any(|row| row.len() > columns) {
+        return None;
+    }
+
+    let alignments: Vec<Alignment> = rows[1].iter().map(|cell| Alignment::from_delimiter(cell)).collect();
+    let mut widths = vec![3; columns];
+    for row in rows.iter().take(1).chain(rows.iter().skip(2)) {
+        for (column, cell) in row.iter().enumerate() {
+            widths[column] = widths[column].max(cell.width());
+        }
+    }
+
+    let mut output = String::new();
+    for (index, (row, line)) in rows.iter().zip(lines).enumerate() {
+        output.push_str(prefix);
+        output.push('|');
+        for (column, width) in widths.iter().enumerate() {
+            let alignment = alignments.get(column).copied().unwrap_or(Alignment::None);
+            let cell = if index == 1 {
+                alignment.delimiter(*width)
+            } else {
+                alignment.pad(row.get(column).map_or("", String::as_str), *width)
+            };
+            output.push(' ');
+            output.push_str(&cell);
+            output.push_str(" |");
+        }
+
+        let content = line.trim_end_matches(['\r', '\n']);
+        output.push_str(&line[content.len()..]);
+    }
+
+    Some(output)
+}
+
+/// Splits a table row into trimmed cells on unescaped pipes, dropping the optional outer pipes
+fn split_row(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.trim().chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                cell.push(ch);
+                if let Some(escaped) = chars.next() {
+                    cell.push(escaped);
+                }
+            }
+            '|' => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(ch),
+        }
+    }
+    cells.push(cell);
+
+    if cells.len() > 1 && cells[0].trim().is_empty() {
+        cells.remove(0);
+    }
+    if cells.len() > 1 && cells.last().is_some_and(|cell| cell.trim().is_empty()) {
+        cells.pop();
+    }
+
+    cells.into_iter().map(|cell| cell.trim().to_string()).collect()
+}
@@ -0,0 +1,360 @@
+use super::{FrontMatterFormat, MarkdownError, front_matter, parser::MarkdownParser, utils};
+use std::ops::Range;
+
+/// Returns true for characters allowed in a tag name
+fn is_tag_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '-' | '/')
+}
+
+/// Normalizes a user-supplied tag name (drops a leading `#`), returning `None` when it is not a valid tag
+pub(crate) fn normalize_tag(tag: &str) -> Option<&str> {
+    let tag = tag.trim();
+    let tag = tag.strip_prefix('#').unwrap_or(tag).trim_matches('/');
+    let valid = !tag.is_empty() && tag.chars().all(is_tag_char) && !tag.chars().all(|ch| ch.is_ascii_digit());
+    valid.then_some(tag)
+}
+
+/// Normalizes every tag name, failing on the first invalid one
+pub(crate) fn normalize_tags(tags: &[String]) -> Result<Vec<&str>, MarkdownError> {
+    tags.iter()
+        .map(|tag| normalize_tag(tag).ok_or_else(|| MarkdownError::ParseError(format!("Invalid tag name: {}", tag))))
+        .collect()
+}
+
+/// Collects front matter `tags` and inline `#tags`, de-duplicated ignoring case in order of appearance
+///
+/// Inline tags must follow whitespace (or start a line) and contain a non-digit, so headings,
+/// URL fragments, and issue numbers like `#42` are not tags. Fenced code and inline code spans
+/// are skipped.
+pub(crate) fn collect_tags(text: &str) -> Vec<String> {
+    let (body, front_matter) = MarkdownParser::extract_front_matter(text);
+
+    let mut tags: Vec<String> = Vec::new();
+    let mut push = |tag: &str| {
+        if let Some(tag) = normalize_tag(tag)
+            && !tags
+                .iter()
+                .any(|existing| existing.to_lowercase() == tag.to_lowercase())
+        {
+            tags.push(tag.to_string());
+        }
+    };
+
+    for value in front_matter.list("tags") {
+        value.split(',').for_each(&mut push);
+    }
+
+    let mut fence = utils::FenceTracker::default();
+    for line in body.lines() {
+        if fence.is_code_line(line) {
+            continue;
+        }
+        for span in inline_tag_spans(line) {
+            push(&line[span]);
+        }
+    }
+
+    tags
+}
+
+/// Rewrites every occurrence of `old_tag` (and its `old_tag/child` descendants) to `new_tag`
+///
+/// Matching ignores case. Only front matter `tags` entries and inline tag tokens are touched;
+/// returns `None` when the document does not use the tag.
+pub(crate) fn rename_tag(text: &str, old_tag: &str, new_tag: &str) -> Option<String> {
+    let (body, front_matter) = MarkdownParser::extract_front_matter(text);
+    let (head, body) = text.split_at(text.len() - body.len());
+
+    let mut output = String::with_capacity(text.len());
+    let mut changed = false;
+
+    match front_matter.format {
+        Some(format) if front_matter.raw.is_some() => {
+            let renamed = rename_front_matter_tags(head, format, old_tag, new_tag);
+            changed |= renamed != head;
+            output.push_str(&renamed);
+        }
+        _ => output.push_str(head),
+    }
+
+    let mut fence = utils::FenceTracker::default();
+    for line in body.split_inclusive('\n') {
+        if fence.is_code_line(line) {
+            output.push_str(line);
+            continue;
+        }
+
+        let mut copied = 0;
+        for span in inline_tag_spans(line) {
+            if let Some(renamed) = renamed_tag(&line[span.clone()], old_tag, new_tag) {
+                output.push_str(&line[copied..span.start]);
+                output.push_str(&renamed);
+                copied = span.end;
+                changed = true;
+            }
+        }
+        output.push_str(&line[copied..]);
+    }
+
+    changed.then_some(output)
+}
+
+/// Adds and removes tags, returning `None` when the text already has the requested tags
+///
+/// Tags already present (in front matter or inline, ignoring case) are not added again. Added tags
+/// are appended to the front matter `tags` list, creating a YAML block when there is none, or with
+/// `inline` as a line of `#tags` at the end of the document. Removed tags are taken out of the front
+/// matter list and every inline occurrence; their `tag/child` descendants are kept.
+pub(crate) fn edit_tags(
+    text: &str, add: &[&str], remove: &[&str], inline: bool,
+) -> Result<Option<String>, MarkdownError> {
+    let is_removed = |tag: &str| {
+        normalize_tag(tag).is_some_and(|tag| {
+            remove
+                .iter()
+                .any(|removed| removed.to_lowercase() == tag.to_lowercase())
+        })
+    };
+
+    let present: Vec<String> = collect_tags(text)
+        .into_iter()
+        .filter(|tag| !is_removed(tag))
+        .map(|tag| tag.to_lowercase())
+        .collect();
+    let mut added: Vec<&str> = Vec::new();
+    for tag in add {
+        let key = tag.to_lowercase();
+        if !present.contains(&key) && !added.iter().any(|existing| existing.to_lowercase() == key) {
+            added.push(tag);
+        }
+    }
+
+    let (_, front_matter) = MarkdownParser::extract_front_matter(text);
+    let listed: Vec<String> = front_matter
+        .list("tags")
+        .iter()
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect();
+    let mut kept: Vec<String> = listed.iter().filter(|tag| !is_removed(tag)).cloned().collect();
+    if !inline {
+        kept.extend(added.iter().map(|tag| tag.to_string()));
+    }
+
+    let mut output = if kept != listed {
+        let format = front_matter.format.unwrap_or(FrontMatterFormat::Yaml);
+        front_matter::set_front_matter_list(text, "tags", &kept, format)?
+    } else {
+        text.to_string()
+    };
+
+    let ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let body_start = output.len() - MarkdownParser::extract_front_matter(&output).0.len();
+    let body = remove_inline_tags(&output[body_start..], &is_removed);
+    output.truncate(body_start);
+    output.push_str(&body);
+
+    if inline && !added.is_empty() {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push_str(ending);
+        }
+        let line: Vec<String> = added.iter().map(|tag| format!("#{}", tag)).collect();
+        output.push_str(&line.join(" "));
+        output.push_str(ending);
+    }
+
+    Ok((output != text).then_some(output))
+}
+
+/// Deletes the inline `#tags` matched by `is_removed` along with the space before each one
+///
+/// A line left blank by the removal is dropped entirely.
+fn remove_inline_tags(body: &str, is_removed: &impl Fn(&str) -> bool) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut fence = utils::FenceTracker::default();
+
+    for line in body.split_inclusive('\n') {
+        if fence.is_code_line(line) {
+            output.push_str(line);
+            continue;
+        }
+
+        let spans: Vec<Range<usize>> = inline_tag_spans(line)
+            .into_iter()
+            .filter(|span| is_removed(&line[span.clone()]))
+            .collect();
+        if spans.is_empty() {
+            output.push_str(line);
+            continue;
+        }
+
+        let mut kept = String::with_capacity(line.len());
+        let mut copied = 0;
+        for span in spans {
+            let hash = span.start - 1;
+            let start = if line[..hash].ends_with([' ', '\t']) && hash > copied { hash - 1 } else { hash };
+            kept.push_str(&line[copied..start]);
+            copied = span.end;
+            if start == hash && line[copied..].starts_with(' ') {
+                copied += 1;
+            }
+        }
+        kept.push_str(&line[copied..]);
+
+        if !kept.trim().is_empty() {
+            output.push_str(&kept);
+        }
+    }
+
+    output
+}
+
+/// Returns the renamed tag when `tag` is `old_tag` or one of its `old_tag/...` descendants
+fn renamed_tag(tag: &str, old_tag: &str, new_tag: &str) -> Option<String> {
+    if tag.to_lowercase() == old_tag.to_lowercase() {
+        return Some(new_tag.to_string());
+    }
+
+    let prefix = tag.get(..old_tag.len())?;
+    let rest = &tag[old_tag.len()..];
+    (rest.starts_with('/') && prefix.to_lowercase() == old_tag.to_lowercase()).then(|| format!("{}{}", new_tag, rest))
+}
+
+/// Finds the byte ranges of inline tag names (without the `#`) in a line of prose
+fn inline_tag_spans(line: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut cursor = 0;
+    let mut previous: Option<char> = None;
+
+    while let Some(ch) = line[cursor..].chars().next() {
+        if ch == '`' {
+            cursor += writer_core::code_span_len(line, cursor);
+            previous = Some('`');
+            continue;
+        }
+
+        if ch == '#' && previous.is_none_or(char::is_whitespace) {
+            let start = cursor + 1;
+            let length: usize = line[start..]
+                .chars()
+                .take_while(|ch| is_tag_char(*ch))
+                .map(char::len_utf8)
+                .sum();
+            let name = line[start..start + length].trim_end_matches('/');
+
+            if normalize_tag(name) == Some(name) {
+                spans.push(start..start + name.len());
+            }
+            if length > 0 {
+                cursor = start + length;
+                previous = line[..cursor].chars().next_back();
+                continue;
+            }
+        }
+
+        cursor += ch.len_utf8();
+        previous = Some(ch);
+    }
+
+    spans
+}
+
+/// Rewrites the `tags` entry of a front matter block (delimiters included), leaving other keys untouched
+fn rename_front_matter_tags(block: &str, format: FrontMatterFormat, old_tag: &str, new_tag: &str) -> String {
+    let separator = match format {
+        FrontMatterFormat::Yaml => ':',
+        FrontMatterFormat::Toml => '=',
+    };
+
+    let mut output = String::with_capacity(block.len());
+    let mut in_block_list = false;
+
+    for line in block.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let ending = &line[content.len()..];
+
+        if in_block_list {
+            let trimmed = content.trim_start();
+            if let Some(item) = trimmed.strip_prefix('-') {
+                let indent = content.len() - trimmed.len();
+                output.push_str(&content[..indent + 1]);
+                output.push_str(&rename_list_items(item, old_tag, new_tag));
+                output.push_str(ending);
+                continue;
+            }
+            in_block_list = false;
+        }
+
+        let is_tags_key = content
+            .split_once(separator)
+            .is_some_and(|(key, _)| key.trim().trim_matches(['"', '\'']) == "tags" && !key.starts_with(' '));
+        if !is_tags_key {
+            output.push_str(line);
+            continue;
+        }
+
+        let (key, value) = content.split_once(separator).unwrap_or((content, ""));
+        output.push_str(key);
+        output.push(separator);
+
+        if value.trim().is_empty() {
+            in_block_list = format == FrontMatterFormat::Yaml;
+            output.push_str(value);
+        } else if let (Some(open), Some(close)) = (value.find('['), value.rfind(']')) {
+            output.push_str(&value[..=open]);
+            output.push_str(&rename_list_items(&value[open + 1..close], old_tag, new_tag));
+            output.push_str(&value[close..]);
+        } else {
+            output.push_str(&rename_list_items(value, old_tag, new_tag));
+        }
+        output.push_str(ending);
+    }
+
+    output
+}
+
+/// Renames matching entries in a comma-separated list, preserving quotes, `#` prefixes, and spacing
+fn rename_list_items(items: &str, old_tag: &str, new_tag: &str) -> String {
+    items
+        .split(',')
+        .map(|item| {
+            let start = item.len() - item.trim_start().len();
+            let end = item.trim_end().len();
+            if start >= end {
+                return item.to_string();
+            }
+
+            let core = &item[start..end];
+            let quote = core
+                .chars()
+                .next()
+                .filter(|ch| matches!(ch, '"' | '\'') && core.len() > 1 && core.ends_with(*ch));
+            let inner = match quote {
+                Some(_) => &core[1..core.len() - 1],
+                None => core,
+            };
+            let (hash, name) = match inner.strip_prefix('#') {
+                Some(name) => ("#", name),
+                None => ("", inner),
+            };
+
+            match renamed_tag(name, old_tag, new_tag) {
+                Some(renamed) => {
+                    let quote = quote.map(String::from).unwrap_or_default();
+                    format!(
+                        "{}{}{}{}{}{}",
+                        &item[..start],
+                        quote,
+                        hash,
+                        renamed,
+                        quote,
+                        &item[end..]
+                    )
+                }
+                None => item.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
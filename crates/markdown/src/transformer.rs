@@ -1,4 +1,4 @@
-use super::PdfNode;
+use super::{AstSourcepos, MarkdownAstNode, MarkdownNodeKind, PdfNode, RawAstNode};
 use comrak::nodes::NodeValue;
 
 pub struct MarkdownTransformer;
@@ -8,6 +8,158 @@ mod docx;
 pub use docx::DocxTransformer;
 
 impl MarkdownTransformer {
+    /// Mirrors a Comrak node and its descendants without interpreting them
+    pub fn transform_to_raw_ast<'a>(node: &'a comrak::nodes::AstNode<'a>) -> RawAstNode {
+        let data = node.data.borrow();
+        let sourcepos = data.sourcepos;
+
+        let (literal, level) = match &data.value {
+            NodeValue::Text(text) => (Some(text.to_string()), None),
+            NodeValue::Code(code) => (Some(code.literal.clone()), None),
+            NodeValue::CodeBlock(code_block) => (Some(code_block.literal.clone()), None),
+            NodeValue::HtmlBlock(html_block) => (Some(html_block.literal.clone()), None),
+            NodeValue::HtmlInline(html) => (Some(html.to_string()), None),
+            NodeValue::FrontMatter(front_matter) => (Some(front_matter.to_string()), None),
+            NodeValue::Heading(heading) => (None, Some(heading.level)),
+            _ => (None, None),
+        };
+
+        RawAstNode {
+            node_type: Self::node_type_name(&data.value).into(),
+            sourcepos: [
+                sourcepos.start.line,
+                sourcepos.start.column,
+                sourcepos.end.line,
+                sourcepos.end.column,
+            ],
+            literal,
+            level,
+            children: node.children().map(Self::transform_to_raw_ast).collect(),
+        }
+    }
+
+    /// Comrak `NodeValue` variant name, as written to `RawAstNode.node_type`
+    fn node_type_name(value: &NodeValue) -> &'static str {
+        match value {
+            NodeValue::Document => "Document",
+            NodeValue::FrontMatter(_) => "FrontMatter",
+            NodeValue::BlockQuote => "BlockQuote",
+            NodeValue::List(_) => "List",
+            NodeValue::Item(_) => "Item",
+            NodeValue::DescriptionList => "DescriptionList",
+            NodeValue::DescriptionItem(_) => "DescriptionItem",
+            NodeValue::DescriptionTerm => "DescriptionTerm",
+            NodeValue::DescriptionDetails => "DescriptionDetails",
+            NodeValue::CodeBlock(_) => "CodeBlock",
+            NodeValue::HtmlBlock(_) => "HtmlBlock",
+            NodeValue::Paragraph => "Paragraph",
+            NodeValue::Heading(_) => "Heading",
+            NodeValue::ThematicBreak => "ThematicBreak",
+            NodeValue::FootnoteDefinition(_) => "FootnoteDefinition",
+            NodeValue::Table(_) => "Table",
+            NodeValue::TableRow(_) => "TableRow",
+            NodeValue::TableCell => "TableCell",
+            NodeValue::Text(_) => "Text",
+            NodeValue::TaskItem(_) => "TaskItem",
+            NodeValue::SoftBreak => "SoftBreak",
+            NodeValue::LineBreak => "LineBreak",
+            NodeValue::Code(_) => "Code",
+            NodeValue::HtmlInline(_) => "HtmlInline",
+            NodeValue::Raw(_) => "Raw",
+            NodeValue::Emph => "Emph",
+            NodeValue::Strong => "Strong",
+            NodeValue::Strikethrough => "Strikethrough",
+            NodeValue::Highlight => "Highlight",
+            NodeValue::Superscript => "Superscript",
+            NodeValue::Link(_) => "Link",
+            NodeValue::Image(_) => "Image",
+            NodeValue::FootnoteReference(_) => "FootnoteReference",
+            NodeValue::Math(_) => "Math",
+            NodeValue::MultilineBlockQuote(_) => "MultilineBlockQuote",
+            NodeValue::Escaped => "Escaped",
+            NodeValue::WikiLink(_) => "WikiLink",
+            NodeValue::Underline => "Underline",
+            NodeValue::Subscript => "Subscript",
+            NodeValue::SpoileredText => "SpoileredText",
+            NodeValue::EscapedTag(_) => "EscapedTag",
+            NodeValue::Alert(_) => "Alert",
+            NodeValue::Subtext => "Subtext",
+            // Variants behind Comrak features this crate does not enable (shortcodes, HEEx)
+            #[allow(unreachable_patterns)]
+            _ => "Other",
+        }
+    }
+
+    /// Maps a Comrak node and its descendants onto Writer's stable syntax tree
+    pub fn transform_to_ast<'a>(node: &'a comrak::nodes::AstNode<'a>) -> MarkdownAstNode {
+        let data = node.data.borrow();
+        let sourcepos = data.sourcepos;
+
+        let (kind, level, ordered, text) = match &data.value {
+            NodeValue::Document => (MarkdownNodeKind::Document, None, None, None),
+            NodeValue::FrontMatter(front_matter) => (
+                MarkdownNodeKind::FrontMatter,
+                None,
+                None,
+                Some(front_matter.to_string()),
+            ),
+            NodeValue::Heading(heading) => (MarkdownNodeKind::Heading, Some(heading.level), None, None),
+            NodeValue::Paragraph => (MarkdownNodeKind::Paragraph, None, None, None),
+            NodeValue::BlockQuote => (MarkdownNodeKind::BlockQuote, None, None, None),
+            NodeValue::List(list) => (
+                MarkdownNodeKind::List,
+                None,
+                Some(list.list_type == comrak::nodes::ListType::Ordered),
+                None,
+            ),
+            NodeValue::Item(_) => (MarkdownNodeKind::Item, None, None, None),
+            NodeValue::TaskItem(_) => (MarkdownNodeKind::TaskItem, None, None, None),
+            NodeValue::CodeBlock(code_block) => (
+                MarkdownNodeKind::CodeBlock,
+                None,
+                None,
+                Some(code_block.literal.clone()),
+            ),
+            NodeValue::HtmlBlock(html_block) => (
+                MarkdownNodeKind::HtmlBlock,
+                None,
+                None,
+                Some(html_block.literal.clone()),
+            ),
+            NodeValue::ThematicBreak => (MarkdownNodeKind::ThematicBreak, None, None, None),
+            NodeValue::Table(_) => (MarkdownNodeKind::Table, None, None, None),
+            NodeValue::TableRow(_) => (MarkdownNodeKind::TableRow, None, None, None),
+            NodeValue::TableCell => (MarkdownNodeKind::TableCell, None, None, None),
+            NodeValue::FootnoteDefinition(_) => (MarkdownNodeKind::FootnoteDefinition, None, None, None),
+            NodeValue::FootnoteReference(_) => (MarkdownNodeKind::FootnoteReference, None, None, None),
+            NodeValue::Text(text) => (MarkdownNodeKind::Text, None, None, Some(text.to_string())),
+            NodeValue::Code(code) => (MarkdownNodeKind::Code, None, None, Some(code.literal.clone())),
+            NodeValue::HtmlInline(html) => (MarkdownNodeKind::HtmlInline, None, None, Some(html.to_string())),
+            NodeValue::Emph => (MarkdownNodeKind::Emphasis, None, None, None),
+            NodeValue::Strong => (MarkdownNodeKind::Strong, None, None, None),
+            NodeValue::Strikethrough => (MarkdownNodeKind::Strikethrough, None, None, None),
+            NodeValue::Link(_) => (MarkdownNodeKind::Link, None, None, None),
+            NodeValue::Image(_) => (MarkdownNodeKind::Image, None, None, None),
+            NodeValue::SoftBreak => (MarkdownNodeKind::SoftBreak, None, None, None),
+            NodeValue::LineBreak => (MarkdownNodeKind::LineBreak, None, None, None),
+            _ => (MarkdownNodeKind::Other, None, None, None),
+        };
+
+        MarkdownAstNode {
+            kind,
+            sourcepos: AstSourcepos {
+                start_line: sourcepos.start.line,
+                start_column: sourcepos.start.column,
+                end_line: sourcepos.end.line,
+                end_column: sourcepos.end.column,
+            },
+            level,
+            ordered,
+            text,
+            children: node.children().map(Self::transform_to_ast).collect(),
+        }
+    }
+
     /// Extracts plain text content from a node and its children
     fn extract_text_content<'a>(node: &'a comrak::nodes::AstNode<'a>) -> String {
         let mut text = String::new();
@@ -1,3 +1,4 @@
+use crate::DocxOptions;
 use comrak::nodes::{ListType, NodeValue};
 use docx_rs::*;
 use std::io::Cursor;
@@ -24,8 +25,32 @@ pub struct DocxTransformer;
 
 impl DocxTransformer {
     /// Transforms a Comrak AST root node into DOCX bytes.
-    pub fn transform_to_docx<'a>(root: &'a comrak::nodes::AstNode<'a>) -> Result<Vec<u8>, DocxError> {
-        let mut doc = Docx::new();
+    pub fn transform_to_docx<'a>(
+        root: &'a comrak::nodes::AstNode<'a>, options: &DocxOptions,
+    ) -> Result<Vec<u8>, DocxError> {
+        let mut buf = Cursor::new(Vec::new());
+        Self::build_docx(root, options).build().pack(&mut buf)?;
+        Ok(buf.into_inner())
+    }
+
+    /// Builds the document model: page setup, default run properties, styles, numbering, and body.
+    fn build_docx<'a>(root: &'a comrak::nodes::AstNode<'a>, options: &DocxOptions) -> Docx {
+        let (width, height) = options.page_size.twips();
+        let margins = options.margins_twips;
+        let mut doc = Docx::new().page_size(width, height).page_margin(
+            PageMargin::new()
+                .top(margins.top)
+                .right(margins.right)
+                .bottom(margins.bottom)
+                .left(margins.left),
+        );
+
+        if let Some(font) = options.base_font.as_deref() {
+            doc = doc.default_fonts(RunFonts::new().ascii(font).hi_ansi(font).east_asia(font).cs(font));
+        }
+        if options.base_font_size > 0 {
+            doc = doc.default_size(options.base_font_size);
+        }
 
         doc = doc
             .add_style(
@@ -90,9 +115,7 @@ impl DocxTransformer {
             doc = doc.add_paragraph(para);
         }
 
-        let mut buf = Cursor::new(Vec::new());
-        doc.build().pack(&mut buf)?;
-        Ok(buf.into_inner())
+        doc
     }
 
     /// Collects block-level elements from the AST into DOCX paragraphs.
@@ -406,7 +429,53 @@ mod tests {
         opts.extension.table = true;
         opts.extension.tasklist = true;
         let root = parse_document(&arena, text, &opts);
-        DocxTransformer::transform_to_docx(root).expect("DOCX generation should succeed")
+        DocxTransformer::transform_to_docx(root, &DocxOptions::default()).expect("DOCX generation should succeed")
+    }
+
+    #[test]
+    fn test_page_setup_and_base_font() {
+        let arena = Arena::new();
+        let root = parse_document(&arena, "# Title\n\nBody text.\n", &Options::default());
+        let build = |options: &DocxOptions| DocxTransformer::build_docx(root, options).build();
+
+        let a4 = build(&DocxOptions::default());
+        let letter = build(&DocxOptions {
+            page_size: crate::PageSize::Letter,
+            margins_twips: crate::Margins { top: 1440, right: 1440, bottom: 1440, left: 1440 },
+            base_font: Some("Georgia".to_string()),
+            base_font_size: 24,
+            ..DocxOptions::default()
+        });
+
+        let a4_document = String::from_utf8(a4.document).unwrap();
+        let letter_document = String::from_utf8(letter.document).unwrap();
+        assert_ne!(a4_document, letter_document);
+        assert!(a4_document.contains("11906") && a4_document.contains("16838"));
+        assert!(letter_document.contains("12240") && letter_document.contains("15840"));
+        assert!(letter_document.contains("1440"));
+
+        assert!(!String::from_utf8(a4.styles).unwrap().contains("Georgia"));
+        assert!(String::from_utf8(letter.styles).unwrap().contains("Georgia"));
+    }
+
+    #[test]
+    fn test_numbered_headings() {
+        let arena = Arena::new();
+        let root = parse_document(
+            &arena,
+            "# Intro\n\n### Skipped\n\n## Second\n\n# Next\n",
+            &Options::default(),
+        );
+        crate::headings::number_headings(&arena, root, 1);
+
+        let document = DocxTransformer::build_docx(root, &DocxOptions::default())
+            .build()
+            .document;
+        let document = String::from_utf8(document).unwrap();
+        for number in ["1 ", "1.1 ", "1.2 ", "2 "] {
+            assert!(document.contains(&format!(">{}<", number)), "missing {:?}", number);
+        }
+        assert!(!document.contains("0."));
     }
 
     #[test]
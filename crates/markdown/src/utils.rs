@@ -1,8 +1,195 @@
+use super::CodeBlock;
+use comrak::nodes::{AstNode, NodeValue};
+use std::collections::HashSet;
+use std::ops::Range;
+use unicode_normalization::char::is_combining_mark;
+
 /// Estimates word count from Markdown text
 ///
-/// This is a simple estimation that counts whitespace-separated tokens
+/// This is a simple estimation that counts whitespace-separated tokens (see [`writer_core::count_words`])
 pub fn estimate_word_count(text: &str) -> usize {
-    text.split_whitespace().filter(|s| !s.is_empty()).count()
+    writer_core::count_words(text)
+}
+
+/// Abbreviations whose trailing period does not end a sentence
+const NON_TERMINAL_ABBREVIATIONS: &[&str] = &[
+    "mr.", "mrs.", "ms.", "dr.", "prof.", "sr.", "jr.", "st.", "vs.", "e.g.", "i.e.", "cf.", "approx.", "no.", "fig.",
+];
+
+/// Counts sentences in prose by splitting on `.`, `!`, and `?` terminators
+///
+/// Known abbreviations (e.g. "Dr.", "e.g.") and single-letter initials do not end a sentence.
+/// Trailing text without a terminator counts as a final sentence.
+pub fn count_sentences(text: &str) -> usize {
+    let mut sentences = 0;
+    let mut pending = false;
+
+    for token in text.split_whitespace() {
+        pending = true;
+
+        let trimmed = token.trim_end_matches(['"', '\'', ')', ']', '\u{201D}', '\u{2019}']);
+        if !trimmed.ends_with(['.', '!', '?', '\u{2026}']) {
+            continue;
+        }
+
+        let word = trimmed
+            .trim_start_matches(['"', '\'', '(', '[', '\u{201C}', '\u{2018}'])
+            .to_lowercase();
+        let is_initial = word.len() == 2 && word.starts_with(|ch: char| ch.is_alphabetic()) && word.ends_with('.');
+        if NON_TERMINAL_ABBREVIATIONS.contains(&word.as_str()) || is_initial {
+            continue;
+        }
+
+        sentences += 1;
+        pending = false;
+    }
+
+    if pending { sentences + 1 } else { sentences }
+}
+
+/// Prefix applied to generated heading ids (matches `header_ids` in the render profiles)
+pub const HEADING_ID_PREFIX: &str = "heading-";
+
+/// Builds the anchor id the renderer generates for a heading, without de-duplication suffixes
+///
+/// Mirrors Comrak's anchorizer: lowercase, keep letters, marks, numbers, connector punctuation,
+/// `-` and spaces, then turn spaces into `-`.
+pub fn heading_anchor(text: &str) -> String {
+    let slug: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|ch| {
+            ch.is_alphanumeric()
+                || is_combining_mark(*ch)
+                || matches!(
+                    ch,
+                    '-' | ' ' | '_' | '‿' | '⁀' | '⁔' | '︳' | '︴' | '﹍' | '﹎' | '﹏' | '＿'
+                )
+        })
+        .map(|ch| if ch == ' ' { '-' } else { ch })
+        .collect();
+    format!("{}{}", HEADING_ID_PREFIX, slug)
+}
+
+/// Hands out heading anchors the way the renderer does, suffixing repeats with `-1`, `-2`, ...
+#[derive(Debug, Default)]
+pub(crate) struct HeadingAnchors(HashSet<String>);
+
+impl HeadingAnchors {
+    pub(crate) fn anchorize(&mut self, text: &str) -> String {
+        let base = heading_anchor(text);
+        let mut anchor = base.clone();
+        let mut suffix = 0;
+        while self.0.contains(&anchor) {
+            suffix += 1;
+            anchor = format!("{}-{}", base, suffix);
+        }
+        self.0.insert(anchor.clone());
+        anchor
+    }
+}
+
+/// Returns true when a link or image URL refers to a local file path
+///
+/// URLs with a scheme (`https:`, `data:`, `mailto:`...), protocol-relative URLs, and bare
+/// fragments are not local. Single-letter schemes are treated as Windows drive letters.
+pub fn is_local_url(url: &str) -> bool {
+    let url = url.trim();
+    if url.is_empty() || url.starts_with('#') || url.starts_with("//") {
+        return false;
+    }
+
+    let has_scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme.starts_with(|ch: char| ch.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'))
+    });
+    !has_scheme
+}
+
+/// Tracks fenced code blocks while scanning Markdown source line by line
+#[derive(Debug, Default)]
+pub struct FenceTracker {
+    open: Option<(char, usize)>,
+}
+
+impl FenceTracker {
+    /// Feeds the next line, returning true when it is a fence delimiter or inside a fenced block
+    pub fn is_code_line(&mut self, line: &str) -> bool {
+        let trimmed = line.trim_start();
+
+        if let Some((marker, length)) = fence_marker(trimmed) {
+            match self.open {
+                None => self.open = Some((marker, length)),
+                Some((open_marker, open_length))
+                    if marker == open_marker && length >= open_length && trimmed[length..].trim().is_empty() =>
+                {
+                    self.open = None;
+                }
+                Some(_) => {}
+            }
+            return true;
+        }
+
+        self.open.is_some()
+    }
+}
+
+/// Returns the marker character and run length when a (left-trimmed) line opens or closes a code fence
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let marker = line.chars().next().filter(|ch| *ch == '`' || *ch == '~')?;
+    let length = line.chars().take_while(|ch| *ch == marker).count();
+    (length >= 3).then_some((marker, length))
+}
+
+/// Byte ranges of the whole lines each code block (fenced or indented) spans in `body`
+///
+/// Blank lines that the parser attaches to the end of an indented block are not included.
+pub(crate) fn code_block_ranges<'a>(root: &'a AstNode<'a>, body: &str) -> Vec<Range<usize>> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(body.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect();
+    let line_start = |line: usize| line_starts.get(line.saturating_sub(1)).copied().unwrap_or(body.len());
+
+    root.descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            if !matches!(data.value, NodeValue::CodeBlock(_)) {
+                return None;
+            }
+
+            let start = line_start(data.sourcepos.start.line);
+            let end = line_start(data.sourcepos.end.line + 1);
+            let content_end = start + body[start..end].trim_end().len();
+            let end = body[content_end..end]
+                .find('\n')
+                .map_or(end, |idx| content_end + idx + 1);
+            Some(start..end)
+        })
+        .collect()
+}
+
+/// Collects every code block, with source lines relative to `root`'s text
+pub(crate) fn code_blocks<'a>(root: &'a AstNode<'a>) -> Vec<CodeBlock> {
+    root.descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            let NodeValue::CodeBlock(block) = &data.value else {
+                return None;
+            };
+
+            Some(CodeBlock {
+                language: block.info.split_whitespace().next().map(str::to_string),
+                content: block.literal.clone(),
+                fenced: block.fenced,
+                start_line: data.sourcepos.start.line,
+                end_line: data.sourcepos.end.line,
+            })
+        })
+        .collect()
 }
 
 /// Validates that HTML output contains source position attributes
@@ -19,3 +206,58 @@ pub fn html_escape(text: &str) -> String {
         .replace('"', "&quot;")
         .replace('\'', "&#x27;")
 }
+
+/// Reverses [`html_escape`] for attribute values
+fn html_unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Rewrites the `src` of `<img>` tags in rendered HTML whose URL is local
+///
+/// The callback receives the unescaped URL; returning `None` leaves the tag untouched.
+pub fn rewrite_img_sources(html: &str, mut rewrite: impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut copied = 0;
+    let mut cursor = 0;
+
+    while let Some(offset) = html[cursor..].find("<img") {
+        let tag_start = cursor + offset;
+        let name_end = tag_start + "<img".len();
+        if !html[name_end..].starts_with(|ch: char| ch.is_ascii_whitespace() || ch == '>') {
+            cursor = name_end;
+            continue;
+        }
+        let tag_end = html[tag_start..].find('>').map_or(html.len(), |end| tag_start + end);
+        cursor = tag_end;
+
+        let tag = &html[tag_start..tag_end];
+        let Some(attr) = tag.find(" src=") else {
+            continue;
+        };
+        let value_start = attr + " src=".len();
+        let Some(quote) = tag[value_start..].chars().next().filter(|ch| *ch == '"' || *ch == '\'') else {
+            continue;
+        };
+        let Some(length) = tag[value_start + 1..].find(quote) else {
+            continue;
+        };
+
+        let url = html_unescape(&tag[value_start + 1..value_start + 1 + length]);
+        if !is_local_url(&url) {
+            continue;
+        }
+
+        if let Some(replacement) = rewrite(&url) {
+            output.push_str(&html[copied..tag_start + value_start + 1]);
+            output.push_str(&html_escape(&replacement));
+            copied = tag_start + value_start + 1 + length;
+        }
+    }
+
+    output.push_str(&html[copied..]);
+    output
+}
@@ -0,0 +1,146 @@
+use super::{parser::MarkdownParser, utils};
+use std::ops::Range;
+
+/// A `[[target#heading|label]]` reference found in Markdown source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WikiLink<'a> {
+    target: &'a str,
+    heading: Option<&'a str>,
+    label: Option<&'a str>,
+}
+
+impl<'a> WikiLink<'a> {
+    fn parse(inner: &'a str) -> Option<Self> {
+        let (reference, label) = match inner.split_once('|') {
+            Some((reference, label)) => (reference, Some(label.trim()).filter(|label| !label.is_empty())),
+            None => (inner, None),
+        };
+        let (target, heading) = match reference.split_once('#') {
+            Some((target, heading)) => (
+                target.trim(),
+                Some(heading.trim()).filter(|heading| !heading.is_empty()),
+            ),
+            None => (reference.trim(), None),
+        };
+
+        if target.is_empty() && heading.is_none() {
+            return None;
+        }
+
+        Some(Self { target, heading, label })
+    }
+
+    fn display_text(&self) -> String {
+        if let Some(label) = self.label {
+            return label.to_string();
+        }
+
+        match (self.target.is_empty(), self.heading) {
+            (false, Some(heading)) => format!("{} > {}", self.target, heading),
+            (true, Some(heading)) => heading.to_string(),
+            _ => self.target.to_string(),
+        }
+    }
+}
+
+/// Rewrites wikilinks in Markdown prose into standard inline links
+///
+/// Front matter, fenced code blocks, inline code spans, and `![[embeds]]` are left untouched,
+/// as are links whose target the resolver cannot map to an href.
+pub(crate) fn rewrite_wikilinks(text: &str, resolver: impl Fn(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut copied = 0;
+
+    for (range, link) in find_wikilinks(text) {
+        if let Some(href) = resolve_href(&link, &resolver) {
+            output.push_str(&text[copied..range.start]);
+            output.push_str(&format_link(&link.display_text(), &href));
+            copied = range.end;
+        }
+    }
+
+    output.push_str(&text[copied..]);
+    output
+}
+
+/// Targets of the wikilinks in Markdown prose, in order, skipping same-document `[[#heading]]` links
+pub(crate) fn wikilink_targets(text: &str) -> Vec<String> {
+    find_wikilinks(text)
+        .into_iter()
+        .filter(|(_, link)| !link.target.is_empty())
+        .map(|(_, link)| link.target.to_string())
+        .collect()
+}
+
+/// Wikilinks outside front matter, fenced code, and code spans, with their byte ranges in `text`
+fn find_wikilinks(text: &str) -> Vec<(Range<usize>, WikiLink<'_>)> {
+    let body_start = text.len() - MarkdownParser::extract_front_matter(text).0.len();
+    let mut links = Vec::new();
+    let mut line_start = body_start;
+
+    let mut fence = utils::FenceTracker::default();
+    for line in text[body_start..].split_inclusive('\n') {
+        if !fence.is_code_line(line) {
+            find_line_wikilinks(line, line_start, &mut links);
+        }
+        line_start += line.len();
+    }
+
+    links
+}
+
+fn find_line_wikilinks<'a>(line: &'a str, offset: usize, links: &mut Vec<(Range<usize>, WikiLink<'a>)>) {
+    let bytes = line.as_bytes();
+    let mut cursor = 0;
+
+    while cursor < bytes.len() {
+        if bytes[cursor] == b'`' {
+            cursor += writer_core::code_span_len(line, cursor);
+            continue;
+        }
+
+        if line[cursor..].starts_with("[[")
+            && (cursor == 0 || bytes[cursor - 1] != b'!')
+            && let Some(end) = line[cursor + 2..].find("]]")
+        {
+            let inner = &line[cursor + 2..cursor + 2 + end];
+            let link_end = cursor + 2 + end + 2;
+
+            if !inner.contains('[')
+                && let Some(link) = WikiLink::parse(inner)
+            {
+                links.push((offset + cursor..offset + link_end, link));
+            }
+
+            cursor = link_end;
+            continue;
+        }
+
+        cursor += line[cursor..].chars().next().map_or(1, char::len_utf8);
+    }
+}
+
+fn resolve_href(link: &WikiLink<'_>, resolver: &impl Fn(&str) -> Option<String>) -> Option<String> {
+    let fragment = link
+        .heading
+        .map(|heading| format!("#{}", utils::heading_anchor(heading)));
+
+    if link.target.is_empty() {
+        return fragment;
+    }
+
+    let href = resolver(link.target)?;
+    Some(match fragment {
+        Some(fragment) => format!("{}{}", href, fragment),
+        None => href,
+    })
+}
+
+fn format_link(text: &str, href: &str) -> String {
+    let escaped_text = text.replace('[', "\\[").replace(']', "\\]");
+    if href.contains(|ch: char| ch.is_whitespace() || ch == '(' || ch == ')') {
+        format!("[{}](<{}>)", escaped_text, href.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        format!("[{}]({})", escaped_text, href)
+    }
+}
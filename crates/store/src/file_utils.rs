@@ -1,19 +1,107 @@
-use std::fs::File;
+use std::collections::HashSet;
+use std::fs::{File, FileType};
 use std::io::Read;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 use super::text_utils;
-use writer_core::AppError;
+use writer_core::{AppError, LocationDescriptor};
 
 const INDEXABLE_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "txt"];
 
 pub fn is_indexable_text_path(path: &Path) -> bool {
+    is_indexable_with_extensions(path, None)
+}
+
+/// Checks a path against a location's extension allowlist, or the default set when none is configured
+pub fn is_indexable_with_extensions(path: &Path, extensions: Option<&[String]>) -> bool {
     let extension = path
         .extension()
         .and_then(|value| value.to_str())
         .unwrap_or("")
         .to_lowercase();
-    INDEXABLE_EXTENSIONS.contains(&extension.as_str())
+
+    match extensions {
+        Some(extensions) => extensions.contains(&extension),
+        None => INDEXABLE_EXTENSIONS.contains(&extension.as_str()),
+    }
+}
+
+/// Normalizes user-provided extensions to lowercase without a leading dot, dropping blanks and duplicates
+pub fn normalize_extensions(extensions: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for extension in extensions {
+        let extension = extension.trim().trim_start_matches('.').to_lowercase();
+        if !extension.is_empty() && !normalized.contains(&extension) {
+            normalized.push(extension);
+        }
+    }
+    normalized
+}
+
+/// Returns true when a wikilink target refers to `rel_path`
+///
+/// Exact relative paths always match; text documents also match without their extension,
+/// and bare names (no `/`) match the filename or stem in any directory.
+pub fn matches_link_target(rel_path: &Path, target: &str) -> bool {
+    let normalize = |value: &str| value.replace('\\', "/").to_lowercase();
+    let target = normalize(target.trim().trim_start_matches('/'));
+    if target.is_empty() {
+        return false;
+    }
+
+    let path = normalize(&rel_path.to_string_lossy());
+    if path == target {
+        return true;
+    }
+
+    if !is_indexable_text_path(rel_path) {
+        return false;
+    }
+
+    if normalize(&rel_path.with_extension("").to_string_lossy()) == target {
+        return true;
+    }
+
+    if target.contains('/') {
+        return false;
+    }
+
+    let filename = rel_path.file_name().map(|name| normalize(&name.to_string_lossy()));
+    let stem = rel_path.file_stem().map(|stem| normalize(&stem.to_string_lossy()));
+    filename.as_deref() == Some(target.as_str()) || stem.as_deref() == Some(target.as_str())
+}
+
+/// Picks the document a wikilink `target` resolves to among `rel_paths`, given in path order
+///
+/// Only paths that [`matches_link_target`] are considered; the shallowest wins, and the first one
+/// listed among equally deep matches.
+pub fn best_link_match(rel_paths: impl IntoIterator<Item = PathBuf>, target: &str) -> Option<PathBuf> {
+    let mut best: Option<PathBuf> = None;
+    for rel_path in rel_paths {
+        if matches_link_target(&rel_path, target)
+            && best
+                .as_ref()
+                .is_none_or(|best| rel_path.components().count() < best.components().count())
+        {
+            best = Some(rel_path);
+        }
+    }
+    best
+}
+
+/// Lookup key a document is found by from wikilinks: its lowercased filename stem
+pub fn link_key(filename: &str) -> String {
+    Path::new(filename)
+        .file_stem()
+        .map_or_else(|| filename.to_lowercase(), |stem| stem.to_string_lossy().to_lowercase())
+}
+
+/// The `link_key` values a document matching `target` can have (see [`matches_link_target`])
+pub fn link_target_keys(target: &str) -> Vec<String> {
+    let target = target.trim().replace('\\', "/");
+    let name = target.rsplit('/').next().unwrap_or_default().to_lowercase();
+    let stem = link_key(&name);
+    if stem == name { vec![name] } else { vec![name, stem] }
 }
 
 pub fn read_file_text_with_detection(path: &Path) -> Result<String, AppError> {
@@ -25,26 +113,322 @@ pub fn read_file_text_with_detection(path: &Path) -> Result<String, AppError> {
     Ok(text)
 }
 
-pub fn collect_file_paths_recursive(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), AppError> {
+/// Decides which symlinks a directory walk follows
+///
+/// Symlinks are skipped unless following is enabled. Followed links must resolve inside the root,
+/// and each directory is entered at most once (by whichever path reaches it first), so link cycles
+/// end and linked content is not listed twice.
+pub struct SymlinkGuard {
+    follow: bool,
+    root: PathBuf,
+    visited: HashSet<PathBuf>,
+}
+
+impl SymlinkGuard {
+    pub fn new(root: &Path, follow: bool) -> Self {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let visited = HashSet::from([root.clone()]);
+        Self { follow, root, visited }
+    }
+
+    /// Returns true when the walk should visit `path`, a directory entry of the given type
+    pub fn allows(&mut self, path: &Path, file_type: &FileType) -> bool {
+        if !file_type.is_symlink() {
+            return !(self.follow && file_type.is_dir())
+                || path
+                    .canonicalize()
+                    .map_or(true, |canonical| self.visited.insert(canonical));
+        }
+
+        if !self.follow {
+            return false;
+        }
+
+        let Ok(target) = path.canonicalize() else {
+            log::debug!("Skipping broken symlink {:?}", path);
+            return false;
+        };
+        if !target.starts_with(&self.root) {
+            log::warn!("Skipping symlink {:?} that points outside the location root", path);
+            return false;
+        }
+
+        !target.is_dir() || self.visited.insert(target)
+    }
+}
+
+/// Number of directories between the location root and `rel_path` (0 for an entry in the root)
+pub fn path_depth(rel_path: &Path) -> usize {
+    rel_path.components().count().saturating_sub(1)
+}
+
+/// Whether `rel_path` is at most `max_depth` directories below the location root (`None` is unlimited)
+pub fn within_depth(rel_path: &Path, max_depth: Option<usize>) -> bool {
+    max_depth.is_none_or(|max_depth| path_depth(rel_path) <= max_depth)
+}
+
+/// Whether files inside the directory `dir` are within `max_depth` of `root`
+pub fn descends_into(root: &Path, dir: &Path, max_depth: Option<usize>) -> bool {
+    max_depth.is_none_or(|max_depth| {
+        dir.strip_prefix(root)
+            .is_ok_and(|rel_dir| rel_dir.components().count() <= max_depth)
+    })
+}
+
+/// Collects files under `dir`, skipping entries whose path relative to `root` the filter ignores
+///
+/// Ignored directories are not descended into, and symlinks are handled by `symlinks`. With
+/// `max_depth`, files more than that many directories below `root` are not collected.
+pub fn collect_file_paths_recursive(
+    root: &Path, dir: &Path, filter: &LocationFilter, max_depth: Option<usize>, symlinks: &mut SymlinkGuard,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), AppError> {
     let entries = std::fs::read_dir(dir).map_err(|e| AppError::io(format!("Failed to read directory: {}", e)))?;
 
     for entry in entries {
         let entry = entry.map_err(|e| AppError::io(format!("Failed to read entry: {}", e)))?;
         let path = entry.path();
+        let file_type = entry
+            .file_type()
+            .map_err(|e| AppError::io(format!("Failed to read entry type: {}", e)))?;
+        if !symlinks.allows(&path, &file_type) {
+            continue;
+        }
+        let is_dir = path.is_dir();
+
+        if let Ok(rel_path) = path.strip_prefix(root)
+            && filter.is_ignored(rel_path, is_dir)
+        {
+            continue;
+        }
 
         if path.is_file() {
             files.push(path);
-        } else if path.is_dir() {
-            collect_file_paths_recursive(&path, files)?;
+        } else if is_dir && descends_into(root, &path, max_depth) {
+            collect_file_paths_recursive(root, &path, filter, max_depth, symlinks, files)?;
         }
     }
 
     Ok(())
 }
 
+/// A location's indexable extensions and ignore globs, parsed once to check many paths against
+///
+/// The default filter uses the default extension set and ignores nothing.
+#[derive(Debug, Clone, Default)]
+pub struct LocationFilter {
+    extensions: Option<Vec<String>>,
+    ignore_globs: Vec<IgnoreGlob>,
+}
+
+/// One gitignore-style pattern with its trailing `/` and leading anchor already resolved
+#[derive(Debug, Clone)]
+struct IgnoreGlob {
+    pattern: String,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl LocationFilter {
+    pub fn new(extensions: Option<&[String]>, ignore_globs: &[String]) -> Self {
+        let ignore_globs = ignore_globs
+            .iter()
+            .map(|glob| glob.trim())
+            .filter(|glob| !glob.is_empty() && !glob.starts_with('#'))
+            .map(|glob| {
+                let (glob, dir_only) = match glob.strip_suffix('/') {
+                    Some(glob) => (glob, true),
+                    None => (glob, false),
+                };
+                let anchored = glob.contains('/');
+                IgnoreGlob { pattern: glob.trim_start_matches('/').to_string(), dir_only, anchored }
+            })
+            .collect();
+
+        Self { extensions: extensions.map(<[String]>::to_vec), ignore_globs }
+    }
+
+    pub fn for_location(location: &LocationDescriptor) -> Self {
+        Self::new(location.indexable_extensions.as_deref(), &location.ignore_globs)
+    }
+
+    /// Whether the path has one of the location's indexable text extensions
+    pub fn is_indexable(&self, path: &Path) -> bool {
+        is_indexable_with_extensions(path, self.extensions.as_deref())
+    }
+
+    /// Returns true when `rel_path` (or one of its parent directories) matches an ignore glob
+    ///
+    /// Patterns without a `/` match a file or directory name at any depth; patterns containing one
+    /// are anchored to the location root. A trailing `/` restricts a pattern to directories. `*` and
+    /// `?` stay within one path segment while `**` spans segments. Negation (`!`) is not supported.
+    pub fn is_ignored(&self, rel_path: &Path, is_dir: bool) -> bool {
+        if self.ignore_globs.is_empty() {
+            return false;
+        }
+
+        let segments: Vec<String> = rel_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+
+        (1..=segments.len()).any(|len| {
+            let segment_is_dir = len < segments.len() || is_dir;
+            self.ignore_globs
+                .iter()
+                .any(|glob| glob.matches_prefix(&segments[..len], segment_is_dir))
+        })
+    }
+}
+
+impl IgnoreGlob {
+    fn matches_prefix(&self, segments: &[String], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        if self.anchored {
+            wildcard_match(self.pattern.as_bytes(), segments.join("/").as_bytes())
+        } else {
+            segments
+                .last()
+                .is_some_and(|name| wildcard_match(self.pattern.as_bytes(), name.as_bytes()))
+        }
+    }
+}
+
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            let rest = &pattern[2..];
+            match rest.strip_prefix(b"/") {
+                Some(rest) => (0..=text.len())
+                    .any(|idx| (idx == 0 || text[idx - 1] == b'/') && wildcard_match(rest, &text[idx..])),
+                None => (0..=text.len()).any(|idx| wildcard_match(rest, &text[idx..])),
+            }
+        }
+        Some(b'*') => (0..=text.len())
+            .take_while(|idx| *idx == 0 || text[idx - 1] != b'/')
+            .any(|idx| wildcard_match(&pattern[1..], &text[idx..])),
+        Some(b'?') => text.first().is_some_and(|byte| *byte != b'/') && wildcard_match(&pattern[1..], &text[1..]),
+        Some(byte) => text.first() == Some(byte) && wildcard_match(&pattern[1..], &text[1..]),
+    }
+}
+
 pub fn fallback_title_from_path(rel_path: &Path) -> Option<String> {
     rel_path
         .file_stem()
         .and_then(|value| value.to_str())
         .map(|value| value.to_string())
 }
+
+/// Longest filename stem `slug_file_stem` produces, in characters
+const MAX_SLUG_CHARS: usize = 80;
+
+/// Filename stem made from a heading or title: its lowercase words joined by `-`
+///
+/// Letters and digits are kept, accented ones included; everything else separates words. Returns
+/// `None` when nothing usable is left.
+pub fn slug_file_stem(text: &str) -> Option<String> {
+    let slug = text
+        .to_lowercase()
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(MAX_SLUG_CHARS).collect();
+    let slug = slug.trim_end_matches('-');
+
+    (!slug.is_empty()).then(|| slug.to_string())
+}
+
+/// Resolves a local link or image URL to a filesystem path
+///
+/// Relative URLs resolve against `doc_dir`; root-relative URLs (`/img/a.png`) resolve against the
+/// location root. Query strings and fragments are dropped and percent escapes decoded.
+pub fn resolve_local_url(url: &str, doc_dir: &Path, location_root: &Path) -> PathBuf {
+    let path = url.trim().split(['?', '#']).next().unwrap_or_default();
+    let path = percent_decode(path);
+
+    match path.strip_prefix('/') {
+        Some(root_relative) => location_root.join(root_relative),
+        None => doc_dir.join(path),
+    }
+}
+
+/// Location-relative path that a local link in the document at `doc_rel_path` points to
+///
+/// Resolves like [`resolve_local_url`] but without touching the filesystem. Returns `None` when the
+/// link climbs out of the location root or names no file (such as `?query` alone).
+pub fn link_target_rel_path(url: &str, doc_rel_path: &Path) -> Option<PathBuf> {
+    let doc_dir = doc_rel_path.parent().unwrap_or(Path::new(""));
+    let mut rel_path = PathBuf::new();
+
+    for component in resolve_local_url(url, doc_dir, Path::new("")).components() {
+        match component {
+            Component::Normal(part) => rel_path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if rel_path.pop() => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    (!rel_path.as_os_str().is_empty()).then_some(rel_path)
+}
+
+/// MIME type for an image file, guessed from its extension
+pub fn image_mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("bmp") => "image/bmp",
+        Some("ico") => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        if bytes[idx] == b'%'
+            && let Some(hex) = value.get(idx + 1..idx + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            decoded.push(byte);
+            idx += 3;
+            continue;
+        }
+
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Fsyncs a directory so entries just created or renamed in it survive a crash
+///
+/// Windows cannot open a directory as a file and commits renames with the file's metadata, so
+/// this is a no-op there.
+#[cfg(unix)]
+pub fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+pub fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
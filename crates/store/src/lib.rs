@@ -1,36 +1,81 @@
-use chrono::{DateTime, Utc};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, SecondsFormat, Utc};
 use rusqlite::{Connection, OptionalExtension, params, params_from_iter, types::Value};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use uuid::Uuid;
 use writer_core::{
-    AppError, DocContent, DocId, DocListOptions, DocMeta, DocSortField, Encoding, ErrorCode, LineEnding,
-    LocationDescriptor, LocationId, SavePolicy, SaveResult, SearchFilters, SearchHit, SortOrder,
+    AppError, DiffHunk, DocContent, DocId, DocListOptions, DocMeta, DocSortField, Durability, Encoding, ErrorCode,
+    GoalProgress, ImportCollision, ImportOptions, ImportReport, IndexAudit, LineEnding, LocationDescriptor, LocationId,
+    RawDocContent, ReconcilePlan, SavePolicy, SaveResult, SearchFilters, SearchHit, SearchOptions, SortOrder,
+    TagBulkFailure, TagBulkReport,
+};
+use writer_core::{
+    default_ignore_globs, diff_lines, is_conflicted_filename_with, is_path_within_location, normalize_relative_path,
+    validate_filename,
+};
+use writer_md::{
+    AssetMode, DocStats, ExportOptions, Heading, ImageRef, LinkRef, MarkdownEngine, MarkdownProfile, RenderResult,
 };
-use writer_core::{is_conflicted_filename, normalize_relative_path};
-use writer_md::{MarkdownEngine, MarkdownProfile};
 
 mod file_utils;
 mod settings;
 mod text_utils;
 
+use file_utils::{LocationFilter, SymlinkGuard};
+
+pub use settings::LocationViewPrefs;
 pub use settings::{
     CaptureDocRef, CaptureMode, FocusDimmingMode, MarkdownPreviewStyle, SessionState, SessionTab, SidebarTreeState,
 };
-pub use settings::{GlobalCaptureSettings, StyleCheckSettings, UiLayoutSettings};
+pub use settings::{GlobalCaptureSettings, MissingCaptureTarget, SearchSettings, StyleCheckSettings, UiLayoutSettings};
 
 const UI_LAYOUT_SETTINGS_KEY: &str = "ui_layout";
 const SIDEBAR_TREE_STATE_KEY: &str = "sidebar_tree";
+const LOCATION_VIEW_PREFS_KEY: &str = "location_view_prefs";
 const STYLE_CHECK_SETTINGS_KEY: &str = "style_check";
+const SEARCH_SETTINGS_KEY: &str = "search";
 const GLOBAL_CAPTURE_SETTINGS_KEY: &str = "global_capture";
 const LAST_OPEN_DOC_SETTINGS_KEY: &str = "last_open_doc";
 const SESSION_STATE_SETTINGS_KEY: &str = "session_state";
+const README_TEMPLATE_SETTINGS_KEY: &str = "readme_template";
 const README_TEMPLATE: &str = include_str!("../assets/README_TEMPLATE.md");
 
+/// Name of the metadata file written at the root of a location export archive
+pub const EXPORT_MANIFEST_NAME: &str = "manifest.json";
+
+/// Most entries a zip archive may hold to be imported into a location
+pub const MAX_IMPORT_ENTRIES: usize = 10_000;
+
+/// Most bytes a zip import may extract, summed over every entry
+pub const MAX_IMPORT_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Directory at a location root holding trashed documents
+const TRASH_DIR: &str = ".trash";
+
+/// Most worker threads `render_many` renders on at once
+const MAX_RENDER_THREADS: usize = 4;
+
+/// How long a fetched link title is reused before it is fetched again
+const LINK_TITLE_CACHE_TTL_HOURS: i64 = 7 * 24;
+
+/// Most hits `search_grouped` returns across all of its groups
+const MAX_GROUPED_SEARCH_HITS: usize = 200;
+
+/// Documents with more headings than this keep no cached outline and are parsed on demand instead
+const MAX_CACHED_OUTLINE_HEADINGS: usize = 1000;
+
+/// Paths left out of location exports on top of the location's ignore globs: the trash and
+/// `.meta.json` sidecars
+const EXPORT_EXCLUDED_GLOBS: &[&str] = &[".trash/", "*.meta.json"];
+
 pub fn get_markdown_help() -> &'static str {
     README_TEMPLATE
 }
@@ -40,6 +85,146 @@ pub struct StyleCheckPattern {
     pub text: String,
     pub category: String,
     pub replacement: Option<String>,
+    #[serde(default)]
+    pub is_regex: bool,
+}
+
+/// Contents of `manifest.json` in a location export archive
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportManifest {
+    pub location_name: String,
+    pub exported_at: DateTime<Utc>,
+    pub files: Vec<ExportManifestEntry>,
+}
+
+/// An archived file; the catalog fields are `None` for files the catalog has no row for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportManifestEntry {
+    /// Path inside the archive, `/`-separated and relative to the location root
+    pub rel_path: String,
+    pub size_bytes: u64,
+    pub mtime: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub content_hash: Option<String>,
+    pub title: Option<String>,
+    pub word_count: Option<usize>,
+}
+
+/// Front matter field written by `stamp_updated_on_save`
+pub const UPDATED_FIELD: &str = "updated";
+
+/// Extensions of the markdown files `stamp_updated_on_save` applies to
+const STAMPED_EXTENSIONS: &[&str] = &["md", "markdown", "mdx"];
+
+/// Unsaved editor content recovered from the autosave table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AutosaveRecovery {
+    pub text: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// A document with recovery content waiting in the autosave table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AutosavePending {
+    pub doc_id: DocId,
+    pub saved_at: DateTime<Utc>,
+    pub size_bytes: usize,
+}
+
+/// What `maintenance_purge` removes
+///
+/// Trashed files are always considered; word-count history points are pruned only when one of the
+/// history limits is set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct PurgePolicy {
+    /// Remove files trashed at least this many days ago (0 empties the trash)
+    pub trash_older_than_days: u32,
+    /// Drop word-count history points recorded more than this many days ago
+    pub history_older_than_days: Option<u32>,
+    /// Keep at most this many of each document's newest word-count history points
+    pub history_max_per_doc: Option<usize>,
+    /// Report what would be removed without deleting anything
+    pub dry_run: bool,
+}
+
+/// A trashed file or trash directory `maintenance_purge` could not remove
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeFailure {
+    pub location_id: LocationId,
+    /// Path relative to the location root
+    pub rel_path: PathBuf,
+    pub error: AppError,
+}
+
+/// Files removed (or, in a dry run, that would be removed) by `maintenance_purge`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PurgeReport {
+    pub dry_run: bool,
+    pub removed: Vec<DocId>,
+    pub reclaimed_bytes: u64,
+    /// Word-count history points dropped by the history limits
+    pub history_points_removed: usize,
+    /// Files and trash directories left in place because reading or deleting them failed
+    pub failed: Vec<PurgeFailure>,
+}
+
+/// A subdirectory of a location listed by `dir_list`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DirEntry {
+    /// Path relative to the location root
+    pub rel_path: PathBuf,
+    /// Subdirectories directly inside it, leaving out ignored ones and symlinks
+    pub child_dirs: usize,
+    /// Files directly inside it, leaving out ignored ones and symlinks
+    pub child_files: usize,
+}
+
+/// How `doc_split_by_heading` names the files it writes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SplitNaming {
+    /// A slug of the section's heading (`getting-started.md`)
+    #[default]
+    Slug,
+    /// The source's name and the section's position (`book-01.md`)
+    Index,
+}
+
+/// Connection settings applied when the store's database is opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreOptions {
+    /// Use write-ahead logging so readers (the UI) don't block on the watcher's writes
+    pub wal: bool,
+    /// How long a statement waits on a locked database before failing with "database is locked"
+    pub busy_timeout: std::time::Duration,
+    /// WAL size, in pages, at which SQLite checkpoints back into the main file; 0 disables it
+    pub wal_autocheckpoint: u32,
+    /// Ceiling on the `limit` a search may ask for (the floor is always 1)
+    ///
+    /// [`Store::open_default`] and [`Store::search_settings_set`] replace it with
+    /// [`SearchSettings::max_search_results`], which documents the performance tradeoff.
+    pub max_search_results: usize,
+}
+
+impl Default for StoreOptions {
+    fn default() -> Self {
+        Self {
+            wal: true,
+            busy_timeout: std::time::Duration::from_secs(5),
+            wal_autocheckpoint: 1000,
+            max_search_results: 200,
+        }
+    }
+}
+
+/// A location's filter and the settings that apply to its files, loaded once per operation
+///
+/// Operations that read or index many files build one of these up front instead of querying the
+/// settings again for every file.
+struct ScanContext {
+    filter: LocationFilter,
+    layout: UiLayoutSettings,
+    search: SearchSettings,
 }
 
 /// Manages the SQLite database for the application
@@ -54,6 +239,51 @@ pub struct StyleCheckPattern {
 ///     7. Session
 pub struct Store {
     conn: Arc<Mutex<Connection>>,
+    max_search_results: AtomicUsize,
+}
+
+impl Drop for Store {
+    fn drop(&mut self) {
+        if let Err(error) = self.wal_checkpoint() {
+            log::warn!("Failed to checkpoint WAL on close: {}", error);
+        }
+    }
+}
+
+/// Converts a modification time to a zip entry timestamp, which only covers 1980..=2107
+fn zip_timestamp(timestamp: DateTime<Utc>) -> Option<zip::DateTime> {
+    use chrono::{Datelike, Timelike};
+
+    let year = u16::try_from(timestamp.year()).ok()?;
+    zip::DateTime::from_date_and_time(
+        year,
+        timestamp.month() as u8,
+        timestamp.day() as u8,
+        timestamp.hour() as u8,
+        timestamp.minute() as u8,
+        timestamp.second() as u8,
+    )
+    .ok()
+}
+
+/// FTS5 tokenizer spec for the search settings
+///
+/// `remove_diacritics 2` also folds characters carrying several accents, which the default mode keeps.
+fn fts_tokenizer(settings: &SearchSettings) -> &'static str {
+    if settings.fold_diacritics { "unicode61 remove_diacritics 2" } else { "unicode61" }
+}
+
+/// Cuts `content` to at most `max_bytes` on a character boundary; 0 means no limit
+fn cap_fts_content(content: &str, max_bytes: usize) -> &str {
+    if max_bytes == 0 || content.len() <= max_bytes {
+        return content;
+    }
+
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    &content[..end]
 }
 
 impl Store {
@@ -86,20 +316,73 @@ impl Store {
         }
     }
 
-    /// Opens or creates the store at the given path
+    /// Opens or creates the store at the given path with the default [`StoreOptions`]
     pub fn open(path: &PathBuf) -> Result<Self, AppError> {
-        log::debug!("Opening store at {:?}", path);
+        Self::open_with_options(path, StoreOptions::default())
+    }
+
+    /// Opens or creates the store at the given path, applying journal and locking options
+    pub fn open_with_options(path: &PathBuf, options: StoreOptions) -> Result<Self, AppError> {
+        log::debug!("Opening store at {:?} with {:?}", path, options);
 
         let conn = Connection::open(path).map_err(|e| AppError::io(format!("Failed to open database: {}", e)))?;
+        Self::configure_connection(&conn, &options)?;
 
-        let store = Self { conn: Arc::new(Mutex::new(conn)) };
+        let store = Self {
+            conn: Arc::new(Mutex::new(conn)),
+            max_search_results: AtomicUsize::new(options.max_search_results.max(1)),
+        };
 
         store.init_schema()?;
+        store.migrate_fts_tokenizer()?;
         log::info!("Store initialized successfully");
 
         Ok(store)
     }
 
+    /// Applies the busy timeout and, when enabled, switches the database to WAL journaling
+    fn configure_connection(conn: &Connection, options: &StoreOptions) -> Result<(), AppError> {
+        conn.busy_timeout(options.busy_timeout)
+            .map_err(|e| AppError::io(format!("Failed to set busy timeout: {}", e)))?;
+
+        if !options.wal {
+            return Ok(());
+        }
+
+        let journal_mode: String = conn
+            .pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))
+            .map_err(|e| AppError::io(format!("Failed to enable WAL journaling: {}", e)))?;
+        if !journal_mode.eq_ignore_ascii_case("wal") {
+            log::warn!("Database kept journal_mode={} instead of WAL", journal_mode);
+            return Ok(());
+        }
+
+        conn.pragma_update(None, "wal_autocheckpoint", options.wal_autocheckpoint)
+            .map_err(|e| AppError::io(format!("Failed to set WAL autocheckpoint: {}", e)))?;
+        conn.pragma_update(None, "synchronous", "NORMAL")
+            .map_err(|e| AppError::io(format!("Failed to set synchronous mode: {}", e)))?;
+        Ok(())
+    }
+
+    /// Most hits a single search returns, from [`StoreOptions::max_search_results`] or the search settings
+    pub fn max_search_results(&self) -> usize {
+        self.max_search_results.load(Ordering::Relaxed)
+    }
+
+    /// Folds the write-ahead log back into the main database file and truncates it
+    ///
+    /// SQLite checkpoints on its own as the log grows; this is for shutdown and maintenance, and is a
+    /// no-op for databases not in WAL mode.
+    pub fn wal_checkpoint(&self) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| AppError::io(format!("Failed to checkpoint WAL: {}", e)))
+    }
+
     /// Opens the store in the default application data directory
     pub fn open_default() -> Result<Self, AppError> {
         let app_dir = Self::default_app_dir()?;
@@ -108,7 +391,12 @@ impl Store {
             .map_err(|e| AppError::io(format!("Failed to create app directory: {}", e)))?;
 
         let db_path = Self::default_db_path()?;
-        Self::open(&db_path)
+        let store = Self::open(&db_path)?;
+        let settings = store.search_settings_get()?;
+        store
+            .max_search_results
+            .store(settings.max_search_results.max(1), Ordering::Relaxed);
+        Ok(store)
     }
 
     /// Initializes the database schema
@@ -129,6 +417,9 @@ impl Store {
         )
         .map_err(|e| AppError::io(format!("Failed to create locations table: {}", e)))?;
 
+        Self::ensure_column(&conn, "locations", "indexable_extensions", "TEXT")?;
+        Self::ensure_column(&conn, "locations", "ignore_globs", "TEXT")?;
+
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_locations_path ON locations(root_path)",
             [],
@@ -157,6 +448,21 @@ impl Store {
         )
         .map_err(|e| AppError::io(format!("Failed to create documents table: {}", e)))?;
 
+        Self::ensure_column(&conn, "documents", "aliases", "TEXT")?;
+        Self::ensure_column(&conn, "documents", "doc_uid", "TEXT")?;
+        Self::ensure_column(&conn, "documents", "is_draft", "INTEGER NOT NULL DEFAULT 0")?;
+        Self::ensure_column(&conn, "documents", "outline_json", "TEXT")?;
+        Self::ensure_column(&conn, "documents", "preview_text", "TEXT")?;
+        Self::ensure_column(&conn, "documents", "link_key", "TEXT")?;
+        Self::assign_missing_doc_uids(&conn)?;
+        Self::assign_missing_link_keys(&conn)?;
+
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_documents_uid ON documents(doc_uid)",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create documents uid index: {}", e)))?;
+
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_documents_mtime ON documents(location_id, mtime DESC)",
             [],
@@ -176,16 +482,72 @@ impl Store {
         .map_err(|e| AppError::io(format!("Failed to create updated_at index: {}", e)))?;
 
         conn.execute(
-            "CREATE VIRTUAL TABLE IF NOT EXISTS docs_fts USING fts5(
-                location_id UNINDEXED,
-                rel_path UNINDEXED,
-                title,
-                content,
-                tokenize = 'unicode61'
+            "CREATE INDEX IF NOT EXISTS idx_documents_link_key ON documents(location_id, link_key)",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create link_key index: {}", e)))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS document_tags (
+                location_id INTEGER NOT NULL,
+                rel_path TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (location_id, rel_path, tag)
+            )",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create document_tags table: {}", e)))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_document_tags_tag ON document_tags(location_id, tag)",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create document_tags index: {}", e)))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS document_links (
+                location_id INTEGER NOT NULL,
+                rel_path TEXT NOT NULL,
+                target TEXT NOT NULL,
+                is_wikilink INTEGER NOT NULL,
+                PRIMARY KEY (location_id, rel_path, target, is_wikilink)
+            )",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create document_links table: {}", e)))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_document_links_target ON document_links(location_id, target)",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create document_links index: {}", e)))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS document_goals (
+                location_id INTEGER NOT NULL,
+                rel_path TEXT NOT NULL,
+                target_words INTEGER NOT NULL,
+                reached_at TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (location_id, rel_path)
+            )",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create document_goals table: {}", e)))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS autosave (
+                location_id INTEGER NOT NULL,
+                rel_path TEXT NOT NULL,
+                text TEXT NOT NULL,
+                saved_at TEXT NOT NULL,
+                PRIMARY KEY (location_id, rel_path)
             )",
             [],
         )
-        .map_err(|e| AppError::io(format!("Failed to create docs_fts table: {}", e)))?;
+        .map_err(|e| AppError::io(format!("Failed to create autosave table: {}", e)))?;
+
+        Self::create_fts_table(&conn, "docs_fts", &SearchSettings::default())?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS app_settings (
@@ -207,10 +569,61 @@ impl Store {
         )
         .map_err(|e| AppError::io(format!("Failed to create kv table: {}", e)))?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS link_titles (
+                url TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                fetched_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create link_titles table: {}", e)))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS word_count_history (
+                id INTEGER PRIMARY KEY,
+                doc_uid TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                word_count INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create word_count_history table: {}", e)))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_word_count_history_uid ON word_count_history(doc_uid)",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create word_count_history index: {}", e)))?;
+
         log::debug!("Database schema initialized");
         Ok(())
     }
 
+    /// Adds a column to an existing table when databases created by older versions lack it
+    fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<(), AppError> {
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info({})", table))
+            .map_err(|e| AppError::io(format!("Failed to read {} schema: {}", table, e)))?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| AppError::io(format!("Failed to read {} columns: {}", table, e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::io(format!("Failed to read {} columns: {}", table, e)))?;
+
+        if columns.iter().any(|existing| existing == column) {
+            return Ok(());
+        }
+
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to add {}.{} column: {}", table, column, e)))?;
+
+        Ok(())
+    }
+
     fn kv_get_json<T>(&self, key: &str) -> Result<Option<T>, AppError>
     where
         T: DeserializeOwned,
@@ -269,6 +682,39 @@ impl Store {
         self.kv_set_json(SIDEBAR_TREE_STATE_KEY, state)
     }
 
+    /// The saved listing view for a location, or the default view when none has been saved
+    pub fn location_view_prefs_get(&self, location_id: LocationId) -> Result<LocationViewPrefs, AppError> {
+        let prefs: BTreeMap<i64, LocationViewPrefs> = self.kv_get_json(LOCATION_VIEW_PREFS_KEY)?.unwrap_or_default();
+        Ok(prefs.get(&location_id.0).cloned().unwrap_or_default())
+    }
+
+    /// Saves the listing view for a location, dropping any saved views of locations that no longer exist
+    pub fn location_view_prefs_set(&self, location_id: LocationId, view: &LocationViewPrefs) -> Result<(), AppError> {
+        let location_ids: HashSet<i64> = self
+            .location_list()?
+            .into_iter()
+            .map(|location| location.id.0)
+            .collect();
+        if !location_ids.contains(&location_id.0) {
+            return Err(AppError::not_found(format!("Location not found: {:?}", location_id)));
+        }
+
+        let mut prefs: BTreeMap<i64, LocationViewPrefs> =
+            self.kv_get_json(LOCATION_VIEW_PREFS_KEY)?.unwrap_or_default();
+        prefs.retain(|id, _| location_ids.contains(id));
+        prefs.insert(location_id.0, view.clone());
+        self.kv_set_json(LOCATION_VIEW_PREFS_KEY, &prefs)
+    }
+
+    fn location_view_prefs_remove(&self, location_id: LocationId) -> Result<(), AppError> {
+        let mut prefs: BTreeMap<i64, LocationViewPrefs> =
+            self.kv_get_json(LOCATION_VIEW_PREFS_KEY)?.unwrap_or_default();
+        if prefs.remove(&location_id.0).is_some() {
+            self.kv_set_json(LOCATION_VIEW_PREFS_KEY, &prefs)?;
+        }
+        Ok(())
+    }
+
     pub fn ui_layout_get(&self) -> Result<UiLayoutSettings, AppError> {
         let conn = self
             .conn
@@ -296,6 +742,7 @@ impl Store {
     }
 
     pub fn ui_layout_set(&self, settings: &UiLayoutSettings) -> Result<(), AppError> {
+        let previous_patterns = self.ui_layout_get().ok().map(|previous| previous.conflict_patterns);
         let conn = self
             .conn
             .lock()
@@ -319,9 +766,154 @@ impl Store {
         )
         .map_err(|e| AppError::io(format!("Failed to persist UI layout settings: {}", e)))?;
 
+        if previous_patterns.as_ref() != Some(&settings.conflict_patterns) {
+            Self::refresh_conflict_flags_locked(&conn, &settings.conflict_patterns)?;
+        }
+
+        Ok(())
+    }
+
+    /// Recomputes every catalog row's conflict flag after the `conflict_patterns` setting changes
+    ///
+    /// Reconcile only re-reads files that changed on disk, so flags derived from settings are
+    /// refreshed here instead.
+    fn refresh_conflict_flags_locked(conn: &Connection, patterns: &[String]) -> Result<(), AppError> {
+        let rows = {
+            let mut stmt = conn
+                .prepare("SELECT location_id, rel_path, filename, is_conflict FROM documents")
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read catalog rows: {}", e)))?;
+            stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i32>(3)? != 0,
+                ))
+            })
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog rows: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))?
+        };
+
+        for (location_id, rel_path, filename, was_conflict) in rows {
+            let is_conflict = is_conflicted_filename_with(&filename, patterns);
+            if is_conflict != was_conflict {
+                conn.execute(
+                    "UPDATE documents SET is_conflict = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+                    params![location_id, rel_path, is_conflict as i32],
+                )
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update conflict flag: {}", e)))?;
+            }
+        }
+
         Ok(())
     }
 
+    /// The persisted layout settings, or the defaults when they cannot be read
+    ///
+    /// Operations load these once and hand them to the helpers below rather than each helper
+    /// re-reading them.
+    fn layout_settings(&self) -> UiLayoutSettings {
+        self.ui_layout_get().unwrap_or_else(|e| {
+            log::warn!("Failed to read UI layout settings, using defaults: {}", e);
+            UiLayoutSettings::default()
+        })
+    }
+
+    /// Loads a location's filter with the layout and search settings for an operation over many files
+    fn scan_context(&self, location: &LocationDescriptor) -> Result<ScanContext, AppError> {
+        Ok(ScanContext {
+            filter: LocationFilter::for_location(location),
+            layout: self.layout_settings(),
+            search: self.search_settings_get()?,
+        })
+    }
+
+    /// Applies trim-on-save from `settings`, returning `None` when it is off
+    fn trim_for_save(settings: &UiLayoutSettings, text: &str) -> Option<String> {
+        if !settings.trim_trailing_whitespace_on_save {
+            return None;
+        }
+
+        let code = if settings.preserve_code_whitespace {
+            match MarkdownEngine::new().code_block_ranges(text, MarkdownProfile::Extended) {
+                Ok(ranges) => ranges,
+                Err(e) => {
+                    log::warn!("Failed to find code blocks, saving text unchanged: {}", e);
+                    return None;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        Some(writer_core::trim_trailing_whitespace(text, &code))
+    }
+
+    /// Applies `stamp_updated_on_save`, returning the text with `updated` set to `now`
+    ///
+    /// Only markdown files that already have a front matter block are stamped, so plain notes never
+    /// gain one; `None` leaves the text as sent.
+    fn stamp_for_save(settings: &UiLayoutSettings, doc_id: &DocId, text: &str, now: DateTime<Utc>) -> Option<String> {
+        let enabled = settings.stamp_updated_on_save;
+        let is_markdown = doc_id
+            .rel_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                STAMPED_EXTENSIONS
+                    .iter()
+                    .any(|stamped| extension.eq_ignore_ascii_case(stamped))
+            });
+        if !enabled || !is_markdown {
+            return None;
+        }
+
+        let engine = MarkdownEngine::new();
+        let format = engine.front_matter(text).format?;
+
+        let fields = HashMap::from([(
+            UPDATED_FIELD.to_string(),
+            now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )]);
+        match engine.upsert_front_matter(text, &fields, format) {
+            Ok(stamped) => Some(stamped),
+            Err(e) => {
+                log::warn!("Failed to stamp {:?}, saving text unchanged: {}", doc_id.rel_path, e);
+                None
+            }
+        }
+    }
+
+    /// Adds the `default_extension` setting to a new document's extensionless path
+    ///
+    /// Paths that already have an extension, dotfiles, and files that already exist are returned as-is.
+    fn with_default_extension(settings: &UiLayoutSettings, doc_id: &DocId, root: &Path) -> Result<DocId, AppError> {
+        let has_extension = doc_id.rel_path.extension().is_some();
+        let is_dotfile = doc_id
+            .rel_path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if has_extension || is_dotfile || doc_id.resolve(root).exists() {
+            return Ok(doc_id.clone());
+        }
+
+        let extension = settings.default_extension.trim().trim_start_matches('.');
+        if extension.is_empty() {
+            return Ok(doc_id.clone());
+        }
+
+        Ok(DocId::new(
+            doc_id.location_id,
+            doc_id.rel_path.with_extension(extension),
+        )?)
+    }
+
+    /// Whether `filename` looks like a conflicted copy, including the `conflict_patterns` setting
+    fn is_conflicted(settings: &UiLayoutSettings, filename: &str) -> bool {
+        is_conflicted_filename_with(filename, &settings.conflict_patterns)
+    }
+
     pub fn style_check_get(&self) -> Result<StyleCheckSettings, AppError> {
         let conn = self
             .conn
@@ -375,61 +967,289 @@ impl Store {
         Ok(())
     }
 
-    pub fn global_capture_get(&self) -> Result<GlobalCaptureSettings, AppError> {
+    pub fn search_settings_get(&self) -> Result<SearchSettings, AppError> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        let maybe_value = conn
-            .query_row(
-                "SELECT value FROM app_settings WHERE key = ?1",
-                params![GLOBAL_CAPTURE_SETTINGS_KEY],
-                |row| row.get::<_, String>(0),
-            )
-            .optional()
-            .map_err(|e| AppError::io(format!("Failed to query global capture settings: {}", e)))?;
-
-        match maybe_value {
-            Some(value) => serde_json::from_str::<GlobalCaptureSettings>(&value).map_err(|e| {
-                AppError::new(
-                    ErrorCode::Parse,
-                    format!("Failed to parse persisted global capture settings: {}", e),
-                )
-            }),
-            None => Ok(GlobalCaptureSettings::default()),
-        }
+        Self::search_settings_locked(&conn)
     }
 
-    pub fn global_capture_set(&self, settings: &GlobalCaptureSettings) -> Result<(), AppError> {
-        let conn = self
+    /// Persists search settings, rebuilding the full-text index when the tokenizer changes
+    ///
+    /// Lowering `fts_max_content_bytes` or `preview_chars` trims rows already indexed; raising either
+    /// only affects documents as they are next indexed.
+    pub fn search_settings_set(&self, settings: &SearchSettings) -> Result<(), AppError> {
+        let mut conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        let settings_json = serde_json::to_string(settings).map_err(|e| {
-            AppError::new(
-                ErrorCode::Parse,
-                format!("Failed to serialize global capture settings: {}", e),
-            )
-        })?;
+        let previous = Self::search_settings_locked(&conn)?;
+        let settings_json = serde_json::to_string(settings)
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to serialize search settings: {}", e)))?;
         let updated_at = Utc::now().to_rfc3339();
 
-        conn.execute(
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::io(format!("Failed to start search settings transaction: {}", e)))?;
+
+        tx.execute(
             "INSERT INTO app_settings (key, value, updated_at)
              VALUES (?1, ?2, ?3)
              ON CONFLICT(key) DO UPDATE SET
              value = excluded.value,
              updated_at = excluded.updated_at",
-            params![GLOBAL_CAPTURE_SETTINGS_KEY, settings_json, updated_at],
+            params![SEARCH_SETTINGS_KEY, settings_json, updated_at],
         )
-        .map_err(|e| AppError::io(format!("Failed to persist global capture settings: {}", e)))?;
+        .map_err(|e| AppError::io(format!("Failed to persist search settings: {}", e)))?;
+
+        if previous.fold_diacritics != settings.fold_diacritics {
+            Self::rebuild_fts_locked(&tx, settings)?;
+        }
+        if settings.fts_max_content_bytes != 0
+            && (previous.fts_max_content_bytes == 0 || settings.fts_max_content_bytes < previous.fts_max_content_bytes)
+        {
+            Self::cap_fts_content_locked(&tx, settings.fts_max_content_bytes)?;
+        }
+        if settings.preview_chars < previous.preview_chars {
+            Self::trim_previews_locked(&tx, settings.preview_chars)?;
+        }
+
+        tx.commit()
+            .map_err(|e| AppError::io(format!("Failed to commit search settings: {}", e)))?;
+        self.max_search_results
+            .store(settings.max_search_results.max(1), Ordering::Relaxed);
 
         Ok(())
     }
 
-    pub fn last_open_doc_get(&self) -> Result<Option<CaptureDocRef>, AppError> {
-        let conn = self
+    /// Recreates `docs_fts` with the tokenizer from the persisted search settings, keeping its rows
+    pub fn rebuild_fts(&self) -> Result<(), AppError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let settings = Self::search_settings_locked(&conn)?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::io(format!("Failed to start FTS rebuild transaction: {}", e)))?;
+
+        Self::rebuild_fts_locked(&tx, &settings)?;
+
+        tx.commit()
+            .map_err(|e| AppError::io(format!("Failed to commit FTS rebuild: {}", e)))?;
+
+        log::info!("Rebuilt docs_fts (fold_diacritics = {})", settings.fold_diacritics);
+        Ok(())
+    }
+
+    /// Rebuilds `docs_fts` when its tokenizer does not match the persisted search settings
+    fn migrate_fts_tokenizer(&self) -> Result<(), AppError> {
+        let matches_settings = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let settings = Self::search_settings_locked(&conn)?;
+            let sql = conn
+                .query_row(
+                    "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'docs_fts'",
+                    [],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()
+                .map_err(|e| AppError::io(format!("Failed to inspect docs_fts schema: {}", e)))?;
+
+            sql.is_some_and(|sql| sql.contains(&format!("tokenize = '{}'", fts_tokenizer(&settings))))
+        };
+
+        if matches_settings {
+            return Ok(());
+        }
+
+        log::info!("docs_fts tokenizer does not match search settings, rebuilding");
+        self.rebuild_fts()
+    }
+
+    /// Trims indexed content longer than `max_bytes` in place
+    fn cap_fts_content_locked(conn: &Connection, max_bytes: usize) -> Result<(), AppError> {
+        let rows = {
+            let mut stmt = conn
+                .prepare("SELECT rowid, content FROM docs_fts WHERE length(CAST(content AS BLOB)) > ?1")
+                .map_err(|e| AppError::io(format!("Failed to read oversized FTS rows: {}", e)))?;
+            stmt.query_map(params![max_bytes as i64], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| AppError::io(format!("Failed to query oversized FTS rows: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::io(format!("Invalid FTS row: {}", e)))?
+        };
+
+        for (rowid, content) in rows {
+            conn.execute(
+                "UPDATE docs_fts SET content = ?2 WHERE rowid = ?1",
+                params![rowid, cap_fts_content(&content, max_bytes)],
+            )
+            .map_err(|e| AppError::io(format!("Failed to cap FTS content: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Re-cuts stored previews longer than `max_chars` the way indexing cuts them (0 clears them all)
+    fn trim_previews_locked(conn: &Connection, max_chars: usize) -> Result<(), AppError> {
+        let rows = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT location_id, rel_path, preview_text FROM documents
+                     WHERE length(preview_text) > ?1",
+                )
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read search previews: {}", e)))?;
+            stmt.query_map(params![max_chars as i64], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query search previews: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid search preview row: {}", e)))?
+        };
+
+        for (location_id, rel_path, preview) in rows {
+            let trimmed = Some(text_utils::prose_preview(&preview, max_chars)).filter(|preview| !preview.is_empty());
+            conn.execute(
+                "UPDATE documents SET preview_text = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+                params![location_id, rel_path, trimmed],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to trim search previews: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    fn search_settings_locked(conn: &Connection) -> Result<SearchSettings, AppError> {
+        let maybe_value = conn
+            .query_row(
+                "SELECT value FROM app_settings WHERE key = ?1",
+                params![SEARCH_SETTINGS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::io(format!("Failed to query search settings: {}", e)))?;
+
+        match maybe_value {
+            Some(value) => serde_json::from_str::<SearchSettings>(&value).map_err(|e| {
+                AppError::new(
+                    ErrorCode::Parse,
+                    format!("Failed to parse persisted search settings: {}", e),
+                )
+            }),
+            None => Ok(SearchSettings::default()),
+        }
+    }
+
+    fn create_fts_table(conn: &Connection, table: &str, settings: &SearchSettings) -> Result<(), AppError> {
+        conn.execute(
+            &format!(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS {} USING fts5(
+                location_id UNINDEXED,
+                rel_path UNINDEXED,
+                title,
+                content,
+                tokenize = '{}'
+            )",
+                table,
+                fts_tokenizer(settings)
+            ),
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to create {} table: {}", table, e)))?;
+
+        Ok(())
+    }
+
+    /// Copies the index into a table with the configured tokenizer and swaps it in place of `docs_fts`
+    fn rebuild_fts_locked(conn: &Connection, settings: &SearchSettings) -> Result<(), AppError> {
+        conn.execute("DROP TABLE IF EXISTS docs_fts_rebuild", [])
+            .map_err(|e| AppError::io(format!("Failed to clear stale FTS rebuild table: {}", e)))?;
+        Self::create_fts_table(conn, "docs_fts_rebuild", settings)?;
+        Self::create_fts_table(conn, "docs_fts", settings)?;
+
+        conn.execute(
+            "INSERT INTO docs_fts_rebuild (location_id, rel_path, title, content)
+             SELECT location_id, rel_path, title, content FROM docs_fts",
+            [],
+        )
+        .map_err(|e| AppError::io(format!("Failed to copy FTS entries: {}", e)))?;
+        conn.execute("DROP TABLE docs_fts", [])
+            .map_err(|e| AppError::io(format!("Failed to drop docs_fts: {}", e)))?;
+        conn.execute("ALTER TABLE docs_fts_rebuild RENAME TO docs_fts", [])
+            .map_err(|e| AppError::io(format!("Failed to rename rebuilt docs_fts: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub fn global_capture_get(&self) -> Result<GlobalCaptureSettings, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let maybe_value = conn
+            .query_row(
+                "SELECT value FROM app_settings WHERE key = ?1",
+                params![GLOBAL_CAPTURE_SETTINGS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::io(format!("Failed to query global capture settings: {}", e)))?;
+
+        match maybe_value {
+            Some(value) => serde_json::from_str::<GlobalCaptureSettings>(&value).map_err(|e| {
+                AppError::new(
+                    ErrorCode::Parse,
+                    format!("Failed to parse persisted global capture settings: {}", e),
+                )
+            }),
+            None => Ok(GlobalCaptureSettings::default()),
+        }
+    }
+
+    pub fn global_capture_set(&self, settings: &GlobalCaptureSettings) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let settings_json = serde_json::to_string(settings).map_err(|e| {
+            AppError::new(
+                ErrorCode::Parse,
+                format!("Failed to serialize global capture settings: {}", e),
+            )
+        })?;
+        let updated_at = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO app_settings (key, value, updated_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET
+             value = excluded.value,
+             updated_at = excluded.updated_at",
+            params![GLOBAL_CAPTURE_SETTINGS_KEY, settings_json, updated_at],
+        )
+        .map_err(|e| AppError::io(format!("Failed to persist global capture settings: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub fn last_open_doc_get(&self) -> Result<Option<CaptureDocRef>, AppError> {
+        let conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
@@ -491,6 +1311,130 @@ impl Store {
         Ok(())
     }
 
+    /// README content written to new locations: the custom template when one is set, otherwise the
+    /// built-in one
+    ///
+    /// An empty custom template is returned as-is; it turns README creation off.
+    pub fn readme_template_get(&self) -> Result<String, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let maybe_value = conn
+            .query_row(
+                "SELECT value FROM app_settings WHERE key = ?1",
+                params![README_TEMPLATE_SETTINGS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::io(format!("Failed to query README template setting: {}", e)))?;
+
+        match maybe_value {
+            Some(value) => serde_json::from_str::<String>(&value).map_err(|e| {
+                AppError::new(
+                    ErrorCode::Parse,
+                    format!("Failed to parse README template setting: {}", e),
+                )
+            }),
+            None => Ok(README_TEMPLATE.to_string()),
+        }
+    }
+
+    /// Sets the README template for new locations, or restores the built-in one when `template` is `None`
+    pub fn readme_template_set(&self, template: Option<&str>) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        if let Some(template) = template {
+            let payload = serde_json::to_string(template).map_err(|e| {
+                AppError::new(
+                    ErrorCode::Parse,
+                    format!("Failed to serialize README template setting: {}", e),
+                )
+            })?;
+            let updated_at = Utc::now().to_rfc3339();
+
+            conn.execute(
+                "INSERT INTO app_settings (key, value, updated_at)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(key) DO UPDATE SET
+                 value = excluded.value,
+                 updated_at = excluded.updated_at",
+                params![README_TEMPLATE_SETTINGS_KEY, payload, updated_at],
+            )
+            .map_err(|e| AppError::io(format!("Failed to persist README template setting: {}", e)))?;
+
+            return Ok(());
+        }
+
+        conn.execute(
+            "DELETE FROM app_settings WHERE key = ?1",
+            params![README_TEMPLATE_SETTINGS_KEY],
+        )
+        .map_err(|e| AppError::io(format!("Failed to clear README template setting: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Returns the cached title of the page at `url` if it was fetched within [`LINK_TITLE_CACHE_TTL_HOURS`]
+    ///
+    /// Only `http(s)` URLs can have titles; anything else is an `InvalidUrl` error. A miss means the
+    /// caller should fetch the page and hand the title to [`Store::link_title_remember`].
+    pub fn link_title_cached(&self, url: &str) -> Result<Option<String>, AppError> {
+        let url = Self::fetchable_url(url)?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let cached = conn
+            .query_row(
+                "SELECT title, fetched_at FROM link_titles WHERE url = ?1",
+                params![url],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::io(format!("Failed to query link title cache: {}", e)))?;
+
+        let expires = chrono::Duration::hours(LINK_TITLE_CACHE_TTL_HOURS);
+        Ok(cached.and_then(|(title, fetched_at)| {
+            let fetched_at = DateTime::parse_from_rfc3339(&fetched_at).ok()?;
+            (Utc::now().signed_duration_since(fetched_at) < expires).then_some(title)
+        }))
+    }
+
+    /// Caches a freshly fetched title for `url`, replacing any stale copy
+    pub fn link_title_remember(&self, url: &str, title: &str) -> Result<(), AppError> {
+        let url = Self::fetchable_url(url)?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+        conn.execute(
+            "INSERT INTO link_titles (url, title, fetched_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(url) DO UPDATE SET
+             title = excluded.title,
+             fetched_at = excluded.fetched_at",
+            params![url, title, Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| AppError::io(format!("Failed to cache link title: {}", e)))?;
+
+        Ok(())
+    }
+
+    fn fetchable_url(url: &str) -> Result<&str, AppError> {
+        let url = url.trim();
+        if writer_core::is_fetchable_url(url) {
+            Ok(url)
+        } else {
+            Err(AppError::invalid_url("Only http and https links can be fetched").with_context(url))
+        }
+    }
+
     fn normalize_session_state(session: &mut SessionState) {
         session.tabs.retain(|tab| !tab.id.is_empty());
 
@@ -791,9 +1735,14 @@ impl Store {
 
         let settings = self.ui_layout_get()?;
         if settings.create_readme_in_new_locations {
+            let template = self.readme_template_get().unwrap_or_else(|e| {
+                log::warn!("Failed to read README template setting, using default: {}", e);
+                README_TEMPLATE.to_string()
+            });
             let readme_path = root_path.join("README.md");
-            if !readme_path.exists()
-                && let Err(e) = std::fs::write(&readme_path, README_TEMPLATE)
+            if !template.is_empty()
+                && !readme_path.exists()
+                && let Err(e) = std::fs::write(&readme_path, template)
             {
                 log::warn!("Failed to create README.md in new location: {}", e);
             }
@@ -801,7 +1750,14 @@ impl Store {
 
         log::info!("Location added successfully: id={}, name={}", id, name);
 
-        Ok(LocationDescriptor { id: LocationId(id), name, root_path, added_at })
+        Ok(LocationDescriptor {
+            id: LocationId(id),
+            name,
+            root_path,
+            added_at,
+            indexable_extensions: None,
+            ignore_globs: default_ignore_globs(),
+        })
     }
 
     /// Lists all locations
@@ -812,25 +1768,11 @@ impl Store {
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
         let mut stmt = conn
-            .prepare("SELECT id, name, root_path, added_at FROM locations ORDER BY added_at DESC")
+            .prepare("SELECT id, name, root_path, added_at, indexable_extensions, ignore_globs FROM locations ORDER BY added_at DESC")
             .map_err(|e| AppError::io(format!("Failed to prepare query: {}", e)))?;
 
         let locations = stmt
-            .query_map([], |row| {
-                let id: i64 = row.get(0)?;
-                let name: String = row.get(1)?;
-                let root_path_str: String = row.get(2)?;
-                let added_at_str: String = row.get(3)?;
-
-                let root_path = PathBuf::from(root_path_str);
-                let added_at = chrono::DateTime::parse_from_rfc3339(&added_at_str)
-                    .map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
-                    })?
-                    .with_timezone(&Utc);
-
-                Ok(LocationDescriptor { id: LocationId(id), name, root_path, added_at })
-            })
+            .query_map([], Self::location_from_row)
             .map_err(|e| AppError::io(format!("Failed to query locations: {}", e)))?;
 
         let mut result = Vec::new();
@@ -853,25 +1795,13 @@ impl Store {
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
         let mut stmt = conn
-            .prepare("SELECT id, name, root_path, added_at FROM locations WHERE id = ?1")
+            .prepare(
+                "SELECT id, name, root_path, added_at, indexable_extensions, ignore_globs FROM locations WHERE id = ?1",
+            )
             .map_err(|e| AppError::io(format!("Failed to prepare query: {}", e)))?;
 
         let mut rows = stmt
-            .query_map(params![location_id.0], |row| {
-                let id: i64 = row.get(0)?;
-                let name: String = row.get(1)?;
-                let root_path_str: String = row.get(2)?;
-                let added_at_str: String = row.get(3)?;
-
-                let root_path = PathBuf::from(root_path_str);
-                let added_at = chrono::DateTime::parse_from_rfc3339(&added_at_str)
-                    .map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
-                    })?
-                    .with_timezone(&Utc);
-
-                Ok(LocationDescriptor { id: LocationId(id), name, root_path, added_at })
-            })
+            .query_map(params![location_id.0], Self::location_from_row)
             .map_err(|e| AppError::io(format!("Failed to query location: {}", e)))?;
 
         match rows.next() {
@@ -881,6 +1811,128 @@ impl Store {
         }
     }
 
+    /// Builds a location descriptor from a `locations` row selected in column order
+    fn location_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<LocationDescriptor> {
+        let id: i64 = row.get(0)?;
+        let name: String = row.get(1)?;
+        let root_path_str: String = row.get(2)?;
+        let added_at_str: String = row.get(3)?;
+        let extensions_json: Option<String> = row.get(4)?;
+        let ignore_globs_json: Option<String> = row.get(5)?;
+
+        let root_path = PathBuf::from(root_path_str);
+        let added_at = chrono::DateTime::parse_from_rfc3339(&added_at_str)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e)))?
+            .with_timezone(&Utc);
+        let indexable_extensions = extensions_json
+            .map(|value| serde_json::from_str::<Vec<String>>(&value))
+            .transpose()
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e)))?;
+        let ignore_globs = ignore_globs_json
+            .map(|value| serde_json::from_str::<Vec<String>>(&value))
+            .transpose()
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e)))?
+            .unwrap_or_else(default_ignore_globs);
+
+        Ok(LocationDescriptor { id: LocationId(id), name, root_path, added_at, indexable_extensions, ignore_globs })
+    }
+
+    /// Sets which file extensions a location indexes and treats as text
+    ///
+    /// `None` (or an empty list) restores the default set. Run [`Store::reconcile_location_index`]
+    /// afterwards to add newly included files to search and drop excluded ones.
+    pub fn location_set_indexable_extensions(
+        &self, location_id: LocationId, extensions: Option<Vec<String>>,
+    ) -> Result<LocationDescriptor, AppError> {
+        let extensions = extensions
+            .map(|extensions| file_utils::normalize_extensions(&extensions))
+            .filter(|extensions| !extensions.is_empty());
+        let extensions_json = extensions
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to encode extensions: {}", e)))?;
+
+        {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let rows_affected = conn
+                .execute(
+                    "UPDATE locations SET indexable_extensions = ?2 WHERE id = ?1",
+                    params![location_id.0, extensions_json],
+                )
+                .map_err(|e| AppError::io(format!("Failed to update location extensions: {}", e)))?;
+
+            if rows_affected == 0 {
+                return Err(AppError::not_found(format!("Location not found: {:?}", location_id)));
+            }
+
+            // An impossible stored size makes the next reconcile treat every file as changed,
+            // so files that switched between indexable and not are re-read.
+            conn.execute(
+                "UPDATE documents SET size_bytes = -1 WHERE location_id = ?1",
+                params![location_id.0],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to invalidate catalog rows: {}", e)))?;
+        }
+
+        log::info!(
+            "Location indexable extensions updated: id={}, extensions={:?}",
+            location_id.0,
+            extensions
+        );
+
+        self.location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))
+    }
+
+    /// Sets the gitignore-style patterns excluded from a location's index
+    ///
+    /// An empty list indexes everything, including hidden directories. Run
+    /// [`Store::reconcile_location_index`] afterwards to prune newly ignored files.
+    pub fn location_set_ignore_globs(
+        &self, location_id: LocationId, ignore_globs: Vec<String>,
+    ) -> Result<LocationDescriptor, AppError> {
+        let mut globs = Vec::new();
+        for glob in ignore_globs
+            .iter()
+            .map(|glob| glob.trim())
+            .filter(|glob| !glob.is_empty())
+        {
+            if !globs.iter().any(|existing| existing == glob) {
+                globs.push(glob.to_string());
+            }
+        }
+        let globs_json = serde_json::to_string(&globs)
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to encode ignore globs: {}", e)))?;
+
+        {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let rows_affected = conn
+                .execute(
+                    "UPDATE locations SET ignore_globs = ?2 WHERE id = ?1",
+                    params![location_id.0, globs_json],
+                )
+                .map_err(|e| AppError::io(format!("Failed to update location ignore globs: {}", e)))?;
+
+            if rows_affected == 0 {
+                return Err(AppError::not_found(format!("Location not found: {:?}", location_id)));
+            }
+        }
+
+        log::info!("Location ignore globs updated: id={}, globs={:?}", location_id.0, globs);
+
+        self.location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))
+    }
+
     /// Removes a location
     pub fn location_remove(&self, location_id: LocationId) -> Result<bool, AppError> {
         let conn = self
@@ -888,12 +1940,21 @@ impl Store {
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
+        Self::forget_documents_locked(&conn, location_id, "%")?;
         let rows_affected = conn
             .execute("DELETE FROM locations WHERE id = ?1", params![location_id.0])
             .map_err(|e| AppError::io(format!("Failed to remove location: {}", e)))?;
+        drop(conn);
 
         if rows_affected > 0 {
             log::info!("Location removed: id={}", location_id.0);
+            if let Err(e) = self.location_view_prefs_remove(location_id) {
+                log::warn!(
+                    "Failed to drop view prefs for removed location {}: {}",
+                    location_id.0,
+                    e
+                );
+            }
             Ok(true)
         } else {
             log::warn!("Attempted to remove non-existent location: id={}", location_id.0);
@@ -901,6 +1962,44 @@ impl Store {
         }
     }
 
+    /// Drops a location's catalog and search index rows, returning how many documents were removed
+    ///
+    /// Unlike [`Store::location_remove`], the location itself stays registered and no files on disk
+    /// are touched; tag and link rows, word-count history, goals and autosaves go too. Catalogue the
+    /// location again with [`Store::reconcile_location_index`].
+    pub fn location_clear_index(&self, location_id: LocationId) -> Result<usize, AppError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to begin index transaction: {}", e)))?;
+
+        Self::forget_documents_locked(&tx, location_id, "%")?;
+        let removed = tx
+            .execute("DELETE FROM documents WHERE location_id = ?1", params![location_id.0])
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear document rows: {}", e)))?;
+        tx.execute("DELETE FROM docs_fts WHERE location_id = ?1", params![location_id.0])
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear FTS rows: {}", e)))?;
+        tx.execute(
+            "DELETE FROM document_tags WHERE location_id = ?1",
+            params![location_id.0],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear tag rows: {}", e)))?;
+        tx.execute(
+            "DELETE FROM document_links WHERE location_id = ?1",
+            params![location_id.0],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear link rows: {}", e)))?;
+
+        tx.commit()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to commit index transaction: {}", e)))?;
+
+        log::info!("Cleared index for location {:?}: {} documents", location_id, removed);
+        Ok(removed)
+    }
+
     /// Validates that all locations still exist on disk
     /// Returns a list of location IDs whose roots no longer exist
     pub fn validate_locations(&self) -> Result<Vec<(LocationId, PathBuf)>, AppError> {
@@ -922,6 +2021,9 @@ impl Store {
     }
 
     /// Lists documents in a location
+    ///
+    /// Documents that tie on the sort field are ordered by path, in either sort order, so the
+    /// listing is the same from run to run and matches [`Store::doc_list_catalog`].
     pub fn doc_list(&self, location_id: LocationId, options: Option<DocListOptions>) -> Result<Vec<DocMeta>, AppError> {
         let location = self
             .location_get(location_id)?
@@ -931,59 +2033,417 @@ impl Store {
         let root_path = &location.root_path;
 
         let mut docs = Vec::new();
+        let mut symlinks = SymlinkGuard::new(root_path, options.follow_symlinks);
+        let scan = self.scan_context(&location)?;
 
-        if options.recursive {
-            self.collect_docs_recursive(root_path, root_path, location_id, &options, &mut docs)?;
+        if options.recursive && options.max_depth != Some(0) {
+            self.collect_docs_recursive(&location, root_path, &scan, &options, &mut symlinks, &mut docs)?;
         } else {
-            self.collect_docs_shallow(root_path, root_path, location_id, &options, &mut docs)?;
+            self.collect_docs_shallow(&location, root_path, &scan, &options, &mut symlinks, &mut docs)?;
         }
 
-        match options.sort_by.unwrap_or(DocSortField::Modified) {
-            DocSortField::Name => {
-                docs.sort_by(|a, b| a.filename.cmp(&b.filename));
-            }
-            DocSortField::Modified => {
-                docs.sort_by(|a, b| b.mtime.cmp(&a.mtime));
-            }
-            DocSortField::Created => {
-                docs.sort_by(|a, b| match (&a.created_at, &b.created_at) {
+        let sort_by = options.sort_by.unwrap_or(DocSortField::Modified);
+        let ascending = matches!(options.sort_order, SortOrder::Ascending);
+        docs.sort_by(|a, b| {
+            let primary = match sort_by {
+                DocSortField::Name => a.filename.cmp(&b.filename),
+                DocSortField::Modified => b.mtime.cmp(&a.mtime),
+                DocSortField::Created => match (&a.created_at, &b.created_at) {
                     (Some(a), Some(b)) => b.cmp(a),
                     (Some(_), None) => std::cmp::Ordering::Less,
                     (None, Some(_)) => std::cmp::Ordering::Greater,
                     (None, None) => std::cmp::Ordering::Equal,
-                });
+                },
+                DocSortField::Size => b.size_bytes.cmp(&a.size_bytes),
+            };
+            let primary = if ascending { primary.reverse() } else { primary };
+            primary.then_with(|| a.id.rel_path.cmp(&b.id.rel_path))
+        });
+
+        log::debug!("Listed {} documents in location {:?}", docs.len(), location_id);
+        Ok(docs)
+    }
+
+    /// Like [`Store::doc_list`], but leaves out documents whose front matter marks them as drafts
+    ///
+    /// The draft flag comes from the catalog (a truthy `draft` field when the document was last
+    /// indexed); documents that are missing it, set it to false, or are not catalogued yet count
+    /// as published.
+    pub fn doc_list_published(
+        &self, location_id: LocationId, options: Option<DocListOptions>,
+    ) -> Result<Vec<DocMeta>, AppError> {
+        let mut docs = self.doc_list(location_id, options)?;
+
+        let drafts: HashSet<String> = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+            let mut stmt = conn
+                .prepare("SELECT rel_path FROM documents WHERE location_id = ?1 AND is_draft = 1")
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare draft query: {}", e)))?;
+            stmt.query_map(params![location_id.0], |row| row.get::<_, String>(0))
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query drafts: {}", e)))?
+                .collect::<Result<_, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read draft row: {}", e)))?
+        };
+
+        docs.retain(|doc| !drafts.contains(doc.id.rel_path.to_string_lossy().as_ref()));
+        Ok(docs)
+    }
+
+    /// The documents before and after `doc_id` when listed by `sort`, for "previous/next" navigation
+    ///
+    /// Siblings are the documents in the same directory, or every document in the location when
+    /// `whole_location` is set, ordered exactly as [`Store::doc_list`] orders them for `sort` with
+    /// the default sort order. The first document has no previous one and the last has no next one.
+    /// A document missing from the listing is a `NotFound` error.
+    pub fn doc_neighbors(
+        &self, doc_id: &DocId, sort: DocSortField, whole_location: bool,
+    ) -> Result<(Option<DocMeta>, Option<DocMeta>), AppError> {
+        let options = DocListOptions { recursive: true, sort_by: Some(sort), ..DocListOptions::default() };
+        let parent = doc_id.rel_path.parent();
+        let mut docs = self.doc_list(doc_id.location_id, Some(options))?;
+        if !whole_location {
+            docs.retain(|doc| doc.id.rel_path.parent() == parent);
+        }
+
+        let position = docs
+            .iter()
+            .position(|doc| doc.id == *doc_id)
+            .ok_or_else(|| AppError::not_found(format!("Document not found: {:?}", doc_id.rel_path)))?;
+
+        let next = docs.get(position + 1).cloned();
+        let previous = position.checked_sub(1).map(|previous| docs.swap_remove(previous));
+        Ok((previous, next))
+    }
+
+    /// Lists a location's documents from the catalog instead of walking the filesystem
+    ///
+    /// Sorting and extension filtering happen in SQL and follow [`Store::doc_list`], so after a
+    /// reconcile the two agree (files matching the location's ignore globs are never catalogued).
+    /// Ties are broken by path. Works while the location root is unavailable.
+    pub fn doc_list_catalog(
+        &self, location_id: LocationId, options: Option<DocListOptions>,
+    ) -> Result<Vec<DocMeta>, AppError> {
+        let options = options.unwrap_or_default();
+        let mut sql = String::from(
+            "SELECT location_id, rel_path, filename, size_bytes, mtime, created_at, content_hash,
+                encoding, line_ending, is_conflict, title, word_count
+             FROM documents
+             WHERE location_id = ?",
+        );
+        let mut query_params = vec![Value::from(location_id.0)];
+
+        if !options.recursive {
+            sql.push_str(" AND rel_path NOT LIKE '%/%'");
+        } else if let Some(max_depth) = options.max_depth {
+            sql.push_str(" AND LENGTH(rel_path) - LENGTH(REPLACE(rel_path, '/', '')) <= ?");
+            query_params.push(Value::from(max_depth as i64));
+        }
+
+        if let Some(extensions) = options.extensions.as_ref().filter(|extensions| !extensions.is_empty()) {
+            let clauses = vec!["LOWER(filename) LIKE ? ESCAPE '\\'"; extensions.len()];
+            sql.push_str(" AND (");
+            sql.push_str(&clauses.join(" OR "));
+            sql.push(')');
+            query_params.extend(extensions.iter().map(|extension| {
+                let escaped = extension
+                    .to_lowercase()
+                    .replace('\\', r"\\")
+                    .replace('%', r"\%")
+                    .replace('_', r"\_");
+                Value::from(format!("%.{}", escaped))
+            }));
+        }
+
+        let ascending = matches!(options.sort_order, SortOrder::Ascending);
+        let (forward, reverse) = if ascending { ("DESC", "ASC") } else { ("ASC", "DESC") };
+        let order_by = match options.sort_by.unwrap_or(DocSortField::Modified) {
+            DocSortField::Name => format!("filename {}", forward),
+            DocSortField::Modified => format!("mtime {}", reverse),
+            DocSortField::Created => format!("created_at IS NULL {}, created_at {}", forward, reverse),
+            DocSortField::Size => format!("size_bytes {}", reverse),
+        };
+        sql.push_str(&format!(" ORDER BY {}, rel_path ASC", order_by));
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare catalog listing: {}", e)))?;
+        let docs = stmt
+            .query_map(params_from_iter(query_params.iter()), Self::catalog_row_to_doc_meta)
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog listing: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))?;
+
+        log::debug!(
+            "Listed {} catalogued documents in location {:?}",
+            docs.len(),
+            location_id
+        );
+        Ok(docs)
+    }
+
+    /// Lists the most recently edited documents across every location, newest first
+    ///
+    /// Ordered by when the catalog entry was last written (a save, a watcher update, or a reindex),
+    /// then by modification time, using the `updated_at` index. Rows whose location no longer exists
+    /// are skipped. With `skip_conflicts_and_trash` set, conflicted copies and files in a location's
+    /// trash are left out.
+    pub fn recently_edited(&self, limit: usize, skip_conflicts_and_trash: bool) -> Result<Vec<DocMeta>, AppError> {
+        let mut sql = String::from(
+            "SELECT d.location_id, d.rel_path, d.filename, d.size_bytes, d.mtime, d.created_at, d.content_hash,
+                d.encoding, d.line_ending, d.is_conflict, d.title, d.word_count
+             FROM documents d
+             JOIN locations l ON l.id = d.location_id",
+        );
+        if skip_conflicts_and_trash {
+            sql.push_str(" WHERE d.is_conflict = 0 AND d.rel_path NOT LIKE ?2");
+        }
+        sql.push_str(" ORDER BY d.updated_at DESC, d.mtime DESC, d.rel_path ASC LIMIT ?1");
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let mut stmt = conn.prepare(&sql).map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to prepare recent documents query: {}", e),
+            )
+        })?;
+        let limit = limit as i64;
+        let rows = if skip_conflicts_and_trash {
+            stmt.query_map(
+                params![limit, format!("{}/%", TRASH_DIR)],
+                Self::catalog_row_to_doc_meta,
+            )
+        } else {
+            stmt.query_map(params![limit], Self::catalog_row_to_doc_meta)
+        };
+
+        rows.map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query recent documents: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))
+    }
+
+    /// Lists catalogued text documents with fewer than `min_words` words, shortest first
+    ///
+    /// The threshold is exclusive: a document with exactly `min_words` words is not a stub. Rows without
+    /// a word count (files that were never read as text) and non-indexable files are left out, as is the
+    /// location's trash. Counts come from the last index, so reconcile first for up-to-date results.
+    pub fn find_stubs(&self, location_id: LocationId, min_words: usize) -> Result<Vec<DocMeta>, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT location_id, rel_path, filename, size_bytes, mtime, created_at, content_hash,
+                    encoding, line_ending, is_conflict, title, word_count
+                 FROM documents
+                 WHERE location_id = ?1 AND word_count IS NOT NULL AND word_count < ?2 AND rel_path NOT LIKE ?3
+                 ORDER BY word_count ASC, rel_path ASC",
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare stub query: {}", e)))?;
+        let docs = stmt
+            .query_map(
+                params![location_id.0, min_words as i64, format!("{}/%", TRASH_DIR)],
+                Self::catalog_row_to_doc_meta,
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query stubs: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))?;
+
+        Ok(docs
+            .into_iter()
+            .filter(|doc| {
+                file_utils::is_indexable_with_extensions(&doc.id.rel_path, location.indexable_extensions.as_deref())
+            })
+            .collect())
+    }
+
+    /// Lists the location's text documents that no other document in the same location links to
+    ///
+    /// Incoming links are read from the link index: local Markdown links resolve against the linking
+    /// document's folder and wikilinks as in [`Store::resolve_wikilink`], so only links between
+    /// documents of this location count, and self-links are ignored. Documents named in the
+    /// `home_document_names` setting and the trash are left out. Reflects the last index, so reconcile
+    /// first for up-to-date results.
+    pub fn find_orphans(&self, location_id: LocationId) -> Result<Vec<DocMeta>, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+        let home_names: HashSet<String> = match self.ui_layout_get() {
+            Ok(settings) => settings.home_document_names,
+            Err(e) => {
+                log::warn!("Failed to read home_document_names setting, using default: {}", e);
+                UiLayoutSettings::default().home_document_names
+            }
+        }
+        .iter()
+        .map(|name| name.to_lowercase())
+        .collect();
+
+        let trash_like = format!("{}/%", TRASH_DIR);
+        let (docs, links, link_rows) = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let mut stmt = conn
+                .prepare(
+                    "SELECT location_id, rel_path, filename, size_bytes, mtime, created_at, content_hash,
+                        encoding, line_ending, is_conflict, title, word_count
+                     FROM documents
+                     WHERE location_id = ?1 AND rel_path NOT LIKE ?2
+                     ORDER BY rel_path ASC",
+                )
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare catalog query: {}", e)))?;
+            let docs = stmt
+                .query_map(params![location_id.0, trash_like], Self::catalog_row_to_doc_meta)
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog rows: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))?;
+
+            let mut stmt = conn
+                .prepare(
+                    "SELECT rel_path, target, is_wikilink FROM document_links
+                     WHERE location_id = ?1 AND rel_path NOT LIKE ?2",
+                )
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare link query: {}", e)))?;
+            let links = stmt
+                .query_map(params![location_id.0, trash_like], |row| {
+                    Ok((
+                        PathBuf::from(row.get::<_, String>(0)?),
+                        row.get::<_, String>(1)?,
+                        row.get::<_, bool>(2)?,
+                    ))
+                })
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query links: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid link row: {}", e)))?;
+
+            let mut stmt = conn
+                .prepare("SELECT rel_path, link_key, aliases FROM documents WHERE location_id = ?1 ORDER BY rel_path")
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare link key query: {}", e)))?;
+            let link_rows = stmt
+                .query_map(params![location_id.0], |row| {
+                    Ok((
+                        PathBuf::from(row.get::<_, String>(0)?),
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                })
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query link keys: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid link key row: {}", e)))?;
+
+            (docs, links, link_rows)
+        };
+
+        // Wikilinks resolve in memory the way `resolve_wikilink` does: by link key, then by alias
+        let mut by_link_key: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        let mut by_alias: HashMap<String, PathBuf> = HashMap::new();
+        for (rel_path, link_key, aliases) in link_rows {
+            let aliases: Vec<String> = aliases
+                .and_then(|aliases| serde_json::from_str(&aliases).ok())
+                .unwrap_or_default();
+            for alias in aliases {
+                by_alias
+                    .entry(alias.to_ascii_lowercase())
+                    .or_insert_with(|| rel_path.clone());
             }
-            DocSortField::Size => {
-                docs.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+            if let Some(link_key) = link_key {
+                by_link_key.entry(link_key).or_default().push(rel_path);
             }
         }
+        let resolve_wikilink = |target: &str| -> Option<PathBuf> {
+            let target = target.trim();
+            let mut candidates: Vec<PathBuf> = file_utils::link_target_keys(target)
+                .iter()
+                .filter_map(|key| by_link_key.get(key))
+                .flatten()
+                .cloned()
+                .collect();
+            candidates.sort();
+            candidates.dedup();
+            file_utils::best_link_match(candidates, target)
+                .or_else(|| by_alias.get(&target.to_ascii_lowercase()).cloned())
+        };
+
+        let mut linked = HashSet::new();
+        for (source, target, is_wikilink) in links {
+            let resolved = if is_wikilink {
+                resolve_wikilink(&target)
+            } else {
+                file_utils::link_target_rel_path(&target, &source)
+            };
 
-        if matches!(options.sort_order, SortOrder::Ascending) {
-            docs.reverse();
+            if let Some(resolved) = resolved.filter(|resolved| *resolved != source) {
+                linked.insert(resolved);
+            }
         }
 
-        log::debug!("Listed {} documents in location {:?}", docs.len(), location_id);
-        Ok(docs)
+        Ok(docs
+            .into_iter()
+            .filter(|doc| !linked.contains(&doc.id.rel_path))
+            .filter(|doc| !home_names.contains(&doc.filename.to_lowercase()))
+            .filter(|doc| {
+                file_utils::is_indexable_with_extensions(&doc.id.rel_path, location.indexable_extensions.as_deref())
+            })
+            .collect())
     }
 
-    /// Lists all directories in a location (excluding the location root).
-    pub fn dir_list(&self, location_id: LocationId) -> Result<Vec<PathBuf>, AppError> {
+    /// Lists the subdirectories under `rel_path` (the location root when `None`), sorted by path
+    ///
+    /// Only direct children are listed unless `recursive` is set. Directories matching the
+    /// location's ignore globs are skipped along with everything inside them, and symlinks are
+    /// never followed. A `rel_path` that does not exist or is not a directory gives an empty list.
+    pub fn dir_list(
+        &self, location_id: LocationId, rel_path: Option<&Path>, recursive: bool,
+    ) -> Result<Vec<DirEntry>, AppError> {
         let location = self
             .location_get(location_id)?
             .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
 
+        let base = match rel_path.filter(|rel_path| !rel_path.as_os_str().is_empty()) {
+            Some(rel_path) => normalize_relative_path(rel_path)?,
+            None => PathBuf::new(),
+        };
+        let start = location.root_path.join(&base);
+        if !std::fs::symlink_metadata(&start).is_ok_and(|metadata| metadata.is_dir()) {
+            return Ok(Vec::new());
+        }
+
         let root_path = &location.root_path;
+        let filter = LocationFilter::for_location(&location);
+        let (children, _) = Self::dir_children(root_path, &start, &filter)?;
         let mut directories = Vec::new();
-        Self::collect_dirs_recursive(root_path, root_path, &mut directories)?;
-        directories.sort();
+        Self::collect_dirs(root_path, children, &filter, recursive, &mut directories)?;
+        directories.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
 
         log::debug!("Listed {} directories in location {:?}", directories.len(), location_id);
         Ok(directories)
     }
 
     fn collect_docs_shallow(
-        &self, root: &Path, current: &Path, location_id: LocationId, options: &DocListOptions, docs: &mut Vec<DocMeta>,
+        &self, location: &LocationDescriptor, current: &Path, scan: &ScanContext, options: &DocListOptions,
+        symlinks: &mut SymlinkGuard, docs: &mut Vec<DocMeta>,
     ) -> Result<(), AppError> {
+        let (root, location_id) = (&location.root_path, location.id);
         let entries =
             std::fs::read_dir(current).map_err(|e| AppError::io(format!("Failed to read directory: {}", e)))?;
 
@@ -995,6 +2455,12 @@ impl Store {
         for entry in entries {
             let entry = entry.map_err(|e| AppError::io(format!("Failed to read entry: {}", e)))?;
             let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(|e| AppError::io(format!("Failed to read entry type: {}", e)))?;
+            if !symlinks.allows(&path, &file_type) {
+                continue;
+            }
 
             if path.is_file() {
                 let filename = path
@@ -1015,7 +2481,7 @@ impl Store {
                     .map_err(|_| AppError::io("Path not within root"))?
                     .to_path_buf();
 
-                let meta = self.read_doc_metadata(&path, location_id, rel_path, &filename)?;
+                let meta = self.read_doc_metadata(&path, scan, location_id, rel_path, &filename)?;
                 docs.push(meta);
             }
         }
@@ -1024,8 +2490,10 @@ impl Store {
     }
 
     fn collect_docs_recursive(
-        &self, root: &Path, current: &Path, location_id: LocationId, options: &DocListOptions, docs: &mut Vec<DocMeta>,
+        &self, location: &LocationDescriptor, current: &Path, scan: &ScanContext, options: &DocListOptions,
+        symlinks: &mut SymlinkGuard, docs: &mut Vec<DocMeta>,
     ) -> Result<(), AppError> {
+        let (root, location_id) = (&location.root_path, location.id);
         let entries =
             std::fs::read_dir(current).map_err(|e| AppError::io(format!("Failed to read directory: {}", e)))?;
 
@@ -1037,6 +2505,12 @@ impl Store {
         for entry in entries {
             let entry = entry.map_err(|e| AppError::io(format!("Failed to read entry: {}", e)))?;
             let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(|e| AppError::io(format!("Failed to read entry type: {}", e)))?;
+            if !symlinks.allows(&path, &file_type) {
+                continue;
+            }
 
             if path.is_file() {
                 let filename = path
@@ -1057,47 +2531,68 @@ impl Store {
                     .map_err(|_| AppError::io("Path not within root"))?
                     .to_path_buf();
 
-                let meta = self.read_doc_metadata(&path, location_id, rel_path, &filename)?;
+                let meta = self.read_doc_metadata(&path, scan, location_id, rel_path, &filename)?;
                 docs.push(meta);
-            } else if path.is_dir() {
-                self.collect_docs_recursive(root, &path, location_id, options, docs)?;
+            } else if path.is_dir() && file_utils::descends_into(root, &path, options.max_depth) {
+                self.collect_docs_recursive(location, &path, scan, options, symlinks, docs)?;
             }
         }
 
         Ok(())
     }
 
-    fn collect_dirs_recursive(root: &Path, current: &Path, directories: &mut Vec<PathBuf>) -> Result<(), AppError> {
-        let entries =
-            std::fs::read_dir(current).map_err(|e| AppError::io(format!("Failed to read directory: {}", e)))?;
+    fn collect_dirs(
+        root: &Path, dirs: Vec<PathBuf>, filter: &LocationFilter, recursive: bool, directories: &mut Vec<DirEntry>,
+    ) -> Result<(), AppError> {
+        for path in dirs {
+            let (children, child_files) = Self::dir_children(root, &path, filter)?;
+            let rel_path = path
+                .strip_prefix(root)
+                .map_err(|_| AppError::io("Path not within root"))?
+                .to_path_buf();
+            directories.push(DirEntry { rel_path, child_dirs: children.len(), child_files });
+
+            if recursive {
+                Self::collect_dirs(root, children, filter, recursive, directories)?;
+            }
+        }
 
+        Ok(())
+    }
+
+    /// The subdirectories of `dir` and the number of files in it, skipping symlinks and ignored entries
+    fn dir_children(root: &Path, dir: &Path, filter: &LocationFilter) -> Result<(Vec<PathBuf>, usize), AppError> {
+        let entries = std::fs::read_dir(dir).map_err(|e| AppError::io(format!("Failed to read directory: {}", e)))?;
+
+        let mut dirs = Vec::new();
+        let mut files = 0;
         for entry in entries {
             let entry = entry.map_err(|e| AppError::io(format!("Failed to read entry: {}", e)))?;
             let file_type = entry
                 .file_type()
                 .map_err(|e| AppError::io(format!("Failed to read entry type: {}", e)))?;
-            if !file_type.is_dir() {
+            if file_type.is_symlink() {
                 continue;
             }
 
             let path = entry.path();
-            let rel_path = path
-                .strip_prefix(root)
-                .map_err(|_| AppError::io("Path not within root"))?
-                .to_path_buf();
-
-            if !rel_path.as_os_str().is_empty() {
-                directories.push(rel_path);
+            let rel_path = path.strip_prefix(root).unwrap_or(&path);
+            if filter.is_ignored(rel_path, file_type.is_dir()) {
+                continue;
             }
 
-            Self::collect_dirs_recursive(root, &path, directories)?;
+            if file_type.is_dir() {
+                dirs.push(path);
+            } else if file_type.is_file() {
+                files += 1;
+            }
         }
 
-        Ok(())
+        Ok((dirs, files))
     }
 
     fn read_doc_metadata(
-        &self, path: &Path, location_id: LocationId, rel_path: PathBuf, filename: &str,
+        &self, path: &Path, scan: &ScanContext, location_id: LocationId, rel_path: PathBuf, filename: &str,
     ) -> Result<DocMeta, AppError> {
         let metadata = std::fs::metadata(path).map_err(|e| AppError::io(format!("Failed to read metadata: {}", e)))?;
 
@@ -1109,9 +2604,12 @@ impl Store {
 
         let created_at = metadata.created().ok().map(DateTime::<Utc>::from);
 
-        let is_conflict = is_conflicted_filename(filename);
-        let text_content =
-            if file_utils::is_indexable_text_path(path) { std::fs::read_to_string(path).ok() } else { None };
+        let is_conflict = Self::is_conflicted(&scan.layout, filename);
+        let text_content = if scan.filter.is_indexable(path) && size_bytes <= scan.layout.max_open_bytes {
+            std::fs::read_to_string(path).ok()
+        } else {
+            None
+        };
 
         let (title, word_count) = match text_content.as_ref() {
             Some(content) => {
@@ -1121,6 +2619,10 @@ impl Store {
             None => (file_utils::fallback_title_from_path(&rel_path), None),
         };
         let content_hash = text_content.as_ref().map(|content| text_utils::hash_text(content));
+        let encoding = match text_content.as_ref() {
+            Some(content) if content.starts_with('\u{feff}') => Encoding::Utf8WithBom,
+            _ => Encoding::default(),
+        };
 
         Ok(DocMeta {
             id: DocId { location_id, rel_path },
@@ -1129,7 +2631,7 @@ impl Store {
             mtime,
             created_at,
             content_hash,
-            encoding: Encoding::default(),
+            encoding,
             line_ending: LineEnding::default(),
             is_conflict,
             title,
@@ -1138,7 +2640,208 @@ impl Store {
     }
 
     /// Opens a document and returns its content with metadata
+    ///
+    /// A document last opened with [`Store::doc_open_with_encoding`] as `Latin1` keeps that encoding,
+    /// since it cannot be told apart from UTF-8 by sniffing.
     pub fn doc_open(&self, doc_id: &DocId) -> Result<DocContent, AppError> {
+        let stored = self
+            .catalog_encoding(doc_id)?
+            .filter(|encoding| *encoding == Encoding::Latin1);
+        self.open_document(doc_id, stored)
+    }
+
+    /// Reads a document's bytes without decoding them, reporting the detected encoding and line ending
+    ///
+    /// Never fails on content: bytes that are not valid in the detected encoding, or that contain a NUL
+    /// character, are flagged `is_binary` instead of returning a `Parse` error. Line endings are taken
+    /// from a lossy decode. Reads are capped at `max_open_bytes` like [`Store::doc_open`].
+    pub fn doc_open_raw(&self, doc_id: &DocId) -> Result<RawDocContent, AppError> {
+        let location = self
+            .location_get(doc_id.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
+
+        let full_path = doc_id.resolve(&location.root_path);
+        if !full_path.is_file() {
+            return Err(AppError::not_found(format!("Document not found: {:?}", full_path)));
+        }
+
+        let metadata =
+            std::fs::metadata(&full_path).map_err(|e| AppError::io(format!("Failed to read metadata: {}", e)))?;
+        let max_open_bytes = self.layout_settings().max_open_bytes;
+        let truncated = metadata.len() > max_open_bytes;
+
+        let file = File::open(&full_path).map_err(|e| AppError::io(format!("Failed to open file: {}", e)))?;
+        let mut bytes = Vec::new();
+        file.take(max_open_bytes)
+            .read_to_end(&mut bytes)
+            .map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
+
+        let encoding = text_utils::detect_encoding(&bytes);
+        let decoded = if truncated {
+            let mut trimmed = bytes.clone();
+            text_utils::trim_incomplete_trailing_char(&mut trimmed);
+            text_utils::decode_with_encoding(&trimmed, encoding)
+        } else {
+            text_utils::decode_with_encoding(&bytes, encoding)
+        };
+
+        let (is_valid_text, is_binary, line_ending) = match decoded {
+            Ok(text) => (true, text.contains('\0'), LineEnding::detect(&text)),
+            Err(_) => {
+                let (text, _) = text_utils::detect_and_decode(&bytes).unwrap_or_default();
+                (false, true, LineEnding::detect(&text))
+            }
+        };
+
+        Ok(RawDocContent { bytes, encoding, line_ending, is_valid_text, is_binary, truncated })
+    }
+
+    /// Opens a document decoded as `encoding` instead of the detected one, and records it in the catalog
+    ///
+    /// Bytes that are not valid in the chosen encoding give a `Parse` error instead of replacement characters.
+    pub fn doc_open_with_encoding(&self, doc_id: &DocId, encoding: Encoding) -> Result<DocContent, AppError> {
+        let content = self.open_document(doc_id, Some(encoding))?;
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+        conn.execute(
+            "UPDATE documents SET encoding = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+            params![
+                doc_id.location_id.0,
+                doc_id.rel_path.to_string_lossy().to_string(),
+                i32::from(encoding)
+            ],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update document encoding: {}", e)))?;
+
+        Ok(content)
+    }
+
+    /// Opens `rel_path`, creating it with `initial_text` (empty by default) when it does not exist
+    ///
+    /// The `default_extension` setting applies to an extensionless path, unless a file already exists at
+    /// that exact path. The path is claimed with an exclusive create, so a file that appears in the meantime
+    /// is opened rather than overwritten. A new file is then written atomically and indexed like any save; if
+    /// saving or reading it back fails, the file and its index entry are removed again so a failed create
+    /// leaves nothing behind.
+    pub fn doc_open_or_create(
+        &self, location_id: LocationId, rel_path: &Path, initial_text: Option<&str>,
+    ) -> Result<DocContent, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+        let doc_id = Self::with_default_extension(
+            &self.layout_settings(),
+            &DocId::new(location_id, rel_path.to_path_buf())?,
+            &location.root_path,
+        )?;
+        let full_path = doc_id.resolve(&location.root_path);
+
+        if let Some(parent) = full_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| AppError::io(format!("Failed to create directory: {}", e)))?;
+        }
+        // Claiming the path with `create_new` means a file created concurrently is opened, never overwritten
+        match File::options().write(true).create_new(true).open(&full_path) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return self.doc_open(&doc_id),
+            Err(e) => return Err(AppError::io(format!("Failed to create file: {}", e))),
+        }
+
+        let created = self
+            .doc_save(&doc_id, initial_text.unwrap_or_default(), Some(SavePolicy::Atomic))
+            .and_then(|_| self.doc_open(&doc_id));
+
+        match &created {
+            Ok(_) => log::info!("Created document: {:?}", doc_id.rel_path),
+            Err(_) => {
+                if let Err(e) = std::fs::remove_file(&full_path) {
+                    log::warn!("Failed to remove {:?} after a failed create: {}", full_path, e);
+                }
+                if let Err(e) = self.remove_document_from_index(&doc_id) {
+                    log::warn!("Failed to unindex {:?} after a failed create: {}", doc_id.rel_path, e);
+                }
+            }
+        }
+
+        created
+    }
+
+    /// Line hunks that saving `new_text` would change relative to the document on disk
+    ///
+    /// The disk copy is decoded with the usual encoding detection, and a missing file counts as empty.
+    /// Line endings are normalized first (see [`diff_lines`]), so an unchanged document gives no hunks.
+    pub fn doc_pending_diff(&self, doc_id: &DocId, new_text: &str) -> Result<Vec<DiffHunk>, AppError> {
+        let location = self
+            .location_get(doc_id.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
+
+        let full_path = doc_id.resolve(&location.root_path);
+        let disk_text = if full_path.is_file() {
+            file_utils::read_file_text_with_detection(&full_path)?
+        } else {
+            String::new()
+        };
+
+        Ok(diff_lines(&disk_text, new_text))
+    }
+
+    /// Reads and renders several documents, returning one result per document in the order given
+    ///
+    /// Documents are read one at a time and then rendered on up to [`MAX_RENDER_THREADS`] scoped
+    /// threads (fewer on machines with fewer cores). A document that cannot be read or rendered gets
+    /// its own error and the rest of the batch still renders.
+    pub fn render_many(&self, docs: Vec<(DocId, MarkdownProfile)>) -> Vec<Result<RenderResult, AppError>> {
+        let sources: Vec<Result<(String, MarkdownProfile), AppError>> = docs
+            .into_iter()
+            .map(|(doc_id, profile)| self.doc_open(&doc_id).map(|content| (content.text, profile)))
+            .collect();
+        if sources.is_empty() {
+            return Vec::new();
+        }
+
+        let workers = std::thread::available_parallelism()
+            .map_or(1, |count| count.get())
+            .clamp(1, MAX_RENDER_THREADS)
+            .min(sources.len());
+        let chunk_size = sources.len().div_ceil(workers);
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = sources
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let handle = scope.spawn(move || {
+                        let engine = MarkdownEngine::new();
+                        chunk
+                            .iter()
+                            .map(|source| match source {
+                                Ok((text, profile)) => engine.render(text, *profile).map_err(|e| {
+                                    AppError::new(ErrorCode::Parse, format!("Failed to render document: {}", e))
+                                }),
+                                Err(e) => Err(e.clone()),
+                            })
+                            .collect::<Vec<_>>()
+                    });
+                    (handle, chunk.len())
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|(handle, len)| {
+                    handle.join().unwrap_or_else(|_| {
+                        log::error!("Render worker panicked; failing its {} documents", len);
+                        (0..len)
+                            .map(|_| Err(AppError::new(ErrorCode::Parse, "Render worker panicked")))
+                            .collect()
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn open_document(&self, doc_id: &DocId, encoding: Option<Encoding>) -> Result<DocContent, AppError> {
         let location = self
             .location_get(doc_id.location_id)?
             .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
@@ -1149,25 +2852,47 @@ impl Store {
             return Err(AppError::not_found(format!("Document not found: {:?}", full_path)));
         }
 
-        let mut file = File::open(&full_path).map_err(|e| AppError::io(format!("Failed to open file: {}", e)))?;
+        let settings = self.layout_settings();
+        let metadata =
+            std::fs::metadata(&full_path).map_err(|e| AppError::io(format!("Failed to read metadata: {}", e)))?;
+        let max_open_bytes = settings.max_open_bytes;
+        let truncated = metadata.len() > max_open_bytes;
+
+        let file = File::open(&full_path).map_err(|e| AppError::io(format!("Failed to open file: {}", e)))?;
         let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes)
+        file.take(max_open_bytes)
+            .read_to_end(&mut bytes)
             .map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
 
-        let (text, encoding) = text_utils::detect_and_decode(&bytes)?;
+        if truncated {
+            match encoding {
+                Some(Encoding::Latin1) => {}
+                Some(Encoding::Utf16Le | Encoding::Utf16Be) => bytes.truncate(bytes.len() & !1),
+                _ => text_utils::trim_incomplete_trailing_char(&mut bytes),
+            }
+            log::warn!(
+                "Opening {:?} truncated: size_bytes={} exceeds max_open_bytes={}",
+                doc_id.rel_path,
+                metadata.len(),
+                max_open_bytes
+            );
+        }
+
+        let (text, encoding) = match encoding {
+            Some(encoding) => (text_utils::decode_with_encoding(&bytes, encoding)?, encoding),
+            None => text_utils::detect_and_decode(&bytes)?,
+        };
 
         let line_ending = LineEnding::detect(&text);
         let (title, word_count) = Self::derive_text_metadata(&text, &doc_id.rel_path);
 
-        let metadata =
-            std::fs::metadata(&full_path).map_err(|e| AppError::io(format!("Failed to read metadata: {}", e)))?;
         let mtime = metadata
             .modified()
             .map_err(|e| AppError::io(format!("Failed to get mtime: {}", e)))?;
 
         let mtime: DateTime<Utc> = mtime.into();
         let created_at = metadata.created().ok().map(DateTime::<Utc>::from);
-        let is_conflict = is_conflicted_filename(&doc_id.rel_path.to_string_lossy());
+        let is_conflict = Self::is_conflicted(&settings, &doc_id.rel_path.to_string_lossy());
 
         let doc_meta = DocMeta {
             id: doc_id.clone(),
@@ -1190,36 +2915,96 @@ impl Store {
 
         log::info!("Opened document: {:?}", doc_id.rel_path);
 
-        Ok(DocContent { text, meta: doc_meta })
+        Ok(DocContent { text, meta: doc_meta, truncated })
     }
 
     /// Saves a document with atomic write semantics
     pub fn doc_save(&self, doc_id: &DocId, text: &str, policy: Option<SavePolicy>) -> Result<SaveResult, AppError> {
+        self.doc_save_with_durability(doc_id, text, policy, Durability::Fast)
+    }
+
+    /// Saves a document, with `Durability::Full` also fsyncing the parent directory after the write
+    ///
+    /// A document larger than `max_open_bytes` on disk only opens truncated, so saving over it is
+    /// refused with a `Conflict` error rather than cutting the file off at the limit.
+    pub fn doc_save_with_durability(
+        &self, doc_id: &DocId, text: &str, policy: Option<SavePolicy>, durability: Durability,
+    ) -> Result<SaveResult, AppError> {
         let policy = policy.unwrap_or_default();
         let location = self
             .location_get(doc_id.location_id)?
             .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
+        let scan = self.scan_context(&location)?;
+        let doc_id = &Self::with_default_extension(&scan.layout, doc_id, &location.root_path)?;
+
+        let stamped = Self::stamp_for_save(&scan.layout, doc_id, text, Utc::now());
+        let updated_stamped = stamped.is_some();
+        let text = stamped.as_deref().unwrap_or(text);
+
+        let trimmed = Self::trim_for_save(&scan.layout, text);
+        let whitespace_trimmed = trimmed.as_deref().is_some_and(|trimmed| trimmed != text);
+        let text = trimmed.as_deref().unwrap_or(text);
 
         let full_path = doc_id.resolve(&location.root_path);
 
+        let max_open_bytes = scan.layout.max_open_bytes;
+        if let Ok(existing) = std::fs::metadata(&full_path)
+            && existing.len() > max_open_bytes
+        {
+            return Err(AppError::new(
+                ErrorCode::Conflict,
+                format!(
+                    "Cannot save {:?}: it is {} bytes, over max_open_bytes={}, and only opens truncated",
+                    doc_id.rel_path,
+                    existing.len(),
+                    max_open_bytes
+                ),
+            ));
+        }
+
         if let Some(parent) = full_path.parent() {
             std::fs::create_dir_all(parent).map_err(|e| AppError::io(format!("Failed to create directory: {}", e)))?;
         }
 
-        let is_conflict = is_conflicted_filename(&doc_id.rel_path.to_string_lossy());
+        if !full_path.exists() && !scan.filter.is_indexable(&doc_id.rel_path) {
+            log::warn!(
+                "Creating {:?}, which is not indexable in location {:?}",
+                doc_id.rel_path,
+                doc_id.location_id
+            );
+        }
+
+        let is_conflict = Self::is_conflicted(&scan.layout, &doc_id.rel_path.to_string_lossy());
+        let encoding = Self::save_encoding(&scan.layout, self.catalog_encoding(doc_id)?, &full_path, text);
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let bytes = match encoding {
+            Encoding::Latin1 => text_utils::encode_latin1(text)?,
+            _ => text_utils::encode_utf8(text, encoding),
+        };
 
         match policy {
             SavePolicy::Atomic => {
-                self.save_atomic(&full_path, text)?;
+                self.save_atomic(&full_path, &bytes)?;
             }
             SavePolicy::InPlace => {
                 let mut file =
                     File::create(&full_path).map_err(|e| AppError::io(format!("Failed to create file: {}", e)))?;
-                file.write_all(text.as_bytes())
+                file.write_all(&bytes)
                     .map_err(|e| AppError::io(format!("Failed to write file: {}", e)))?;
+                if durability == Durability::Full {
+                    file.sync_all()
+                        .map_err(|e| AppError::io(format!("Failed to fsync file: {}", e)))?;
+                }
             }
         }
 
+        if durability == Durability::Full
+            && let Some(parent) = full_path.parent()
+            && let Err(e) = file_utils::sync_dir(parent)
+        {
+            log::warn!("Failed to fsync directory {:?} after save: {}", parent, e);
+        }
+
         let metadata =
             std::fs::metadata(&full_path).map_err(|e| AppError::io(format!("Failed to read metadata: {}", e)))?;
         let mtime = metadata
@@ -1244,23 +3029,56 @@ impl Store {
             mtime,
             created_at,
             content_hash: Some(text_utils::hash_text(text)),
-            encoding: Encoding::Utf8,
+            encoding,
             line_ending,
             is_conflict,
             title,
             word_count: Some(word_count),
         };
 
-        self.update_doc_in_catalog(doc_id, &new_meta)?;
-        self.index_document_text(doc_id, &new_meta, text)?;
+        self.write_document_index(&scan, doc_id, &new_meta, Some(text))?;
+        let goal_progress = self.record_goal_progress(doc_id, word_count)?;
+        if let Err(e) = self.autosave_clear(doc_id) {
+            log::warn!("Failed to clear autosave entry for {:?}: {}", doc_id.rel_path, e);
+        }
 
         log::info!("Saved document: {:?}", doc_id.rel_path);
 
-        Ok(SaveResult { success: true, new_meta: Some(new_meta), conflict_detected: is_conflict })
+        Ok(SaveResult {
+            success: true,
+            new_meta: Some(new_meta),
+            conflict_detected: is_conflict,
+            goal_progress,
+            whitespace_trimmed,
+            updated_stamped,
+        })
+    }
+
+    /// Encoding a save writes, given the one recorded in the catalog (`stored`)
+    ///
+    /// A `Latin1` document stays Latin-1. Otherwise it is UTF-8, with a BOM when the catalog (or, for
+    /// a file not catalogued yet, the file's first bytes) says it has one, when the text starts with
+    /// one, or when the file is new (or empty) and the `write_utf8_bom` setting is on.
+    fn save_encoding(settings: &UiLayoutSettings, stored: Option<Encoding>, full_path: &Path, text: &str) -> Encoding {
+        let wants_bom = match stored {
+            Some(Encoding::Latin1) => return Encoding::Latin1,
+            Some(stored) => stored == Encoding::Utf8WithBom,
+            None => {
+                let mut head = [0u8; 3];
+                match File::open(full_path) {
+                    Ok(file) if file.metadata().is_ok_and(|metadata| metadata.len() == 0) => settings.write_utf8_bom,
+                    Ok(mut file) => file.read_exact(&mut head).is_ok() && head == text_utils::UTF8_BOM,
+                    Err(_) if !full_path.exists() => settings.write_utf8_bom,
+                    Err(_) => false,
+                }
+            }
+        };
+
+        if wants_bom || text.starts_with('\u{feff}') { Encoding::Utf8WithBom } else { Encoding::Utf8 }
     }
 
     /// Atomic save implementation: write to temp file, fsync, rename
-    fn save_atomic(&self, target_path: &Path, text: &str) -> Result<(), AppError> {
+    fn save_atomic(&self, target_path: &Path, bytes: &[u8]) -> Result<(), AppError> {
         let parent_dir = target_path
             .parent()
             .ok_or_else(|| AppError::invalid_path("Target path has no parent directory"))?;
@@ -1271,7 +3089,7 @@ impl Store {
         let temp_path = temp_file.path();
 
         let mut file = temp_file.as_file();
-        file.write_all(text.as_bytes())
+        file.write_all(bytes)
             .map_err(|e| AppError::io(format!("Failed to write temp file: {}", e)))?;
 
         file.sync_all()
@@ -1294,6 +3112,7 @@ impl Store {
 
     /// Renames a document to a new name within the same directory
     pub fn doc_rename(&self, doc_id: &DocId, new_name: &str) -> Result<DocMeta, AppError> {
+        validate_filename(new_name)?;
         let location = self
             .location_get(doc_id.location_id)?
             .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
@@ -1325,19 +3144,26 @@ impl Store {
 
         let new_doc_id = DocId::new(doc_id.location_id, new_rel_path.clone())?;
 
+        let uid = self.doc_uid(doc_id)?;
+        let created_at = self.catalog_created_at(doc_id)?;
         self.remove_document_from_index(doc_id)?;
+        self.autosave_move(doc_id, &new_doc_id)?;
+        self.goal_move(doc_id, &new_doc_id)?;
 
         let filename = new_path
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown")
             .to_string();
-        let new_meta = self.read_doc_metadata(&new_path, doc_id.location_id, new_rel_path, &filename)?;
+        let scan = self.scan_context(&location)?;
+        let mut new_meta = self.read_doc_metadata(&new_path, &scan, doc_id.location_id, new_rel_path, &filename)?;
+        new_meta.created_at = created_at.or(new_meta.created_at);
+        self.catalog_carry_uid(&new_doc_id, &new_meta, uid)?;
 
-        if file_utils::is_indexable_text_path(&new_path) {
+        if scan.filter.is_indexable(&new_path) {
             let text = std::fs::read_to_string(&new_path)
                 .map_err(|e| AppError::io(format!("Failed to read renamed file: {}", e)))?;
-            self.index_document_text(&new_doc_id, &new_meta, &text)?;
+            self.index_document_text(&scan, &new_doc_id, &new_meta, &text)?;
         }
 
         log::info!("Renamed document: {:?} -> {:?}", doc_id.rel_path, new_doc_id.rel_path);
@@ -1345,6 +3171,33 @@ impl Store {
         Ok(new_meta)
     }
 
+    /// Normalizes `rel_path` and checks that it stays inside the location, returning the relative path
+    ///
+    /// `.` and `..` components are resolved lexically, and the deepest part of the destination that
+    /// already exists is checked on disk, so a symlinked folder pointing outside the root is refused.
+    /// The destination itself need not exist. Escapes are `InvalidPath` errors. The result can be
+    /// passed straight to [`Store::doc_move`].
+    pub fn resolve_within_location(&self, location_id: LocationId, rel_path: &Path) -> Result<PathBuf, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let normalized_rel_path = normalize_relative_path(rel_path)?;
+        let full_path = location.root_path.join(&normalized_rel_path);
+        let existing = full_path
+            .ancestors()
+            .find(|ancestor| ancestor.exists())
+            .unwrap_or(&location.root_path)
+            .to_path_buf();
+
+        if !is_path_within_location(&existing, &location.root_path) {
+            return Err(AppError::invalid_path("Path traversal attempt detected")
+                .with_context("Access denied: path escapes location root"));
+        }
+
+        Ok(normalized_rel_path)
+    }
+
     /// Moves a document to a new relative path within the same location
     pub fn doc_move(&self, doc_id: &DocId, new_rel_path: &Path) -> Result<DocMeta, AppError> {
         let location = self
@@ -1375,19 +3228,32 @@ impl Store {
 
         let new_doc_id = DocId::new(doc_id.location_id, new_rel_path.to_path_buf())?;
 
+        let uid = self.doc_uid(doc_id)?;
+        let created_at = self.catalog_created_at(doc_id)?;
         self.remove_document_from_index(doc_id)?;
+        self.autosave_move(doc_id, &new_doc_id)?;
+        self.goal_move(doc_id, &new_doc_id)?;
 
         let filename = new_path
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown")
             .to_string();
-        let new_meta = self.read_doc_metadata(&new_path, doc_id.location_id, new_rel_path.to_path_buf(), &filename)?;
+        let scan = self.scan_context(&location)?;
+        let mut new_meta = self.read_doc_metadata(
+            &new_path,
+            &scan,
+            doc_id.location_id,
+            new_rel_path.to_path_buf(),
+            &filename,
+        )?;
+        new_meta.created_at = created_at.or(new_meta.created_at);
+        self.catalog_carry_uid(&new_doc_id, &new_meta, uid)?;
 
-        if file_utils::is_indexable_text_path(&new_path) {
+        if scan.filter.is_indexable(&new_path) {
             let text = std::fs::read_to_string(&new_path)
                 .map_err(|e| AppError::io(format!("Failed to read moved file: {}", e)))?;
-            self.index_document_text(&new_doc_id, &new_meta, &text)?;
+            self.index_document_text(&scan, &new_doc_id, &new_meta, &text)?;
         }
 
         log::info!("Moved document: {:?} -> {:?}", doc_id.rel_path, new_doc_id.rel_path);
@@ -1442,24 +3308,32 @@ impl Store {
             )));
         }
 
+        let uid = self.doc_uid(doc_id)?;
+        let created_at = self.catalog_created_at(doc_id)?;
         self.remove_document_from_index(doc_id)?;
+        self.autosave_move(doc_id, &new_doc_id)?;
+        self.goal_move(doc_id, &new_doc_id)?;
 
         let filename = new_path
             .file_name()
             .and_then(|name| name.to_str())
             .unwrap_or("unknown")
             .to_string();
-        let new_meta = self.read_doc_metadata(
+        let scan = self.scan_context(&target_location)?;
+        let mut new_meta = self.read_doc_metadata(
             &new_path,
+            &scan,
             target_location_id,
             normalized_new_rel_path.clone(),
             &filename,
         )?;
+        new_meta.created_at = created_at.or(new_meta.created_at);
+        self.catalog_carry_uid(&new_doc_id, &new_meta, uid)?;
 
-        if file_utils::is_indexable_text_path(&new_path) {
+        if scan.filter.is_indexable(&new_path) {
             let text = std::fs::read_to_string(&new_path)
                 .map_err(|e| AppError::io(format!("Failed to read moved file: {}", e)))?;
-            self.index_document_text(&new_doc_id, &new_meta, &text)?;
+            self.index_document_text(&scan, &new_doc_id, &new_meta, &text)?;
         }
 
         log::info!(
@@ -1473,1242 +3347,6845 @@ impl Store {
         Ok(new_meta)
     }
 
-    /// Deletes a document from disk and removes it from the index
-    pub fn doc_delete(&self, doc_id: &DocId) -> Result<bool, AppError> {
-        let location = self
-            .location_get(doc_id.location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
-
-        let full_path = doc_id.resolve(&location.root_path);
-
-        if !full_path.exists() {
-            return Ok(false);
+    /// Appends `source` to the end of `target` and deletes `source`
+    ///
+    /// The target keeps its front matter; the source's front matter is dropped when
+    /// `strip_source_front_matter` is set. A blank separator defaults to a thematic break (`---`).
+    /// Both documents must exist and be fully loaded (not truncated) before anything is written.
+    pub fn doc_merge(
+        &self, target: &DocId, source: &DocId, separator: &str, strip_source_front_matter: bool,
+    ) -> Result<DocMeta, AppError> {
+        if target == source {
+            return Err(AppError::invalid_path("Cannot merge a document into itself"));
         }
 
-        std::fs::remove_file(&full_path).map_err(|e| AppError::io(format!("Failed to delete file: {}", e)))?;
-
-        self.remove_document_from_index(doc_id)?;
-
-        log::info!("Deleted document: {:?}", doc_id.rel_path);
-
-        Ok(true)
-    }
-
-    pub fn dir_create(&self, location_id: LocationId, rel_path: &Path) -> Result<bool, AppError> {
-        let location = self
-            .location_get(location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
-
-        let normalized_rel_path = normalize_relative_path(rel_path)?;
-        let full_path = location.root_path.join(&normalized_rel_path);
+        let source_location = self
+            .location_get(source.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", source.location_id)))?;
+        if !source.resolve(&source_location.root_path).is_file() {
+            return Err(AppError::not_found(format!(
+                "Source document not found: {:?}",
+                source.rel_path
+            )));
+        }
 
-        if full_path.exists() {
-            if full_path.is_dir() {
-                return Ok(false);
-            }
+        let target_content = self.doc_open(target)?;
+        let source_content = self.doc_open(source)?;
+        if target_content.truncated || source_content.truncated {
             return Err(AppError::new(
-                ErrorCode::Conflict,
-                "A file already exists at the target directory path",
+                ErrorCode::Parse,
+                "Cannot merge documents that are too large to open fully",
             ));
         }
 
-        std::fs::create_dir_all(&full_path).map_err(|e| AppError::io(format!("Failed to create directory: {}", e)))?;
-
-        log::info!("Created directory: {:?}", normalized_rel_path);
-        Ok(true)
-    }
+        let source_text = if strip_source_front_matter {
+            MarkdownEngine::new().body(&source_content.text)
+        } else {
+            source_content.text.as_str()
+        };
+        let separator = match separator.trim() {
+            "" => "---",
+            _ => separator.trim_matches(['\r', '\n']),
+        };
 
-    pub fn dir_rename(&self, location_id: LocationId, rel_path: &Path, new_name: &str) -> Result<PathBuf, AppError> {
-        let location = self
-            .location_get(location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
-        let normalized_rel_path = normalize_relative_path(rel_path)?;
-        let new_name_path = normalize_relative_path(Path::new(new_name))
-            .map_err(|_| AppError::invalid_path("New directory name is invalid"))?;
+        let merged = [
+            target_content.text.trim_end_matches(['\r', '\n']),
+            separator,
+            source_text.trim_start_matches(['\r', '\n']),
+        ]
+        .join("\n\n");
+        let merged = match LineEnding::detect(&target_content.text) {
+            LineEnding::CrLf => merged.replace("\r\n", "\n").replace('\n', "\r\n"),
+            _ => merged,
+        };
 
-        if new_name_path.components().count() != 1 {
-            return Err(AppError::invalid_path(
-                "New directory name must be a single path segment",
-            ));
-        }
+        let result = self.doc_save(target, &merged, None)?;
+        self.doc_delete(source)?;
 
-        let current_parent = normalized_rel_path.parent().unwrap_or(Path::new(""));
-        let next_rel_path = normalize_relative_path(&current_parent.join(&new_name_path))?;
+        log::info!("Merged document {:?} into {:?}", source.rel_path, target.rel_path);
 
-        let current_full_path = location.root_path.join(&normalized_rel_path);
-        let next_full_path = location.root_path.join(&next_rel_path);
+        result
+            .new_meta
+            .ok_or_else(|| AppError::io("Merged document metadata unavailable after save"))
+    }
 
-        if !current_full_path.exists() {
-            return Err(AppError::not_found("Directory not found"));
-        }
-        if !current_full_path.is_dir() {
-            return Err(AppError::invalid_path("Path is not a directory"));
-        }
-        if next_full_path.exists() {
+    /// Splits a document into one new file per section, starting a section at each heading of
+    /// `level` or above (`level` 2 splits at both `#` and `##` headings)
+    ///
+    /// Files are written next to the source and indexed. Text before the first heading stays with
+    /// the first section. With `keep_front_matter` set, every file starts with the source's front
+    /// matter; otherwise it is dropped. Names that are taken get a `-2`, `-3`, ... suffix, so no
+    /// existing file is overwritten. With `trash_original` set, the source is moved into the
+    /// location's trash once every part is written, and dropped from the catalog and search index.
+    /// If any part or the move fails, the parts already written are deleted and the source is left
+    /// as it was. Returns the new documents in order.
+    pub fn doc_split_by_heading(
+        &self, doc_id: &DocId, level: u8, naming: SplitNaming, keep_front_matter: bool, trash_original: bool,
+    ) -> Result<Vec<DocMeta>, AppError> {
+        if !(1..=6).contains(&level) {
             return Err(AppError::new(
-                ErrorCode::Conflict,
-                "A file or directory already exists at the destination",
+                ErrorCode::Parse,
+                format!("Heading level must be between 1 and 6, got {}", level),
             ));
         }
 
-        std::fs::rename(&current_full_path, &next_full_path)
-            .map_err(|e| AppError::io(format!("Failed to rename directory: {}", e)))?;
-
-        self.update_directory_paths_in_index(location_id, &normalized_rel_path, &next_rel_path)?;
-
-        log::info!("Renamed directory: {:?} -> {:?}", normalized_rel_path, next_rel_path);
-        Ok(next_rel_path)
-    }
-
-    pub fn dir_move(&self, location_id: LocationId, rel_path: &Path, new_rel_path: &Path) -> Result<PathBuf, AppError> {
         let location = self
-            .location_get(location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
-        let normalized_rel_path = normalize_relative_path(rel_path)?;
-        let normalized_new_rel_path = normalize_relative_path(new_rel_path)?;
-
-        let old_str = normalized_rel_path.to_string_lossy();
-        let new_str = normalized_new_rel_path.to_string_lossy();
-        if new_str == old_str || new_str.starts_with(&format!("{}/", old_str)) {
-            return Err(AppError::invalid_path(
-                "Cannot move a directory into itself or one of its descendants",
-            ));
-        }
-
-        let current_full_path = location.root_path.join(&normalized_rel_path);
-        let next_full_path = location.root_path.join(&normalized_new_rel_path);
+            .location_get(doc_id.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
 
-        if !current_full_path.exists() {
-            return Err(AppError::not_found("Directory not found"));
-        }
-        if !current_full_path.is_dir() {
-            return Err(AppError::invalid_path("Path is not a directory"));
-        }
-        if next_full_path.exists() {
+        let content = self.doc_open(doc_id)?;
+        if content.truncated {
             return Err(AppError::new(
-                ErrorCode::Conflict,
-                "A file or directory already exists at the destination",
+                ErrorCode::Parse,
+                "Cannot split a document that is too large to open fully",
             ));
         }
 
-        if let Some(parent) = next_full_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| AppError::io(format!("Failed to create destination directory: {}", e)))?;
+        let text = content.text.as_str();
+        let engine = MarkdownEngine::new();
+        let front_matter = &text[..text.len() - engine.body(text).len()];
+        let headings: Vec<Heading> = engine
+            .metadata(text, MarkdownProfile::Extended)
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to parse document: {}", e)))?
+            .outline
+            .into_iter()
+            .filter(|heading| heading.level <= level)
+            .collect();
+        if headings.is_empty() {
+            return Err(AppError::not_found(format!(
+                "No headings at level {} or above to split at",
+                level
+            )));
         }
 
-        std::fs::rename(&current_full_path, &next_full_path)
-            .map_err(|e| AppError::io(format!("Failed to move directory: {}", e)))?;
-
-        self.update_directory_paths_in_index(location_id, &normalized_rel_path, &normalized_new_rel_path)?;
-
-        log::info!(
-            "Moved directory: {:?} -> {:?}",
-            normalized_rel_path,
-            normalized_new_rel_path
-        );
-        Ok(normalized_new_rel_path)
-    }
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(at, _)| at + 1))
+            .collect();
+        let mut starts: Vec<usize> = headings
+            .iter()
+            .map(|heading| {
+                line_starts
+                    .get(heading.line.saturating_sub(1))
+                    .copied()
+                    .unwrap_or(text.len())
+            })
+            .collect();
+        starts[0] = front_matter.len();
 
-    pub fn dir_move_to_location(
-        &self, source_location_id: LocationId, rel_path: &Path, target_location_id: LocationId, new_rel_path: &Path,
-    ) -> Result<PathBuf, AppError> {
-        if source_location_id == target_location_id {
-            return self.dir_move(source_location_id, rel_path, new_rel_path);
+        let newline = match LineEnding::detect(text) {
+            LineEnding::CrLf => "\r\n",
+            _ => "\n",
+        };
+        let stem = doc_id
+            .rel_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "section".to_string());
+        let extension = doc_id
+            .rel_path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_else(|| "md".to_string());
+        let dir = doc_id.rel_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let width = headings.len().to_string().len().max(2);
+
+        let mut created = Vec::with_capacity(headings.len());
+        for (index, heading) in headings.iter().enumerate() {
+            let end = starts.get(index + 1).copied().unwrap_or(text.len());
+            let section = text[starts[index]..end].trim_start_matches(['\r', '\n']);
+
+            let numbered = format!("{}-{:0width$}", stem, index + 1, width = width);
+            let base = match naming {
+                SplitNaming::Slug => file_utils::slug_file_stem(&heading.text).unwrap_or(numbered),
+                SplitNaming::Index => numbered,
+            };
+            let rel_path = Self::unused_rel_path(&location.root_path, &dir, &base, &extension);
+
+            let mut output = String::new();
+            if keep_front_matter && !front_matter.is_empty() {
+                output.push_str(front_matter);
+                output.push_str(newline);
+            }
+            output.push_str(section);
+
+            let saved = DocId::new(doc_id.location_id, rel_path)
+                .map_err(AppError::from)
+                .and_then(|new_doc_id| {
+                    self.doc_save(&new_doc_id, &output, None)?
+                        .new_meta
+                        .ok_or_else(|| AppError::io("Split document metadata unavailable after save"))
+                });
+            match saved {
+                Ok(meta) => created.push(meta),
+                Err(error) => {
+                    self.discard_split_parts(&created);
+                    return Err(error);
+                }
+            }
         }
 
-        let source_location = self
-            .location_get(source_location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", source_location_id)))?;
-        let target_location = self
-            .location_get(target_location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", target_location_id)))?;
+        if trash_original {
+            let trash_dir = Path::new(TRASH_DIR).join(&dir);
+            let trashed = Self::unused_rel_path(&location.root_path, &trash_dir, &stem, &extension);
+            if let Err(error) = self.trash_unindexed(&location, doc_id, &trashed) {
+                self.discard_split_parts(&created);
+                return Err(error);
+            }
+        }
 
-        let normalized_rel_path = normalize_relative_path(rel_path)?;
-        let normalized_new_rel_path = normalize_relative_path(new_rel_path)?;
+        log::info!("Split document {:?} into {} files", doc_id.rel_path, created.len());
 
-        let source_full_path = source_location.root_path.join(&normalized_rel_path);
-        let target_full_path = target_location.root_path.join(&normalized_new_rel_path);
+        Ok(created)
+    }
 
-        if !source_full_path.exists() {
-            return Err(AppError::not_found("Directory not found"));
+    /// Deletes the parts a failed split already wrote, logging any that cannot be removed
+    fn discard_split_parts(&self, created: &[DocMeta]) {
+        for part in created {
+            if let Err(error) = self.doc_delete(&part.id) {
+                log::warn!("Failed to remove split part {:?}: {}", part.id.rel_path, error);
+            }
         }
-        if !source_full_path.is_dir() {
-            return Err(AppError::invalid_path("Path is not a directory"));
+    }
+
+    /// Moves a document to `trashed` (a path under the location's trash) and drops it from the
+    /// catalog and search index, so the trashed copy never shows up in search
+    fn trash_unindexed(&self, location: &LocationDescriptor, doc_id: &DocId, trashed: &Path) -> Result<(), AppError> {
+        let target = location.root_path.join(trashed);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::io(format!("Failed to create trash directory: {}", e)))?;
         }
-        if target_full_path.exists() {
-            return Err(AppError::new(
-                ErrorCode::Conflict,
-                "A file or directory already exists at the destination",
-            ));
+        std::fs::rename(doc_id.resolve(&location.root_path), &target)
+            .map_err(|e| AppError::io(format!("Failed to move document to trash: {}", e)))?;
+        // The purge ages trashed files by mtime, which a rename keeps, so stamp the trash time
+        if let Err(e) = File::options()
+            .write(true)
+            .open(&target)
+            .and_then(|file| file.set_modified(std::time::SystemTime::now()))
+        {
+            log::warn!("Failed to stamp trash time on {:?}: {}", target, e);
         }
 
-        if let Some(parent) = target_full_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| AppError::io(format!("Failed to create destination directory: {}", e)))?;
+        self.forget_document(doc_id)?;
+        self.remove_document_from_index(doc_id)
+    }
+
+    /// `<dir>/<base>.<extension>`, or with the first free `-2`, `-3`, ... suffix when that file exists
+    fn unused_rel_path(root: &Path, dir: &Path, base: &str, extension: &str) -> PathBuf {
+        let mut rel_path = dir.join(format!("{}.{}", base, extension));
+        let mut suffix = 1;
+        while root.join(&rel_path).exists() {
+            suffix += 1;
+            rel_path = dir.join(format!("{}-{}.{}", base, suffix, extension));
         }
+        rel_path
+    }
 
-        Self::move_directory_on_disk(&source_full_path, &target_full_path)?;
-        self.update_directory_paths_in_index_across_locations(
-            source_location_id,
-            target_location_id,
-            &normalized_rel_path,
-            &normalized_new_rel_path,
-        )?;
+    /// Stable identifier of a catalogued document, or `None` when the path is not in the catalog
+    ///
+    /// The uid is assigned when the document is first catalogued and follows it through renames and
+    /// moves made in the app, so it can be stored in links and session state instead of the path.
+    pub fn doc_uid(&self, doc_id: &DocId) -> Result<Option<String>, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        log::info!(
-            "Moved directory across locations: source_location={:?}, target_location={:?}, from={:?}, to={:?}",
-            source_location_id,
-            target_location_id,
-            normalized_rel_path,
-            normalized_new_rel_path
-        );
-        Ok(normalized_new_rel_path)
+        conn.query_row(
+            "SELECT doc_uid FROM documents WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .map(Option::flatten)
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read document uid: {}", e)))
     }
 
-    pub fn dir_delete(&self, location_id: LocationId, rel_path: &Path) -> Result<bool, AppError> {
-        let location = self
-            .location_get(location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
-        let normalized_rel_path = normalize_relative_path(rel_path)?;
-        let full_path = location.root_path.join(&normalized_rel_path);
+    /// The `created_at` recorded in the catalog for a document, if it has a row
+    ///
+    /// Moves read this before the old row is removed so the original creation time outlives the
+    /// fresh timestamp the filesystem may report for the relocated file.
+    fn catalog_created_at(&self, doc_id: &DocId) -> Result<Option<DateTime<Utc>>, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        if !full_path.exists() {
-            return Ok(false);
-        }
-        if !full_path.is_dir() {
-            return Err(AppError::invalid_path("Path is not a directory"));
-        }
+        let created_at = conn
+            .query_row(
+                "SELECT created_at FROM documents WHERE location_id = ?1 AND rel_path = ?2",
+                params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read document created_at: {}", e)))?
+            .flatten();
 
-        std::fs::remove_dir_all(&full_path).map_err(|e| AppError::io(format!("Failed to delete directory: {}", e)))?;
+        Ok(created_at
+            .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+            .map(|timestamp| timestamp.with_timezone(&Utc)))
+    }
 
-        self.remove_directory_from_index(location_id, &normalized_rel_path)?;
+    /// The encoding recorded in the catalog for a document, if it has a row
+    fn catalog_encoding(&self, doc_id: &DocId) -> Result<Option<Encoding>, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        log::info!("Deleted directory: {:?}", normalized_rel_path);
-        Ok(true)
+        conn.query_row(
+            "SELECT encoding FROM documents WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
+            |row| row.get::<_, i32>(0),
+        )
+        .optional()
+        .map(|encoding| encoding.map(Encoding::from))
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read document encoding: {}", e)))
     }
 
-    fn update_directory_paths_in_index(
-        &self, location_id: LocationId, old_rel_path: &Path, new_rel_path: &Path,
-    ) -> Result<(), AppError> {
+    /// Looks up the catalogued document carrying `uid`
+    pub fn doc_by_uid(&self, uid: &str) -> Result<Option<DocMeta>, AppError> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        let old_prefix = old_rel_path.to_string_lossy().to_string();
-        let new_prefix = new_rel_path.to_string_lossy().to_string();
-        let escaped_old_prefix = old_prefix.replace('\\', r"\\").replace('%', r"\%").replace('_', r"\_");
-        let old_like = format!("{}/%", escaped_old_prefix);
-        let updated_at = Utc::now().to_rfc3339();
-
-        conn.execute(
-            "UPDATE documents
-             SET rel_path = ?2 || substr(rel_path, length(?1) + 1), updated_at = ?5
-             WHERE location_id = ?3 AND (rel_path = ?1 OR rel_path LIKE ?4 ESCAPE '\\')",
-            params![old_prefix, new_prefix, location_id.0, old_like, updated_at],
-        )
-        .map_err(|e| {
-            AppError::new(
-                ErrorCode::Index,
-                format!("Failed to update directory document rows: {}", e),
-            )
-        })?;
-
-        conn.execute(
-            "UPDATE docs_fts
-             SET rel_path = ?2 || substr(rel_path, length(?1) + 1)
-             WHERE location_id = ?3 AND (rel_path = ?1 OR rel_path LIKE ?4 ESCAPE '\\')",
-            params![old_prefix, new_prefix, location_id.0, old_like],
+        conn.query_row(
+            "SELECT location_id, rel_path, filename, size_bytes, mtime, created_at, content_hash,
+                encoding, line_ending, is_conflict, title, word_count
+             FROM documents
+             WHERE doc_uid = ?1",
+            params![uid],
+            Self::catalog_row_to_doc_meta,
         )
-        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update directory FTS rows: {}", e)))?;
-
-        Ok(())
+        .optional()
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to look up document by uid: {}", e)))
     }
 
-    fn update_directory_paths_in_index_across_locations(
-        &self, source_location_id: LocationId, target_location_id: LocationId, old_rel_path: &Path, new_rel_path: &Path,
-    ) -> Result<(), AppError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
-
-        let old_prefix = old_rel_path.to_string_lossy().to_string();
-        let new_prefix = new_rel_path.to_string_lossy().to_string();
-        let escaped_old_prefix = old_prefix.replace('\\', r"\\").replace('%', r"\%").replace('_', r"\_");
-        let old_like = format!("{}/%", escaped_old_prefix);
-        let updated_at = Utc::now().to_rfc3339();
-
-        conn.execute(
-            "UPDATE documents
-             SET location_id = ?2,
-                 rel_path = ?4 || substr(rel_path, length(?3) + 1),
-                 updated_at = ?5
-             WHERE location_id = ?1 AND (rel_path = ?3 OR rel_path LIKE ?6 ESCAPE '\\')",
-            params![
-                source_location_id.0,
-                target_location_id.0,
-                old_prefix,
-                new_prefix,
-                updated_at,
-                old_like
-            ],
-        )
-        .map_err(|e| {
-            AppError::new(
-                ErrorCode::Index,
-                format!("Failed to update cross-location directory document rows: {}", e),
-            )
-        })?;
-
-        conn.execute(
-            "UPDATE docs_fts
-             SET location_id = ?2,
-                 rel_path = ?4 || substr(rel_path, length(?3) + 1)
-             WHERE location_id = ?1 AND (rel_path = ?3 OR rel_path LIKE ?5 ESCAPE '\\')",
-            params![
-                source_location_id.0,
-                target_location_id.0,
-                old_prefix,
-                new_prefix,
-                old_like
-            ],
-        )
-        .map_err(|e| {
-            AppError::new(
-                ErrorCode::Index,
-                format!("Failed to update cross-location directory FTS rows: {}", e),
-            )
-        })?;
+    /// Deletes a document from disk and removes it from the index
+    pub fn doc_delete(&self, doc_id: &DocId) -> Result<bool, AppError> {
+        let location = self
+            .location_get(doc_id.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
 
-        Ok(())
-    }
+        let full_path = doc_id.resolve(&location.root_path);
 
-    fn move_directory_on_disk(source_path: &Path, destination_path: &Path) -> Result<(), AppError> {
-        match std::fs::rename(source_path, destination_path) {
-            Ok(()) => Ok(()),
-            Err(error) if error.kind() == std::io::ErrorKind::CrossesDevices => {
-                Self::copy_directory_recursive(source_path, destination_path)?;
-                std::fs::remove_dir_all(source_path)
-                    .map_err(|e| AppError::io(format!("Failed to remove source directory after copy: {}", e)))?;
-                Ok(())
-            }
-            Err(error) => Err(AppError::io(format!("Failed to move directory: {}", error))),
+        if !full_path.exists() {
+            return Ok(false);
         }
-    }
-
-    fn copy_directory_recursive(source_path: &Path, destination_path: &Path) -> Result<(), AppError> {
-        std::fs::create_dir_all(destination_path)
-            .map_err(|e| AppError::io(format!("Failed to create directory while moving: {}", e)))?;
-
-        let entries = std::fs::read_dir(source_path)
-            .map_err(|e| AppError::io(format!("Failed to read source directory while moving: {}", e)))?;
-
-        for entry_result in entries {
-            let entry = entry_result.map_err(|e| AppError::io(format!("Failed to read directory entry: {}", e)))?;
-            let source_child = entry.path();
-            let destination_child = destination_path.join(entry.file_name());
-            let file_type = entry
-                .file_type()
-                .map_err(|e| AppError::io(format!("Failed to read directory entry type: {}", e)))?;
 
-            if file_type.is_dir() {
-                Self::copy_directory_recursive(&source_child, &destination_child)?;
-                continue;
-            }
+        std::fs::remove_file(&full_path).map_err(|e| AppError::io(format!("Failed to delete file: {}", e)))?;
 
-            if file_type.is_file() {
-                std::fs::copy(&source_child, &destination_child)
-                    .map_err(|e| AppError::io(format!("Failed to copy file while moving directory: {}", e)))?;
-                continue;
-            }
+        self.forget_document(doc_id)?;
+        self.remove_document_from_index(doc_id)?;
 
-            return Err(AppError::io(format!(
-                "Unsupported filesystem entry while moving directory: {:?}",
-                source_child
-            )));
-        }
+        log::info!("Deleted document: {:?}", doc_id.rel_path);
 
-        Ok(())
+        Ok(true)
     }
 
-    fn remove_directory_from_index(&self, location_id: LocationId, rel_path: &Path) -> Result<(), AppError> {
+    /// Drops what the store keeps about a document beyond its index rows, once it is deleted or trashed
+    fn forget_document(&self, doc_id: &DocId) -> Result<(), AppError> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        let prefix = rel_path.to_string_lossy().to_string();
-        let escaped_prefix = prefix.replace('\\', r"\\").replace('%', r"\%").replace('_', r"\_");
-        let prefix_like = format!("{}/%", escaped_prefix);
+        let escaped = doc_id
+            .rel_path
+            .to_string_lossy()
+            .replace('\\', r"\\")
+            .replace('%', r"\%")
+            .replace('_', r"\_");
+        Self::forget_documents_locked(&conn, doc_id.location_id, &escaped)
+    }
 
+    /// Drops the word-count history, goals and autosaves of the documents in a location whose path matches
+    /// `rel_path_like` (a `LIKE` pattern escaped with `\`)
+    ///
+    /// History is keyed by the catalog uid, so this must run before the documents' catalog rows go.
+    fn forget_documents_locked(
+        conn: &Connection, location_id: LocationId, rel_path_like: &str,
+    ) -> Result<(), AppError> {
         conn.execute(
-            "DELETE FROM documents
-             WHERE location_id = ?1 AND (rel_path = ?2 OR rel_path LIKE ?3 ESCAPE '\\')",
-            params![location_id.0, prefix, prefix_like],
+            "DELETE FROM word_count_history WHERE doc_uid IN (
+                SELECT doc_uid FROM documents
+                WHERE location_id = ?1 AND rel_path LIKE ?2 ESCAPE '\\' AND doc_uid IS NOT NULL
+             )",
+            params![location_id.0, rel_path_like],
         )
-        .map_err(|e| {
-            AppError::new(
-                ErrorCode::Index,
-                format!("Failed to delete directory document rows: {}", e),
-            )
-        })?;
-
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear word count history: {}", e)))?;
         conn.execute(
-            "DELETE FROM docs_fts
-             WHERE location_id = ?1 AND (rel_path = ?2 OR rel_path LIKE ?3 ESCAPE '\\')",
-            params![location_id.0, prefix, prefix_like],
+            "DELETE FROM document_goals WHERE location_id = ?1 AND rel_path LIKE ?2 ESCAPE '\\'",
+            params![location_id.0, rel_path_like],
         )
-        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to delete directory FTS rows: {}", e)))?;
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear document goals: {}", e)))?;
+        conn.execute(
+            "DELETE FROM autosave WHERE location_id = ?1 AND rel_path LIKE ?2 ESCAPE '\\'",
+            params![location_id.0, rel_path_like],
+        )
+        .map_err(|e| AppError::io(format!("Failed to clear autosave entries: {}", e)))?;
 
         Ok(())
     }
 
-    /// Updates document entry in catalog
-    fn update_doc_in_catalog(&self, doc_id: &DocId, meta: &DocMeta) -> Result<(), AppError> {
+    /// Returns the word-count goal set for a document, if any
+    pub fn doc_goal_get(&self, doc_id: &DocId) -> Result<Option<usize>, AppError> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        let rel_path_str = doc_id.rel_path.to_string_lossy().to_string();
-        let mtime_str = meta.mtime.to_rfc3339();
-        let created_at_str = meta.created_at.map(|timestamp| timestamp.to_rfc3339());
-        let updated_at_str = Utc::now().to_rfc3339();
-        let encoding: i32 = meta.encoding.into();
-        let line_ending: i32 = meta.line_ending.into();
+        conn.query_row(
+            "SELECT target_words FROM document_goals WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()
+        .map(|target| target.map(|target| target as usize))
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query document goal: {}", e)))
+    }
+
+    /// Sets a document's word-count goal, re-arming the one-time "goal reached" progress event
+    pub fn doc_goal_set(&self, doc_id: &DocId, target_words: usize) -> Result<(), AppError> {
+        if target_words == 0 {
+            return Err(AppError::new(
+                ErrorCode::Parse,
+                "Word-count goal must be greater than zero",
+            ));
+        }
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
         conn.execute(
-            "INSERT INTO documents
-             (
-                location_id,
-                rel_path,
-                filename,
-                size_bytes,
-                mtime,
-                created_at,
-                content_hash,
-                encoding,
-                line_ending,
-                is_conflict,
-                title,
-                word_count,
-                updated_at
-             )
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            "INSERT INTO document_goals (location_id, rel_path, target_words, reached_at, updated_at)
+             VALUES (?1, ?2, ?3, NULL, ?4)
              ON CONFLICT(location_id, rel_path) DO UPDATE SET
-             filename = excluded.filename,
-             size_bytes = excluded.size_bytes,
-             mtime = excluded.mtime,
-             created_at = COALESCE(documents.created_at, excluded.created_at),
-             content_hash = excluded.content_hash,
-             encoding = excluded.encoding,
-             line_ending = excluded.line_ending,
-             is_conflict = excluded.is_conflict,
-             title = excluded.title,
-             word_count = excluded.word_count,
+             target_words = excluded.target_words,
+             reached_at = NULL,
              updated_at = excluded.updated_at",
             params![
                 doc_id.location_id.0,
-                rel_path_str,
-                meta.filename,
-                meta.size_bytes as i64,
-                mtime_str,
-                created_at_str,
-                meta.content_hash.clone(),
-                encoding,
-                line_ending,
-                meta.is_conflict as i32,
-                meta.title,
-                meta.word_count.map(|n| n as i64),
-                updated_at_str,
+                doc_id.rel_path.to_string_lossy().to_string(),
+                target_words as i64,
+                Utc::now().to_rfc3339()
             ],
         )
-        .map_err(|e| AppError::io(format!("Failed to update document catalog: {}", e)))?;
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to persist document goal: {}", e)))?;
 
         Ok(())
     }
 
-    fn index_document_text(&self, doc_id: &DocId, meta: &DocMeta, text: &str) -> Result<(), AppError> {
-        if !file_utils::is_indexable_text_path(&doc_id.rel_path) {
-            self.remove_fts_entry(doc_id)?;
-            return Ok(());
-        }
+    /// Removes a document's word-count goal, returning whether one was set
+    pub fn doc_goal_clear(&self, doc_id: &DocId) -> Result<bool, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        let title = meta
-            .title
-            .clone()
-            .or_else(|| file_utils::fallback_title_from_path(&doc_id.rel_path))
-            .unwrap_or_else(|| "Untitled".to_string());
-        self.upsert_fts_entry(doc_id, &title, text)
+        let removed = conn
+            .execute(
+                "DELETE FROM document_goals WHERE location_id = ?1 AND rel_path = ?2",
+                params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear document goal: {}", e)))?;
+
+        Ok(removed > 0)
     }
 
-    fn upsert_fts_entry(&self, doc_id: &DocId, title: &str, content: &str) -> Result<(), AppError> {
+    /// Stores in-progress editor content for crash recovery, replacing any earlier entry for the document
+    ///
+    /// Text larger than the `max_open_bytes` setting is not stored; returns whether the entry was written.
+    pub fn autosave_put(&self, doc_id: &DocId, text: &str) -> Result<bool, AppError> {
+        let max_bytes = self.layout_settings().max_open_bytes;
+        if text.len() as u64 > max_bytes {
+            log::warn!(
+                "Skipping autosave for {:?}: {} bytes exceeds max_open_bytes={}",
+                doc_id.rel_path,
+                text.len(),
+                max_bytes
+            );
+            return Ok(false);
+        }
+
         let conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        let rel_path = doc_id.rel_path.to_string_lossy().to_string();
-
-        conn.execute(
-            "DELETE FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
-            params![doc_id.location_id.0, rel_path],
-        )
-        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove existing FTS row: {}", e)))?;
-
         conn.execute(
-            "INSERT INTO docs_fts (location_id, rel_path, title, content) VALUES (?1, ?2, ?3, ?4)",
+            "INSERT INTO autosave (location_id, rel_path, text, saved_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(location_id, rel_path) DO UPDATE SET
+             text = excluded.text,
+             saved_at = excluded.saved_at",
             params![
                 doc_id.location_id.0,
                 doc_id.rel_path.to_string_lossy().to_string(),
-                title,
-                content
+                text,
+                Utc::now().to_rfc3339()
             ],
         )
-        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to insert FTS row: {}", e)))?;
+        .map_err(|e| AppError::io(format!("Failed to write autosave entry: {}", e)))?;
 
-        Ok(())
+        Ok(true)
     }
 
-    fn remove_fts_entry(&self, doc_id: &DocId) -> Result<(), AppError> {
+    /// Removes and returns a document's autosave entry, if it has one
+    pub fn autosave_take(&self, doc_id: &DocId) -> Result<Option<AutosaveRecovery>, AppError> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+        let rel_path = doc_id.rel_path.to_string_lossy().to_string();
+
+        let entry = conn
+            .query_row(
+                "SELECT text, saved_at FROM autosave WHERE location_id = ?1 AND rel_path = ?2",
+                params![doc_id.location_id.0, rel_path],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::io(format!("Failed to read autosave entry: {}", e)))?;
+        let Some((text, saved_at)) = entry else {
+            return Ok(None);
+        };
 
         conn.execute(
-            "DELETE FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
+            "DELETE FROM autosave WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, rel_path],
+        )
+        .map_err(|e| AppError::io(format!("Failed to clear autosave entry: {}", e)))?;
+
+        let saved_at = DateTime::parse_from_rfc3339(&saved_at)
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Invalid autosave timestamp: {}", e)))?;
+        Ok(Some(AutosaveRecovery { text, saved_at }))
+    }
+
+    /// Drops a document's autosave entry without reading it, once its content is on disk or gone
+    fn autosave_clear(&self, doc_id: &DocId) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        conn.execute(
+            "DELETE FROM autosave WHERE location_id = ?1 AND rel_path = ?2",
             params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
         )
-        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove FTS row: {}", e)))?;
+        .map_err(|e| AppError::io(format!("Failed to clear autosave entry: {}", e)))?;
 
         Ok(())
     }
 
-    pub fn remove_document_from_index(&self, doc_id: &DocId) -> Result<(), AppError> {
+    /// Lists documents in existing locations that have autosave entries, most recent first
+    pub fn autosave_pending(&self) -> Result<Vec<AutosavePending>, AppError> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
-        let rel_path = doc_id.rel_path.to_string_lossy().to_string();
+        let mut stmt = conn
+            .prepare(
+                "SELECT a.location_id, a.rel_path, a.saved_at, length(CAST(a.text AS BLOB))
+                 FROM autosave a
+                 INNER JOIN locations l ON l.id = a.location_id
+                 ORDER BY a.saved_at DESC, a.location_id ASC, a.rel_path ASC",
+            )
+            .map_err(|e| AppError::io(format!("Failed to prepare autosave query: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })
+            .map_err(|e| AppError::io(format!("Failed to query autosave entries: {}", e)))?;
+
+        let mut pending = Vec::new();
+        for row in rows {
+            let (location_id, rel_path, saved_at, size_bytes) =
+                row.map_err(|e| AppError::io(format!("Failed to read autosave entry: {}", e)))?;
+            let (Ok(doc_id), Ok(saved_at)) = (
+                DocId::new(LocationId(location_id), PathBuf::from(&rel_path)),
+                DateTime::parse_from_rfc3339(&saved_at),
+            ) else {
+                log::warn!(
+                    "Skipping invalid autosave entry: location={}, path={}",
+                    location_id,
+                    rel_path
+                );
+                continue;
+            };
+            pending.push(AutosavePending {
+                doc_id,
+                saved_at: saved_at.with_timezone(&Utc),
+                size_bytes: size_bytes as usize,
+            });
+        }
+
+        Ok(pending)
+    }
+
+    /// Re-keys a document's autosave entry after it is renamed or moved
+    fn autosave_move(&self, from: &DocId, to: &DocId) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
         conn.execute(
-            "DELETE FROM documents WHERE location_id = ?1 AND rel_path = ?2",
-            params![doc_id.location_id.0, rel_path.clone()],
+            "UPDATE OR REPLACE autosave SET location_id = ?3, rel_path = ?4 WHERE location_id = ?1 AND rel_path = ?2",
+            params![
+                from.location_id.0,
+                from.rel_path.to_string_lossy().to_string(),
+                to.location_id.0,
+                to.rel_path.to_string_lossy().to_string()
+            ],
         )
-        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove document row: {}", e)))?;
+        .map_err(|e| AppError::io(format!("Failed to move autosave entry: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Re-keys a document's word-count goal after it is renamed or moved
+    fn goal_move(&self, from: &DocId, to: &DocId) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
 
         conn.execute(
-            "DELETE FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
-            params![doc_id.location_id.0, rel_path],
+            "UPDATE OR REPLACE document_goals SET location_id = ?3, rel_path = ?4
+             WHERE location_id = ?1 AND rel_path = ?2",
+            params![
+                from.location_id.0,
+                from.rel_path.to_string_lossy().to_string(),
+                to.location_id.0,
+                to.rel_path.to_string_lossy().to_string()
+            ],
         )
-        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove FTS row: {}", e)))?;
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to move document goal: {}", e)))?;
 
         Ok(())
     }
 
-    pub fn reindex_document(&self, doc_id: &DocId) -> Result<(), AppError> {
+    /// Computes goal progress for a just-saved document
+    ///
+    /// Returns `None` when no goal is set or it was already reached; the save that first reaches
+    /// the target marks the goal so later saves stay quiet until the goal is set again.
+    fn record_goal_progress(&self, doc_id: &DocId, current_words: usize) -> Result<Option<GoalProgress>, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let rel_path = doc_id.rel_path.to_string_lossy().to_string();
+        let goal = conn
+            .query_row(
+                "SELECT target_words, reached_at FROM document_goals WHERE location_id = ?1 AND rel_path = ?2",
+                params![doc_id.location_id.0, rel_path],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query document goal: {}", e)))?;
+
+        let Some((target_words, None)) = goal else {
+            return Ok(None);
+        };
+
+        let progress = GoalProgress { current_words, target_words: target_words as usize };
+        if progress.is_reached() {
+            conn.execute(
+                "UPDATE document_goals SET reached_at = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+                params![doc_id.location_id.0, rel_path, Utc::now().to_rfc3339()],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to mark document goal reached: {}", e)))?;
+        }
+
+        Ok(Some(progress))
+    }
+
+    pub fn dir_create(&self, location_id: LocationId, rel_path: &Path) -> Result<bool, AppError> {
         let location = self
-            .location_get(doc_id.location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
-        let full_path = doc_id.resolve(&location.root_path);
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let normalized_rel_path = normalize_relative_path(rel_path)?;
+        let full_path = location.root_path.join(&normalized_rel_path);
+
+        if full_path.exists() {
+            if full_path.is_dir() {
+                return Ok(false);
+            }
+            return Err(AppError::new(
+                ErrorCode::Conflict,
+                "A file already exists at the target directory path",
+            ));
+        }
+
+        std::fs::create_dir_all(&full_path).map_err(|e| AppError::io(format!("Failed to create directory: {}", e)))?;
+
+        log::info!("Created directory: {:?}", normalized_rel_path);
+        Ok(true)
+    }
+
+    pub fn dir_rename(&self, location_id: LocationId, rel_path: &Path, new_name: &str) -> Result<PathBuf, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+        let normalized_rel_path = normalize_relative_path(rel_path)?;
+        validate_filename(new_name)?;
+        let new_name_path = PathBuf::from(new_name);
+
+        let current_parent = normalized_rel_path.parent().unwrap_or(Path::new(""));
+        let next_rel_path = normalize_relative_path(&current_parent.join(&new_name_path))?;
+
+        let current_full_path = location.root_path.join(&normalized_rel_path);
+        let next_full_path = location.root_path.join(&next_rel_path);
+
+        if !current_full_path.exists() {
+            return Err(AppError::not_found("Directory not found"));
+        }
+        if !current_full_path.is_dir() {
+            return Err(AppError::invalid_path("Path is not a directory"));
+        }
+        if next_full_path.exists() {
+            return Err(AppError::new(
+                ErrorCode::Conflict,
+                "A file or directory already exists at the destination",
+            ));
+        }
+
+        std::fs::rename(&current_full_path, &next_full_path)
+            .map_err(|e| AppError::io(format!("Failed to rename directory: {}", e)))?;
+
+        self.update_directory_paths_in_index(location_id, &normalized_rel_path, &next_rel_path)?;
+
+        log::info!("Renamed directory: {:?} -> {:?}", normalized_rel_path, next_rel_path);
+        Ok(next_rel_path)
+    }
+
+    pub fn dir_move(&self, location_id: LocationId, rel_path: &Path, new_rel_path: &Path) -> Result<PathBuf, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+        let normalized_rel_path = normalize_relative_path(rel_path)?;
+        let normalized_new_rel_path = normalize_relative_path(new_rel_path)?;
+
+        let old_str = normalized_rel_path.to_string_lossy();
+        let new_str = normalized_new_rel_path.to_string_lossy();
+        if new_str == old_str || new_str.starts_with(&format!("{}/", old_str)) {
+            return Err(AppError::invalid_path(
+                "Cannot move a directory into itself or one of its descendants",
+            ));
+        }
+
+        let current_full_path = location.root_path.join(&normalized_rel_path);
+        let next_full_path = location.root_path.join(&normalized_new_rel_path);
+
+        if !current_full_path.exists() {
+            return Err(AppError::not_found("Directory not found"));
+        }
+        if !current_full_path.is_dir() {
+            return Err(AppError::invalid_path("Path is not a directory"));
+        }
+        if next_full_path.exists() {
+            return Err(AppError::new(
+                ErrorCode::Conflict,
+                "A file or directory already exists at the destination",
+            ));
+        }
+
+        if let Some(parent) = next_full_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::io(format!("Failed to create destination directory: {}", e)))?;
+        }
+
+        std::fs::rename(&current_full_path, &next_full_path)
+            .map_err(|e| AppError::io(format!("Failed to move directory: {}", e)))?;
+
+        self.update_directory_paths_in_index(location_id, &normalized_rel_path, &normalized_new_rel_path)?;
+
+        log::info!(
+            "Moved directory: {:?} -> {:?}",
+            normalized_rel_path,
+            normalized_new_rel_path
+        );
+        Ok(normalized_new_rel_path)
+    }
+
+    pub fn dir_move_to_location(
+        &self, source_location_id: LocationId, rel_path: &Path, target_location_id: LocationId, new_rel_path: &Path,
+    ) -> Result<PathBuf, AppError> {
+        if source_location_id == target_location_id {
+            return self.dir_move(source_location_id, rel_path, new_rel_path);
+        }
+
+        let source_location = self
+            .location_get(source_location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", source_location_id)))?;
+        let target_location = self
+            .location_get(target_location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", target_location_id)))?;
+
+        let normalized_rel_path = normalize_relative_path(rel_path)?;
+        let normalized_new_rel_path = normalize_relative_path(new_rel_path)?;
+
+        let source_full_path = source_location.root_path.join(&normalized_rel_path);
+        let target_full_path = target_location.root_path.join(&normalized_new_rel_path);
+
+        if !source_full_path.exists() {
+            return Err(AppError::not_found("Directory not found"));
+        }
+        if !source_full_path.is_dir() {
+            return Err(AppError::invalid_path("Path is not a directory"));
+        }
+        if target_full_path.exists() {
+            return Err(AppError::new(
+                ErrorCode::Conflict,
+                "A file or directory already exists at the destination",
+            ));
+        }
+
+        if let Some(parent) = target_full_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::io(format!("Failed to create destination directory: {}", e)))?;
+        }
+
+        Self::move_directory_on_disk(&source_full_path, &target_full_path)?;
+        self.update_directory_paths_in_index_across_locations(
+            source_location_id,
+            target_location_id,
+            &normalized_rel_path,
+            &normalized_new_rel_path,
+        )?;
+
+        log::info!(
+            "Moved directory across locations: source_location={:?}, target_location={:?}, from={:?}, to={:?}",
+            source_location_id,
+            target_location_id,
+            normalized_rel_path,
+            normalized_new_rel_path
+        );
+        Ok(normalized_new_rel_path)
+    }
+
+    pub fn dir_delete(&self, location_id: LocationId, rel_path: &Path) -> Result<bool, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+        let normalized_rel_path = normalize_relative_path(rel_path)?;
+        let full_path = location.root_path.join(&normalized_rel_path);
 
         if !full_path.exists() {
-            self.remove_document_from_index(doc_id)?;
-            return Ok(());
+            return Ok(false);
+        }
+        if !full_path.is_dir() {
+            return Err(AppError::invalid_path("Path is not a directory"));
         }
 
-        let filename = full_path
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-        let meta = self.read_doc_metadata(&full_path, doc_id.location_id, doc_id.rel_path.clone(), &filename)?;
-        self.update_doc_in_catalog(doc_id, &meta)?;
+        std::fs::remove_dir_all(&full_path).map_err(|e| AppError::io(format!("Failed to delete directory: {}", e)))?;
+
+        self.remove_directory_from_index(location_id, &normalized_rel_path)?;
+
+        log::info!("Deleted directory: {:?}", normalized_rel_path);
+        Ok(true)
+    }
+
+    fn update_directory_paths_in_index(
+        &self, location_id: LocationId, old_rel_path: &Path, new_rel_path: &Path,
+    ) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let old_prefix = old_rel_path.to_string_lossy().to_string();
+        let new_prefix = new_rel_path.to_string_lossy().to_string();
+        let escaped_old_prefix = old_prefix.replace('\\', r"\\").replace('%', r"\%").replace('_', r"\_");
+        let old_like = format!("{}/%", escaped_old_prefix);
+        let updated_at = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE documents
+             SET rel_path = ?2 || substr(rel_path, length(?1) + 1), updated_at = ?5
+             WHERE location_id = ?3 AND (rel_path = ?1 OR rel_path LIKE ?4 ESCAPE '\\')",
+            params![old_prefix, new_prefix, location_id.0, old_like, updated_at],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to update directory document rows: {}", e),
+            )
+        })?;
+
+        conn.execute(
+            "UPDATE docs_fts
+             SET rel_path = ?2 || substr(rel_path, length(?1) + 1)
+             WHERE location_id = ?3 AND (rel_path = ?1 OR rel_path LIKE ?4 ESCAPE '\\')",
+            params![old_prefix, new_prefix, location_id.0, old_like],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update directory FTS rows: {}", e)))?;
+
+        conn.execute(
+            "UPDATE document_tags
+             SET rel_path = ?2 || substr(rel_path, length(?1) + 1)
+             WHERE location_id = ?3 AND (rel_path = ?1 OR rel_path LIKE ?4 ESCAPE '\\')",
+            params![old_prefix, new_prefix, location_id.0, old_like],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update directory tag rows: {}", e)))?;
+
+        conn.execute(
+            "UPDATE document_links
+             SET rel_path = ?2 || substr(rel_path, length(?1) + 1)
+             WHERE location_id = ?3 AND (rel_path = ?1 OR rel_path LIKE ?4 ESCAPE '\\')",
+            params![old_prefix, new_prefix, location_id.0, old_like],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update directory link rows: {}", e)))?;
+
+        conn.execute(
+            "UPDATE OR REPLACE document_goals
+             SET rel_path = ?2 || substr(rel_path, length(?1) + 1)
+             WHERE location_id = ?3 AND (rel_path = ?1 OR rel_path LIKE ?4 ESCAPE '\\')",
+            params![old_prefix, new_prefix, location_id.0, old_like],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update directory goal rows: {}", e)))?;
+
+        conn.execute(
+            "UPDATE OR REPLACE autosave
+             SET rel_path = ?2 || substr(rel_path, length(?1) + 1)
+             WHERE location_id = ?3 AND (rel_path = ?1 OR rel_path LIKE ?4 ESCAPE '\\')",
+            params![old_prefix, new_prefix, location_id.0, old_like],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to update directory autosave rows: {}", e),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    fn update_directory_paths_in_index_across_locations(
+        &self, source_location_id: LocationId, target_location_id: LocationId, old_rel_path: &Path, new_rel_path: &Path,
+    ) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let old_prefix = old_rel_path.to_string_lossy().to_string();
+        let new_prefix = new_rel_path.to_string_lossy().to_string();
+        let escaped_old_prefix = old_prefix.replace('\\', r"\\").replace('%', r"\%").replace('_', r"\_");
+        let old_like = format!("{}/%", escaped_old_prefix);
+        let updated_at = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE documents
+             SET location_id = ?2,
+                 rel_path = ?4 || substr(rel_path, length(?3) + 1),
+                 updated_at = ?5
+             WHERE location_id = ?1 AND (rel_path = ?3 OR rel_path LIKE ?6 ESCAPE '\\')",
+            params![
+                source_location_id.0,
+                target_location_id.0,
+                old_prefix,
+                new_prefix,
+                updated_at,
+                old_like
+            ],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to update cross-location directory document rows: {}", e),
+            )
+        })?;
+
+        conn.execute(
+            "UPDATE docs_fts
+             SET location_id = ?2,
+                 rel_path = ?4 || substr(rel_path, length(?3) + 1)
+             WHERE location_id = ?1 AND (rel_path = ?3 OR rel_path LIKE ?5 ESCAPE '\\')",
+            params![
+                source_location_id.0,
+                target_location_id.0,
+                old_prefix,
+                new_prefix,
+                old_like
+            ],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to update cross-location directory FTS rows: {}", e),
+            )
+        })?;
+
+        conn.execute(
+            "UPDATE document_tags
+             SET location_id = ?2,
+                 rel_path = ?4 || substr(rel_path, length(?3) + 1)
+             WHERE location_id = ?1 AND (rel_path = ?3 OR rel_path LIKE ?5 ESCAPE '\\')",
+            params![
+                source_location_id.0,
+                target_location_id.0,
+                old_prefix,
+                new_prefix,
+                old_like
+            ],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to update cross-location directory tag rows: {}", e),
+            )
+        })?;
+
+        conn.execute(
+            "UPDATE document_links
+             SET location_id = ?2,
+                 rel_path = ?4 || substr(rel_path, length(?3) + 1)
+             WHERE location_id = ?1 AND (rel_path = ?3 OR rel_path LIKE ?5 ESCAPE '\\')",
+            params![
+                source_location_id.0,
+                target_location_id.0,
+                old_prefix,
+                new_prefix,
+                old_like
+            ],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to update cross-location directory link rows: {}", e),
+            )
+        })?;
+
+        conn.execute(
+            "UPDATE OR REPLACE autosave
+             SET location_id = ?2,
+                 rel_path = ?4 || substr(rel_path, length(?3) + 1)
+             WHERE location_id = ?1 AND (rel_path = ?3 OR rel_path LIKE ?5 ESCAPE '\\')",
+            params![
+                source_location_id.0,
+                target_location_id.0,
+                old_prefix,
+                new_prefix,
+                old_like
+            ],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to update cross-location directory autosave rows: {}", e),
+            )
+        })?;
+
+        conn.execute(
+            "UPDATE OR REPLACE document_goals
+             SET location_id = ?2,
+                 rel_path = ?4 || substr(rel_path, length(?3) + 1)
+             WHERE location_id = ?1 AND (rel_path = ?3 OR rel_path LIKE ?5 ESCAPE '\\')",
+            params![
+                source_location_id.0,
+                target_location_id.0,
+                old_prefix,
+                new_prefix,
+                old_like
+            ],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to update cross-location directory goal rows: {}", e),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    fn move_directory_on_disk(source_path: &Path, destination_path: &Path) -> Result<(), AppError> {
+        match std::fs::rename(source_path, destination_path) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::CrossesDevices => {
+                Self::copy_directory_recursive(source_path, destination_path)?;
+                std::fs::remove_dir_all(source_path)
+                    .map_err(|e| AppError::io(format!("Failed to remove source directory after copy: {}", e)))?;
+                Ok(())
+            }
+            Err(error) => Err(AppError::io(format!("Failed to move directory: {}", error))),
+        }
+    }
+
+    fn copy_directory_recursive(source_path: &Path, destination_path: &Path) -> Result<(), AppError> {
+        std::fs::create_dir_all(destination_path)
+            .map_err(|e| AppError::io(format!("Failed to create directory while moving: {}", e)))?;
+
+        let entries = std::fs::read_dir(source_path)
+            .map_err(|e| AppError::io(format!("Failed to read source directory while moving: {}", e)))?;
+
+        for entry_result in entries {
+            let entry = entry_result.map_err(|e| AppError::io(format!("Failed to read directory entry: {}", e)))?;
+            let source_child = entry.path();
+            let destination_child = destination_path.join(entry.file_name());
+            let file_type = entry
+                .file_type()
+                .map_err(|e| AppError::io(format!("Failed to read directory entry type: {}", e)))?;
+
+            if file_type.is_dir() {
+                Self::copy_directory_recursive(&source_child, &destination_child)?;
+                continue;
+            }
+
+            if file_type.is_file() {
+                std::fs::copy(&source_child, &destination_child)
+                    .map_err(|e| AppError::io(format!("Failed to copy file while moving directory: {}", e)))?;
+                continue;
+            }
+
+            return Err(AppError::io(format!(
+                "Unsupported filesystem entry while moving directory: {:?}",
+                source_child
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn remove_directory_from_index(&self, location_id: LocationId, rel_path: &Path) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let prefix = rel_path.to_string_lossy().to_string();
+        let escaped_prefix = prefix.replace('\\', r"\\").replace('%', r"\%").replace('_', r"\_");
+        let prefix_like = format!("{}/%", escaped_prefix);
+
+        Self::forget_documents_locked(&conn, location_id, &escaped_prefix)?;
+        Self::forget_documents_locked(&conn, location_id, &prefix_like)?;
+        conn.execute(
+            "DELETE FROM documents
+             WHERE location_id = ?1 AND (rel_path = ?2 OR rel_path LIKE ?3 ESCAPE '\\')",
+            params![location_id.0, prefix, prefix_like],
+        )
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Index,
+                format!("Failed to delete directory document rows: {}", e),
+            )
+        })?;
+
+        conn.execute(
+            "DELETE FROM docs_fts
+             WHERE location_id = ?1 AND (rel_path = ?2 OR rel_path LIKE ?3 ESCAPE '\\')",
+            params![location_id.0, prefix, prefix_like],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to delete directory FTS rows: {}", e)))?;
+
+        conn.execute(
+            "DELETE FROM document_tags
+             WHERE location_id = ?1 AND (rel_path = ?2 OR rel_path LIKE ?3 ESCAPE '\\')",
+            params![location_id.0, prefix, prefix_like],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to delete directory tag rows: {}", e)))?;
+
+        conn.execute(
+            "DELETE FROM document_links
+             WHERE location_id = ?1 AND (rel_path = ?2 OR rel_path LIKE ?3 ESCAPE '\\')",
+            params![location_id.0, prefix, prefix_like],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to delete directory link rows: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Writes a document's catalog row and adds a word-count history point when its count changed
+    ///
+    /// A new row takes `carried_uid` (the uid of a renamed or moved document) or a fresh one.
+    fn update_doc_in_catalog_locked(
+        conn: &Connection, doc_id: &DocId, meta: &DocMeta, carried_uid: Option<&str>,
+    ) -> Result<(), AppError> {
+        let rel_path_str = doc_id.rel_path.to_string_lossy().to_string();
+        let mtime_str = meta.mtime.to_rfc3339();
+        let created_at_str = meta.created_at.map(|timestamp| timestamp.to_rfc3339());
+        let updated_at_str = Utc::now().to_rfc3339();
+        let encoding: i32 = meta.encoding.into();
+        let line_ending: i32 = meta.line_ending.into();
+        let existing_uid = conn
+            .query_row(
+                "SELECT doc_uid FROM documents WHERE location_id = ?1 AND rel_path = ?2",
+                params![doc_id.location_id.0, rel_path_str],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read document uid: {}", e)))?
+            .flatten();
+        // A uid is only generated for a row that does not have one yet
+        let doc_uid = existing_uid
+            .or_else(|| carried_uid.map(str::to_string))
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        // Latin-1 is never sniffed, so re-indexing as plain UTF-8 keeps an encoding chosen on open
+        conn.execute(
+            "INSERT INTO documents
+             (
+                location_id,
+                rel_path,
+                filename,
+                size_bytes,
+                mtime,
+                created_at,
+                content_hash,
+                encoding,
+                line_ending,
+                is_conflict,
+                title,
+                word_count,
+                updated_at,
+                doc_uid,
+                link_key
+             )
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+             ON CONFLICT(location_id, rel_path) DO UPDATE SET
+             filename = excluded.filename,
+             link_key = excluded.link_key,
+             size_bytes = excluded.size_bytes,
+             mtime = excluded.mtime,
+             created_at = COALESCE(documents.created_at, excluded.created_at),
+             content_hash = excluded.content_hash,
+             encoding = CASE
+                WHEN documents.encoding = ?16 AND excluded.encoding = ?17 THEN documents.encoding
+                ELSE excluded.encoding
+             END,
+             line_ending = excluded.line_ending,
+             is_conflict = excluded.is_conflict,
+             title = excluded.title,
+             word_count = excluded.word_count,
+             updated_at = excluded.updated_at,
+             doc_uid = COALESCE(documents.doc_uid, excluded.doc_uid)",
+            params![
+                doc_id.location_id.0,
+                rel_path_str,
+                meta.filename,
+                meta.size_bytes as i64,
+                mtime_str,
+                created_at_str,
+                meta.content_hash.clone(),
+                encoding,
+                line_ending,
+                meta.is_conflict as i32,
+                meta.title,
+                meta.word_count.map(|n| n as i64),
+                updated_at_str,
+                doc_uid,
+                file_utils::link_key(&meta.filename),
+                i32::from(Encoding::Latin1),
+                i32::from(Encoding::Utf8),
+            ],
+        )
+        .map_err(|e| AppError::io(format!("Failed to update document catalog: {}", e)))?;
+
+        match meta.word_count {
+            Some(word_count) => Self::record_word_count_locked(conn, doc_id, meta.mtime, word_count),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends a point to a document's word-count history unless the count equals the latest point
+    ///
+    /// History is keyed by the document's uid, so it follows the document through renames and moves.
+    fn record_word_count_locked(
+        conn: &Connection, doc_id: &DocId, recorded_at: DateTime<Utc>, word_count: usize,
+    ) -> Result<(), AppError> {
+        conn.execute(
+            "INSERT INTO word_count_history (doc_uid, recorded_at, word_count)
+             SELECT d.doc_uid, ?3, ?4 FROM documents d
+             WHERE d.location_id = ?1 AND d.rel_path = ?2 AND d.doc_uid IS NOT NULL
+             AND ?4 IS NOT (
+                SELECT h.word_count FROM word_count_history h
+                WHERE h.doc_uid = d.doc_uid
+                ORDER BY h.id DESC
+                LIMIT 1
+             )",
+            params![
+                doc_id.location_id.0,
+                doc_id.rel_path.to_string_lossy().to_string(),
+                recorded_at.to_rfc3339(),
+                word_count as i64
+            ],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to record word count: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Gives every catalog row written before uids existed a fresh one
+    fn assign_missing_doc_uids(conn: &Connection) -> Result<(), AppError> {
+        let keys = {
+            let mut stmt = conn
+                .prepare("SELECT location_id, rel_path FROM documents WHERE doc_uid IS NULL")
+                .map_err(|e| AppError::io(format!("Failed to read documents without uids: {}", e)))?;
+            stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+                .map_err(|e| AppError::io(format!("Failed to query documents without uids: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::io(format!("Invalid document row: {}", e)))?
+        };
+
+        for (location_id, rel_path) in keys {
+            conn.execute(
+                "UPDATE documents SET doc_uid = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+                params![location_id, rel_path, Uuid::new_v4().to_string()],
+            )
+            .map_err(|e| AppError::io(format!("Failed to assign document uid: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Fills in the wikilink lookup key for catalog rows written before it existed
+    fn assign_missing_link_keys(conn: &Connection) -> Result<(), AppError> {
+        let rows = {
+            let mut stmt = conn
+                .prepare("SELECT location_id, rel_path, filename FROM documents WHERE link_key IS NULL")
+                .map_err(|e| AppError::io(format!("Failed to read documents without link keys: {}", e)))?;
+            stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| AppError::io(format!("Failed to query documents without link keys: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::io(format!("Invalid document row: {}", e)))?
+        };
+
+        for (location_id, rel_path, filename) in rows {
+            conn.execute(
+                "UPDATE documents SET link_key = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+                params![location_id, rel_path, file_utils::link_key(&filename)],
+            )
+            .map_err(|e| AppError::io(format!("Failed to assign link key: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the catalog row for a renamed or moved document, keeping the uid it had at its old path
+    fn catalog_carry_uid(&self, doc_id: &DocId, meta: &DocMeta, uid: Option<String>) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        Self::update_doc_in_catalog_locked(&conn, doc_id, meta, uid.as_deref())?;
+        if let Some(uid) = uid {
+            conn.execute(
+                "UPDATE documents SET doc_uid = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+                params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string(), uid],
+            )
+            .map_err(|e| AppError::io(format!("Failed to carry document uid: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    fn index_document_text(
+        &self, scan: &ScanContext, doc_id: &DocId, meta: &DocMeta, text: &str,
+    ) -> Result<(), AppError> {
+        let text = Some(text).filter(|text| Self::should_index_text(scan, doc_id, text));
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        Self::index_document_text_locked(&conn, &scan.search, doc_id, meta, text)
+    }
+
+    /// Writes a document's catalog row and search index entry in a single transaction
+    ///
+    /// Pass `None` for files that are not indexed as text; their FTS row and aliases are cleared.
+    /// If any statement fails the transaction is rolled back, leaving the previous catalog and
+    /// FTS state intact.
+    fn write_document_index(
+        &self, scan: &ScanContext, doc_id: &DocId, meta: &DocMeta, text: Option<&str>,
+    ) -> Result<(), AppError> {
+        let text = text.filter(|text| Self::should_index_text(scan, doc_id, text));
+
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to begin index transaction: {}", e)))?;
+
+        Self::update_doc_in_catalog_locked(&tx, doc_id, meta, None)?;
+        Self::index_document_text_locked(&tx, &scan.search, doc_id, meta, text)?;
+
+        tx.commit()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to commit index transaction: {}", e)))
+    }
+
+    /// Returns true when text for a document should be added to the search index
+    fn should_index_text(scan: &ScanContext, doc_id: &DocId, text: &str) -> bool {
+        scan.filter.is_indexable(&doc_id.rel_path) && text.len() as u64 <= scan.layout.max_open_bytes
+    }
+
+    fn index_document_text_locked(
+        conn: &Connection, search_settings: &SearchSettings, doc_id: &DocId, meta: &DocMeta, text: Option<&str>,
+    ) -> Result<(), AppError> {
+        let Some(text) = text else {
+            Self::remove_fts_entry_locked(conn, doc_id)?;
+            Self::update_doc_links_locked(conn, doc_id, &[], &[])?;
+            Self::update_doc_draft_locked(conn, doc_id, false)?;
+            Self::update_doc_outline_locked(conn, doc_id, None)?;
+            Self::update_doc_preview_locked(conn, doc_id, None)?;
+            return Self::update_doc_aliases_locked(conn, doc_id, &[]);
+        };
+
+        let engine = MarkdownEngine::new();
+        let parsed = match search_settings.preview_chars {
+            0 => engine
+                .metadata(text, MarkdownProfile::Extended)
+                .map(|metadata| (metadata, None)),
+            _ => engine
+                .metadata_with_text(text, MarkdownProfile::Extended)
+                .map(|(metadata, plain_text)| (metadata, Some(plain_text))),
+        };
+        let (metadata, plain_text) =
+            parsed.map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to parse document: {}", e)))?;
+        Self::update_doc_aliases_locked(conn, doc_id, &metadata.front_matter.aliases())?;
+        Self::update_doc_draft_locked(conn, doc_id, metadata.front_matter.is_draft())?;
+        Self::update_doc_tags_locked(conn, doc_id, &engine.tags(text))?;
+        Self::update_doc_links_locked(conn, doc_id, &metadata.links, &engine.wikilink_targets(text))?;
+
+        Self::update_doc_outline_locked(conn, doc_id, Some(&metadata.outline))?;
+
+        let title = meta
+            .title
+            .clone()
+            .or_else(|| file_utils::fallback_title_from_path(&doc_id.rel_path))
+            .unwrap_or_else(|| "Untitled".to_string());
+        let preview = plain_text
+            .map(|plain_text| text_utils::prose_preview(&plain_text, search_settings.preview_chars))
+            .filter(|preview| !preview.is_empty());
+        Self::update_doc_preview_locked(conn, doc_id, preview.as_deref())?;
+
+        let max_bytes = search_settings.fts_max_content_bytes;
+        Self::upsert_fts_entry_locked(conn, doc_id, &title, cap_fts_content(text, max_bytes))
+    }
+
+    /// Replaces the tags recorded for a document
+    fn update_doc_tags_locked(conn: &Connection, doc_id: &DocId, tags: &[String]) -> Result<(), AppError> {
+        let rel_path = doc_id.rel_path.to_string_lossy().to_string();
+
+        conn.execute(
+            "DELETE FROM document_tags WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, rel_path],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear document tags: {}", e)))?;
+
+        for tag in tags {
+            conn.execute(
+                "INSERT OR IGNORE INTO document_tags (location_id, rel_path, tag) VALUES (?1, ?2, ?3)",
+                params![doc_id.location_id.0, rel_path, tag],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to insert document tag: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the outgoing links recorded for a document with `links` and `wikilinks`
+    ///
+    /// Local Markdown link URLs and wikilink targets are stored as written and resolved against the
+    /// catalog when read, so they follow the document through renames and see documents added later.
+    /// Remote URLs are not recorded.
+    fn update_doc_links_locked(
+        conn: &Connection, doc_id: &DocId, links: &[LinkRef], wikilinks: &[String],
+    ) -> Result<(), AppError> {
+        let rel_path = doc_id.rel_path.to_string_lossy().to_string();
+
+        conn.execute(
+            "DELETE FROM document_links WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, rel_path],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to clear document links: {}", e)))?;
+
+        let path_links = links.iter().filter(|link| link.is_local).map(|link| (&link.url, false));
+        let wikilinks = wikilinks.iter().map(|target| (target, true));
+
+        for (target, is_wikilink) in path_links.chain(wikilinks) {
+            conn.execute(
+                "INSERT OR IGNORE INTO document_links (location_id, rel_path, target, is_wikilink)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![doc_id.location_id.0, rel_path, target, is_wikilink],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to insert document link: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Caches a catalogued document's heading outline, clearing it when there is none to keep
+    ///
+    /// Outlines longer than [`MAX_CACHED_OUTLINE_HEADINGS`] are not stored.
+    fn update_doc_outline_locked(
+        conn: &Connection, doc_id: &DocId, outline: Option<&[Heading]>,
+    ) -> Result<(), AppError> {
+        let outline_json = match outline {
+            Some(outline) if outline.len() <= MAX_CACHED_OUTLINE_HEADINGS => Some(
+                serde_json::to_string(outline)
+                    .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to serialize outline: {}", e)))?,
+            ),
+            _ => None,
+        };
+
+        conn.execute(
+            "UPDATE documents SET outline_json = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+            params![
+                doc_id.location_id.0,
+                doc_id.rel_path.to_string_lossy().to_string(),
+                outline_json
+            ],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update document outline: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Stores the plain-text preview returned with a document's search hits
+    fn update_doc_preview_locked(conn: &Connection, doc_id: &DocId, preview: Option<&str>) -> Result<(), AppError> {
+        conn.execute(
+            "UPDATE documents SET preview_text = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+            params![
+                doc_id.location_id.0,
+                doc_id.rel_path.to_string_lossy().to_string(),
+                preview
+            ],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update document preview: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Records whether a catalogued document's front matter marks it as a draft
+    fn update_doc_draft_locked(conn: &Connection, doc_id: &DocId, is_draft: bool) -> Result<(), AppError> {
+        conn.execute(
+            "UPDATE documents SET is_draft = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+            params![
+                doc_id.location_id.0,
+                doc_id.rel_path.to_string_lossy().to_string(),
+                is_draft
+            ],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update document draft flag: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Stores the front matter aliases declared by a catalogued document
+    fn update_doc_aliases_locked(conn: &Connection, doc_id: &DocId, aliases: &[String]) -> Result<(), AppError> {
+        let aliases_json = if aliases.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::to_string(aliases)
+                    .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to encode aliases: {}", e)))?,
+            )
+        };
+
+        conn.execute(
+            "UPDATE documents SET aliases = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+            params![
+                doc_id.location_id.0,
+                doc_id.rel_path.to_string_lossy().to_string(),
+                aliases_json
+            ],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to update document aliases: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Resolves a link target to the document in a location that declares it as a front matter alias
+    ///
+    /// Matching ignores ASCII case. Returns `None` when no indexed document declares the alias.
+    pub fn resolve_alias(&self, location_id: LocationId, alias: &str) -> Result<Option<DocMeta>, AppError> {
+        let alias = alias.trim();
+        if alias.is_empty() {
+            return Ok(None);
+        }
+
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let rel_path = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            conn.query_row(
+                "SELECT d.rel_path
+                 FROM documents d
+                 WHERE d.location_id = ?1
+                   AND d.aliases IS NOT NULL
+                   AND EXISTS (SELECT 1 FROM json_each(d.aliases) WHERE lower(json_each.value) = lower(?2))
+                 ORDER BY d.rel_path
+                 LIMIT 1",
+                params![location_id.0, alias],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to resolve alias: {}", e)))?
+        };
+
+        let Some(rel_path) = rel_path.map(PathBuf::from) else {
+            return Ok(None);
+        };
+
+        let full_path = location.root_path.join(&rel_path);
+        if !full_path.is_file() {
+            return Ok(None);
+        }
+
+        let filename = rel_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        self.read_doc_metadata(
+            &full_path,
+            &self.scan_context(&location)?,
+            location_id,
+            rel_path,
+            &filename,
+        )
+        .map(Some)
+    }
+
+    /// Resolves a wikilink target to the relative path of a catalogued document
+    ///
+    /// Paths and filenames take precedence (shallowest match first); front matter aliases are
+    /// consulted when no file matches. Candidates are looked up by filename stem through the
+    /// catalog's `link_key` index rather than by scanning the location.
+    pub fn resolve_wikilink(&self, location_id: LocationId, target: &str) -> Result<Option<PathBuf>, AppError> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Ok(None);
+        }
+
+        let rel_paths = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let keys = file_utils::link_target_keys(target);
+            let mut stmt = conn
+                .prepare(
+                    "SELECT rel_path FROM documents
+                     WHERE location_id = ?1 AND link_key IN (?2, ?3)
+                     ORDER BY rel_path",
+                )
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read catalog rows: {}", e)))?;
+            stmt.query_map(params![location_id.0, keys[0], keys.last()], |row| {
+                row.get::<_, String>(0)
+            })
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog rows: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid rel_path row: {}", e)))?
+        };
+
+        if let Some(rel_path) = file_utils::best_link_match(rel_paths.into_iter().map(PathBuf::from), target) {
+            return Ok(Some(rel_path));
+        }
+
+        Ok(self.resolve_alias(location_id, target)?.map(|meta| meta.id.rel_path))
+    }
+
+    /// Computes a document's statistics from `text` when given (an unsaved buffer) or from the file on disk
+    pub fn doc_stats(&self, doc_id: &DocId, text: Option<&str>) -> Result<DocStats, AppError> {
+        let content;
+        let text = match text {
+            Some(text) => text,
+            None => {
+                content = self.doc_open(doc_id)?;
+                &content.text
+            }
+        };
+
+        MarkdownEngine::new()
+            .stats(text, MarkdownProfile::Extended)
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to parse document: {}", e)))
+    }
+
+    /// Returns a document's heading outline, from the catalog when it is current
+    ///
+    /// The outline cached at indexing time is used while the file's modification time still
+    /// matches the catalog; otherwise (not catalogued yet, changed on disk, or too many headings
+    /// to cache) the file is parsed. Returns `None` for documents that are not indexed as text,
+    /// such as files over the size limit.
+    pub fn doc_outline_cached(&self, doc_id: &DocId) -> Result<Option<Vec<Heading>>, AppError> {
+        let location = self
+            .location_get(doc_id.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
+
+        let full_path = doc_id.resolve(&location.root_path);
+        let metadata = std::fs::metadata(&full_path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => AppError::not_found(format!("Document not found: {:?}", doc_id.rel_path)),
+            _ => AppError::io(format!("Failed to read metadata: {}", e)),
+        })?;
+        if !LocationFilter::for_location(&location).is_indexable(&doc_id.rel_path)
+            || metadata.len() > self.layout_settings().max_open_bytes
+        {
+            return Ok(None);
+        }
+
+        let file_mtime: Option<DateTime<Utc>> = metadata.modified().ok().map(DateTime::<Utc>::from);
+        let cached = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+            conn.query_row(
+                "SELECT mtime, outline_json FROM documents WHERE location_id = ?1 AND rel_path = ?2",
+                params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read cached outline: {}", e)))?
+        };
+
+        if let Some((mtime, Some(outline_json))) = cached
+            && DateTime::parse_from_rfc3339(&mtime)
+                .ok()
+                .map(|mtime| mtime.with_timezone(&Utc))
+                == file_mtime
+            && let Ok(outline) = serde_json::from_str::<Vec<Heading>>(&outline_json)
+        {
+            return Ok(Some(outline));
+        }
+
+        let content = self.doc_open(doc_id)?;
+        let metadata = MarkdownEngine::new()
+            .metadata(&content.text, MarkdownProfile::Extended)
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to parse document: {}", e)))?;
+        Ok(Some(metadata.outline))
+    }
+
+    /// Word count over time for a document, oldest point first, for charting progress
+    ///
+    /// A point is recorded, at the file's modification time, whenever a save or index changes the
+    /// catalog's `word_count`, so words are counted the same way (front matter left out). The current
+    /// count is appended when it differs from the last recorded point, so the series always ends
+    /// at the listing's value; a document with no history yet gets that single point.
+    pub fn word_count_history(&self, doc_id: &DocId) -> Result<Vec<(DateTime<Utc>, usize)>, AppError> {
+        let content = self.doc_open(doc_id)?;
+        let (_, word_count) = Self::derive_text_metadata(&content.text, &doc_id.rel_path);
+
+        let mut history = match self.doc_uid(doc_id)? {
+            Some(uid) => {
+                let conn = self
+                    .conn
+                    .lock()
+                    .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+                let mut stmt = conn
+                    .prepare("SELECT recorded_at, word_count FROM word_count_history WHERE doc_uid = ?1 ORDER BY id")
+                    .map_err(|e| {
+                        AppError::new(ErrorCode::Index, format!("Failed to read word count history: {}", e))
+                    })?;
+                stmt.query_map(params![uid], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+                })
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query word count history: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid word count history row: {}", e)))?
+                .into_iter()
+                .filter_map(|(recorded_at, count)| {
+                    let recorded_at = DateTime::parse_from_rfc3339(&recorded_at).ok()?;
+                    Some((recorded_at.with_timezone(&Utc), count as usize))
+                })
+                .collect::<Vec<_>>()
+            }
+            None => Vec::new(),
+        };
+        history.sort_by_key(|(recorded_at, _)| *recorded_at);
+
+        if history.last().map(|(_, count)| *count) != Some(word_count) {
+            history.push((content.meta.mtime, word_count));
+        }
+        Ok(history)
+    }
+
+    /// Anchor of the last heading starting on or before `line`, as the renderer ids it
+    ///
+    /// Reads the outline cached in the catalog, parsing `content` only for documents without one.
+    fn heading_anchor_above(outline_json: Option<&str>, content: &str, line: usize) -> Option<String> {
+        let outline = match outline_json.and_then(|json| serde_json::from_str::<Vec<Heading>>(json).ok()) {
+            Some(outline) => outline,
+            None => {
+                MarkdownEngine::new()
+                    .metadata(content, MarkdownProfile::Extended)
+                    .ok()?
+                    .outline
+            }
+        };
+
+        outline
+            .into_iter()
+            .take_while(|heading| heading.line <= line)
+            .last()
+            .and_then(|heading| heading.anchor)
+    }
+
+    /// Lists the local images a document embeds that do not exist on disk
+    ///
+    /// Image paths resolve against the document's directory (root-relative paths against the
+    /// location root). Remote URLs and data URIs are skipped.
+    pub fn check_document_images(&self, doc_id: &DocId) -> Result<Vec<ImageRef>, AppError> {
+        let location = self
+            .location_get(doc_id.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
+
+        let content = self.doc_open(doc_id)?;
+        let metadata = MarkdownEngine::new()
+            .metadata(&content.text, MarkdownProfile::Extended)
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to parse document: {}", e)))?;
+
+        let full_path = doc_id.resolve(&location.root_path);
+        let doc_dir = full_path.parent().unwrap_or(&location.root_path);
+
+        Ok(metadata
+            .images
+            .into_iter()
+            .filter(|image| image.is_local)
+            .filter(|image| !file_utils::resolve_local_url(&image.url, doc_dir, &location.root_path).exists())
+            .collect())
+    }
+
+    /// Exports a document as standalone HTML to `output_path`, bundling local images per `options.asset_mode`
+    ///
+    /// Image URLs resolve relative to the document (root-relative ones against the location root).
+    /// Remote URLs and images that cannot be read are left as written.
+    pub fn doc_export_html(&self, doc_id: &DocId, options: &ExportOptions, output_path: &Path) -> Result<(), AppError> {
+        let location = self
+            .location_get(doc_id.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
+
+        let content = self.doc_open(doc_id)?;
+        let engine = MarkdownEngine::new();
+        let html = engine
+            .export_html(&content.text, MarkdownProfile::Extended, options)
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to export document: {}", e)))?;
+
+        let full_path = doc_id.resolve(&location.root_path);
+        let doc_dir = full_path.parent().unwrap_or(&location.root_path);
+        let resolve = |url: &str| file_utils::resolve_local_url(url, doc_dir, &location.root_path);
+
+        let html = match options.asset_mode {
+            AssetMode::None => html,
+            AssetMode::Inline => engine.rewrite_image_sources(&html, |url| {
+                let image_path = resolve(url);
+                match std::fs::read(&image_path) {
+                    Ok(bytes) => Some(format!(
+                        "data:{};base64,{}",
+                        file_utils::image_mime_type(&image_path),
+                        BASE64.encode(&bytes)
+                    )),
+                    Err(e) => {
+                        log::warn!("Failed to inline image {:?}: {}", image_path, e);
+                        None
+                    }
+                }
+            }),
+            AssetMode::CopyFolder => {
+                let assets_dir = output_path.parent().unwrap_or(Path::new(".")).join("assets");
+                let mut copied: HashMap<PathBuf, String> = HashMap::new();
+                let mut used_names: HashSet<String> = HashSet::new();
+
+                engine.rewrite_image_sources(&html, |url| {
+                    let image_path = resolve(url);
+                    if let Some(name) = copied.get(&image_path) {
+                        return Some(format!("assets/{}", name.replace(' ', "%20")));
+                    }
+
+                    // Names are compared case-insensitively so images never overwrite each other on
+                    // case-insensitive filesystems.
+                    let file_name = Path::new(image_path.file_name()?);
+                    let stem = file_name.file_stem().unwrap_or_default().to_string_lossy();
+                    let extension = file_name.extension().map(|extension| extension.to_string_lossy());
+                    let mut name = file_name.to_string_lossy().to_string();
+                    let mut suffix = 1;
+                    while used_names.contains(&name.to_lowercase()) {
+                        name = match &extension {
+                            Some(extension) => format!("{}-{}.{}", stem, suffix, extension),
+                            None => format!("{}-{}", stem, suffix),
+                        };
+                        suffix += 1;
+                    }
+
+                    let result = std::fs::create_dir_all(&assets_dir)
+                        .and_then(|_| std::fs::copy(&image_path, assets_dir.join(&name)));
+                    match result {
+                        Ok(_) => {
+                            used_names.insert(name.to_lowercase());
+                            copied.insert(image_path, name.clone());
+                            Some(format!("assets/{}", name.replace(' ', "%20")))
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to copy image {:?} into export assets: {}", image_path, e);
+                            None
+                        }
+                    }
+                })
+            }
+        };
+
+        std::fs::write(output_path, html)
+            .map_err(|e| AppError::io(format!("Failed to write HTML export {:?}: {}", output_path, e)))?;
+
+        Ok(())
+    }
+
+    /// Zips every file under a location root into `out_path`, returning the number of files archived
+    ///
+    /// Files keep their paths relative to the root. The location's ignore globs apply, and the trash
+    /// and `.meta.json` sidecars are always skipped. Each file is streamed into the archive, which also
+    /// gets a [`EXPORT_MANIFEST_NAME`] entry describing the files with their catalog metadata.
+    pub fn export_location_zip(&self, location_id: LocationId, out_path: &Path) -> Result<usize, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let mut ignore_globs = location.ignore_globs.clone();
+        ignore_globs.extend(EXPORT_EXCLUDED_GLOBS.iter().map(|glob| glob.to_string()));
+        let filter = LocationFilter::new(location.indexable_extensions.as_deref(), &ignore_globs);
+
+        let mut file_paths = Vec::new();
+        file_utils::collect_file_paths_recursive(
+            &location.root_path,
+            &location.root_path,
+            &filter,
+            None,
+            &mut SymlinkGuard::new(&location.root_path, false),
+            &mut file_paths,
+        )?;
+        file_paths.retain(|path| path.as_path() != out_path);
+        file_paths.sort();
+
+        if file_paths
+            .iter()
+            .any(|path| path == &location.root_path.join(EXPORT_MANIFEST_NAME))
+        {
+            return Err(AppError::new(
+                ErrorCode::Conflict,
+                format!("Location already has a {} at its root", EXPORT_MANIFEST_NAME),
+            ));
+        }
+
+        let mut catalog: HashMap<String, DocMeta> = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let mut stmt = conn
+                .prepare(
+                    "SELECT location_id, rel_path, filename, size_bytes, mtime, created_at, content_hash,
+                        encoding, line_ending, is_conflict, title, word_count
+                     FROM documents
+                     WHERE location_id = ?1",
+                )
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read catalog rows: {}", e)))?;
+            stmt.query_map(params![location_id.0], Self::catalog_row_to_doc_meta)
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog rows: {}", e)))?
+                .map(|row| row.map(|meta| (meta.id.rel_path.to_string_lossy().into_owned(), meta)))
+                .collect::<Result<_, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))?
+        };
+
+        let out_file =
+            File::create(out_path).map_err(|e| AppError::io(format!("Failed to create {:?}: {}", out_path, e)))?;
+        let mut zip = zip::ZipWriter::new(std::io::BufWriter::new(out_file));
+        let zip_error = |e: zip::result::ZipError| AppError::io(format!("Failed to write zip archive: {}", e));
+
+        let mut entries = Vec::with_capacity(file_paths.len());
+        for full_path in &file_paths {
+            let rel_path = full_path
+                .strip_prefix(&location.root_path)
+                .map_err(|_| AppError::invalid_path("File path escaped location root"))?;
+            let archive_path = rel_path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            let mut file =
+                File::open(full_path).map_err(|e| AppError::io(format!("Failed to open {:?}: {}", full_path, e)))?;
+            let metadata = file
+                .metadata()
+                .map_err(|e| AppError::io(format!("Failed to read metadata: {}", e)))?;
+            let disk_mtime = metadata.modified().ok().map(DateTime::<Utc>::from);
+
+            let mut options = zip::write::FileOptions::default();
+            if let Some(modified) = disk_mtime.and_then(zip_timestamp) {
+                options = options.last_modified_time(modified);
+            }
+            zip.start_file(archive_path.as_str(), options).map_err(zip_error)?;
+            std::io::copy(&mut file, &mut zip)
+                .map_err(|e| AppError::io(format!("Failed to archive {:?}: {}", full_path, e)))?;
+
+            let catalogued = catalog.remove(&rel_path.to_string_lossy().into_owned());
+            entries.push(ExportManifestEntry {
+                rel_path: archive_path,
+                size_bytes: metadata.len(),
+                mtime: catalogued.as_ref().map(|meta| meta.mtime).or(disk_mtime),
+                created_at: catalogued.as_ref().and_then(|meta| meta.created_at),
+                content_hash: catalogued.as_ref().and_then(|meta| meta.content_hash.clone()),
+                title: catalogued.as_ref().and_then(|meta| meta.title.clone()),
+                word_count: catalogued.and_then(|meta| meta.word_count),
+            });
+        }
+
+        let count = entries.len();
+        let manifest = ExportManifest { location_name: location.name, exported_at: Utc::now(), files: entries };
+        let manifest_json = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| AppError::io(format!("Failed to serialize export manifest: {}", e)))?;
+
+        zip.start_file(EXPORT_MANIFEST_NAME, zip::write::FileOptions::default())
+            .map_err(zip_error)?;
+        zip.write_all(&manifest_json)
+            .map_err(|e| AppError::io(format!("Failed to write export manifest: {}", e)))?;
+        zip.finish()
+            .map_err(zip_error)?
+            .flush()
+            .map_err(|e| AppError::io(format!("Failed to write zip archive: {}", e)))?;
+
+        log::info!(
+            "Exported {} files from location {:?} to {:?}",
+            count,
+            location_id,
+            out_path
+        );
+        Ok(count)
+    }
+
+    /// Renders a location's catalog as CSV for spreadsheets, one row per document sorted by path
+    ///
+    /// Columns are always `rel_path,title,word_count,size_bytes,mtime,is_conflict,tags`, preceded by a
+    /// header row. Fields are quoted per RFC 4180, rows end in CRLF, and tags are joined with `;`.
+    /// Reflects the last index, so reconcile first for up-to-date results.
+    pub fn export_catalog_csv(&self, location_id: LocationId) -> Result<String, AppError> {
+        self.location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT d.rel_path, COALESCE(d.title, ''), d.word_count, d.size_bytes, d.mtime, d.is_conflict,
+                    (SELECT GROUP_CONCAT(tag, ';') FROM (
+                        SELECT t.tag FROM document_tags t
+                        WHERE t.location_id = d.location_id AND t.rel_path = d.rel_path
+                        ORDER BY t.tag
+                    ))
+                 FROM documents d
+                 WHERE d.location_id = ?1
+                 ORDER BY d.rel_path",
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare catalog export: {}", e)))?;
+
+        let rows = stmt
+            .query_map(params![location_id.0], |row| {
+                Ok([
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<i64>>(2)?
+                        .map(|count| count.to_string())
+                        .unwrap_or_default(),
+                    row.get::<_, i64>(3)?.to_string(),
+                    row.get::<_, String>(4)?,
+                    (row.get::<_, i64>(5)? != 0).to_string(),
+                    row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+                ])
+            })
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog export: {}", e)))?;
+
+        let mut csv = String::from("rel_path,title,word_count,size_bytes,mtime,is_conflict,tags\r\n");
+        for row in rows {
+            let fields = row.map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))?;
+            let line: Vec<String> = fields.iter().map(|field| text_utils::csv_field(field)).collect();
+            csv.push_str(&line.join(","));
+            csv.push_str("\r\n");
+        }
+
+        Ok(csv)
+    }
+
+    /// Extracts a zip archive into a location and reconciles its index
+    ///
+    /// Entries with absolute paths or paths escaping the root are refused (and listed in the report),
+    /// directory entries are skipped, and a root-level [`EXPORT_MANIFEST_NAME`] is not extracted.
+    /// Existing files are handled per `options.on_collision`. An unreadable archive is a `Parse` error,
+    /// as is one with more than [`MAX_IMPORT_ENTRIES`] entries or more than [`MAX_IMPORT_BYTES`] of
+    /// content; the size is checked against the declared sizes up front and again while extracting.
+    pub fn import_location_zip(
+        &self, location_id: LocationId, zip_path: &Path, options: &ImportOptions,
+    ) -> Result<ImportReport, AppError> {
+        self.import_location_zip_limited(location_id, zip_path, options, MAX_IMPORT_ENTRIES, MAX_IMPORT_BYTES)
+    }
+
+    fn import_location_zip_limited(
+        &self, location_id: LocationId, zip_path: &Path, options: &ImportOptions, max_entries: usize, max_bytes: u64,
+    ) -> Result<ImportReport, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let zip_file =
+            File::open(zip_path).map_err(|e| AppError::io(format!("Failed to open {:?}: {}", zip_path, e)))?;
+        let mut archive = zip::ZipArchive::new(std::io::BufReader::new(zip_file))
+            .map_err(|e| AppError::new(ErrorCode::Parse, format!("Invalid zip archive {:?}: {}", zip_path, e)))?;
+
+        if archive.len() > max_entries {
+            return Err(AppError::new(
+                ErrorCode::Parse,
+                format!(
+                    "Zip archive has {} entries, more than the {} allowed",
+                    archive.len(),
+                    max_entries
+                ),
+            ));
+        }
+        let mut declared_bytes = 0u64;
+        for index in 0..archive.len() {
+            let entry = archive
+                .by_index_raw(index)
+                .map_err(|e| AppError::new(ErrorCode::Parse, format!("Invalid zip entry #{}: {}", index, e)))?;
+            declared_bytes = declared_bytes.saturating_add(entry.size());
+        }
+        let too_large = || {
+            AppError::new(
+                ErrorCode::Parse,
+                format!("Zip archive expands to more than the {} bytes allowed", max_bytes),
+            )
+        };
+        if declared_bytes > max_bytes {
+            return Err(too_large());
+        }
+
+        let mut remaining_bytes = max_bytes;
+        let mut report = ImportReport::default();
+        for index in 0..archive.len() {
+            let mut entry = archive
+                .by_index(index)
+                .map_err(|e| AppError::new(ErrorCode::Parse, format!("Invalid zip entry #{}: {}", index, e)))?;
+            if entry.is_dir() {
+                continue;
+            }
+
+            let name = entry.name().to_string();
+            let rel_path = match normalize_relative_path(Path::new(&name)) {
+                Ok(rel_path) => rel_path,
+                Err(e) => {
+                    log::warn!("Refusing zip entry {:?}: {}", name, e);
+                    report.rejected.push(name);
+                    continue;
+                }
+            };
+            if rel_path == Path::new(EXPORT_MANIFEST_NAME) {
+                continue;
+            }
+
+            let rel_path = match (location.root_path.join(&rel_path).exists(), options.on_collision) {
+                (false, _) | (true, ImportCollision::Overwrite) => rel_path,
+                (true, ImportCollision::Skip) => {
+                    report.skipped.push(rel_path);
+                    continue;
+                }
+                (true, ImportCollision::Rename) => Self::free_rel_path(&location.root_path, &rel_path),
+            };
+
+            let full_path = location.root_path.join(&rel_path);
+            let parent = full_path.parent().unwrap_or(&location.root_path);
+            std::fs::create_dir_all(parent).map_err(|e| AppError::io(format!("Failed to create directory: {}", e)))?;
+
+            let mut temp_file = tempfile::NamedTempFile::new_in(parent)
+                .map_err(|e| AppError::io(format!("Failed to create temp file: {}", e)))?;
+            // Declared sizes can lie, so extraction stops one byte past what is left of the budget
+            let written = std::io::copy(&mut (&mut entry).take(remaining_bytes + 1), &mut temp_file).map_err(|e| {
+                AppError::new(
+                    ErrorCode::Parse,
+                    format!("Failed to extract zip entry {:?}: {}", name, e),
+                )
+            })?;
+            if written > remaining_bytes {
+                return Err(too_large());
+            }
+            remaining_bytes -= written;
+            temp_file
+                .persist(&full_path)
+                .map_err(|e| AppError::io(format!("Failed to write {:?}: {}", full_path, e)))?;
+
+            report.imported.push(rel_path);
+        }
+
+        self.reconcile_location_index(location_id)?;
+
+        log::info!(
+            "Imported {} files into location {:?} from {:?} ({} skipped, {} rejected)",
+            report.imported.len(),
+            location_id,
+            zip_path,
+            report.skipped.len(),
+            report.rejected.len()
+        );
+        Ok(report)
+    }
+
+    /// First `stem-N.ext` next to `rel_path` that does not exist under `root`
+    fn free_rel_path(root: &Path, rel_path: &Path) -> PathBuf {
+        let stem = rel_path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = rel_path.extension().map(|extension| extension.to_string_lossy());
+
+        (1..)
+            .map(|suffix| {
+                let name = match &extension {
+                    Some(extension) => format!("{}-{}.{}", stem, suffix, extension),
+                    None => format!("{}-{}", stem, suffix),
+                };
+                rel_path.with_file_name(name)
+            })
+            .find(|candidate| !root.join(candidate).exists())
+            .expect("an unused suffix exists")
+    }
+
+    /// Renames a tag in every document of a location that uses it, returning the number of documents rewritten
+    ///
+    /// Front matter `tags` entries and inline `#tags` are rewritten (matching ignores case, and
+    /// `old_tag/child` tags move along with their parent) with atomic saves that reindex each
+    /// document. Truncated or non-UTF-8 documents are skipped rather than rewritten.
+    pub fn tag_rename(&self, location_id: LocationId, old_tag: &str, new_tag: &str) -> Result<usize, AppError> {
+        let engine = MarkdownEngine::new();
+        let (Some(old_key), Some(_)) = (engine.normalize_tag(old_tag), engine.normalize_tag(new_tag)) else {
+            return Err(AppError::new(
+                ErrorCode::Parse,
+                format!("Invalid tag name: {} -> {}", old_tag, new_tag),
+            ));
+        };
+        let old_key = old_key.to_lowercase();
+
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let rel_paths = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let mut stmt = conn
+                .prepare("SELECT rel_path, tag FROM document_tags WHERE location_id = ?1 ORDER BY rel_path")
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read document tags: {}", e)))?;
+            let rows = stmt
+                .query_map(params![location_id.0], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query document tags: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid document tag row: {}", e)))?;
+
+            let mut rel_paths: Vec<String> = rows
+                .into_iter()
+                .filter(|(_, tag)| {
+                    let tag = tag.to_lowercase();
+                    tag == old_key || tag.strip_prefix(&old_key).is_some_and(|rest| rest.starts_with('/'))
+                })
+                .map(|(rel_path, _)| rel_path)
+                .collect();
+            rel_paths.dedup();
+            rel_paths
+        };
+
+        let mut renamed = 0usize;
+        for rel_path in rel_paths {
+            let doc_id = DocId::new(location_id, PathBuf::from(&rel_path))?;
+            if !doc_id.resolve(&location.root_path).is_file() {
+                continue;
+            }
+
+            let content = self.doc_open(&doc_id)?;
+            if content.truncated || content.meta.encoding != Encoding::Utf8 {
+                log::warn!(
+                    "Skipping tag rename in {:?}: document is truncated or not UTF-8",
+                    rel_path
+                );
+                continue;
+            }
+
+            let updated = engine
+                .rename_tag(&content.text, old_tag, new_tag)
+                .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to rename tag: {}", e)))?;
+            if let Some(updated) = updated {
+                self.doc_save(&doc_id, &updated, None)?;
+                renamed += 1;
+            }
+        }
+
+        log::info!(
+            "Renamed tag in location {:?}: {} -> {} ({} documents)",
+            location_id,
+            old_tag,
+            new_tag,
+            renamed
+        );
+        Ok(renamed)
+    }
+
+    /// Empties old entries from every location's trash directory, deleting the files on disk, and
+    /// prunes word-count history past the policy's limits
+    ///
+    /// Only regular files inside `<root>/.trash/` are considered, so live documents are never touched;
+    /// symlinks are skipped rather than followed. A file's age is taken from its modification time,
+    /// which is set to the trash time when the store moves a document to the trash. Directories
+    /// emptied by the purge are removed, and any catalog rows for the purged files are dropped. With
+    /// `dry_run` nothing is deleted. A file or trash directory that cannot be read or deleted is
+    /// recorded in `failed` and the purge carries on.
+    pub fn maintenance_purge(&self, policy: PurgePolicy) -> Result<PurgeReport, AppError> {
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(policy.trash_older_than_days));
+        let mut report = PurgeReport { dry_run: policy.dry_run, ..PurgeReport::default() };
+        report.history_points_removed = self.purge_word_count_history(&policy)?;
+
+        for location in self.location_list()? {
+            let trash_dir = location.root_path.join(TRASH_DIR);
+            if !trash_dir.is_dir() {
+                continue;
+            }
+
+            let mut files = Vec::new();
+            let mut symlinks = file_utils::SymlinkGuard::new(&trash_dir, false);
+            if let Err(error) = file_utils::collect_file_paths_recursive(
+                &trash_dir,
+                &trash_dir,
+                &LocationFilter::default(),
+                None,
+                &mut symlinks,
+                &mut files,
+            ) {
+                log::warn!("Failed to list trash in {:?}: {}", trash_dir, error);
+                report.failed.push(PurgeFailure {
+                    location_id: location.id,
+                    rel_path: PathBuf::from(TRASH_DIR),
+                    error,
+                });
+                continue;
+            }
+            files.sort();
+
+            for path in files {
+                let rel_path = path.strip_prefix(&location.root_path).unwrap_or(&path).to_path_buf();
+                match self.purge_trashed_file(&location, &trash_dir, &path, cutoff, policy.dry_run) {
+                    Ok(Some((doc_id, size))) => {
+                        report.reclaimed_bytes += size;
+                        report.removed.push(doc_id);
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        log::warn!("Failed to purge trashed file {:?}: {}", path, error);
+                        report
+                            .failed
+                            .push(PurgeFailure { location_id: location.id, rel_path, error });
+                    }
+                }
+            }
+        }
+
+        log::info!(
+            "Purged trash{}: {} files, {} bytes, {} history points, {} failed",
+            if policy.dry_run { " (dry run)" } else { "" },
+            report.removed.len(),
+            report.reclaimed_bytes,
+            report.history_points_removed,
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    /// Drops word-count history points older than `history_older_than_days` or beyond the newest
+    /// `history_max_per_doc` of their document, returning how many were (or would be) dropped
+    fn purge_word_count_history(&self, policy: &PurgePolicy) -> Result<usize, AppError> {
+        if policy.history_older_than_days.is_none() && policy.history_max_per_doc.is_none() {
+            return Ok(0);
+        }
+        let cutoff = policy
+            .history_older_than_days
+            .map(|days| Utc::now() - chrono::Duration::days(i64::from(days)));
+
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let rows = {
+            let mut stmt = conn
+                .prepare("SELECT id, doc_uid, recorded_at FROM word_count_history ORDER BY doc_uid, id DESC")
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read word count history: {}", e)))?;
+            stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query word count history: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid word count history row: {}", e)))?
+        };
+
+        let mut stale = Vec::new();
+        let mut current_uid = None;
+        let mut newer_points = 0;
+        for (id, uid, recorded_at) in rows {
+            if current_uid.as_ref() != Some(&uid) {
+                current_uid = Some(uid);
+                newer_points = 0;
+            }
+            let too_old = cutoff.is_some_and(|cutoff| {
+                DateTime::parse_from_rfc3339(&recorded_at).is_ok_and(|recorded_at| recorded_at < cutoff)
+            });
+            let beyond_limit = policy.history_max_per_doc.is_some_and(|max| newer_points >= max);
+            if too_old || beyond_limit {
+                stale.push(id);
+            }
+            newer_points += 1;
+        }
+
+        if !policy.dry_run && !stale.is_empty() {
+            let tx = conn.transaction().map_err(|e| {
+                AppError::new(
+                    ErrorCode::Index,
+                    format!("Failed to begin history prune transaction: {}", e),
+                )
+            })?;
+            for id in &stale {
+                tx.execute("DELETE FROM word_count_history WHERE id = ?1", params![id])
+                    .map_err(|e| {
+                        AppError::new(ErrorCode::Index, format!("Failed to prune word count history: {}", e))
+                    })?;
+            }
+            tx.commit()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to commit history prune: {}", e)))?;
+        }
+
+        Ok(stale.len())
+    }
+
+    /// Deletes one trashed file if it is a regular file older than `cutoff`, returning its id and size
+    ///
+    /// `None` means the file was kept. In a dry run the file is only inspected.
+    fn purge_trashed_file(
+        &self, location: &LocationDescriptor, trash_dir: &Path, path: &Path, cutoff: DateTime<Utc>, dry_run: bool,
+    ) -> Result<Option<(DocId, u64)>, AppError> {
+        let metadata = std::fs::symlink_metadata(path)
+            .map_err(|e| AppError::io(format!("Failed to read trashed file {:?}: {}", path, e)))?;
+        let modified = metadata
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .map_err(|e| AppError::io(format!("Failed to read trashed file time {:?}: {}", path, e)))?;
+        if !metadata.is_file() || modified > cutoff {
+            return Ok(None);
+        }
+
+        let rel_path = path
+            .strip_prefix(&location.root_path)
+            .map_err(|_| AppError::invalid_path("Trashed file not within location root"))?
+            .to_path_buf();
+        let doc_id = DocId::new(location.id, rel_path)?;
+
+        if !dry_run {
+            std::fs::remove_file(path)
+                .map_err(|e| AppError::io(format!("Failed to delete trashed file {:?}: {}", path, e)))?;
+            if let Err(e) = self.remove_document_from_index(&doc_id) {
+                log::warn!("Purged {:?} but failed to drop its catalog row: {}", doc_id.rel_path, e);
+            }
+            Self::remove_empty_dirs(trash_dir, path.parent());
+        }
+
+        Ok(Some((doc_id, metadata.len())))
+    }
+
+    /// Removes `dir` and its parents while they are empty, stopping at (and keeping) `stop_at`
+    fn remove_empty_dirs(stop_at: &Path, dir: Option<&Path>) {
+        let mut current = dir;
+        while let Some(dir) = current {
+            if dir == stop_at || !dir.starts_with(stop_at) || std::fs::remove_dir(dir).is_err() {
+                break;
+            }
+            current = dir.parent();
+        }
+    }
+
+    /// Lists distinct tags with the number of documents using each, most used first
+    ///
+    /// Scoped to one location, or every location with `None`. Tags differing only in case are counted
+    /// together under their most common spelling; documents in the location's trash are left out.
+    pub fn tag_cloud(&self, location_id: Option<LocationId>) -> Result<Vec<(String, usize)>, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT tag, COUNT(*) FROM document_tags
+                 WHERE (?1 IS NULL OR location_id = ?1) AND rel_path NOT LIKE ?2
+                 GROUP BY tag",
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare tag query: {}", e)))?;
+        let rows = stmt
+            .query_map(params![location_id.map(|id| id.0), format!("{}/%", TRASH_DIR)], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+            })
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query tags: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid tag row: {}", e)))?;
+
+        // Per lowercased tag: total documents, then the spelling used by the most documents
+        let mut groups: HashMap<String, (usize, String, usize)> = HashMap::new();
+        for (tag, count) in rows {
+            let group = groups.entry(tag.to_lowercase()).or_insert((0, tag.clone(), 0));
+            group.0 += count;
+            if count > group.2 || (count == group.2 && tag < group.1) {
+                group.1 = tag;
+                group.2 = count;
+            }
+        }
+
+        let mut cloud: Vec<(String, usize)> = groups.into_values().map(|(total, tag, _)| (tag, total)).collect();
+        cloud.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase())));
+        Ok(cloud)
+    }
+
+    /// Adds and removes tags across many documents, saving each changed document atomically
+    ///
+    /// Tags go into the front matter `tags` list, or as inline `#tags` when `bulk_tags_inline` is set;
+    /// removal takes a tag out of both. Adding a tag a document already has is a no-op. Each save
+    /// reindexes the document, which refreshes `document_tags`. A document that cannot be read or
+    /// saved (including truncated and non-UTF-8 ones) is reported in `failed` and the rest of the
+    /// batch still runs; only an invalid tag name fails the whole call.
+    pub fn tag_apply_bulk(
+        &self, doc_ids: Vec<DocId>, add: Vec<String>, remove: Vec<String>,
+    ) -> Result<TagBulkReport, AppError> {
+        let engine = MarkdownEngine::new();
+        if let Some(tag) = add
+            .iter()
+            .chain(&remove)
+            .find(|tag| engine.normalize_tag(tag).is_none())
+        {
+            return Err(AppError::new(ErrorCode::Parse, format!("Invalid tag name: {}", tag)));
+        }
+        let inline = self.ui_layout_get()?.bulk_tags_inline;
+
+        let mut report = TagBulkReport::default();
+        for doc_id in doc_ids {
+            let edited = self.doc_open(&doc_id).and_then(|content| {
+                if content.truncated || content.meta.encoding != Encoding::Utf8 {
+                    return Err(AppError::new(ErrorCode::Conflict, "Document is truncated or not UTF-8"));
+                }
+
+                let updated = engine
+                    .edit_tags(&content.text, &add, &remove, inline)
+                    .map_err(|e| AppError::new(ErrorCode::Parse, format!("Failed to edit tags: {}", e)))?;
+                match updated {
+                    Some(updated) => self.doc_save(&doc_id, &updated, None).map(|_| true),
+                    None => Ok(false),
+                }
+            });
+
+            match edited {
+                Ok(true) => report.updated.push(doc_id),
+                Ok(false) => report.unchanged.push(doc_id),
+                Err(error) => {
+                    log::warn!("Failed to apply tags to {:?}: {}", doc_id.rel_path, error);
+                    report.failed.push(TagBulkFailure { doc_id, error });
+                }
+            }
+        }
+
+        log::info!(
+            "Applied bulk tags (+{:?} -{:?}): {} updated, {} unchanged, {} failed",
+            add,
+            remove,
+            report.updated.len(),
+            report.unchanged.len(),
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    /// Lists catalogued conflicted copies across all locations, most recently updated first
+    pub fn list_conflicts(&self) -> Result<Vec<DocMeta>, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT
+                    d.location_id,
+                    d.rel_path,
+                    d.filename,
+                    d.size_bytes,
+                    d.mtime,
+                    d.created_at,
+                    d.content_hash,
+                    d.encoding,
+                    d.line_ending,
+                    d.is_conflict,
+                    d.title,
+                    d.word_count
+                 FROM documents d
+                 INNER JOIN locations l ON l.id = d.location_id
+                 WHERE d.is_conflict = 1
+                 ORDER BY d.updated_at DESC, d.location_id ASC, d.rel_path ASC",
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare conflict query: {}", e)))?;
+
+        let rows = stmt
+            .query_map([], Self::catalog_row_to_doc_meta)
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query conflicts: {}", e)))?;
+
+        let mut conflicts = Vec::new();
+        for row in rows {
+            match row {
+                Ok(meta) => conflicts.push(meta),
+                Err(e) => log::warn!("Failed to parse conflict row: {}", e),
+            }
+        }
+
+        log::debug!("Listed {} conflicted documents", conflicts.len());
+        Ok(conflicts)
+    }
+
+    /// Builds document metadata from a catalog row selected in `documents` column order
+    fn catalog_row_to_doc_meta(row: &rusqlite::Row<'_>) -> rusqlite::Result<DocMeta> {
+        let parse_timestamp = |index: usize, value: String| {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+        };
+
+        let rel_path = PathBuf::from(row.get::<_, String>(1)?);
+        let mtime = parse_timestamp(4, row.get(4)?)?;
+        let created_at = row
+            .get::<_, Option<String>>(5)?
+            .map(|value| parse_timestamp(5, value))
+            .transpose()?;
+
+        Ok(DocMeta {
+            id: DocId { location_id: LocationId(row.get(0)?), rel_path },
+            filename: row.get(2)?,
+            size_bytes: row.get::<_, i64>(3)? as u64,
+            mtime,
+            created_at,
+            content_hash: row.get(6)?,
+            encoding: Encoding::from(row.get::<_, i32>(7)?),
+            line_ending: LineEnding::from(row.get::<_, i32>(8)?),
+            is_conflict: row.get::<_, i32>(9)? != 0,
+            title: row.get(10)?,
+            word_count: row.get::<_, Option<i64>>(11)?.map(|count| count as usize),
+        })
+    }
+
+    fn upsert_fts_entry_locked(conn: &Connection, doc_id: &DocId, title: &str, content: &str) -> Result<(), AppError> {
+        let rel_path = doc_id.rel_path.to_string_lossy().to_string();
+
+        conn.execute(
+            "DELETE FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, rel_path],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove existing FTS row: {}", e)))?;
+
+        conn.execute(
+            "INSERT INTO docs_fts (location_id, rel_path, title, content) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                doc_id.location_id.0,
+                doc_id.rel_path.to_string_lossy().to_string(),
+                title,
+                content
+            ],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to insert FTS row: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Removes a document's FTS row along with the tags derived from its text
+    fn remove_fts_entry_locked(conn: &Connection, doc_id: &DocId) -> Result<(), AppError> {
+        conn.execute(
+            "DELETE FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove FTS row: {}", e)))?;
+
+        Self::update_doc_tags_locked(conn, doc_id, &[])
+    }
+
+    pub fn remove_document_from_index(&self, doc_id: &DocId) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let rel_path = doc_id.rel_path.to_string_lossy().to_string();
+
+        conn.execute(
+            "DELETE FROM documents WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, rel_path.clone()],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove document row: {}", e)))?;
+
+        conn.execute(
+            "DELETE FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, rel_path.clone()],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove FTS row: {}", e)))?;
+
+        conn.execute(
+            "DELETE FROM document_tags WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, rel_path],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove document tags: {}", e)))?;
+
+        conn.execute(
+            "DELETE FROM document_links WHERE location_id = ?1 AND rel_path = ?2",
+            params![doc_id.location_id.0, rel_path],
+        )
+        .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove document links: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub fn reindex_document(&self, doc_id: &DocId) -> Result<(), AppError> {
+        let location = self
+            .location_get(doc_id.location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", doc_id.location_id)))?;
+        let full_path = doc_id.resolve(&location.root_path);
+
+        let scan = self.scan_context(&location)?;
+        if !full_path.exists() || scan.filter.is_ignored(&doc_id.rel_path, full_path.is_dir()) {
+            self.remove_document_from_index(doc_id)?;
+            return Ok(());
+        }
+
+        let filename = full_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let meta = self.read_doc_metadata(
+            &full_path,
+            &scan,
+            doc_id.location_id,
+            doc_id.rel_path.clone(),
+            &filename,
+        )?;
+
+        let text = if scan.filter.is_indexable(&full_path) && meta.size_bytes <= scan.layout.max_open_bytes {
+            Some(file_utils::read_file_text_with_detection(&full_path)?)
+        } else {
+            None
+        };
+
+        self.write_document_index(&scan, doc_id, &meta, text.as_deref())
+    }
+
+    /// Computes what [`Store::reconcile_location_index`] would change without touching the catalog
+    ///
+    /// Only file metadata (size and mtime) is compared, so no document contents are read.
+    pub fn reconcile_location_plan(&self, location_id: LocationId) -> Result<ReconcilePlan, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let plan = self.scan_location_changes(&location, false, None)?;
+        Ok(plan)
+    }
+
+    /// Checks a location's files, catalog, and search index against each other without changing anything
+    ///
+    /// Disk and catalog are compared as [`Store::reconcile_location_plan`] does (ignore globs apply,
+    /// symlinks are not followed). A catalogued document is expected in the search index when it has
+    /// an indexable extension, fits within `max_open_bytes`, and was read as text. Every list is
+    /// sorted by path. Fails with `NotFound` when the location root is unavailable, since every
+    /// catalog entry would otherwise look missing.
+    pub fn index_audit(&self, location_id: LocationId) -> Result<IndexAudit, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+        if !location.root_path.is_dir() {
+            return Err(AppError::not_found(format!(
+                "Location root is unavailable: {:?}",
+                location.root_path
+            )));
+        }
+
+        let plan = self.scan_location_changes(&location, false, None)?;
+        let max_open_bytes = self.layout_settings().max_open_bytes;
+
+        let (catalog, search_entries) = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let mut stmt = conn
+                .prepare("SELECT rel_path, size_bytes, word_count IS NOT NULL FROM documents WHERE location_id = ?1")
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read catalog rows: {}", e)))?;
+            let catalog = stmt
+                .query_map(params![location_id.0], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, bool>(2)?))
+                })
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog rows: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))?;
+
+            let mut stmt = conn
+                .prepare("SELECT rel_path FROM docs_fts WHERE CAST(location_id AS INTEGER) = ?1")
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read search entries: {}", e)))?;
+            let search_entries = stmt
+                .query_map(params![location_id.0], |row| row.get::<_, String>(0))
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query search entries: {}", e)))?
+                .collect::<Result<HashSet<_>, _>>()
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid search entry: {}", e)))?;
+
+            (catalog, search_entries)
+        };
+
+        let catalogued: HashSet<&str> = catalog.iter().map(|(rel_path, _, _)| rel_path.as_str()).collect();
+        let mut orphaned_search_entries: Vec<PathBuf> = search_entries
+            .iter()
+            .filter(|rel_path| !catalogued.contains(rel_path.as_str()))
+            .map(PathBuf::from)
+            .collect();
+        let filter = LocationFilter::for_location(&location);
+        let mut missing_from_search: Vec<PathBuf> = catalog
+            .iter()
+            .filter(|(rel_path, size_bytes, read_as_text)| {
+                *read_as_text
+                    && *size_bytes as u64 <= max_open_bytes
+                    && filter.is_indexable(Path::new(rel_path))
+                    && !search_entries.contains(rel_path)
+            })
+            .map(|(rel_path, _, _)| PathBuf::from(rel_path))
+            .collect();
+        orphaned_search_entries.sort();
+        missing_from_search.sort();
+
+        Ok(IndexAudit {
+            missing_from_disk: plan.removed,
+            missing_from_catalog: plan.added,
+            orphaned_search_entries,
+            missing_from_search,
+        })
+    }
+
+    /// Diffs the files under a location root against its catalog rows
+    ///
+    /// With `max_depth`, both sides are limited to that many directories below the root, so deeper
+    /// rows are left alone.
+    fn scan_location_changes(
+        &self, location: &LocationDescriptor, follow_symlinks: bool, max_depth: Option<usize>,
+    ) -> Result<ReconcilePlan, AppError> {
+        if !location.root_path.exists() {
+            return Ok(ReconcilePlan::default());
+        }
+
+        let mut file_paths = Vec::new();
+        file_utils::collect_file_paths_recursive(
+            &location.root_path,
+            &location.root_path,
+            &LocationFilter::for_location(location),
+            max_depth,
+            &mut SymlinkGuard::new(&location.root_path, follow_symlinks),
+            &mut file_paths,
+        )?;
+        file_paths.retain(|path| path.is_file());
+
+        let mut catalog = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+            let mut stmt = conn
+                .prepare("SELECT rel_path, size_bytes, mtime FROM documents WHERE location_id = ?1")
+                .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read catalog rows: {}", e)))?;
+            stmt.query_map(params![location.id.0], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    (row.get::<_, i64>(1)?, row.get::<_, String>(2)?),
+                ))
+            })
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog rows: {}", e)))?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid catalog row: {}", e)))?
+        };
+        catalog.retain(|rel_path, _| file_utils::within_depth(Path::new(rel_path), max_depth));
+
+        let mut plan = ReconcilePlan::default();
+        for full_path in &file_paths {
+            let rel_path = full_path
+                .strip_prefix(&location.root_path)
+                .map_err(|_| AppError::invalid_path("File path escaped location root"))?
+                .to_path_buf();
+
+            let Some((size_bytes, mtime)) = catalog.remove(rel_path.to_string_lossy().as_ref()) else {
+                plan.added.push(rel_path);
+                continue;
+            };
+
+            let metadata =
+                std::fs::metadata(full_path).map_err(|e| AppError::io(format!("Failed to read metadata: {}", e)))?;
+            let disk_mtime = metadata
+                .modified()
+                .map(|modified| DateTime::<Utc>::from(modified).to_rfc3339())
+                .map_err(|e| AppError::io(format!("Failed to get mtime: {}", e)))?;
+
+            if metadata.len() as i64 != size_bytes || disk_mtime != mtime {
+                plan.updated.push(rel_path);
+            } else {
+                plan.unchanged += 1;
+            }
+        }
+
+        plan.removed = catalog.into_keys().map(PathBuf::from).collect();
+        plan.added.sort();
+        plan.updated.sort();
+        plan.removed.sort();
+
+        Ok(plan)
+    }
+
+    /// Brings a location's catalog and search index in line with its files on disk
+    ///
+    /// Only files that are new or whose size or mtime differs from their catalog row are read and
+    /// re-indexed, as planned by [`Store::reconcile_location_plan`]. Rows for files that are gone
+    /// are removed. Returns the number of files added to the search index.
+    pub fn reconcile_location_index(&self, location_id: LocationId) -> Result<usize, AppError> {
+        self.reconcile_location_index_with_symlinks(location_id, false)
+    }
+
+    /// Reconciles a location's catalog, optionally following symlinks that stay inside its root
+    pub fn reconcile_location_index_with_symlinks(
+        &self, location_id: LocationId, follow_symlinks: bool,
+    ) -> Result<usize, AppError> {
+        self.reconcile_location_index_with_depth(location_id, follow_symlinks, None)
+    }
+
+    /// Reconciles only the files at most `max_depth` directories below the location root
+    ///
+    /// `Some(0)` covers the root's own files and `None` the whole tree. Catalog rows deeper than the
+    /// limit are neither refreshed nor removed.
+    pub fn reconcile_location_index_with_depth(
+        &self, location_id: LocationId, follow_symlinks: bool, max_depth: Option<usize>,
+    ) -> Result<usize, AppError> {
+        let indexed = self.reconcile_location_scan(
+            location_id,
+            follow_symlinks,
+            max_depth,
+            &AtomicBool::new(false),
+            |_, _| {},
+        )?;
+        Ok(indexed.unwrap_or(0))
+    }
+
+    /// Reconciles a location's catalog like [`Store::reconcile_location_index`], reporting progress as it goes
+    ///
+    /// `on_progress` receives `(processed, total)` once before the first file and again after each one,
+    /// where `total` is the number of added and changed files found up front. Setting `cancel` stops the pass
+    /// between files and returns `None`; files already processed stay refreshed and stale rows are
+    /// left for the next pass.
+    pub fn reconcile_location_index_with_progress(
+        &self, location_id: LocationId, cancel: &AtomicBool, on_progress: impl FnMut(usize, usize),
+    ) -> Result<Option<usize>, AppError> {
+        self.reconcile_location_scan(location_id, false, None, cancel, on_progress)
+    }
+
+    fn reconcile_location_scan(
+        &self, location_id: LocationId, follow_symlinks: bool, max_depth: Option<usize>, cancel: &AtomicBool,
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Result<Option<usize>, AppError> {
+        let location = self
+            .location_get(location_id)?
+            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+
+        let plan = self.scan_location_changes(&location, follow_symlinks, max_depth)?;
+        log::debug!(
+            "Reconciling location {:?}: added={}, updated={}, removed={}, unchanged={}",
+            location_id,
+            plan.added.len(),
+            plan.updated.len(),
+            plan.removed.len(),
+            plan.unchanged
+        );
+
+        let scan = self.scan_context(&location)?;
+        let mut indexed = 0usize;
+        let max_open_bytes = scan.layout.max_open_bytes;
+        let total = plan.added.len() + plan.updated.len();
+        on_progress(0, total);
+
+        for (processed, rel_path) in plan.added.into_iter().chain(plan.updated).enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                log::info!(
+                    "Reconcile of location {:?} cancelled after {} of {} files",
+                    location_id,
+                    processed,
+                    total
+                );
+                return Ok(None);
+            }
+
+            let full_path = location.root_path.join(&rel_path);
+            let doc_id = DocId::new(location_id, rel_path.clone())?;
+
+            let filename = rel_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("unknown")
+                .to_string();
+            let meta = self.read_doc_metadata(&full_path, &scan, location_id, rel_path, &filename)?;
+
+            let text = if meta.size_bytes > max_open_bytes {
+                log::warn!(
+                    "Skipping FTS index for {:?}: size_bytes={} exceeds max_open_bytes={}",
+                    full_path,
+                    meta.size_bytes,
+                    max_open_bytes
+                );
+                None
+            } else if scan.filter.is_indexable(&full_path) {
+                match file_utils::read_file_text_with_detection(&full_path) {
+                    Ok(text) => Some(text),
+                    Err(error) => {
+                        log::warn!("Skipping FTS index for {:?} after decode failure: {}", full_path, error);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            self.write_document_index(&scan, &doc_id, &meta, text.as_deref())?;
+            if text.is_some() {
+                indexed += 1;
+            }
+
+            on_progress(processed + 1, total);
+        }
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        for rel_path in plan.removed {
+            let rel_path = rel_path.to_string_lossy().to_string();
+            let escaped = rel_path.replace('\\', r"\\").replace('%', r"\%").replace('_', r"\_");
+            Self::forget_documents_locked(&conn, location_id, &escaped)?;
+            conn.execute(
+                "DELETE FROM documents WHERE location_id = ?1 AND rel_path = ?2",
+                params![location_id.0, rel_path.clone()],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove stale document row: {}", e)))?;
+            conn.execute(
+                "DELETE FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
+                params![location_id.0, rel_path.clone()],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove stale FTS row: {}", e)))?;
+            conn.execute(
+                "DELETE FROM document_tags WHERE location_id = ?1 AND rel_path = ?2",
+                params![location_id.0, rel_path],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove stale tag rows: {}", e)))?;
+            conn.execute(
+                "DELETE FROM document_links WHERE location_id = ?1 AND rel_path = ?2",
+                params![location_id.0, rel_path],
+            )
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove stale link rows: {}", e)))?;
+        }
+
+        Ok(Some(indexed))
+    }
+
+    pub fn reconcile_indexes(&self) -> Result<usize, AppError> {
+        let locations = self.location_list()?;
+        let mut indexed = 0usize;
+
+        for location in locations {
+            indexed += self.reconcile_location_index(location.id)?;
+        }
+
+        Ok(indexed)
+    }
+
+    pub fn search(
+        &self, query: &str, filters: Option<SearchFilters>, limit: usize,
+    ) -> Result<Vec<SearchHit>, AppError> {
+        self.search_with_options(query, filters, limit, &SearchOptions::default())
+    }
+
+    /// Searches every location (or those in `filters.locations`) and groups the hits by location
+    ///
+    /// Each group holds at most `per_location_limit` hits in the usual bm25 order, and groups follow
+    /// the sidebar order of [`Store::location_list`] or the order given in the filter. Locations
+    /// without hits are left out. No more than [`MAX_GROUPED_SEARCH_HITS`] hits are returned in total,
+    /// so later locations may be dropped once that budget is spent.
+    pub fn search_grouped(
+        &self, query: &str, filters: Option<SearchFilters>, per_location_limit: usize,
+    ) -> Result<Vec<(LocationId, Vec<SearchHit>)>, AppError> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let filters = filters.unwrap_or_default();
+        let location_ids = match filters.locations.clone().filter(|items| !items.is_empty()) {
+            Some(ids) => {
+                let mut seen = HashSet::new();
+                ids.into_iter().filter(|id| seen.insert(*id)).collect()
+            }
+            None => self
+                .location_list()?
+                .into_iter()
+                .map(|location| location.id)
+                .collect::<Vec<_>>(),
+        };
+
+        let options = SearchOptions::default();
+        let mut remaining = MAX_GROUPED_SEARCH_HITS;
+        let mut groups = Vec::new();
+        for location_id in location_ids {
+            if remaining == 0 {
+                break;
+            }
+
+            let location_filters = SearchFilters { locations: Some(vec![location_id]), ..filters.clone() };
+            let limit = per_location_limit.max(1).min(remaining);
+            let hits = self.search_with_options(query, Some(location_filters), limit, &options)?;
+            if hits.is_empty() {
+                continue;
+            }
+
+            remaining -= hits.len();
+            groups.push((location_id, hits));
+        }
+
+        Ok(groups)
+    }
+
+    /// Full-text search with a custom snippet length, ellipsis, and highlight markers
+    ///
+    /// With `fts_max_content_bytes` set, only the start of each long document is indexed, so text
+    /// past the cap never matches and snippets come from the indexed part alone.
+    /// `limit` is clamped to between 1 and [`Store::max_search_results`].
+    pub fn search_with_options(
+        &self, query: &str, filters: Option<SearchFilters>, limit: usize, options: &SearchOptions,
+    ) -> Result<Vec<SearchHit>, AppError> {
+        options.validate()?;
+
+        let normalized_query = query.trim();
+        if normalized_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let filters = filters.unwrap_or_default();
+        let SearchFilters { locations, file_types, date_range } = filters;
+        let mut sql = String::from(
+            "SELECT
+                d.location_id,
+                d.rel_path,
+                COALESCE(NULLIF(d.title, ''), d.filename, d.rel_path) AS title,
+                snippet(docs_fts, 3, ?, ?, ?, ?) AS snippet,
+                docs_fts.content AS content,
+                d.outline_json,
+                d.preview_text
+             FROM docs_fts
+             JOIN documents d
+               ON d.location_id = CAST(docs_fts.location_id AS INTEGER)
+              AND d.rel_path = docs_fts.rel_path
+             WHERE docs_fts MATCH ?",
+        );
+
+        let mut query_params: Vec<Value> = vec![
+            Value::from(options.highlight_open.clone()),
+            Value::from(options.highlight_close.clone()),
+            Value::from(options.ellipsis.clone()),
+            Value::from(options.snippet_tokens as i64),
+            Value::from(normalized_query.to_string()),
+        ];
+
+        if let Some(locations) = locations.filter(|items| !items.is_empty()) {
+            sql.push_str(" AND d.location_id IN (");
+            sql.push_str(&vec!["?"; locations.len()].join(", "));
+            sql.push(')');
+            query_params.extend(locations.into_iter().map(|id| Value::from(id.0)));
+        }
+
+        if let Some(file_types) = file_types {
+            let normalized_types = file_types
+                .into_iter()
+                .map(|extension| extension.trim().trim_start_matches('.').to_lowercase())
+                .filter(|extension| !extension.is_empty())
+                .collect::<Vec<_>>();
+
+            if !normalized_types.is_empty() {
+                let mut clauses = Vec::new();
+                for extension in normalized_types {
+                    clauses.push("LOWER(d.filename) LIKE ?".to_string());
+                    query_params.push(Value::from(format!("%.{}", extension)));
+                }
+
+                sql.push_str(" AND (");
+                sql.push_str(&clauses.join(" OR "));
+                sql.push(')');
+            }
+        }
+
+        if let Some(date_range) = date_range {
+            if let Some(from) = date_range.from.filter(|value| !value.is_empty()) {
+                sql.push_str(" AND d.updated_at >= ?");
+                query_params.push(Value::from(from));
+            }
+            if let Some(to) = date_range.to.filter(|value| !value.is_empty()) {
+                sql.push_str(" AND d.updated_at <= ?");
+                query_params.push(Value::from(to));
+            }
+        }
+
+        let bounded_limit = limit.clamp(1, self.max_search_results());
+        sql.push_str(" ORDER BY bm25(docs_fts), d.mtime DESC LIMIT ?");
+        query_params.push(Value::from(bounded_limit as i64));
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare search query: {}", e)))?;
+
+        let rows = stmt
+            .query_map(params_from_iter(query_params.iter()), |row| {
+                let location_id: i64 = row.get(0)?;
+                let rel_path: String = row.get(1)?;
+                let title: String = row.get(2)?;
+                let snippet_marked: String = row.get(3)?;
+                let full_content: String = row.get(4)?;
+                let outline_json: Option<String> = row.get(5)?;
+                let preview: Option<String> = row.get(6)?;
+                let (snippet, matches) = text_utils::extract_highlight_matches(
+                    &snippet_marked,
+                    &options.highlight_open,
+                    &options.highlight_close,
+                );
+                let (line, column) = text_utils::locate_query_position(&full_content, normalized_query);
+                let nearest_heading_anchor = Self::heading_anchor_above(outline_json.as_deref(), &full_content, line);
+
+                Ok(SearchHit {
+                    location_id: LocationId(location_id),
+                    rel_path,
+                    title,
+                    snippet,
+                    line,
+                    column,
+                    matches,
+                    nearest_heading_anchor,
+                    preview,
+                })
+            })
+            .map_err(|e| AppError::new(ErrorCode::Index, format!("Search query failed: {}", e)))?;
+
+        let mut hits = Vec::new();
+        for row in rows {
+            let hit = row.map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to parse search hit: {}", e)))?;
+            hits.push(hit);
+        }
+
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_store() -> (Store, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let store = Store::open(&db_path).unwrap();
+        (store, temp_dir)
+    }
+
+    #[test]
+    fn test_max_search_results_raises_the_limit_ceiling() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let options = StoreOptions { max_search_results: 500, ..StoreOptions::default() };
+        let store = Store::open_with_options(&db_path, options).unwrap();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Corpus".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        for index in 0..250 {
+            let doc_id = DocId::new(location.id, PathBuf::from(format!("note-{:03}.md", index))).unwrap();
+            store.doc_save(&doc_id, "A corpustoken note", None).unwrap();
+        }
+
+        assert_eq!(store.max_search_results(), 500);
+        assert_eq!(store.search("corpustoken", None, 1000).unwrap().len(), 250);
+        assert_eq!(store.search("corpustoken", None, 0).unwrap().len(), 1);
+
+        let default_store = Store::open(&db_path).unwrap();
+        assert_eq!(default_store.search("corpustoken", None, 1000).unwrap().len(), 200);
+
+        default_store
+            .search_settings_set(&SearchSettings { max_search_results: 220, ..SearchSettings::default() })
+            .unwrap();
+        assert_eq!(default_store.max_search_results(), 220);
+        assert_eq!(default_store.search("corpustoken", None, 1000).unwrap().len(), 220);
+    }
+
+    #[test]
+    fn test_open_with_options_enables_wal_for_concurrent_writers() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let options = StoreOptions { busy_timeout: std::time::Duration::from_millis(2500), ..StoreOptions::default() };
+        let watcher = Arc::new(Store::open_with_options(&db_path, options.clone()).unwrap());
+        let ui = Arc::new(Store::open_with_options(&db_path, options).unwrap());
+
+        {
+            let conn = ui.conn.lock().unwrap();
+            let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+            let busy_timeout: i64 = conn.query_row("PRAGMA busy_timeout", [], |row| row.get(0)).unwrap();
+            assert_eq!(journal_mode, "wal");
+            assert_eq!(busy_timeout, 2500);
+        }
+
+        let writers: Vec<_> = [watcher.clone(), ui.clone()]
+            .into_iter()
+            .enumerate()
+            .map(|(writer, store)| {
+                std::thread::spawn(move || {
+                    for round in 0..25 {
+                        let doc_ref = CaptureDocRef {
+                            location_id: writer as i64,
+                            rel_path: format!("writer-{}/round-{}.md", writer, round),
+                        };
+                        store.last_open_doc_set(Some(&doc_ref)).unwrap();
+                        store
+                            .readme_template_set(Some(&format!("# {} {}", writer, round)))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert!(watcher.last_open_doc_get().unwrap().is_some());
+        ui.wal_checkpoint().unwrap();
+        assert_eq!(std::fs::metadata(temp_dir.path().join("test.db-wal")).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_location_add_and_list() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        assert_eq!(location.name, "Test Location");
+        assert_eq!(location.root_path, location_path);
+
+        let locations = store.location_list().unwrap();
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].name, "Test Location");
+        assert_eq!(locations[0].root_path, location_path);
+    }
+
+    #[test]
+    fn test_location_duplicate() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        store.location_add("First".to_string(), location_path.clone()).unwrap();
+
+        let result = store.location_add("Second".to_string(), location_path);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().code, ErrorCode::Conflict);
+    }
+
+    #[test]
+    fn test_location_remove() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        let location = store.location_add("Test".to_string(), location_path).unwrap();
+        let removed = store.location_remove(location.id).unwrap();
+        assert!(removed);
+
+        let locations = store.location_list().unwrap();
+        assert!(locations.is_empty());
+
+        let removed_again = store.location_remove(location.id).unwrap();
+        assert!(!removed_again);
+    }
+
+    #[test]
+    fn test_location_get() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let location = store.location_add("Test".to_string(), location_path.clone()).unwrap();
+        let retrieved = store.location_get(location.id).unwrap();
+        assert!(retrieved.is_some());
+
+        let retrieved = retrieved.unwrap();
+        assert_eq!(retrieved.name, "Test");
+        assert_eq!(retrieved.root_path, location_path);
+
+        let not_found = store.location_get(LocationId(999)).unwrap();
+        assert!(not_found.is_none());
+    }
+
+    #[test]
+    fn test_validate_locations() {
+        let (store, _temp) = create_test_store();
+
+        let existing_dir = TempDir::new().unwrap();
+        let _ = store
+            .location_add("Existing".to_string(), existing_dir.path().to_path_buf())
+            .unwrap();
+
+        let non_existent_path = PathBuf::from("/non/existent/path/12345");
+        let non_existent = store
+            .location_add("NonExistent".to_string(), non_existent_path.clone())
+            .unwrap();
+
+        let missing = store.validate_locations().unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].0, non_existent.id);
+        assert_eq!(missing[0].1, non_existent_path);
+    }
+
+    #[test]
+    fn test_doc_list_shallow() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        std::fs::write(location_path.join("file1.md"), "# File 1").unwrap();
+        std::fs::write(location_path.join("file2.txt"), "File 2 content").unwrap();
+
+        let docs = store.doc_list(location.id, None).unwrap();
+        assert_eq!(docs.len(), 2);
+    }
+
+    #[test]
+    fn test_doc_list_recursive() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        std::fs::write(location_path.join("file1.md"), "# File 1").unwrap();
+        std::fs::create_dir(location_path.join("subdir")).unwrap();
+        std::fs::write(location_path.join("subdir/file2.md"), "# File 2").unwrap();
+
+        let options = DocListOptions { recursive: true, ..Default::default() };
+        let docs = store.doc_list(location.id, Some(options)).unwrap();
+        assert_eq!(docs.len(), 2);
+    }
+
+    #[test]
+    fn test_doc_list_max_depth() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        std::fs::create_dir_all(location_path.join("a/b/c")).unwrap();
+        std::fs::write(location_path.join("root.md"), "# Root").unwrap();
+        std::fs::write(location_path.join("a/one.md"), "# One").unwrap();
+        std::fs::write(location_path.join("a/b/two.md"), "# Two").unwrap();
+        std::fs::write(location_path.join("a/b/c/three.md"), "# Three").unwrap();
+
+        let list = |max_depth: Option<usize>| {
+            let options = DocListOptions { recursive: true, max_depth, ..DocListOptions::default() };
+            let mut paths: Vec<String> = store
+                .doc_list(location.id, Some(options))
+                .unwrap()
+                .into_iter()
+                .map(|doc| doc.id.rel_path.to_string_lossy().replace('\\', "/"))
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(list(Some(1)), vec!["a/one.md", "root.md"]);
+        assert_eq!(list(Some(0)), vec!["root.md"]);
+        assert_eq!(list(None).len(), 4);
+
+        store
+            .reconcile_location_index_with_depth(location.id, false, Some(1))
+            .unwrap();
+        let options = DocListOptions { recursive: true, ..DocListOptions::default() };
+        assert_eq!(store.doc_list_catalog(location.id, Some(options)).unwrap().len(), 2);
+
+        store.reconcile_location_index(location.id).unwrap();
+        let options = DocListOptions { recursive: true, max_depth: Some(2), ..DocListOptions::default() };
+        assert_eq!(store.doc_list_catalog(location.id, Some(options)).unwrap().len(), 3);
+
+        std::fs::remove_file(location_path.join("root.md")).unwrap();
+        store
+            .reconcile_location_index_with_depth(location.id, false, Some(0))
+            .unwrap();
+        let options = DocListOptions { recursive: true, ..DocListOptions::default() };
+        assert_eq!(store.doc_list_catalog(location.id, Some(options)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_find_stubs() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        std::fs::write(location_path.join("empty.md"), "").unwrap();
+        std::fs::write(location_path.join("short.md"), "Two words").unwrap();
+        std::fs::write(location_path.join("five.md"), "Exactly five words are here").unwrap();
+        std::fs::write(location_path.join("long.md"), "word ".repeat(200)).unwrap();
+        std::fs::write(location_path.join("image.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+
+        let stubs: Vec<PathBuf> = store
+            .find_stubs(location.id, 5)
+            .unwrap()
+            .into_iter()
+            .map(|doc| doc.id.rel_path)
+            .collect();
+        assert_eq!(stubs, vec![PathBuf::from("empty.md"), PathBuf::from("short.md")]);
+
+        assert_eq!(store.find_stubs(location.id, 6).unwrap().len(), 3);
+        assert!(store.find_stubs(location.id, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_orphans() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+        let other_dir = TempDir::new().unwrap();
+        let other = store
+            .location_add("Other Location".to_string(), other_dir.path().to_path_buf())
+            .unwrap();
+
+        std::fs::create_dir_all(location_path.join("notes")).unwrap();
+        std::fs::write(
+            location_path.join("notes/source.md"),
+            "See [the target](../target%20doc.md#intro).",
+        )
+        .unwrap();
+        std::fs::write(
+            location_path.join("target doc.md"),
+            "Links to [[wiki]] and [self](target%20doc.md).",
+        )
+        .unwrap();
+        std::fs::write(location_path.join("notes/wiki.md"), "# Wiki").unwrap();
+        std::fs::write(
+            location_path.join("lonely.md"),
+            "[Elsewhere](https://example.com/lonely.md)",
+        )
+        .unwrap();
+        std::fs::write(other_dir.path().join("linker.md"), "[[lonely]] and [[source]]").unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        store.reconcile_location_index(other.id).unwrap();
+
+        let orphans = |store: &Store| -> Vec<PathBuf> {
+            store
+                .find_orphans(location.id)
+                .unwrap()
+                .into_iter()
+                .map(|doc| doc.id.rel_path)
+                .collect()
+        };
+        assert_eq!(
+            orphans(&store),
+            vec![PathBuf::from("lonely.md"), PathBuf::from("notes/source.md")]
+        );
+
+        std::fs::write(location_path.join("notes/wiki.md"), "Back to [[lonely]].").unwrap();
+        std::fs::remove_file(location_path.join("target doc.md")).unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(
+            orphans(&store),
+            vec![PathBuf::from("notes/source.md"), PathBuf::from("notes/wiki.md")]
+        );
+    }
+
+    #[test]
+    fn test_doc_list_with_extension_filter() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        std::fs::write(location_path.join("file1.md"), "# File 1").unwrap();
+        std::fs::write(location_path.join("file2.txt"), "File 2").unwrap();
+        std::fs::write(location_path.join("file3.rs"), "fn main() {}").unwrap();
+
+        let options =
+            DocListOptions { recursive: false, extensions: Some(vec!["md".to_string()]), ..Default::default() };
+        let docs = store.doc_list(location.id, Some(options)).unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].filename, "file1.md");
+    }
+
+    #[test]
+    fn test_doc_open() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        let content = "# Test Document\n\nThis is a test.";
+        std::fs::write(location_path.join("test.md"), content).unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("test.md")).unwrap();
+        let doc_content = store.doc_open(&doc_id).unwrap();
+
+        assert_eq!(doc_content.text, content);
+        assert_eq!(doc_content.meta.filename, "test.md");
+        assert_eq!(doc_content.meta.word_count, Some(7));
+        assert_eq!(doc_content.meta.title, Some("Test Document".to_string()));
+    }
+
+    #[test]
+    fn test_doc_open_uses_markdown_front_matter_title_and_excludes_fm_from_word_count() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        let content = "---\ntitle: Front Matter Title\n---\n\nBody words only";
+        std::fs::write(location_path.join("frontmatter.md"), content).unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("frontmatter.md")).unwrap();
+        let doc_content = store.doc_open(&doc_id).unwrap();
+
+        assert_eq!(doc_content.meta.title, Some("Front Matter Title".to_string()));
+        assert_eq!(doc_content.meta.word_count, Some(3));
+    }
+
+    #[test]
+    fn test_doc_save_round_trips_utf8_bom() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Windows".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let catalog_encoding = |rel_path: &str| -> i32 {
+            store
+                .conn
+                .lock()
+                .unwrap()
+                .query_row(
+                    "SELECT encoding FROM documents WHERE rel_path = ?1",
+                    params![rel_path],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+
+        let bom_path = location_dir.path().join("bom.md");
+        std::fs::write(&bom_path, [&text_utils::UTF8_BOM[..], "# Café".as_bytes()].concat()).unwrap();
+        let bom_id = DocId::new(location.id, PathBuf::from("bom.md")).unwrap();
+
+        let opened = store.doc_open(&bom_id).unwrap();
+        assert_eq!(opened.text, "# Café");
+        assert_eq!(opened.meta.encoding, Encoding::Utf8WithBom);
+
+        let saved = store.doc_save(&bom_id, "# Café\n\nEdited", None).unwrap();
+        assert_eq!(saved.new_meta.unwrap().encoding, Encoding::Utf8WithBom);
+        let bytes = std::fs::read(&bom_path).unwrap();
+        assert_eq!(
+            bytes,
+            [&text_utils::UTF8_BOM[..], "# Café\n\nEdited".as_bytes()].concat()
+        );
+        assert_eq!(catalog_encoding("bom.md"), i32::from(Encoding::Utf8WithBom));
+
+        let reopened = store.doc_open(&bom_id).unwrap();
+        assert_eq!(reopened.text, "# Café\n\nEdited");
+        store
+            .doc_save(&bom_id, &format!("\u{feff}{}", reopened.text), None)
+            .unwrap();
+        assert_eq!(std::fs::read(&bom_path).unwrap(), bytes);
+
+        let plain_id = DocId::new(location.id, PathBuf::from("plain.md")).unwrap();
+        store.doc_save(&plain_id, "Plain", None).unwrap();
+        assert_eq!(std::fs::read(location_dir.path().join("plain.md")).unwrap(), b"Plain");
+        assert_eq!(catalog_encoding("plain.md"), i32::from(Encoding::Utf8));
+
+        store
+            .ui_layout_set(&UiLayoutSettings { write_utf8_bom: true, ..settings })
+            .unwrap();
+        store.doc_save(&plain_id, "Still plain", None).unwrap();
+        assert_eq!(
+            std::fs::read(location_dir.path().join("plain.md")).unwrap(),
+            b"Still plain"
+        );
+
+        let new_id = DocId::new(location.id, PathBuf::from("new.md")).unwrap();
+        store.doc_save(&new_id, "New", None).unwrap();
+        assert!(
+            std::fs::read(location_dir.path().join("new.md"))
+                .unwrap()
+                .starts_with(&text_utils::UTF8_BOM)
+        );
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(catalog_encoding("new.md"), i32::from(Encoding::Utf8WithBom));
+        assert_eq!(catalog_encoding("plain.md"), i32::from(Encoding::Utf8));
+    }
+
+    #[test]
+    fn test_doc_save_atomic() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("new_file.md")).unwrap();
+        let content = "# New Document\n\nContent here.";
+        let result = store.doc_save(&doc_id, content, None).unwrap();
+        assert!(result.success);
+        assert!(result.new_meta.is_some());
+        assert!(!result.conflict_detected);
+
+        let saved_path = location_path.join("new_file.md");
+        assert!(saved_path.exists());
+        let saved_content = std::fs::read_to_string(saved_path).unwrap();
+        assert_eq!(saved_content, content);
+    }
+
+    #[test]
+    fn test_doc_save_full_durability_syncs_and_writes() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        for (rel_path, policy) in [
+            ("durable/atomic.md", SavePolicy::Atomic),
+            ("durable/in-place.md", SavePolicy::InPlace),
+        ] {
+            let doc_id = DocId::new(location.id, PathBuf::from(rel_path)).unwrap();
+            let result = store
+                .doc_save_with_durability(&doc_id, "Durable content", Some(policy), Durability::Full)
+                .unwrap();
+
+            assert!(result.success);
+            assert_eq!(
+                std::fs::read_to_string(location_path.join(rel_path)).unwrap(),
+                "Durable content"
+            );
+        }
+
+        file_utils::sync_dir(&location_path).unwrap();
+    }
+
+    #[test]
+    fn test_doc_save_trims_trailing_whitespace_outside_code() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("trim.md")).unwrap();
+        let text = "# Title  \n\nProse\t\n\n```\nkeep  \n```\n\n";
+
+        let result = store.doc_save(&doc_id, text, None).unwrap();
+        assert!(!result.whitespace_trimmed);
+        assert_eq!(std::fs::read_to_string(location_path.join("trim.md")).unwrap(), text);
+
+        let settings = UiLayoutSettings { trim_trailing_whitespace_on_save: true, ..store.ui_layout_get().unwrap() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let result = store.doc_save(&doc_id, text, None).unwrap();
+        assert!(result.whitespace_trimmed);
+        assert_eq!(
+            std::fs::read_to_string(location_path.join("trim.md")).unwrap(),
+            "# Title\n\nProse\n\n```\nkeep  \n```\n"
+        );
+
+        store
+            .ui_layout_set(&UiLayoutSettings { preserve_code_whitespace: false, ..settings })
+            .unwrap();
+        store.doc_save(&doc_id, text, None).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(location_path.join("trim.md")).unwrap(),
+            "# Title\n\nProse\n\n```\nkeep\n```\n"
+        );
+
+        let result = store.doc_save(&doc_id, "Clean\n", None).unwrap();
+        assert!(!result.whitespace_trimmed);
+    }
+
+    #[test]
+    fn test_doc_save_stamps_updated_front_matter() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("post.md")).unwrap();
+        let text = "---\ntitle: Post\n---\n\nBody\n";
+
+        let result = store.doc_save(&doc_id, text, None).unwrap();
+        assert!(!result.updated_stamped);
+
+        let settings = UiLayoutSettings { stamp_updated_on_save: true, ..store.ui_layout_get().unwrap() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let result = store.doc_save(&doc_id, text, None).unwrap();
+        assert!(result.updated_stamped);
+        let first = std::fs::read_to_string(location_path.join("post.md")).unwrap();
+        let first_updated = MarkdownEngine::new().front_matter(&first).fields["updated"].clone();
+        assert!(DateTime::parse_from_rfc3339(&first_updated).is_ok());
+        assert!(first.starts_with("---\ntitle: Post\nupdated: "));
+        assert!(first.ends_with("---\n\nBody\n"));
+        assert_eq!(
+            result.new_meta.unwrap().content_hash,
+            Some(text_utils::hash_text(&first))
+        );
+
+        let later = DateTime::parse_from_rfc3339(&first_updated)
+            .unwrap()
+            .with_timezone(&Utc)
+            + chrono::Duration::hours(1);
+        let second = Store::stamp_for_save(&settings, &doc_id, &first, later).unwrap();
+        let second_updated = MarkdownEngine::new().front_matter(&second).fields["updated"].clone();
+        assert_eq!(second_updated, later.to_rfc3339_opts(SecondsFormat::Secs, true));
+        assert_eq!(second.matches("updated:").count(), 1);
+
+        let plain = DocId::new(location.id, PathBuf::from("plain.md")).unwrap();
+        assert!(
+            !store
+                .doc_save(&plain, "No front matter\n", None)
+                .unwrap()
+                .updated_stamped
+        );
+        let notes = DocId::new(location.id, PathBuf::from("notes.txt")).unwrap();
+        assert!(!store.doc_save(&notes, text, None).unwrap().updated_stamped);
+    }
+
+    #[test]
+    fn test_doc_save_applies_default_extension() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("notes/todo")).unwrap();
+        let result = store.doc_save(&doc_id, "- [ ] write", None).unwrap();
+
+        assert_eq!(result.new_meta.unwrap().id.rel_path, PathBuf::from("notes/todo.md"));
+        assert_eq!(
+            std::fs::read_to_string(location_path.join("notes/todo.md")).unwrap(),
+            "- [ ] write"
+        );
+        assert!(!location_path.join("notes/todo").exists());
+
+        let doc_id = DocId::new(location.id, PathBuf::from("notes/plan.txt")).unwrap();
+        let result = store.doc_save(&doc_id, "Plan", None).unwrap();
+        assert_eq!(result.new_meta.unwrap().id.rel_path, PathBuf::from("notes/plan.txt"));
+        assert!(!location_path.join("notes/plan.txt.md").exists());
+    }
+
+    #[test]
+    fn test_doc_open_or_create() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        let existing = DocId::new(location.id, PathBuf::from("existing.md")).unwrap();
+        store.doc_save(&existing, "# Existing\n\nKept as is.", None).unwrap();
+        let opened = store
+            .doc_open_or_create(location.id, Path::new("existing.md"), Some("# Template"))
+            .unwrap();
+        assert_eq!(opened.text, "# Existing\n\nKept as is.");
+        assert_eq!(
+            std::fs::read_to_string(location_path.join("existing.md")).unwrap(),
+            "# Existing\n\nKept as is."
+        );
+
+        let created = store
+            .doc_open_or_create(location.id, Path::new("journal/today"), Some("# Today\n\nquokka"))
+            .unwrap();
+        assert_eq!(created.meta.id.rel_path, PathBuf::from("journal/today.md"));
+        assert_eq!(created.text, "# Today\n\nquokka");
+        assert_eq!(
+            std::fs::read_to_string(location_path.join("journal/today.md")).unwrap(),
+            "# Today\n\nquokka"
+        );
+        assert!(!location_path.join("journal/today").exists());
+
+        let hits = store.search("quokka", None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rel_path, "journal/today.md");
+
+        let empty = store
+            .doc_open_or_create(location.id, Path::new("blank.md"), None)
+            .unwrap();
+        assert_eq!(empty.text, "");
+        assert!(location_path.join("blank.md").exists());
+    }
+
+    #[test]
+    fn test_doc_pending_diff() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Test Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+
+        let hunks = store.doc_pending_diff(&doc_id, "# Draft\nFirst line\n").unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].old_count, hunks[0].new_count), (0, 2));
+
+        std::fs::write(
+            location_dir.path().join("draft.md"),
+            "# Draft\r\nKeep\r\nDrop me\r\nEnd\r\n",
+        )
+        .unwrap();
+        assert!(
+            store
+                .doc_pending_diff(&doc_id, "# Draft\nKeep\nDrop me\nEnd\n")
+                .unwrap()
+                .is_empty()
+        );
+
+        let hunks = store
+            .doc_pending_diff(&doc_id, "# Draft\nKeep\nEnd\nAdded one\nAdded two\n")
+            .unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].old_start, 3);
+        assert_eq!(hunks[0].removed, vec!["Drop me"]);
+        assert!(hunks[0].added.is_empty());
+        assert_eq!((hunks[1].old_start, hunks[1].new_start), (5, 4));
+        assert_eq!(hunks[1].added, vec!["Added one", "Added two"]);
+    }
+
+    #[test]
+    fn test_autosave_put_take_and_rename() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Test Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+        store.doc_save(&doc_id, "Saved", None).unwrap();
+
+        assert!(store.autosave_take(&doc_id).unwrap().is_none());
+        assert!(store.autosave_put(&doc_id, "First edit").unwrap());
+        assert!(store.autosave_put(&doc_id, "Second edit").unwrap());
+
+        let pending = store.autosave_pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].doc_id, doc_id);
+        assert_eq!(pending[0].size_bytes, "Second edit".len());
+
+        let renamed = store.doc_rename(&doc_id, "renamed.md").unwrap();
+        assert!(store.autosave_take(&doc_id).unwrap().is_none());
+        let recovery = store.autosave_take(&renamed.id).unwrap().unwrap();
+        assert_eq!(recovery.text, "Second edit");
+        assert!(store.autosave_take(&renamed.id).unwrap().is_none());
+        assert!(store.autosave_pending().unwrap().is_empty());
+
+        let settings = UiLayoutSettings { max_open_bytes: 4, ..store.ui_layout_get().unwrap() };
+        store.ui_layout_set(&settings).unwrap();
+        assert!(!store.autosave_put(&renamed.id, "Too long").unwrap());
+        assert!(store.autosave_pending().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_doc_uid_survives_rename_and_reconcile() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Test Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+        store.doc_save(&doc_id, "# Draft", None).unwrap();
+
+        let uid = store.doc_uid(&doc_id).unwrap().unwrap();
+        let renamed = store.doc_rename(&doc_id, "final.md").unwrap();
+        assert!(store.doc_uid(&doc_id).unwrap().is_none());
+        assert_eq!(store.doc_uid(&renamed.id).unwrap().as_deref(), Some(uid.as_str()));
+
+        std::fs::write(location_dir.path().join("final.md"), "# Final").unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(store.doc_uid(&renamed.id).unwrap().as_deref(), Some(uid.as_str()));
+
+        let found = store.doc_by_uid(&uid).unwrap().unwrap();
+        assert_eq!(found.id, renamed.id);
+        assert!(store.doc_by_uid("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_created_at_survives_rename_and_move() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Test Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+        store.doc_save(&doc_id, "# Draft", None).unwrap();
+
+        let original = DateTime::parse_from_rfc3339("2001-02-03T04:05:06Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE documents SET created_at = ?3 WHERE location_id = ?1 AND rel_path = ?2",
+                params![location.id.0, "draft.md", original.to_rfc3339()],
+            )
+            .unwrap();
+
+        let renamed = store.doc_rename(&doc_id, "final.md").unwrap();
+        assert_eq!(renamed.created_at, Some(original));
+        assert_eq!(store.catalog_created_at(&renamed.id).unwrap(), Some(original));
+
+        let moved = store.doc_move(&renamed.id, Path::new("archive/2001/final.md")).unwrap();
+        assert_eq!(moved.created_at, Some(original));
+        assert_eq!(store.catalog_created_at(&moved.id).unwrap(), Some(original));
+        assert!(store.catalog_created_at(&doc_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_link_title_cache_reuses_fresh_titles() {
+        let (store, _temp) = create_test_store();
+
+        let url = "https://example.com/essay";
+        assert_eq!(store.link_title_cached(url).unwrap(), None);
+        store.link_title_remember(url, "Essay").unwrap();
+        assert_eq!(store.link_title_cached(url).unwrap().as_deref(), Some("Essay"));
+        assert_eq!(
+            store
+                .link_title_cached(" https://example.com/essay ")
+                .unwrap()
+                .as_deref(),
+            Some("Essay")
+        );
+
+        let stale = (Utc::now() - chrono::Duration::hours(LINK_TITLE_CACHE_TTL_HOURS + 1)).to_rfc3339();
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE link_titles SET fetched_at = ?2 WHERE url = ?1",
+                params![url, stale],
+            )
+            .unwrap();
+        assert_eq!(store.link_title_cached(url).unwrap(), None);
+        store.link_title_remember(url, "Essay, revised").unwrap();
+        assert_eq!(store.link_title_cached(url).unwrap().as_deref(), Some("Essay, revised"));
+
+        assert_eq!(
+            store.link_title_cached("file:///etc/hosts").unwrap_err().code,
+            ErrorCode::InvalidUrl
+        );
+        assert_eq!(
+            store
+                .link_title_remember("file:///etc/hosts", "Hosts")
+                .unwrap_err()
+                .code,
+            ErrorCode::InvalidUrl
+        );
+    }
+
+    #[test]
+    fn test_doc_save_clears_autosave() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Test Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("note.md")).unwrap();
+
+        store.autosave_put(&doc_id, "Unsaved").unwrap();
+        assert_eq!(store.autosave_pending().unwrap().len(), 1);
+
+        store.doc_save(&doc_id, "Saved", None).unwrap();
+        assert!(store.autosave_pending().unwrap().is_empty());
+        assert!(store.autosave_take(&doc_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_export_catalog_csv_escapes_fields() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("CSV Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("notes, vol 1.md")).unwrap();
+        let text = "---\ntitle: \"Hello, \\\"World\\\"\"\ntags: [plot, draft]\n---\nOne two three";
+        store.doc_save(&doc_id, text, None).unwrap();
+        let formula_id = DocId::new(location.id, PathBuf::from("plan.md")).unwrap();
+        store
+            .doc_save(&formula_id, "---\ntitle: \"=1+2\"\n---\nBody", None)
+            .unwrap();
+
+        let csv = store.export_catalog_csv(location.id).unwrap();
+        let mut lines = csv.split("\r\n");
+        assert_eq!(
+            lines.next(),
+            Some("rel_path,title,word_count,size_bytes,mtime,is_conflict,tags")
+        );
+
+        let meta = store
+            .doc_list_catalog(location.id, None)
+            .unwrap()
+            .into_iter()
+            .find(|meta| meta.id == doc_id)
+            .unwrap();
+        let row = lines.next().unwrap();
+        assert_eq!(
+            row,
+            format!(
+                "\"notes, vol 1.md\",\"Hello, \"\"World\"\"\",{},{},{},false,draft;plot",
+                meta.word_count.unwrap(),
+                meta.size_bytes,
+                meta.mtime.to_rfc3339()
+            )
+        );
+        assert!(lines.next().unwrap().starts_with("plan.md,'=1+2,"));
+        assert_eq!(lines.collect::<Vec<_>>(), vec![""]);
+    }
+
+    #[test]
+    fn test_export_location_zip_writes_files_and_manifest() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        std::fs::create_dir_all(location_path.join("notes/deep")).unwrap();
+        std::fs::create_dir_all(location_path.join(".trash")).unwrap();
+        std::fs::write(location_path.join("notes/deep/b.md"), "# Deep\n\nBody").unwrap();
+        std::fs::write(location_path.join("notes/image.png"), [0u8, 1, 2]).unwrap();
+        std::fs::write(location_path.join("notes/a.meta.json"), "{}").unwrap();
+        std::fs::write(location_path.join(".trash/old.md"), "gone").unwrap();
+
+        let location = store
+            .location_add("Export Location".to_string(), location_path.clone())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("a.md")).unwrap();
+        store.doc_save(&doc_id, "# Alpha\n\nOne two three", None).unwrap();
+
+        let out_dir = TempDir::new().unwrap();
+        let out_path = out_dir.path().join("export.zip");
+        let count = store.export_location_zip(location.id, &out_path).unwrap();
+        assert_eq!(count, 3);
+
+        let mut archive = zip::ZipArchive::new(File::open(&out_path).unwrap()).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["a.md", "manifest.json", "notes/deep/b.md", "notes/image.png"]
+        );
+
+        let mut manifest_json = String::new();
+        archive
+            .by_name(EXPORT_MANIFEST_NAME)
+            .unwrap()
+            .read_to_string(&mut manifest_json)
+            .unwrap();
+        let manifest: ExportManifest = serde_json::from_str(&manifest_json).unwrap();
+        assert_eq!(manifest.location_name, "Export Location");
+        assert_eq!(manifest.files.len(), 3);
+
+        let alpha = manifest.files.iter().find(|entry| entry.rel_path == "a.md").unwrap();
+        assert_eq!(alpha.title.as_deref(), Some("Alpha"));
+        assert!(alpha.content_hash.is_some());
+    }
+
+    #[test]
+    fn test_import_location_zip_round_trips_export() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let source_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(source_dir.path().join("notes")).unwrap();
+        std::fs::write(source_dir.path().join("a.md"), "# Alpha").unwrap();
+        std::fs::write(source_dir.path().join("notes/b.md"), "# Beta").unwrap();
+        let source = store
+            .location_add("Source".to_string(), source_dir.path().to_path_buf())
+            .unwrap();
+
+        let out_dir = TempDir::new().unwrap();
+        let zip_path = out_dir.path().join("export.zip");
+        store.export_location_zip(source.id, &zip_path).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        std::fs::write(target_dir.path().join("a.md"), "# Existing").unwrap();
+        let target = store
+            .location_add("Target".to_string(), target_dir.path().to_path_buf())
+            .unwrap();
+
+        let report = store
+            .import_location_zip(target.id, &zip_path, &ImportOptions::default())
+            .unwrap();
+        assert_eq!(report.imported, vec![PathBuf::from("notes/b.md")]);
+        assert_eq!(report.skipped, vec![PathBuf::from("a.md")]);
+        assert!(!target_dir.path().join(EXPORT_MANIFEST_NAME).exists());
+        assert_eq!(
+            std::fs::read_to_string(target_dir.path().join("a.md")).unwrap(),
+            "# Existing"
+        );
+
+        let options = ImportOptions { on_collision: ImportCollision::Rename };
+        let report = store.import_location_zip(target.id, &zip_path, &options).unwrap();
+        assert_eq!(
+            report.imported,
+            vec![PathBuf::from("a-1.md"), PathBuf::from("notes/b-1.md")]
+        );
+        assert_eq!(
+            std::fs::read_to_string(target_dir.path().join("a-1.md")).unwrap(),
+            "# Alpha"
+        );
+
+        let options = ImportOptions { on_collision: ImportCollision::Overwrite };
+        store.import_location_zip(target.id, &zip_path, &options).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(target_dir.path().join("a.md")).unwrap(),
+            "# Alpha"
+        );
+
+        let listed = store
+            .doc_list(
+                target.id,
+                Some(DocListOptions { recursive: true, ..DocListOptions::default() }),
+            )
+            .unwrap();
+        assert_eq!(listed.len(), 4);
+    }
+
+    #[test]
+    fn test_import_location_zip_rejects_unsafe_and_corrupt_archives() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let location_root = target_dir.path().join("root");
+        std::fs::create_dir_all(&location_root).unwrap();
+        let location = store.location_add("Target".to_string(), location_root.clone()).unwrap();
+
+        let zip_path = target_dir.path().join("unsafe.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        for name in ["../escape.md", "/absolute.md", "safe/../ok.md"] {
+            zip.start_file(name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(b"content").unwrap();
+        }
+        zip.finish().unwrap();
+
+        let report = store
+            .import_location_zip(location.id, &zip_path, &ImportOptions::default())
+            .unwrap();
+        assert_eq!(report.imported, vec![PathBuf::from("ok.md")]);
+        assert_eq!(
+            report.rejected,
+            vec!["../escape.md".to_string(), "/absolute.md".to_string()]
+        );
+        assert!(!target_dir.path().join("escape.md").exists());
+
+        let error = store
+            .import_location_zip_limited(location.id, &zip_path, &ImportOptions::default(), 2, MAX_IMPORT_BYTES)
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Parse);
+        let error = store
+            .import_location_zip_limited(
+                location.id,
+                &zip_path,
+                &ImportOptions::default(),
+                MAX_IMPORT_ENTRIES,
+                20,
+            )
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Parse);
+
+        let corrupt_path = target_dir.path().join("corrupt.zip");
+        std::fs::write(&corrupt_path, b"not a zip").unwrap();
+        let error = store
+            .import_location_zip(location.id, &corrupt_path, &ImportOptions::default())
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Parse);
+    }
+
+    #[test]
+    fn test_doc_list_catalog_matches_filesystem_listing() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let root = location_dir.path();
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(root.join("beta.md"), "# Beta\n\nLonger body text here").unwrap();
+        std::fs::write(root.join("alpha.MD"), "# Alpha").unwrap();
+        std::fs::write(root.join("notes.txt"), "plain").unwrap();
+        std::fs::write(root.join("nested/gamma.md"), "# Gamma\n\nMid").unwrap();
+        std::fs::write(root.join("nested/under_score.md"), "x").unwrap();
+
+        let location = store.location_add("Catalog".to_string(), root.to_path_buf()).unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+
+        let paths = |docs: Vec<DocMeta>| docs.into_iter().map(|doc| doc.id.rel_path).collect::<Vec<_>>();
+        for (sort_by, sort_order) in [
+            (DocSortField::Name, SortOrder::Descending),
+            (DocSortField::Name, SortOrder::Ascending),
+            (DocSortField::Size, SortOrder::Descending),
+            (DocSortField::Size, SortOrder::Ascending),
+        ] {
+            for recursive in [false, true] {
+                let options = DocListOptions {
+                    recursive,
+                    extensions: Some(vec!["md".to_string()]),
+                    sort_by: Some(sort_by),
+                    sort_order,
+                    follow_symlinks: false,
+                    max_depth: None,
+                };
+                assert_eq!(
+                    paths(store.doc_list_catalog(location.id, Some(options.clone())).unwrap()),
+                    paths(store.doc_list(location.id, Some(options)).unwrap()),
+                    "sort_by={:?}, sort_order={:?}, recursive={}",
+                    sort_by,
+                    sort_order,
+                    recursive
+                );
+            }
+        }
+
+        let escaped =
+            DocListOptions { recursive: true, extensions: Some(vec!["_d".to_string()]), ..DocListOptions::default() };
+        assert!(store.doc_list_catalog(location.id, Some(escaped)).unwrap().is_empty());
+
+        let all = store
+            .doc_list_catalog(
+                location.id,
+                Some(DocListOptions { recursive: true, ..DocListOptions::default() }),
+            )
+            .unwrap();
+        assert_eq!(all.len(), 5);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_directories_skipped_by_default_and_followed_when_enabled() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let outside_dir = TempDir::new().unwrap();
+        let root = location_dir.path();
+        std::fs::create_dir_all(root.join("real")).unwrap();
+        std::fs::write(root.join("top.md"), "# Top").unwrap();
+        std::fs::write(root.join("real/inner.md"), "# Inner").unwrap();
+        std::fs::write(outside_dir.path().join("secret.md"), "# Secret").unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("linked")).unwrap();
+        std::os::unix::fs::symlink(root, root.join("real/loop")).unwrap();
+        std::os::unix::fs::symlink(outside_dir.path(), root.join("outside")).unwrap();
+
+        let location = store.location_add("Links".to_string(), root.to_path_buf()).unwrap();
+        let list = |follow_symlinks: bool| {
+            let options = DocListOptions {
+                recursive: true,
+                sort_by: Some(DocSortField::Name),
+                follow_symlinks,
+                ..DocListOptions::default()
+            };
+            let mut paths: Vec<PathBuf> = store
+                .doc_list(location.id, Some(options))
+                .unwrap()
+                .into_iter()
+                .map(|doc| doc.id.rel_path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(
+            list(false),
+            vec![PathBuf::from("real/inner.md"), PathBuf::from("top.md")]
+        );
+        let followed = list(true);
+        assert!(followed.contains(&PathBuf::from("top.md")));
+        assert!(!followed.iter().any(|path| path.starts_with("outside")));
+        assert!(!followed.iter().any(|path| path.starts_with("real/loop")));
+        assert_eq!(followed.iter().filter(|path| path.ends_with("inner.md")).count(), 1);
+
+        let recursive = DocListOptions { recursive: true, ..DocListOptions::default() };
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(
+            store
+                .doc_list_catalog(location.id, Some(recursive.clone()))
+                .unwrap()
+                .len(),
+            2
+        );
+
+        store.reconcile_location_index_with_symlinks(location.id, true).unwrap();
+        let catalogued = store.doc_list_catalog(location.id, Some(recursive)).unwrap();
+        assert_eq!(catalogued.len(), 2);
+        assert!(!catalogued.iter().any(|doc| doc.id.rel_path.starts_with("outside")));
+    }
+
+    #[test]
+    fn test_doc_save_overwrite() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("overwrite.md")).unwrap();
+        store.doc_save(&doc_id, "Initial content", None).unwrap();
+
+        let new_content = "Updated content here";
+        let result = store.doc_save(&doc_id, new_content, None).unwrap();
+
+        assert!(result.success);
+
+        let saved_path = location_path.join("overwrite.md");
+        let saved_content = std::fs::read_to_string(saved_path).unwrap();
+        assert_eq!(saved_content, new_content);
+    }
+
+    #[test]
+    fn test_doc_save_creates_directories() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("level1/level2/file.md")).unwrap();
+        let result = store.doc_save(&doc_id, "Nested content", None);
+
+        assert!(result.is_ok());
+        assert!(location_path.join("level1/level2/file.md").exists());
+    }
+
+    #[test]
+    fn test_rename_across_extensions_updates_search() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Extension Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        store
+            .location_set_indexable_extensions(location.id, Some(vec!["md".to_string()]))
+            .unwrap();
+
+        let txt_id = DocId::new(location.id, PathBuf::from("note.txt")).unwrap();
+        store.doc_save(&txt_id, "The renametoken note", None).unwrap();
+        assert!(store.search("renametoken", None, 10).unwrap().is_empty());
+
+        let md = store.doc_rename(&txt_id, "note.md").unwrap();
+        let hits = store.search("renametoken", None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rel_path, "note.md");
+
+        let bin = store.doc_rename(&md.id, "note.bin").unwrap();
+        assert!(store.search("renametoken", None, 10).unwrap().is_empty());
+
+        let moved = store.doc_move(&bin.id, Path::new("drafts/note.md")).unwrap();
+        assert_eq!(store.search("renametoken", None, 10).unwrap().len(), 1);
+        store.doc_move(&moved.id, Path::new("attachments/note.png")).unwrap();
+        assert!(store.search("renametoken", None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_doc_move_to_different_location() {
+        let (store, _temp) = create_test_store();
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let source_location = store
+            .location_add("Source".to_string(), source_dir.path().to_path_buf())
+            .unwrap();
+        let target_location = store
+            .location_add("Target".to_string(), target_dir.path().to_path_buf())
+            .unwrap();
+
+        let source_doc_id = DocId::new(source_location.id, PathBuf::from("notes/source.md")).unwrap();
+        store
+            .doc_save(&source_doc_id, "# Cross Location\n\nMove me safely.", None)
+            .unwrap();
+
+        let moved_meta = store
+            .doc_move_to_location(&source_doc_id, target_location.id, Path::new("archive/moved.md"))
+            .unwrap();
+
+        assert_eq!(moved_meta.id.location_id, target_location.id);
+        assert_eq!(moved_meta.id.rel_path, PathBuf::from("archive/moved.md"));
+        assert!(!source_dir.path().join("notes/source.md").exists());
+        assert!(target_dir.path().join("archive/moved.md").exists());
+
+        let moved_doc_id = DocId::new(target_location.id, PathBuf::from("archive/moved.md")).unwrap();
+        let moved_doc = store.doc_open(&moved_doc_id).unwrap();
+        assert_eq!(moved_doc.text, "# Cross Location\n\nMove me safely.");
+
+        let source_docs = store.doc_list(source_location.id, None).unwrap();
+        assert!(source_docs.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_within_location() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Resolve".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let resolved = store
+            .resolve_within_location(location.id, Path::new("./drafts/../archive//old/./note.md"))
+            .unwrap();
+        assert_eq!(resolved, PathBuf::from("archive/old/note.md"));
+
+        let doc_id = DocId::new(location.id, PathBuf::from("note.md")).unwrap();
+        store.doc_save(&doc_id, "# Note", None).unwrap();
+        let moved = store.doc_move(&doc_id, &resolved).unwrap();
+        assert_eq!(moved.id.rel_path, resolved);
+        assert!(location_dir.path().join("archive/old/note.md").exists());
+
+        for escape in ["../outside.md", "archive/../../outside.md", "/etc/passwd"] {
+            let error = store
+                .resolve_within_location(location.id, Path::new(escape))
+                .unwrap_err();
+            assert_eq!(error.code, ErrorCode::InvalidPath, "{}", escape);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_within_location_rejects_symlink_escape() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let outside_dir = TempDir::new().unwrap();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Resolve Links".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        std::os::unix::fs::symlink(outside_dir.path(), location_dir.path().join("linked")).unwrap();
+
+        let error = store
+            .resolve_within_location(location.id, Path::new("linked/new.md"))
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidPath);
+    }
+
+    #[test]
+    fn test_doc_neighbors_by_name() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Chapters".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        for name in ["b-middle.md", "c-last.md", "a-first.md"] {
+            std::fs::write(location_dir.path().join(name), format!("# {}", name)).unwrap();
+        }
+        std::fs::create_dir_all(location_dir.path().join("appendix")).unwrap();
+        std::fs::write(location_dir.path().join("appendix/b-notes.md"), "# Notes").unwrap();
+
+        let doc = |rel_path: &str| DocId::new(location.id, PathBuf::from(rel_path)).unwrap();
+        let names = |(previous, next): (Option<DocMeta>, Option<DocMeta>)| {
+            (previous.map(|meta| meta.id.rel_path), next.map(|meta| meta.id.rel_path))
+        };
+
+        assert_eq!(
+            names(
+                store
+                    .doc_neighbors(&doc("b-middle.md"), DocSortField::Name, false)
+                    .unwrap()
+            ),
+            (Some(PathBuf::from("a-first.md")), Some(PathBuf::from("c-last.md")))
+        );
+        assert_eq!(
+            names(
+                store
+                    .doc_neighbors(&doc("a-first.md"), DocSortField::Name, false)
+                    .unwrap()
+            ),
+            (None, Some(PathBuf::from("b-middle.md")))
+        );
+        assert_eq!(
+            names(
+                store
+                    .doc_neighbors(&doc("c-last.md"), DocSortField::Name, false)
+                    .unwrap()
+            ),
+            (Some(PathBuf::from("b-middle.md")), None)
+        );
+        assert_eq!(
+            names(
+                store
+                    .doc_neighbors(&doc("b-middle.md"), DocSortField::Name, true)
+                    .unwrap()
+            ),
+            (
+                Some(PathBuf::from("a-first.md")),
+                Some(PathBuf::from("appendix/b-notes.md"))
+            )
+        );
+        assert_eq!(
+            store
+                .doc_neighbors(&doc("missing.md"), DocSortField::Name, false)
+                .unwrap_err()
+                .code,
+            ErrorCode::NotFound
+        );
+    }
+
+    #[test]
+    fn test_directory_create_and_delete() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Directory Ops".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let created = store.dir_create(location.id, Path::new("nested/notes")).unwrap();
+        assert!(created);
+        assert!(location_dir.path().join("nested/notes").is_dir());
+
+        let created_again = store.dir_create(location.id, Path::new("nested/notes")).unwrap();
+        assert!(!created_again);
+
+        let deleted = store.dir_delete(location.id, Path::new("nested")).unwrap();
+        assert!(deleted);
+        assert!(!location_dir.path().join("nested").exists());
+    }
+
+    #[test]
+    fn test_directory_move_updates_catalog_paths() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Directory Move".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let doc_a = DocId::new(location.id, PathBuf::from("old/sub/a.md")).unwrap();
+        let doc_b = DocId::new(location.id, PathBuf::from("old/sub/deep/b.md")).unwrap();
+        store.doc_save(&doc_a, "# A\n\nalphatoken", None).unwrap();
+        store.doc_save(&doc_b, "# B\n\ndeeptoken", None).unwrap();
+
+        let moved = store
+            .dir_move(location.id, Path::new("old/sub"), Path::new("new/archive"))
+            .unwrap();
+        assert_eq!(moved, PathBuf::from("new/archive"));
+        assert!(location_dir.path().join("new/archive/a.md").exists());
+        assert!(location_dir.path().join("new/archive/deep/b.md").exists());
+
+        let alpha_hits = store.search("alphatoken", None, 10).unwrap();
+        assert_eq!(alpha_hits.len(), 1);
+        assert_eq!(alpha_hits[0].rel_path, "new/archive/a.md");
+
+        let deep_hits = store.search("deeptoken", None, 10).unwrap();
+        assert_eq!(deep_hits.len(), 1);
+        assert_eq!(deep_hits[0].rel_path, "new/archive/deep/b.md");
+    }
+
+    #[test]
+    fn test_directory_move_to_different_location_updates_catalog_paths() {
+        let (store, _temp) = create_test_store();
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let source_location = store
+            .location_add("Directory Move Source".to_string(), source_dir.path().to_path_buf())
+            .unwrap();
+        let target_location = store
+            .location_add("Directory Move Target".to_string(), target_dir.path().to_path_buf())
+            .unwrap();
+
+        let doc_a = DocId::new(source_location.id, PathBuf::from("old/sub/a.md")).unwrap();
+        let doc_b = DocId::new(source_location.id, PathBuf::from("old/sub/deep/b.md")).unwrap();
+        store.doc_save(&doc_a, "# A\n\nalphatoken", None).unwrap();
+        store.doc_save(&doc_b, "# B\n\ndeeptoken", None).unwrap();
+
+        let moved = store
+            .dir_move_to_location(
+                source_location.id,
+                Path::new("old/sub"),
+                target_location.id,
+                Path::new("new/archive"),
+            )
+            .unwrap();
+
+        assert_eq!(moved, PathBuf::from("new/archive"));
+        assert!(!source_dir.path().join("old/sub").exists());
+        assert!(target_dir.path().join("new/archive/a.md").exists());
+        assert!(target_dir.path().join("new/archive/deep/b.md").exists());
+
+        let alpha_hits = store.search("alphatoken", None, 10).unwrap();
+        assert_eq!(alpha_hits.len(), 1);
+        assert_eq!(alpha_hits[0].location_id, target_location.id);
+        assert_eq!(alpha_hits[0].rel_path, "new/archive/a.md");
+
+        let deep_hits = store.search("deeptoken", None, 10).unwrap();
+        assert_eq!(deep_hits.len(), 1);
+        assert_eq!(deep_hits[0].location_id, target_location.id);
+        assert_eq!(deep_hits[0].rel_path, "new/archive/deep/b.md");
+    }
+
+    #[test]
+    fn test_directory_list_includes_empty_and_nested_directories() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Directory List".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        store.dir_create(location.id, Path::new("Samples")).unwrap();
+        store.dir_create(location.id, Path::new("Samples/sibling")).unwrap();
+        store.dir_create(location.id, Path::new("Empty")).unwrap();
+
+        let directories = store.dir_list(location.id, None, true).unwrap();
+        let as_strings = directories
+            .iter()
+            .map(|entry| entry.rel_path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            as_strings,
+            vec![
+                "Empty".to_string(),
+                "Samples".to_string(),
+                "Samples/sibling".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_returns_indexed_results() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Search Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("chapter-1.md")).unwrap();
+        store
+            .doc_save(&doc_id, "# Chapter One\nThe stormlight archives begin here.", None)
+            .unwrap();
+
+        let results = store.search("stormlight", None, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].location_id, location.id);
+        assert_eq!(results[0].rel_path, "chapter-1.md");
+        assert!(!results[0].snippet.is_empty());
+    }
+
+    #[test]
+    fn test_search_grouped_by_location() {
+        let (store, _temp) = create_test_store();
+        let first_dir = TempDir::new().unwrap();
+        let second_dir = TempDir::new().unwrap();
+        let empty_dir = TempDir::new().unwrap();
+        let first = store
+            .location_add("First".to_string(), first_dir.path().to_path_buf())
+            .unwrap();
+        let second = store
+            .location_add("Second".to_string(), second_dir.path().to_path_buf())
+            .unwrap();
+        let empty = store
+            .location_add("Empty".to_string(), empty_dir.path().to_path_buf())
+            .unwrap();
+
+        for (location_id, rel_path, text) in [
+            (first.id, "skybreaker.md", "# Skybreaker\nhighstorm highstorm highstorm"),
+            (
+                first.id,
+                "mention.md",
+                "# Mention\nOne highstorm among many other words in this longer note.",
+            ),
+            (second.id, "windrunner.md", "# Windrunner\nThe highstorm arrives."),
+        ] {
+            let doc_id = DocId::new(location_id, PathBuf::from(rel_path)).unwrap();
+            store.doc_save(&doc_id, text, None).unwrap();
+        }
+        store
+            .doc_save(&DocId::new(empty.id, PathBuf::from("calm.md")).unwrap(), "# Calm", None)
+            .unwrap();
+
+        let groups = store.search_grouped("highstorm", None, 10).unwrap();
+        let locations: HashSet<LocationId> = groups.iter().map(|(location_id, _)| *location_id).collect();
+        assert_eq!(locations, HashSet::from([first.id, second.id]));
+
+        let (_, first_hits) = groups.iter().find(|(location_id, _)| *location_id == first.id).unwrap();
+        let first_paths: Vec<&str> = first_hits.iter().map(|hit| hit.rel_path.as_str()).collect();
+        let ungrouped: Vec<String> = store
+            .search(
+                "highstorm",
+                Some(SearchFilters { locations: Some(vec![first.id]), ..Default::default() }),
+                10,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.rel_path)
+            .collect();
+        assert_eq!(first_paths, ungrouped.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(first_paths.len(), 2);
+
+        let capped = store.search_grouped("highstorm", None, 1).unwrap();
+        assert!(capped.iter().all(|(_, hits)| hits.len() == 1));
+        assert_eq!(capped.len(), 2);
+
+        let filtered = store
+            .search_grouped(
+                "highstorm",
+                Some(SearchFilters { locations: Some(vec![second.id, empty.id]), ..Default::default() }),
+                10,
+            )
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].0, second.id);
+        assert_eq!(filtered[0].1[0].rel_path, "windrunner.md");
+    }
+
+    #[test]
+    fn test_location_clear_index_keeps_files() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let cleared_dir = TempDir::new().unwrap();
+        let kept_dir = TempDir::new().unwrap();
+        let cleared = store
+            .location_add("Cleared".to_string(), cleared_dir.path().to_path_buf())
+            .unwrap();
+        let kept = store
+            .location_add("Kept".to_string(), kept_dir.path().to_path_buf())
+            .unwrap();
+
+        for (location_id, rel_path) in [
+            (cleared.id, "one.md"),
+            (cleared.id, "nested/two.md"),
+            (kept.id, "three.md"),
+        ] {
+            let doc_id = DocId::new(location_id, PathBuf::from(rel_path)).unwrap();
+            store.doc_save(&doc_id, "The clearindextoken #tagged", None).unwrap();
+        }
+
+        assert_eq!(store.location_clear_index(cleared.id).unwrap(), 2);
+
+        let hits = store.search("clearindextoken", None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].location_id, kept.id);
+        assert!(store.doc_list_catalog(cleared.id, None).unwrap().is_empty());
+        assert_eq!(store.tag_cloud(None).unwrap(), vec![("tagged".to_string(), 1)]);
+
+        assert!(cleared_dir.path().join("one.md").exists());
+        assert!(cleared_dir.path().join("nested/two.md").exists());
+        assert!(store.location_get(cleared.id).unwrap().is_some());
+        assert_eq!(store.location_clear_index(cleared.id).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_doc_list_published_skips_drafts() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Blog".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let draft = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+        let post = DocId::new(location.id, PathBuf::from("post.md")).unwrap();
+        store
+            .doc_save(&draft, "---\ntitle: Draft\ndraft: true\n---\n\nNot yet.", None)
+            .unwrap();
+        store
+            .doc_save(&post, "---\ntitle: Post\ndraft: false\n---\n\nOut now.", None)
+            .unwrap();
+
+        let published = store.doc_list_published(location.id, None).unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].id, post);
+        assert_eq!(store.doc_list(location.id, None).unwrap().len(), 2);
+
+        {
+            let conn = store.conn.lock().unwrap();
+            conn.execute("UPDATE documents SET is_draft = 0, size_bytes = -1", [])
+                .unwrap();
+        }
+        assert_eq!(store.doc_list_published(location.id, None).unwrap().len(), 2);
+
+        store.reconcile_location_index(location.id).unwrap();
+        let published = store.doc_list_published(location.id, None).unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].id, post);
+    }
+
+    #[test]
+    fn test_word_count_history_records_each_save() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("History".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+
+        for text in [
+            "---\ntitle: Draft\n---\nOne two three",
+            "---\ntitle: Draft\n---\nOne two three four five",
+            "---\ntitle: Draft, renamed\n---\nOne two three four five",
+            "One",
+        ] {
+            store.doc_save(&doc_id, text, None).unwrap();
+        }
+
+        let history = store.word_count_history(&doc_id).unwrap();
+        let counts: Vec<usize> = history.iter().map(|(_, count)| *count).collect();
+        assert_eq!(counts, vec![3, 5, 1]);
+        assert!(history.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+        let catalogued: i64 = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT word_count FROM documents WHERE rel_path = 'draft.md'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(history.last().unwrap().1 as i64, catalogued);
+
+        let renamed = store.doc_rename(&doc_id, "renamed.md").unwrap();
+        store.doc_save(&renamed.id, "One two", None).unwrap();
+        let counts: Vec<usize> = store
+            .word_count_history(&renamed.id)
+            .unwrap()
+            .iter()
+            .map(|(_, count)| *count)
+            .collect();
+        assert_eq!(counts, vec![3, 5, 1, 2]);
+
+        std::fs::write(location_dir.path().join("fresh.md"), "Just four words here").unwrap();
+        let fresh = DocId::new(location.id, PathBuf::from("fresh.md")).unwrap();
+        let history = store.word_count_history(&fresh).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].1, 4);
+
+        store.doc_delete(&renamed.id).unwrap();
+        let orphaned: i64 = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM word_count_history WHERE word_count = 5",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(orphaned, 0);
+
+        let missing = DocId::new(location.id, PathBuf::from("missing.md")).unwrap();
+        assert!(matches!(
+            store.word_count_history(&missing).unwrap_err().code,
+            ErrorCode::NotFound
+        ));
+    }
+
+    #[test]
+    fn test_doc_outline_cached_matches_fresh_parse() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Outline".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("notes.md")).unwrap();
+        let text = "---\ntitle: Notes\n---\n\n# Notes\n\n## First\n\nBody\n\nSecond\n------\n\n### Deep";
+        store.doc_save(&doc_id, text, None).unwrap();
+
+        let cached_json: Option<String> = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT outline_json FROM documents WHERE rel_path = 'notes.md'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(cached_json.is_some());
+
+        let fresh = MarkdownEngine::new()
+            .metadata(text, MarkdownProfile::Extended)
+            .unwrap()
+            .outline;
+        assert_eq!(fresh.len(), 4);
+        assert_eq!(store.doc_outline_cached(&doc_id).unwrap(), Some(fresh));
+
+        store.doc_save(&doc_id, "# Renamed\n\n## Only", None).unwrap();
+        let outline = store.doc_outline_cached(&doc_id).unwrap().unwrap();
+        let texts: Vec<&str> = outline.iter().map(|heading| heading.text.as_str()).collect();
+        assert_eq!(texts, vec!["Renamed", "Only"]);
+
+        {
+            let conn = store.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE documents SET mtime = '2000-01-01T00:00:00+00:00', outline_json = '[]' WHERE rel_path = 'notes.md'",
+                [],
+            )
+            .unwrap();
+        }
+        assert_eq!(store.doc_outline_cached(&doc_id).unwrap().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_recently_edited_spans_locations() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let first_dir = TempDir::new().unwrap();
+        let second_dir = TempDir::new().unwrap();
+        let first = store
+            .location_add("First".to_string(), first_dir.path().to_path_buf())
+            .unwrap();
+        let second = store
+            .location_add("Second".to_string(), second_dir.path().to_path_buf())
+            .unwrap();
+
+        let saves = [
+            (first.id, "old.md"),
+            (second.id, "middle.md"),
+            (first.id, "notes (conflict).md"),
+            (second.id, ".trash/gone.md"),
+            (first.id, "newest.md"),
+        ];
+        for (location_id, rel_path) in saves {
+            let doc_id = DocId::new(location_id, PathBuf::from(rel_path)).unwrap();
+            store.doc_save(&doc_id, "Some text", None).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let recent = store.recently_edited(10, true).unwrap();
+        let paths: Vec<(LocationId, &str)> = recent
+            .iter()
+            .map(|doc| (doc.id.location_id, doc.id.rel_path.to_str().unwrap()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![(first.id, "newest.md"), (second.id, "middle.md"), (first.id, "old.md")]
+        );
+        assert!(store.doc_open(&recent[0].id).is_ok());
+
+        let everything = store.recently_edited(10, false).unwrap();
+        assert_eq!(everything.len(), 5);
+        assert_eq!(everything[1].id.rel_path, PathBuf::from(".trash/gone.md"));
+        assert_eq!(store.recently_edited(2, false).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_doc_split_by_heading_writes_one_file_per_section() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Book".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        std::fs::create_dir_all(location_dir.path().join("drafts")).unwrap();
+        std::fs::write(location_dir.path().join("drafts/the-end.md"), "Already here").unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("drafts/book.md")).unwrap();
+        let text = "---\nauthor: Ada\n---\n\nA foreword.\n\n# Opening Lines\n\nsplittoken one\n\n## Aside\n\n\
+                    ```\n# not a heading\n```\n\n# The End\n\nsplittoken two\n";
+        store.doc_save(&doc_id, text, None).unwrap();
+
+        let created = store
+            .doc_split_by_heading(&doc_id, 1, SplitNaming::Slug, true, true)
+            .unwrap();
+        let paths: Vec<&Path> = created.iter().map(|doc| doc.id.rel_path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![Path::new("drafts/opening-lines.md"), Path::new("drafts/the-end-2.md")]
+        );
+
+        let first = std::fs::read_to_string(location_dir.path().join("drafts/opening-lines.md")).unwrap();
+        assert_eq!(
+            first,
+            "---\nauthor: Ada\n---\n\nA foreword.\n\n# Opening Lines\n\nsplittoken one\n\n## Aside\n\n\
+             ```\n# not a heading\n```\n\n"
+        );
+        let second = std::fs::read_to_string(location_dir.path().join("drafts/the-end-2.md")).unwrap();
+        assert_eq!(second, "---\nauthor: Ada\n---\n\n# The End\n\nsplittoken two\n");
+
+        assert!(!location_dir.path().join("drafts/book.md").exists());
+        assert!(location_dir.path().join(".trash/drafts/book.md").exists());
+        let hits = store.search("splittoken", None, 10).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|hit| !hit.rel_path.starts_with(".trash")));
+        let catalog = store.doc_list_catalog(location.id, None).unwrap();
+        assert!(catalog.iter().all(|doc| !doc.id.rel_path.starts_with(".trash")));
+
+        let numbered = store
+            .doc_split_by_heading(&created[0].id, 2, SplitNaming::Index, false, false)
+            .unwrap();
+        let names: Vec<&str> = numbered.iter().map(|doc| doc.filename.as_str()).collect();
+        assert_eq!(names, vec!["opening-lines-01.md", "opening-lines-02.md"]);
+    }
+
+    #[test]
+    fn test_doc_split_by_heading_rolls_back_when_trashing_fails() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Book".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        std::fs::write(location_dir.path().join(".trash"), "not a directory").unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("drafts/book.md")).unwrap();
+        let text = "# One
+
+rolltoken one
+
+# Two
+
+rolltoken two
+";
+        store.doc_save(&doc_id, text, None).unwrap();
+
+        assert!(
+            store
+                .doc_split_by_heading(&doc_id, 1, SplitNaming::Slug, false, true)
+                .is_err()
+        );
+        assert!(!location_dir.path().join("drafts/one.md").exists());
+        assert!(!location_dir.path().join("drafts/two.md").exists());
+        assert_eq!(
+            std::fs::read_to_string(location_dir.path().join("drafts/book.md")).unwrap(),
+            text
+        );
+        let hits = store.search("rolltoken", None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rel_path, "drafts/book.md");
+    }
+
+    #[test]
+    fn test_dir_list_under_subpath_with_counts_and_ignores() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let root = location_dir.path();
+        let location = store.location_add("Tree".to_string(), root.to_path_buf()).unwrap();
+
+        for dir in [
+            "book/part-1/scenes",
+            "book/part-2",
+            "book/.cache",
+            "notes",
+            ".git/objects",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "book/outline.md",
+            "book/part-1/one.md",
+            "book/part-1/two.md",
+            "book/.cache/x.bin",
+        ] {
+            std::fs::write(root.join(file), "text").unwrap();
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("notes"), root.join("book/linked")).unwrap();
+
+        let top = store.dir_list(location.id, None, false).unwrap();
+        let top_paths: Vec<&Path> = top.iter().map(|entry| entry.rel_path.as_path()).collect();
+        assert_eq!(top_paths, vec![Path::new("book"), Path::new("notes")]);
+        assert_eq!((top[0].child_dirs, top[0].child_files), (2, 1));
+
+        let book = store.dir_list(location.id, Some(Path::new("book")), true).unwrap();
+        assert_eq!(
+            book,
+            vec![
+                DirEntry { rel_path: PathBuf::from("book/part-1"), child_dirs: 1, child_files: 2 },
+                DirEntry { rel_path: PathBuf::from("book/part-1/scenes"), child_dirs: 0, child_files: 0 },
+                DirEntry { rel_path: PathBuf::from("book/part-2"), child_dirs: 0, child_files: 0 },
+            ]
+        );
+
+        assert!(
+            store
+                .dir_list(location.id, Some(Path::new("missing/dir")), true)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            store
+                .dir_list(location.id, Some(Path::new("book/outline.md")), true)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            store
+                .dir_list(location.id, Some(Path::new("../outside")), true)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_search_hits_report_nearest_heading_anchor() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Guide".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let guide = DocId::new(location.id, PathBuf::from("guide.md")).unwrap();
+        store
+            .doc_save(
+                &guide,
+                "# Guide\n\nIntro text.\n\n## Setup\n\nInstall the anchortoken package.\n\n## Usage\n\nRun it.",
+                None,
+            )
+            .unwrap();
+        let intro = DocId::new(location.id, PathBuf::from("intro.md")).unwrap();
+        store
+            .doc_save(&intro, "The preludetoken comes first.\n\n# Later", None)
+            .unwrap();
+
+        let hits = store.search("anchortoken", None, 10).unwrap();
+        assert_eq!(hits[0].nearest_heading_anchor.as_deref(), Some("heading-setup"));
+
+        let hits = store.search("preludetoken", None, 10).unwrap();
+        assert_eq!(hits[0].nearest_heading_anchor, None);
+
+        {
+            let conn = store.conn.lock().unwrap();
+            conn.execute("UPDATE documents SET outline_json = NULL", []).unwrap();
+        }
+        let hits = store.search("anchortoken", None, 10).unwrap();
+        assert_eq!(hits[0].nearest_heading_anchor.as_deref(), Some("heading-setup"));
+    }
+
+    #[test]
+    fn test_index_audit_reports_desynced_rows() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let root = location_dir.path();
+        let location = store.location_add("Audit".to_string(), root.to_path_buf()).unwrap();
+
+        for rel_path in ["kept.md", "deleted.md", "unsearchable.md"] {
+            let doc_id = DocId::new(location.id, PathBuf::from(rel_path)).unwrap();
+            store.doc_save(&doc_id, "Audit text", None).unwrap();
+        }
+        std::fs::write(root.join("image.png"), [0u8; 4]).unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        assert!(store.index_audit(location.id).unwrap().is_consistent());
+
+        std::fs::remove_file(root.join("deleted.md")).unwrap();
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(root.join("nested/untracked.md"), "New").unwrap();
+        std::fs::create_dir_all(root.join(".hidden")).unwrap();
+        std::fs::write(root.join(".hidden/ignored.md"), "Ignored").unwrap();
+        {
+            let conn = store.conn.lock().unwrap();
+            conn.execute("DELETE FROM docs_fts WHERE rel_path = 'unsearchable.md'", [])
+                .unwrap();
+            conn.execute(
+                "INSERT INTO docs_fts (location_id, rel_path, title, content) VALUES (?1, 'ghost.md', 'Ghost', 'Boo')",
+                params![location.id.0],
+            )
+            .unwrap();
+        }
+
+        let audit = store.index_audit(location.id).unwrap();
+        assert_eq!(
+            audit,
+            IndexAudit {
+                missing_from_disk: vec![PathBuf::from("deleted.md")],
+                missing_from_catalog: vec![PathBuf::from("nested/untracked.md")],
+                orphaned_search_entries: vec![PathBuf::from("ghost.md")],
+                missing_from_search: vec![PathBuf::from("unsearchable.md")],
+            }
+        );
+        assert!(!audit.is_consistent());
+        assert!(root.join("kept.md").exists());
+        assert_eq!(store.doc_list_catalog(location.id, None).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_search_hit_preview_is_plain_prose() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Previews".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("post.md")).unwrap();
+        let text = "---\ntitle: Post\n---\n\n# Hello *World*\n\nSome **bold** prose with a [link](https://example.com) \
+                    and `code`.\n\n- previewtoken item";
+        store.doc_save(&doc_id, text, None).unwrap();
+
+        let preview = store.search("previewtoken", None, 10).unwrap()[0]
+            .preview
+            .clone()
+            .unwrap();
+        assert!(
+            preview.starts_with("Hello World Some bold prose with a link and code."),
+            "{}",
+            preview
+        );
+        assert!(preview.contains("previewtoken item"), "{}", preview);
+        assert!(!preview.contains(['#', '*', '[', '`', '\n']), "{}", preview);
+
+        store
+            .search_settings_set(&SearchSettings { preview_chars: 20, ..SearchSettings::default() })
+            .unwrap();
+        let trimmed = store.search("previewtoken", None, 10).unwrap()[0]
+            .preview
+            .clone()
+            .unwrap();
+        assert_eq!(trimmed, "Hello World Some…");
+
+        store
+            .doc_save(&doc_id, "Rewritten previewtoken body that runs on for a while", None)
+            .unwrap();
+        let regenerated = store.search("previewtoken", None, 10).unwrap()[0]
+            .preview
+            .clone()
+            .unwrap();
+        assert_eq!(regenerated, "Rewritten…");
+    }
+
+    #[test]
+    fn test_search_with_options_custom_snippet_and_markers() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Search Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("long.md")).unwrap();
+        let text = "alpha beta gamma delta epsilon zeta eta theta iota kappa lambda mu nu xi omicron \
+                    pi rho sigma tau upsilon phi chi psi omega stormlight alpha beta gamma delta epsilon \
+                    zeta eta theta iota kappa lambda mu nu xi omicron pi rho sigma tau upsilon << >>";
+        store.doc_save(&doc_id, text, None).unwrap();
+
+        let short = store.search("stormlight", None, 10).unwrap();
+        let options = SearchOptions {
+            snippet_tokens: 40,
+            ellipsis: "…".to_string(),
+            highlight_open: "[[hl]]".to_string(),
+            highlight_close: "[[/hl]]".to_string(),
+        };
+        let long = store.search_with_options("stormlight", None, 10, &options).unwrap();
+
+        assert_eq!(long.len(), 1);
+        let hit = &long[0];
+        assert!(hit.snippet.split_whitespace().count() > short[0].snippet.split_whitespace().count());
+        assert!(hit.snippet.ends_with('…'));
+        assert!(!hit.snippet.contains("[[hl]]"));
+        assert_eq!(hit.matches.len(), 1);
+        assert_eq!(&hit.snippet[hit.matches[0].start..hit.matches[0].end], "stormlight");
+
+        let markers = SearchOptions { snippet_tokens: 64, ..options.clone() };
+        let hit = &store.search_with_options("upsilon", None, 10, &markers).unwrap()[0];
+        assert!(hit.snippet.contains("<< >>"));
+        assert_eq!(hit.matches.len(), 2);
+        for range in &hit.matches {
+            assert_eq!(&hit.snippet[range.start..range.end], "upsilon");
+        }
+
+        for snippet_tokens in [0, 65] {
+            let invalid = SearchOptions { snippet_tokens, ..SearchOptions::default() };
+            let error = store.search_with_options("stormlight", None, 10, &invalid).unwrap_err();
+            assert_eq!(error.code, ErrorCode::Parse);
+        }
+        let invalid = SearchOptions { highlight_close: "<<".to_string(), ..SearchOptions::default() };
+        assert!(store.search_with_options("stormlight", None, 10, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_fts_max_content_bytes_caps_indexed_content() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Search Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let fts_content = |rel_path: &str| -> String {
+            store
+                .conn
+                .lock()
+                .unwrap()
+                .query_row(
+                    "SELECT content FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
+                    params![location.id.0, rel_path],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+
+        let early = DocId::new(location.id, PathBuf::from("early.md")).unwrap();
+        store.doc_save(&early, &"word ".repeat(100), None).unwrap();
+        store
+            .search_settings_set(&SearchSettings { fts_max_content_bytes: 12, ..SearchSettings::default() })
+            .unwrap();
+        assert_eq!(fts_content("early.md"), "word word wo");
+
+        let huge = DocId::new(location.id, PathBuf::from("huge.md")).unwrap();
+        let text = format!("# Huge\n\nCafé {}needle", "filler ".repeat(1000));
+        let result = store.doc_save(&huge, &text, None).unwrap();
+        assert_eq!(result.new_meta.unwrap().size_bytes, text.len() as u64);
+        assert_eq!(fts_content("huge.md"), "# Huge\n\nCaf");
+        assert!(store.search("needle", None, 10).unwrap().is_empty());
+
+        std::fs::write(location_dir.path().join("huge.md"), format!("{} again", text)).unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(fts_content("huge.md"), "# Huge\n\nCaf");
+
+        store.search_settings_set(&SearchSettings::default()).unwrap();
+        store.doc_save(&huge, &text, None).unwrap();
+        assert_eq!(store.search("needle", None, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_search_settings_fold_diacritics_rebuilds_fts() {
+        let (store, temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("Search Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("menu.md")).unwrap();
+        store
+            .doc_save(&doc_id, "# Menu\nCoffee at the Café Ørsted.", None)
+            .unwrap();
+
+        assert_eq!(store.search_settings_get().unwrap(), SearchSettings::default());
+        store
+            .search_settings_set(&SearchSettings { fold_diacritics: true, ..SearchSettings::default() })
+            .unwrap();
+        assert!(store.search_settings_get().unwrap().fold_diacritics);
+
+        let results = store.search("cafe", None, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rel_path, "menu.md");
+
+        drop(store);
+        let reopened = Store::open(&temp.path().join("test.db")).unwrap();
+        assert!(reopened.search_settings_get().unwrap().fold_diacritics);
+        let fts_sql: String = reopened
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT sql FROM sqlite_master WHERE name = 'docs_fts'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert!(fts_sql.contains("remove_diacritics 2"));
+
+        reopened.rebuild_fts().unwrap();
+        assert_eq!(reopened.search("cafe", None, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_reconcile_location_index_removes_deleted_docs_from_search() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Reconcile Location".to_string(), location_path.clone())
+            .unwrap();
+
+        let full_path = location_path.join("notes.md");
+        std::fs::write(&full_path, "# Notes\nIndex me").unwrap();
+
+        let indexed = store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(indexed, 1);
+        assert_eq!(store.search("Index", None, 10).unwrap().len(), 1);
 
-        if file_utils::is_indexable_text_path(&full_path) {
-            let text = file_utils::read_file_text_with_detection(&full_path)?;
-            self.index_document_text(doc_id, &meta, &text)?;
-        } else {
-            self.remove_fts_entry(doc_id)?;
-        }
+        std::fs::remove_file(full_path).unwrap();
 
-        Ok(())
+        let indexed_after_delete = store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(indexed_after_delete, 0);
+        assert!(store.search("Index", None, 10).unwrap().is_empty());
     }
 
-    pub fn reconcile_location_index(&self, location_id: LocationId) -> Result<usize, AppError> {
-        let location = self
-            .location_get(location_id)?
-            .ok_or_else(|| AppError::not_found(format!("Location not found: {:?}", location_id)))?;
+    #[test]
+    fn test_reconcile_and_dir_delete_forget_removed_documents() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-        if !location.root_path.exists() {
-            return Ok(0);
+        let location_dir = TempDir::new().unwrap();
+        let root = location_dir.path();
+        let location = store.location_add("Forget".to_string(), root.to_path_buf()).unwrap();
+        let loose = DocId::new(location.id, PathBuf::from("loose.md")).unwrap();
+        let nested = DocId::new(location.id, PathBuf::from("drafts/nested.md")).unwrap();
+        let kept = DocId::new(location.id, PathBuf::from("kept.md")).unwrap();
+        for doc_id in [&loose, &nested, &kept] {
+            store.doc_save(doc_id, "One two", None).unwrap();
+            store.doc_save(doc_id, "One two three", None).unwrap();
+            store.doc_goal_set(doc_id, 500).unwrap();
+            assert!(store.autosave_put(doc_id, "One two three four").unwrap());
         }
+        let history_rows = |store: &Store| -> i64 {
+            store
+                .conn
+                .lock()
+                .unwrap()
+                .query_row("SELECT COUNT(*) FROM word_count_history", [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(history_rows(&store), 6);
+
+        std::fs::remove_file(root.join("loose.md")).unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(history_rows(&store), 4);
+        assert_eq!(store.doc_goal_get(&loose).unwrap(), None);
+
+        assert!(store.dir_delete(location.id, Path::new("drafts")).unwrap());
+        assert_eq!(history_rows(&store), 2);
+        assert_eq!(store.doc_goal_get(&nested).unwrap(), None);
+        assert_eq!(store.word_count_history(&kept).unwrap().len(), 2);
+        assert_eq!(store.doc_goal_get(&kept).unwrap(), Some(500));
+        let pending: Vec<DocId> = store
+            .autosave_pending()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.doc_id)
+            .collect();
+        assert_eq!(pending, vec![kept]);
+    }
 
-        let mut file_paths = Vec::new();
-        file_utils::collect_file_paths_recursive(&location.root_path, &mut file_paths)?;
-
-        let mut seen_rel_paths = HashSet::new();
-        let mut indexed = 0usize;
+    #[test]
+    fn test_reconcile_location_plan_matches_reconcile_effects() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
 
-        for full_path in file_paths {
-            if !full_path.is_file() {
-                continue;
-            }
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-            let rel_path = full_path
-                .strip_prefix(&location.root_path)
-                .map_err(|_| AppError::invalid_path("File path escaped location root"))?
-                .to_path_buf();
-            let doc_id = DocId::new(location_id, rel_path.clone())?;
-            let rel_path_str = rel_path.to_string_lossy().to_string();
-            seen_rel_paths.insert(rel_path_str);
+        let location = store
+            .location_add("Plan Location".to_string(), location_path.clone())
+            .unwrap();
 
-            let filename = rel_path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-            let meta = self.read_doc_metadata(&full_path, location_id, rel_path, &filename)?;
-            self.update_doc_in_catalog(&doc_id, &meta)?;
+        std::fs::write(location_path.join("keep.md"), "# Keep").unwrap();
+        std::fs::write(location_path.join("edit.md"), "# Edit").unwrap();
+        std::fs::write(location_path.join("gone.md"), "# Gone").unwrap();
 
-            if file_utils::is_indexable_text_path(&full_path) {
-                match file_utils::read_file_text_with_detection(&full_path) {
-                    Ok(text) => {
-                        self.index_document_text(&doc_id, &meta, &text)?;
-                        indexed += 1;
-                    }
-                    Err(error) => {
-                        log::warn!("Skipping FTS index for {:?} after decode failure: {}", full_path, error);
-                        self.remove_fts_entry(&doc_id)?;
-                    }
-                }
-            } else {
-                self.remove_fts_entry(&doc_id)?;
-            }
-        }
+        let initial_plan = store.reconcile_location_plan(location.id).unwrap();
+        assert_eq!(initial_plan.added.len(), 3);
+        assert_eq!(store.reconcile_location_index(location.id).unwrap(), 3);
 
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
+        std::fs::write(location_path.join("edit.md"), "# Edit\nplanupdatedtoken and more text").unwrap();
+        std::fs::create_dir_all(location_path.join("new")).unwrap();
+        std::fs::write(location_path.join("new/fresh.md"), "# Fresh\nplanaddedtoken").unwrap();
+        std::fs::remove_file(location_path.join("gone.md")).unwrap();
 
-        let mut stmt = conn
-            .prepare("SELECT rel_path FROM documents WHERE location_id = ?1")
-            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to read catalog rows: {}", e)))?;
-        let existing = stmt
-            .query_map(params![location_id.0], |row| row.get::<_, String>(0))
-            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to query catalog rows: {}", e)))?;
+        let plan = store.reconcile_location_plan(location.id).unwrap();
+        assert_eq!(plan.added, vec![PathBuf::from("new/fresh.md")]);
+        assert_eq!(plan.updated, vec![PathBuf::from("edit.md")]);
+        assert_eq!(plan.removed, vec![PathBuf::from("gone.md")]);
+        assert_eq!(plan.unchanged, 1);
 
-        let mut stale_rel_paths = Vec::new();
-        for row in existing {
-            let rel_path = row.map_err(|e| AppError::new(ErrorCode::Index, format!("Invalid rel_path row: {}", e)))?;
-            if !seen_rel_paths.contains(&rel_path) {
-                stale_rel_paths.push(rel_path);
-            }
-        }
-        drop(stmt);
+        assert!(store.search("planaddedtoken", None, 10).unwrap().is_empty());
+        assert!(store.search("planupdatedtoken", None, 10).unwrap().is_empty());
+        assert_eq!(store.reconcile_location_plan(location.id).unwrap(), plan);
 
-        for rel_path in stale_rel_paths {
-            conn.execute(
-                "DELETE FROM documents WHERE location_id = ?1 AND rel_path = ?2",
-                params![location_id.0, rel_path.clone()],
-            )
-            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove stale document row: {}", e)))?;
-            conn.execute(
-                "DELETE FROM docs_fts WHERE location_id = ?1 AND rel_path = ?2",
-                params![location_id.0, rel_path],
-            )
-            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to remove stale FTS row: {}", e)))?;
-        }
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(store.search("planaddedtoken", None, 10).unwrap().len(), 1);
+        assert_eq!(store.search("planupdatedtoken", None, 10).unwrap().len(), 1);
+        assert!(store.search("Gone", None, 10).unwrap().is_empty());
 
-        Ok(indexed)
+        let settled = store.reconcile_location_plan(location.id).unwrap();
+        assert!(settled.added.is_empty() && settled.updated.is_empty() && settled.removed.is_empty());
+        assert_eq!(
+            settled.unchanged,
+            plan.added.len() + plan.updated.len() + plan.unchanged
+        );
     }
 
-    pub fn reconcile_indexes(&self) -> Result<usize, AppError> {
-        let locations = self.location_list()?;
-        let mut indexed = 0usize;
+    #[test]
+    fn test_reconcile_skips_unchanged_files() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
 
-        for location in locations {
-            indexed += self.reconcile_location_index(location.id)?;
-        }
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-        Ok(indexed)
-    }
+        let location = store
+            .location_add("Skip Location".to_string(), location_path.clone())
+            .unwrap();
 
-    pub fn search(
-        &self, query: &str, filters: Option<SearchFilters>, limit: usize,
-    ) -> Result<Vec<SearchHit>, AppError> {
-        let normalized_query = query.trim();
-        if normalized_query.is_empty() {
-            return Ok(Vec::new());
-        }
+        std::fs::write(location_path.join("steady.md"), "# Steady\nsteadytoken").unwrap();
+        std::fs::write(location_path.join("moving.md"), "# Moving\nmovingtoken").unwrap();
+        assert_eq!(store.reconcile_location_index(location.id).unwrap(), 2);
 
-        let filters = filters.unwrap_or_default();
-        let SearchFilters { locations, file_types, date_range } = filters;
-        let mut sql = String::from(
-            "SELECT
-                d.location_id,
-                d.rel_path,
-                COALESCE(NULLIF(d.title, ''), d.filename, d.rel_path) AS title,
-                snippet(docs_fts, 3, '<<', '>>', ' ... ', 12) AS snippet,
-                docs_fts.content AS content
-             FROM docs_fts
-             JOIN documents d
-               ON d.location_id = CAST(docs_fts.location_id AS INTEGER)
-              AND d.rel_path = docs_fts.rel_path
-             WHERE docs_fts MATCH ?",
-        );
+        // Dropping the FTS rows shows which files the next pass actually re-reads.
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM docs_fts WHERE location_id = ?1", params![location.id.0])
+            .unwrap();
+        std::fs::write(location_path.join("moving.md"), "# Moving\nmovingtoken, now longer").unwrap();
 
-        let mut query_params: Vec<Value> = vec![Value::from(normalized_query.to_string())];
+        assert_eq!(store.reconcile_location_index(location.id).unwrap(), 1);
+        assert!(store.search("steadytoken", None, 10).unwrap().is_empty());
+        assert_eq!(store.search("movingtoken", None, 10).unwrap().len(), 1);
 
-        if let Some(locations) = locations.filter(|items| !items.is_empty()) {
-            sql.push_str(" AND d.location_id IN (");
-            sql.push_str(&vec!["?"; locations.len()].join(", "));
-            sql.push(')');
-            query_params.extend(locations.into_iter().map(|id| Value::from(id.0)));
-        }
+        store
+            .location_set_indexable_extensions(location.id, Some(vec!["md".to_string()]))
+            .unwrap();
+        assert_eq!(store.reconcile_location_index(location.id).unwrap(), 2);
+        assert_eq!(store.search("steadytoken", None, 10).unwrap().len(), 1);
+    }
 
-        if let Some(file_types) = file_types {
-            let normalized_types = file_types
-                .into_iter()
-                .map(|extension| extension.trim().trim_start_matches('.').to_lowercase())
-                .filter(|extension| !extension.is_empty())
-                .collect::<Vec<_>>();
+    #[test]
+    fn test_reconcile_progress_counts_up_to_total() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
 
-            if !normalized_types.is_empty() {
-                let mut clauses = Vec::new();
-                for extension in normalized_types {
-                    clauses.push("LOWER(d.filename) LIKE ?".to_string());
-                    query_params.push(Value::from(format!("%.{}", extension)));
-                }
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-                sql.push_str(" AND (");
-                sql.push_str(&clauses.join(" OR "));
-                sql.push(')');
-            }
-        }
+        let location = store
+            .location_add("Progress Location".to_string(), location_path.clone())
+            .unwrap();
 
-        if let Some(date_range) = date_range {
-            if let Some(from) = date_range.from.filter(|value| !value.is_empty()) {
-                sql.push_str(" AND d.updated_at >= ?");
-                query_params.push(Value::from(from));
-            }
-            if let Some(to) = date_range.to.filter(|value| !value.is_empty()) {
-                sql.push_str(" AND d.updated_at <= ?");
-                query_params.push(Value::from(to));
-            }
+        std::fs::create_dir_all(location_path.join("nested")).unwrap();
+        for rel_path in ["one.md", "two.md", "nested/three.md", "nested/four.txt"] {
+            std::fs::write(location_path.join(rel_path), "progresstoken").unwrap();
         }
 
-        let bounded_limit = limit.clamp(1, 200);
-        sql.push_str(" ORDER BY bm25(docs_fts), d.mtime DESC LIMIT ?");
-        query_params.push(Value::from(bounded_limit as i64));
-
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| AppError::new(ErrorCode::Io, "Failed to lock database connection"))?;
-
-        let mut stmt = conn
-            .prepare(&sql)
-            .map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to prepare search query: {}", e)))?;
+        let cancelled = AtomicBool::new(true);
+        let mut reports = Vec::new();
+        let outcome = store
+            .reconcile_location_index_with_progress(location.id, &cancelled, |processed, total| {
+                reports.push((processed, total))
+            })
+            .unwrap();
+        assert_eq!(outcome, None);
+        assert_eq!(reports, vec![(0, 4)]);
+        assert!(store.search("progresstoken", None, 10).unwrap().is_empty());
+
+        let mut reports = Vec::new();
+        let outcome = store
+            .reconcile_location_index_with_progress(location.id, &AtomicBool::new(false), |processed, total| {
+                reports.push((processed, total))
+            })
+            .unwrap();
+        assert_eq!(outcome, Some(4));
+        assert_eq!(reports.first(), Some(&(0, 4)));
+        assert_eq!(reports.last(), Some(&(4, 4)));
+        assert!(reports.windows(2).all(|pair| pair[1].0 == pair[0].0 + 1));
+        assert!(reports.iter().all(|&(_, total)| total == 4));
+        assert_eq!(store.search("progresstoken", None, 10).unwrap().len(), 4);
+    }
 
-        let rows = stmt
-            .query_map(params_from_iter(query_params.iter()), |row| {
-                let location_id: i64 = row.get(0)?;
-                let rel_path: String = row.get(1)?;
-                let title: String = row.get(2)?;
-                let snippet_marked: String = row.get(3)?;
-                let full_content: String = row.get(4)?;
-                let (snippet, matches) = text_utils::extract_highlight_matches(&snippet_marked);
-                let (line, column) = text_utils::locate_query_position(&full_content, normalized_query);
+    #[test]
+    fn test_doc_save_rolls_back_catalog_when_fts_update_fails() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
 
-                Ok(SearchHit { location_id: LocationId(location_id), rel_path, title, snippet, line, column, matches })
-            })
-            .map_err(|e| AppError::new(ErrorCode::Index, format!("Search query failed: {}", e)))?;
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-        let mut hits = Vec::new();
-        for row in rows {
-            let hit = row.map_err(|e| AppError::new(ErrorCode::Index, format!("Failed to parse search hit: {}", e)))?;
-            hits.push(hit);
-        }
+        let location = store
+            .location_add("Transaction Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+        store.doc_save(&doc_id, "# First Draft", None).unwrap();
+
+        let catalog_title = |store: &Store, rel_path: &str| -> Option<String> {
+            let conn = store.conn.lock().unwrap();
+            conn.query_row(
+                "SELECT title FROM documents WHERE location_id = ?1 AND rel_path = ?2",
+                params![location.id.0, rel_path],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()
+            .unwrap()
+            .flatten()
+        };
+        assert_eq!(catalog_title(&store, "draft.md"), Some("First Draft".to_string()));
 
-        Ok(hits)
-    }
-}
+        store.conn.lock().unwrap().execute("DROP TABLE docs_fts", []).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+        assert!(store.doc_save(&doc_id, "# Second Draft", None).is_err());
+        assert_eq!(catalog_title(&store, "draft.md"), Some("First Draft".to_string()));
 
-    fn create_test_store() -> (Store, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let db_path = temp_dir.path().join("test.db");
-        let store = Store::open(&db_path).unwrap();
-        (store, temp_dir)
+        let new_doc_id = DocId::new(location.id, PathBuf::from("fresh.md")).unwrap();
+        assert!(store.doc_save(&new_doc_id, "# Fresh", None).is_err());
+        assert_eq!(catalog_title(&store, "fresh.md"), None);
+        assert!(store.reindex_document(&new_doc_id).is_err());
+        assert_eq!(catalog_title(&store, "fresh.md"), None);
     }
 
     #[test]
-    fn test_location_add_and_list() {
+    fn test_resolve_alias_finds_document_declaring_alias() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
         let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
+            .location_add("Alias Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        assert_eq!(location.name, "Test Location");
-        assert_eq!(location.root_path, location_path);
+        let doc_id = DocId::new(location.id, PathBuf::from("notes/foo-note.md")).unwrap();
+        store
+            .doc_save(&doc_id, "---\naliases: [Foo, Bar]\n---\n\n# Foo Note", None)
+            .unwrap();
 
-        let locations = store.location_list().unwrap();
-        assert_eq!(locations.len(), 1);
-        assert_eq!(locations[0].name, "Test Location");
-        assert_eq!(locations[0].root_path, location_path);
+        let resolved = store.resolve_alias(location.id, "foo").unwrap().unwrap();
+        assert_eq!(resolved.id.rel_path, PathBuf::from("notes/foo-note.md"));
+        assert_eq!(resolved.title, Some("Foo Note".to_string()));
+        assert!(store.resolve_alias(location.id, "Bar").unwrap().is_some());
+        assert!(store.resolve_alias(location.id, "Baz").unwrap().is_none());
+
+        store.doc_save(&doc_id, "# Foo Note\nAliases removed.", None).unwrap();
+        assert!(store.resolve_alias(location.id, "Foo").unwrap().is_none());
     }
 
     #[test]
-    fn test_location_duplicate() {
+    fn test_doc_open_truncates_oversized_file_on_char_boundary_and_skips_index() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
 
-        store.location_add("First".to_string(), location_path.clone()).unwrap();
+        let settings = UiLayoutSettings {
+            create_readme_in_new_locations: false,
+            max_open_bytes: 64,
+            ..UiLayoutSettings::default()
+        };
+        store.ui_layout_set(&settings).unwrap();
 
-        let result = store.location_add("Second".to_string(), location_path);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err().code, ErrorCode::Conflict);
+        let location = store
+            .location_add("Large Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let large_text = format!("largetoken {}", "é".repeat(100));
+        std::fs::write(location_dir.path().join("large.log.md"), &large_text).unwrap();
+        std::fs::write(location_dir.path().join("small.md"), "smalltoken").unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("large.log.md")).unwrap();
+        let content = store.doc_open(&doc_id).unwrap();
+        assert!(content.truncated);
+        assert_eq!(content.text, format!("largetoken {}", "é".repeat(26)));
+        assert!(!content.text.contains('\u{fffd}'));
+        assert_eq!(content.meta.size_bytes, large_text.len() as u64);
+
+        let small_id = DocId::new(location.id, PathBuf::from("small.md")).unwrap();
+        assert!(!store.doc_open(&small_id).unwrap().truncated);
+
+        assert!(store.search("largetoken", None, 10).unwrap().is_empty());
+        assert_eq!(store.search("smalltoken", None, 10).unwrap().len(), 1);
     }
 
     #[test]
-    fn test_location_remove() {
+    fn test_doc_save_refuses_to_overwrite_truncated_document() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
-        let location = store.location_add("Test".to_string(), location_path).unwrap();
-        let removed = store.location_remove(location.id).unwrap();
-        assert!(removed);
 
-        let locations = store.location_list().unwrap();
-        assert!(locations.is_empty());
+        let settings = UiLayoutSettings {
+            create_readme_in_new_locations: false,
+            max_open_bytes: 64,
+            ..UiLayoutSettings::default()
+        };
+        store.ui_layout_set(&settings).unwrap();
+        let location = store
+            .location_add("Large Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let large_text = "word ".repeat(40);
+        let large_path = location_dir.path().join("large.md");
+        std::fs::write(&large_path, &large_text).unwrap();
 
-        let removed_again = store.location_remove(location.id).unwrap();
-        assert!(!removed_again);
+        let doc_id = DocId::new(location.id, PathBuf::from("large.md")).unwrap();
+        let content = store.doc_open(&doc_id).unwrap();
+        assert!(content.truncated);
+
+        let error = store.doc_save(&doc_id, &content.text, None).unwrap_err();
+        assert_eq!(error.code, ErrorCode::Conflict);
+        assert_eq!(std::fs::read_to_string(&large_path).unwrap(), large_text);
+
+        let small_id = DocId::new(location.id, PathBuf::from("small.md")).unwrap();
+        store.doc_save(&small_id, &large_text, None).unwrap();
+        assert!(store.doc_save(&small_id, "short", None).is_err());
     }
 
     #[test]
-    fn test_location_get() {
+    fn test_ignore_globs_skip_directories_and_prune_reconciled_files() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
 
-        let location = store.location_add("Test".to_string(), location_path.clone()).unwrap();
-        let retrieved = store.location_get(location.id).unwrap();
-        assert!(retrieved.is_some());
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-        let retrieved = retrieved.unwrap();
-        assert_eq!(retrieved.name, "Test");
-        assert_eq!(retrieved.root_path, location_path);
+        let location = store
+            .location_add("Ignore Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        assert_eq!(location.ignore_globs, vec![".*/".to_string()]);
+
+        for rel_path in [
+            "notes/keep.md",
+            "node_modules/pkg/readme.md",
+            ".git/notes.md",
+            "drafts/old/skip.md",
+        ] {
+            let full_path = location_dir.path().join(rel_path);
+            std::fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+            std::fs::write(&full_path, format!("# {}", rel_path)).unwrap();
+        }
 
-        let not_found = store.location_get(LocationId(999)).unwrap();
-        assert!(not_found.is_none());
+        let rel_paths = |store: &Store| {
+            let conn = store.conn.lock().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT rel_path FROM documents WHERE location_id = ?1 ORDER BY rel_path")
+                .unwrap();
+            stmt.query_map(params![location.id.0], |row| row.get::<_, String>(0))
+                .unwrap()
+                .map(|rel_path| PathBuf::from(rel_path.unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(
+            rel_paths(&store),
+            vec![
+                PathBuf::from("drafts/old/skip.md"),
+                PathBuf::from("node_modules/pkg/readme.md"),
+                PathBuf::from("notes/keep.md"),
+            ]
+        );
+
+        let updated = store
+            .location_set_ignore_globs(
+                location.id,
+                vec![
+                    "node_modules/".to_string(),
+                    " /drafts/** ".to_string(),
+                    ".*/".to_string(),
+                ],
+            )
+            .unwrap();
+        assert_eq!(updated.ignore_globs, vec!["node_modules/", "/drafts/**", ".*/"]);
+
+        let plan = store.reconcile_location_plan(location.id).unwrap();
+        assert_eq!(
+            plan.removed,
+            vec![
+                PathBuf::from("drafts/old/skip.md"),
+                PathBuf::from("node_modules/pkg/readme.md")
+            ]
+        );
+
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(rel_paths(&store), vec![PathBuf::from("notes/keep.md")]);
+
+        let ignored_id = DocId::new(location.id, PathBuf::from("node_modules/pkg/readme.md")).unwrap();
+        store.reindex_document(&ignored_id).unwrap();
+        assert_eq!(rel_paths(&store), vec![PathBuf::from("notes/keep.md")]);
     }
 
     #[test]
-    fn test_validate_locations() {
+    fn test_indexable_extensions_allowlist_controls_search_index() {
         let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
 
-        let existing_dir = TempDir::new().unwrap();
-        let _ = store
-            .location_add("Existing".to_string(), existing_dir.path().to_path_buf())
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let location = store
+            .location_add("Allowlist Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
+        assert_eq!(location.indexable_extensions, None);
 
-        let non_existent_path = PathBuf::from("/non/existent/path/12345");
-        let non_existent = store
-            .location_add("NonExistent".to_string(), non_existent_path.clone())
+        std::fs::write(location_dir.path().join("note.md"), "# Note\nmdtoken").unwrap();
+        std::fs::write(location_dir.path().join("plain.txt"), "txttoken").unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(store.search("mdtoken", None, 10).unwrap().len(), 1);
+
+        let updated = store
+            .location_set_indexable_extensions(location.id, Some(vec![".TXT".to_string(), "txt".to_string()]))
             .unwrap();
+        assert_eq!(updated.indexable_extensions, Some(vec!["txt".to_string()]));
+        assert_eq!(
+            store.location_get(location.id).unwrap().unwrap().indexable_extensions,
+            Some(vec!["txt".to_string()])
+        );
 
-        let missing = store.validate_locations().unwrap();
-        assert_eq!(missing.len(), 1);
-        assert_eq!(missing[0].0, non_existent.id);
-        assert_eq!(missing[0].1, non_existent_path);
+        store.reconcile_location_index(location.id).unwrap();
+        assert!(store.search("mdtoken", None, 10).unwrap().is_empty());
+        assert_eq!(store.search("txttoken", None, 10).unwrap().len(), 1);
+
+        let doc_list = store.doc_list(location.id, None).unwrap();
+        let note = doc_list.iter().find(|meta| meta.filename == "note.md").unwrap();
+        assert_eq!(note.word_count, None);
+
+        let reset = store.location_set_indexable_extensions(location.id, None).unwrap();
+        assert_eq!(reset.indexable_extensions, None);
+        store.reconcile_location_index(location.id).unwrap();
+        assert_eq!(store.search("mdtoken", None, 10).unwrap().len(), 1);
     }
 
     #[test]
-    fn test_doc_list_shallow() {
+    fn test_list_conflicts_returns_conflicted_copies_across_locations() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
 
         let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
         store.ui_layout_set(&settings).unwrap();
 
         let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
+            .location_add("Conflict Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        std::fs::write(location_path.join("file1.md"), "# File 1").unwrap();
-        std::fs::write(location_path.join("file2.txt"), "File 2 content").unwrap();
+        std::fs::write(location_dir.path().join("draft.md"), "# Draft").unwrap();
+        std::fs::create_dir_all(location_dir.path().join("notes")).unwrap();
+        std::fs::write(
+            location_dir.path().join("notes/draft (conflicted copy).md"),
+            "# Draft\nTheirs",
+        )
+        .unwrap();
 
-        let docs = store.doc_list(location.id, None).unwrap();
-        assert_eq!(docs.len(), 2);
+        assert!(store.list_conflicts().unwrap().is_empty());
+
+        store.reconcile_location_index(location.id).unwrap();
+
+        let conflicts = store.list_conflicts().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id.location_id, location.id);
+        assert_eq!(
+            conflicts[0].id.rel_path,
+            PathBuf::from("notes/draft (conflicted copy).md")
+        );
+        assert!(conflicts[0].is_conflict);
+        assert_eq!(conflicts[0].title, Some("Draft".to_string()));
     }
 
     #[test]
-    fn test_doc_list_recursive() {
+    fn test_conflict_patterns_setting_detects_syncthing_copies() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
 
         let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
         store.ui_layout_set(&settings).unwrap();
 
         let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
+            .location_add("Syncthing Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        std::fs::write(location_path.join("file1.md"), "# File 1").unwrap();
-        std::fs::create_dir(location_path.join("subdir")).unwrap();
-        std::fs::write(location_path.join("subdir/file2.md"), "# File 2").unwrap();
+        let filename = "draft.sync-conflict-20240115-101500-ABCDEFG.md";
+        std::fs::write(location_dir.path().join("draft.md"), "# Draft").unwrap();
+        std::fs::write(location_dir.path().join(filename), "# Draft\nTheirs").unwrap();
+        std::fs::write(location_dir.path().join("notes (conflicted copy).md"), "# Notes").unwrap();
 
-        let options = DocListOptions { recursive: true, ..Default::default() };
-        let docs = store.doc_list(location.id, Some(options)).unwrap();
-        assert_eq!(docs.len(), 2);
+        store.reconcile_location_index(location.id).unwrap();
+        let conflicts = store.list_conflicts().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id.rel_path, PathBuf::from("notes (conflicted copy).md"));
+
+        store
+            .ui_layout_set(&UiLayoutSettings { conflict_patterns: vec!["Sync-Conflict".to_string()], ..settings })
+            .unwrap();
+        assert_eq!(
+            store.ui_layout_get().unwrap().conflict_patterns,
+            vec!["Sync-Conflict".to_string()]
+        );
+
+        store.reconcile_location_index(location.id).unwrap();
+        let mut conflicts: Vec<PathBuf> = store
+            .list_conflicts()
+            .unwrap()
+            .into_iter()
+            .map(|meta| meta.id.rel_path)
+            .collect();
+        conflicts.sort();
+        assert_eq!(
+            conflicts,
+            vec![PathBuf::from(filename), PathBuf::from("notes (conflicted copy).md")]
+        );
+
+        let doc_id = DocId::new(location.id, PathBuf::from(filename)).unwrap();
+        let result = store.doc_save(&doc_id, "# Draft\nMerged", None).unwrap();
+        assert!(result.conflict_detected);
     }
 
     #[test]
-    fn test_doc_list_with_extension_filter() {
+    fn test_render_many_renders_each_document_in_order() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
 
         let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
         store.ui_layout_set(&settings).unwrap();
 
         let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
+            .location_add("Render Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        std::fs::write(location_path.join("file1.md"), "# File 1").unwrap();
-        std::fs::write(location_path.join("file2.txt"), "File 2").unwrap();
-        std::fs::write(location_path.join("file3.rs"), "fn main() {}").unwrap();
+        std::fs::write(location_dir.path().join("one.md"), "# One\n\nFirst.").unwrap();
+        std::fs::write(location_dir.path().join("two.md"), "# Two\n\nSecond.").unwrap();
+        std::fs::create_dir_all(location_dir.path().join("nested")).unwrap();
+        std::fs::write(location_dir.path().join("nested/three.md"), "# Three\n\nThird.").unwrap();
+
+        let doc = |rel_path: &str| DocId::new(location.id, PathBuf::from(rel_path)).unwrap();
+        let results = store.render_many(vec![
+            (doc("one.md"), MarkdownProfile::Extended),
+            (doc("two.md"), MarkdownProfile::GfmSafe),
+            (doc("nested/three.md"), MarkdownProfile::Extended),
+        ]);
+
+        let titles: Vec<Option<String>> = results
+            .into_iter()
+            .map(|result| result.unwrap().metadata.title)
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                Some("One".to_string()),
+                Some("Two".to_string()),
+                Some("Three".to_string())
+            ]
+        );
 
-        let options =
-            DocListOptions { recursive: false, extensions: Some(vec!["md".to_string()]), ..Default::default() };
-        let docs = store.doc_list(location.id, Some(options)).unwrap();
-        assert_eq!(docs.len(), 1);
-        assert_eq!(docs[0].filename, "file1.md");
+        let results = store.render_many(vec![
+            (doc("missing.md"), MarkdownProfile::Extended),
+            (doc("one.md"), MarkdownProfile::Extended),
+        ]);
+        assert_eq!(results[0].as_ref().unwrap_err().code, ErrorCode::NotFound);
+        assert_eq!(results[1].as_ref().unwrap().metadata.title, Some("One".to_string()));
+        assert!(store.render_many(Vec::new()).is_empty());
     }
 
     #[test]
-    fn test_doc_open() {
+    fn test_doc_open_raw_flags_binary_content() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
         let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
+            .location_add("Raw Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        let content = "# Test Document\n\nThis is a test.";
-        std::fs::write(location_path.join("test.md"), content).unwrap();
+        let invalid = b"# Notes\r\n\xc3\x28 broken\r\n".to_vec();
+        std::fs::write(location_dir.path().join("broken.md"), &invalid).unwrap();
+        std::fs::write(location_dir.path().join("nul.md"), b"# Notes\n\0\n").unwrap();
+        std::fs::write(location_dir.path().join("text.md"), "\u{feff}# Notes\nCafé\n").unwrap();
 
-        let doc_id = DocId::new(location.id, PathBuf::from("test.md")).unwrap();
-        let doc_content = store.doc_open(&doc_id).unwrap();
+        let doc = |rel_path: &str| DocId::new(location.id, PathBuf::from(rel_path)).unwrap();
 
-        assert_eq!(doc_content.text, content);
-        assert_eq!(doc_content.meta.filename, "test.md");
-        assert_eq!(doc_content.meta.word_count, Some(7));
-        assert_eq!(doc_content.meta.title, Some("Test Document".to_string()));
+        let raw = store.doc_open_raw(&doc("broken.md")).unwrap();
+        assert_eq!(raw.bytes, invalid);
+        assert_eq!(raw.encoding, Encoding::Utf8);
+        assert_eq!(raw.line_ending, LineEnding::CrLf);
+        assert!(!raw.is_valid_text);
+        assert!(raw.is_binary);
+
+        let raw = store.doc_open_raw(&doc("nul.md")).unwrap();
+        assert!(raw.is_valid_text);
+        assert!(raw.is_binary);
+
+        let raw = store.doc_open_raw(&doc("text.md")).unwrap();
+        assert_eq!(raw.encoding, Encoding::Utf8WithBom);
+        assert_eq!(raw.line_ending, LineEnding::Lf);
+        assert!(raw.is_valid_text);
+        assert!(!raw.is_binary);
+        assert!(!raw.truncated);
+
+        assert_eq!(
+            store.doc_open_raw(&doc("missing.md")).unwrap_err().code,
+            ErrorCode::NotFound
+        );
     }
 
     #[test]
-    fn test_doc_open_uses_markdown_front_matter_title_and_excludes_fm_from_word_count() {
-        let (store, _temp) = create_test_store();
+    fn test_resolve_wikilink_prefers_paths_then_aliases() {
+        let (store, temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
         let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
+            .location_add("Wiki Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        let content = "---\ntitle: Front Matter Title\n---\n\nBody words only";
-        std::fs::write(location_path.join("frontmatter.md"), content).unwrap();
+        for (rel_path, text) in [
+            ("My Note.md", "# My Note"),
+            ("archive/My Note.md", "# Old Note"),
+            ("people/ada.md", "---\naliases: [Ada Lovelace]\n---\n# Ada"),
+            ("Café.md", "# Café"),
+        ] {
+            let doc_id = DocId::new(location.id, PathBuf::from(rel_path)).unwrap();
+            store.doc_save(&doc_id, text, None).unwrap();
+        }
 
-        let doc_id = DocId::new(location.id, PathBuf::from("frontmatter.md")).unwrap();
-        let doc_content = store.doc_open(&doc_id).unwrap();
+        assert_eq!(
+            store.resolve_wikilink(location.id, "my note").unwrap(),
+            Some(PathBuf::from("My Note.md"))
+        );
+        assert_eq!(
+            store.resolve_wikilink(location.id, "archive/My Note").unwrap(),
+            Some(PathBuf::from("archive/My Note.md"))
+        );
+        assert_eq!(
+            store.resolve_wikilink(location.id, "Ada Lovelace").unwrap(),
+            Some(PathBuf::from("people/ada.md"))
+        );
+        assert_eq!(store.resolve_wikilink(location.id, "Nobody").unwrap(), None);
+        assert_eq!(
+            store.resolve_wikilink(location.id, "CAFÉ").unwrap(),
+            Some(PathBuf::from("Café.md"))
+        );
 
-        assert_eq!(doc_content.meta.title, Some("Front Matter Title".to_string()));
-        assert_eq!(doc_content.meta.word_count, Some(3));
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute("UPDATE documents SET link_key = NULL", [])
+            .unwrap();
+        let reopened = Store::open(&temp.path().join("test.db")).unwrap();
+        assert_eq!(
+            reopened.resolve_wikilink(location.id, "My Note.md").unwrap(),
+            Some(PathBuf::from("My Note.md"))
+        );
     }
 
     #[test]
-    fn test_doc_save_atomic() {
+    fn test_doc_merge_appends_source_and_removes_it() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
         let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
+            .location_add("Merge Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        let doc_id = DocId::new(location.id, PathBuf::from("new_file.md")).unwrap();
-        let content = "# New Document\n\nContent here.";
-        let result = store.doc_save(&doc_id, content, None).unwrap();
-        assert!(result.success);
-        assert!(result.new_meta.is_some());
-        assert!(!result.conflict_detected);
+        let target = DocId::new(location.id, PathBuf::from("target.md")).unwrap();
+        let source = DocId::new(location.id, PathBuf::from("notes/source.md")).unwrap();
+        store
+            .doc_save(&target, "---\ntitle: Target\n---\n\n# Target\n\nFirst part.\n\n", None)
+            .unwrap();
+        store
+            .doc_save(&source, "---\ntitle: Source\n---\n\nmergedtoken second part.\n", None)
+            .unwrap();
 
-        let saved_path = location_path.join("new_file.md");
-        assert!(saved_path.exists());
-        let saved_content = std::fs::read_to_string(saved_path).unwrap();
-        assert_eq!(saved_content, content);
+        let meta = store.doc_merge(&target, &source, "", true).unwrap();
+        assert_eq!(meta.title, Some("Target".to_string()));
+        assert_eq!(
+            std::fs::read_to_string(location_dir.path().join("target.md")).unwrap(),
+            "---\ntitle: Target\n---\n\n# Target\n\nFirst part.\n\n---\n\nmergedtoken second part.\n"
+        );
+        assert!(!location_dir.path().join("notes/source.md").exists());
+
+        let hits = store.search("mergedtoken", None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rel_path, "target.md");
+
+        let error = store.doc_merge(&target, &source, "", true).unwrap_err();
+        assert!(matches!(error.code, ErrorCode::NotFound));
+
+        let error = store.doc_merge(&target, &target, "", true).unwrap_err();
+        assert!(matches!(error.code, ErrorCode::InvalidPath));
+
+        store.doc_save(&source, "A long source document", None).unwrap();
+        let settings = UiLayoutSettings { max_open_bytes: 8, ..store.ui_layout_get().unwrap() };
+        store.ui_layout_set(&settings).unwrap();
+        let error = store.doc_merge(&target, &source, "", true).unwrap_err();
+        assert!(matches!(error.code, ErrorCode::Parse));
+        assert!(matches!(
+            store
+                .doc_split_by_heading(&target, 1, SplitNaming::Slug, true, false)
+                .unwrap_err()
+                .code,
+            ErrorCode::Parse
+        ));
+        assert!(location_dir.path().join("notes/source.md").exists());
     }
 
     #[test]
-    fn test_doc_save_overwrite() {
+    fn test_doc_list_breaks_ties_by_path() {
         let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
+        let root = location_dir.path();
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        let shared = std::time::SystemTime::now() - std::time::Duration::from_secs(60 * 60);
+        let older = shared - std::time::Duration::from_secs(60 * 60);
+        for (rel_path, mtime) in [
+            ("b.md", shared),
+            ("sub/a.md", shared),
+            ("a.md", shared),
+            ("old.md", older),
+        ] {
+            std::fs::write(root.join(rel_path), "Same size").unwrap();
+            File::options()
+                .write(true)
+                .open(root.join(rel_path))
+                .unwrap()
+                .set_modified(mtime)
+                .unwrap();
+        }
+        let location = store.location_add("Ties".to_string(), root.to_path_buf()).unwrap();
+
+        let listed = |options: DocListOptions| -> Vec<String> {
+            store
+                .doc_list(location.id, Some(options))
+                .unwrap()
+                .iter()
+                .map(|doc| doc.id.rel_path.to_string_lossy().to_string())
+                .collect()
+        };
 
-        let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
-            .unwrap();
+        assert_eq!(listed(DocListOptions::default()), vec!["a.md", "b.md", "old.md"]);
+        assert_eq!(
+            listed(DocListOptions { recursive: true, ..DocListOptions::default() }),
+            vec!["a.md", "b.md", "sub/a.md", "old.md"]
+        );
+        assert_eq!(
+            listed(DocListOptions { recursive: true, sort_order: SortOrder::Ascending, ..DocListOptions::default() }),
+            vec!["old.md", "a.md", "b.md", "sub/a.md"]
+        );
+        assert_eq!(
+            listed(DocListOptions { recursive: true, sort_by: Some(DocSortField::Size), ..DocListOptions::default() }),
+            vec!["a.md", "b.md", "old.md", "sub/a.md"]
+        );
+        assert_eq!(
+            listed(DocListOptions { recursive: true, sort_by: Some(DocSortField::Name), ..DocListOptions::default() }),
+            vec!["a.md", "sub/a.md", "b.md", "old.md"]
+        );
+    }
 
-        let doc_id = DocId::new(location.id, PathBuf::from("overwrite.md")).unwrap();
-        store.doc_save(&doc_id, "Initial content", None).unwrap();
+    #[test]
+    fn test_maintenance_purge_empties_old_trash_only() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-        let new_content = "Updated content here";
-        let result = store.doc_save(&doc_id, new_content, None).unwrap();
+        let location_dir = TempDir::new().unwrap();
+        let root = location_dir.path();
+        std::fs::create_dir_all(root.join(".trash/nested")).unwrap();
+        std::fs::write(root.join("live.md"), "Live").unwrap();
+        std::fs::write(root.join(".trash/recent.md"), "Recent").unwrap();
+        std::fs::write(root.join(".trash/nested/old.md"), "Old text").unwrap();
+        let ten_days_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(10 * 24 * 60 * 60);
+        for path in [root.join("live.md"), root.join(".trash/nested/old.md")] {
+            File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(ten_days_ago)
+                .unwrap();
+        }
+        let location = store.location_add("Trash".to_string(), root.to_path_buf()).unwrap();
+        let old = DocId::new(location.id, PathBuf::from(".trash/nested/old.md")).unwrap();
 
-        assert!(result.success);
+        let policy = PurgePolicy { trash_older_than_days: 7, dry_run: true, ..PurgePolicy::default() };
+        let report = store.maintenance_purge(policy.clone()).unwrap();
+        assert_eq!(report.removed, vec![old.clone()]);
+        assert_eq!(report.reclaimed_bytes, "Old text".len() as u64);
+        assert!(root.join(".trash/nested/old.md").exists());
 
-        let saved_path = location_path.join("overwrite.md");
-        let saved_content = std::fs::read_to_string(saved_path).unwrap();
-        assert_eq!(saved_content, new_content);
+        let report = store
+            .maintenance_purge(PurgePolicy { dry_run: false, ..policy })
+            .unwrap();
+        assert!(!report.dry_run);
+        assert_eq!(report.removed, vec![old]);
+        assert!(report.failed.is_empty());
+        assert!(!root.join(".trash/nested").exists());
+        assert!(root.join(".trash/recent.md").exists());
+        assert!(root.join("live.md").exists());
+
+        let report = store.maintenance_purge(PurgePolicy::default()).unwrap();
+        assert_eq!(report.removed.len(), 1);
+        assert!(root.join(".trash").is_dir());
+        assert!(!root.join(".trash/recent.md").exists());
+        assert!(root.join("live.md").exists());
     }
 
     #[test]
-    fn test_doc_save_creates_directories() {
+    fn test_maintenance_purge_ages_trash_from_trash_time() {
         let (store, _temp) = create_test_store();
-        let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-        let location = store
-            .location_add("Test Location".to_string(), location_path.clone())
+        let location_dir = TempDir::new().unwrap();
+        let root = location_dir.path();
+        std::fs::write(root.join("notes.md"), "# One\nFirst\n# Two\nSecond\n").unwrap();
+        let sixty_days_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(60 * 24 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(root.join("notes.md"))
+            .unwrap()
+            .set_modified(sixty_days_ago)
             .unwrap();
+        let location = store.location_add("Trash".to_string(), root.to_path_buf()).unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("notes.md")).unwrap();
 
-        let doc_id = DocId::new(location.id, PathBuf::from("level1/level2/file.md")).unwrap();
-        let result = store.doc_save(&doc_id, "Nested content", None);
+        store
+            .doc_split_by_heading(&doc_id, 1, SplitNaming::Slug, true, true)
+            .unwrap();
+        assert!(root.join(".trash/notes.md").exists());
 
-        assert!(result.is_ok());
-        assert!(location_path.join("level1/level2/file.md").exists());
+        let report = store
+            .maintenance_purge(PurgePolicy { trash_older_than_days: 30, ..PurgePolicy::default() })
+            .unwrap();
+        assert!(report.removed.is_empty());
+        assert!(root.join(".trash/notes.md").exists());
     }
 
     #[test]
-    fn test_doc_move_to_different_location() {
+    fn test_maintenance_purge_prunes_word_count_history() {
         let (store, _temp) = create_test_store();
-        let source_dir = TempDir::new().unwrap();
-        let target_dir = TempDir::new().unwrap();
-
         let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
         store.ui_layout_set(&settings).unwrap();
 
-        let source_location = store
-            .location_add("Source".to_string(), source_dir.path().to_path_buf())
+        let location_dir = TempDir::new().unwrap();
+        let location = store
+            .location_add("History".to_string(), location_dir.path().to_path_buf())
             .unwrap();
-        let target_location = store
-            .location_add("Target".to_string(), target_dir.path().to_path_buf())
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+        for text in ["One", "One two", "One two three", "One two three four"] {
+            store.doc_save(&doc_id, text, None).unwrap();
+        }
+        let old = (Utc::now() - chrono::Duration::days(90)).to_rfc3339();
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE word_count_history SET recorded_at = ?1 WHERE word_count = 1",
+                params![old],
+            )
             .unwrap();
+        let counts = |store: &Store| -> Vec<usize> {
+            store
+                .word_count_history(&doc_id)
+                .unwrap()
+                .iter()
+                .map(|(_, count)| *count)
+                .collect()
+        };
 
-        let source_doc_id = DocId::new(source_location.id, PathBuf::from("notes/source.md")).unwrap();
-        store
-            .doc_save(&source_doc_id, "# Cross Location\n\nMove me safely.", None)
+        let report = store.maintenance_purge(PurgePolicy::default()).unwrap();
+        assert_eq!(report.history_points_removed, 0);
+
+        let policy = PurgePolicy { history_older_than_days: Some(30), dry_run: true, ..PurgePolicy::default() };
+        let report = store.maintenance_purge(policy.clone()).unwrap();
+        assert_eq!(report.history_points_removed, 1);
+        assert_eq!(counts(&store), vec![1, 2, 3, 4]);
+
+        let report = store
+            .maintenance_purge(PurgePolicy { dry_run: false, ..policy })
+            .unwrap();
+        assert_eq!(report.history_points_removed, 1);
+        assert_eq!(counts(&store), vec![2, 3, 4]);
+
+        let report = store
+            .maintenance_purge(PurgePolicy { history_max_per_doc: Some(2), ..PurgePolicy::default() })
             .unwrap();
+        assert_eq!(report.history_points_removed, 1);
+        assert_eq!(counts(&store), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_tag_cloud_groups_case_and_skips_trash() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-        let moved_meta = store
-            .doc_move_to_location(&source_doc_id, target_location.id, Path::new("archive/moved.md"))
+        let first_dir = TempDir::new().unwrap();
+        let second_dir = TempDir::new().unwrap();
+        let first = store
+            .location_add("First".to_string(), first_dir.path().to_path_buf())
+            .unwrap();
+        let second = store
+            .location_add("Second".to_string(), second_dir.path().to_path_buf())
             .unwrap();
 
-        assert_eq!(moved_meta.id.location_id, target_location.id);
-        assert_eq!(moved_meta.id.rel_path, PathBuf::from("archive/moved.md"));
-        assert!(!source_dir.path().join("notes/source.md").exists());
-        assert!(target_dir.path().join("archive/moved.md").exists());
-
-        let moved_doc_id = DocId::new(target_location.id, PathBuf::from("archive/moved.md")).unwrap();
-        let moved_doc = store.doc_open(&moved_doc_id).unwrap();
-        assert_eq!(moved_doc.text, "# Cross Location\n\nMove me safely.");
+        let save = |location_id: LocationId, rel_path: &str, text: &str| {
+            let doc_id = DocId::new(location_id, PathBuf::from(rel_path)).unwrap();
+            store.doc_save(&doc_id, text, None).unwrap();
+        };
+        save(first.id, "a.md", "Notes #Draft #ideas");
+        save(first.id, "b.md", "---\ntags: [draft]\n---\n\nBody");
+        save(first.id, "c.md", "More #draft");
+        save(first.id, ".trash/old.md", "Gone #draft #archived");
+        save(second.id, "d.md", "Elsewhere #ideas #zebra");
 
-        let source_docs = store.doc_list(source_location.id, None).unwrap();
-        assert!(source_docs.is_empty());
+        assert_eq!(
+            store.tag_cloud(None).unwrap(),
+            vec![
+                ("draft".to_string(), 3),
+                ("ideas".to_string(), 2),
+                ("zebra".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            store.tag_cloud(Some(second.id)).unwrap(),
+            vec![("ideas".to_string(), 1), ("zebra".to_string(), 1)]
+        );
     }
 
     #[test]
-    fn test_directory_create_and_delete() {
+    fn test_tag_apply_bulk_adds_and_removes_across_documents() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
         let location = store
-            .location_add("Directory Ops".to_string(), location_dir.path().to_path_buf())
+            .location_add("Test Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let first = DocId::new(location.id, PathBuf::from("first.md")).unwrap();
+        let second = DocId::new(location.id, PathBuf::from("second.md")).unwrap();
+        let missing = DocId::new(location.id, PathBuf::from("missing.md")).unwrap();
+        store
+            .doc_save(&first, "---\ntags: [draft]\n---\n\nFirst", None)
             .unwrap();
+        store.doc_save(&second, "Second #draft", None).unwrap();
+        let tags = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let indexed_tags = |doc_id: &DocId| -> Vec<String> {
+            let conn = store.conn.lock().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT tag FROM document_tags WHERE location_id = ?1 AND rel_path = ?2 ORDER BY tag")
+                .unwrap();
+            stmt.query_map(
+                params![doc_id.location_id.0, doc_id.rel_path.to_string_lossy().to_string()],
+                |row| row.get(0),
+            )
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect()
+        };
 
-        let created = store.dir_create(location.id, Path::new("nested/notes")).unwrap();
-        assert!(created);
-        assert!(location_dir.path().join("nested/notes").is_dir());
+        let report = store
+            .tag_apply_bulk(
+                vec![second.clone(), missing.clone(), first.clone()],
+                tags(&["review"]),
+                Vec::new(),
+            )
+            .unwrap();
+        assert_eq!(report.updated, vec![second.clone(), first.clone()]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].doc_id, missing);
+        assert_eq!(indexed_tags(&first), vec!["draft", "review"]);
 
-        let created_again = store.dir_create(location.id, Path::new("nested/notes")).unwrap();
-        assert!(!created_again);
+        let report = store
+            .tag_apply_bulk(vec![first.clone(), second.clone()], tags(&["review"]), Vec::new())
+            .unwrap();
+        assert!(report.updated.is_empty());
+        assert_eq!(report.unchanged, vec![first.clone(), second.clone()]);
 
-        let deleted = store.dir_delete(location.id, Path::new("nested")).unwrap();
-        assert!(deleted);
-        assert!(!location_dir.path().join("nested").exists());
+        let report = store
+            .tag_apply_bulk(vec![first.clone(), second.clone()], Vec::new(), tags(&["draft"]))
+            .unwrap();
+        assert_eq!(report.updated.len(), 2);
+        assert_eq!(
+            store.doc_open(&second).unwrap().text,
+            "---\ntags: [review]\n---\n\nSecond"
+        );
+        assert_eq!(indexed_tags(&first), vec!["review"]);
+        assert!(
+            store
+                .tag_apply_bulk(vec![first], tags(&["two words"]), Vec::new())
+                .is_err()
+        );
     }
 
     #[test]
-    fn test_directory_move_updates_catalog_paths() {
+    fn test_tag_rename_rewrites_tags_on_disk_and_in_index() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
         let location = store
-            .location_add("Directory Move".to_string(), location_dir.path().to_path_buf())
+            .location_add("Tag Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        let doc_a = DocId::new(location.id, PathBuf::from("old/sub/a.md")).unwrap();
-        let doc_b = DocId::new(location.id, PathBuf::from("old/sub/deep/b.md")).unwrap();
-        store.doc_save(&doc_a, "# A\n\nalphatoken", None).unwrap();
-        store.doc_save(&doc_b, "# B\n\ndeeptoken", None).unwrap();
+        for (rel_path, text) in [
+            (
+                "one.md",
+                "---\ntags: [Draft, keep]\n---\n\n# One\n\nA draft of #draft/ch1 text.\n",
+            ),
+            ("notes/two.md", "# Two\n\nStill a #DRAFT, see `#draft` and #drafty.\n"),
+            ("three.md", "# Three\n\nNothing to rename about draft.\n"),
+        ] {
+            let doc_id = DocId::new(location.id, PathBuf::from(rel_path)).unwrap();
+            store.doc_save(&doc_id, text, None).unwrap();
+        }
 
-        let moved = store
-            .dir_move(location.id, Path::new("old/sub"), Path::new("new/archive"))
-            .unwrap();
-        assert_eq!(moved, PathBuf::from("new/archive"));
-        assert!(location_dir.path().join("new/archive/a.md").exists());
-        assert!(location_dir.path().join("new/archive/deep/b.md").exists());
+        assert_eq!(store.tag_rename(location.id, "draft", "wip").unwrap(), 2);
 
-        let alpha_hits = store.search("alphatoken", None, 10).unwrap();
-        assert_eq!(alpha_hits.len(), 1);
-        assert_eq!(alpha_hits[0].rel_path, "new/archive/a.md");
+        let read = |rel_path: &str| std::fs::read_to_string(location_dir.path().join(rel_path)).unwrap();
+        assert_eq!(
+            read("one.md"),
+            "---\ntags: [wip, keep]\n---\n\n# One\n\nA draft of #wip/ch1 text.\n"
+        );
+        assert_eq!(
+            read("notes/two.md"),
+            "# Two\n\nStill a #wip, see `#draft` and #drafty.\n"
+        );
+        assert_eq!(read("three.md"), "# Three\n\nNothing to rename about draft.\n");
 
-        let deep_hits = store.search("deeptoken", None, 10).unwrap();
-        assert_eq!(deep_hits.len(), 1);
-        assert_eq!(deep_hits[0].rel_path, "new/archive/deep/b.md");
+        let conn = store.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT rel_path, tag FROM document_tags WHERE location_id = ?1 ORDER BY rel_path, tag")
+            .unwrap();
+        let tags: Vec<(String, String)> = stmt
+            .query_map(params![location.id.0], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("notes/two.md".to_string(), "drafty".to_string()),
+                ("notes/two.md".to_string(), "wip".to_string()),
+                ("one.md".to_string(), "keep".to_string()),
+                ("one.md".to_string(), "wip".to_string()),
+                ("one.md".to_string(), "wip/ch1".to_string()),
+            ]
+        );
+        drop(stmt);
+        drop(conn);
+
+        assert!(store.tag_rename(location.id, "draft", "two words").is_err());
     }
 
     #[test]
-    fn test_directory_move_to_different_location_updates_catalog_paths() {
+    fn test_check_document_images_reports_missing_local_images() {
         let (store, _temp) = create_test_store();
-        let source_dir = TempDir::new().unwrap();
-        let target_dir = TempDir::new().unwrap();
-        let source_location = store
-            .location_add("Directory Move Source".to_string(), source_dir.path().to_path_buf())
-            .unwrap();
-        let target_location = store
-            .location_add("Directory Move Target".to_string(), target_dir.path().to_path_buf())
-            .unwrap();
+        let location_dir = TempDir::new().unwrap();
 
-        let doc_a = DocId::new(source_location.id, PathBuf::from("old/sub/a.md")).unwrap();
-        let doc_b = DocId::new(source_location.id, PathBuf::from("old/sub/deep/b.md")).unwrap();
-        store.doc_save(&doc_a, "# A\n\nalphatoken", None).unwrap();
-        store.doc_save(&doc_b, "# B\n\ndeeptoken", None).unwrap();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
 
-        let moved = store
-            .dir_move_to_location(
-                source_location.id,
-                Path::new("old/sub"),
-                target_location.id,
-                Path::new("new/archive"),
-            )
+        let location = store
+            .location_add("Image Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        assert_eq!(moved, PathBuf::from("new/archive"));
-        assert!(!source_dir.path().join("old/sub").exists());
-        assert!(target_dir.path().join("new/archive/a.md").exists());
-        assert!(target_dir.path().join("new/archive/deep/b.md").exists());
+        std::fs::create_dir_all(location_dir.path().join("notes/img")).unwrap();
+        std::fs::write(location_dir.path().join("notes/img/cat one.png"), b"png").unwrap();
+        std::fs::write(location_dir.path().join("banner.png"), b"png").unwrap();
 
-        let alpha_hits = store.search("alphatoken", None, 10).unwrap();
-        assert_eq!(alpha_hits.len(), 1);
-        assert_eq!(alpha_hits[0].location_id, target_location.id);
-        assert_eq!(alpha_hits[0].rel_path, "new/archive/a.md");
+        let doc_id = DocId::new(location.id, PathBuf::from("notes/post.md")).unwrap();
+        let text = "![Cat](img/cat%20one.png)\n\n![Banner](/banner.png)\n\n![Missing dog](img/dog.png)\n\n\
+                    ![Remote](https://example.com/x.png) ![Inline](data:image/png;base64,AAAA)";
+        store.doc_save(&doc_id, text, None).unwrap();
 
-        let deep_hits = store.search("deeptoken", None, 10).unwrap();
-        assert_eq!(deep_hits.len(), 1);
-        assert_eq!(deep_hits[0].location_id, target_location.id);
-        assert_eq!(deep_hits[0].rel_path, "new/archive/deep/b.md");
+        let missing = store.check_document_images(&doc_id).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].alt, "Missing dog");
+        assert_eq!(missing[0].url, "img/dog.png");
+        assert_eq!(missing[0].line, 5);
     }
 
     #[test]
-    fn test_directory_list_includes_empty_and_nested_directories() {
+    fn test_doc_stats_reads_saved_or_unsaved_text() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
         let location = store
-            .location_add("Directory List".to_string(), location_dir.path().to_path_buf())
+            .location_add("Stats Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+        store
+            .doc_save(&doc_id, "# Draft\n\n- [x] Outline #plot\n- [ ] Write\n", None)
             .unwrap();
 
-        store.dir_create(location.id, Path::new("Samples")).unwrap();
-        store.dir_create(location.id, Path::new("Samples/sibling")).unwrap();
-        store.dir_create(location.id, Path::new("Empty")).unwrap();
-
-        let directories = store.dir_list(location.id).unwrap();
-        let as_strings = directories
-            .iter()
-            .map(|path| path.to_string_lossy().to_string())
-            .collect::<Vec<_>>();
+        let saved = store.doc_stats(&doc_id, None).unwrap();
+        assert_eq!(saved.heading_count, 1);
+        assert_eq!(saved.task_completion, Some(0.5));
+        assert_eq!(saved.tags, vec!["plot".to_string()]);
 
+        let unsaved = store.doc_stats(&doc_id, Some("One two three.")).unwrap();
         assert_eq!(
-            as_strings,
-            vec![
-                "Empty".to_string(),
-                "Samples".to_string(),
-                "Samples/sibling".to_string()
-            ]
+            (unsaved.word_count, unsaved.heading_count, unsaved.task_completion),
+            (3, 0, None)
         );
+
+        let missing = DocId::new(location.id, PathBuf::from("missing.md")).unwrap();
+        assert!(store.doc_stats(&missing, None).is_err());
     }
 
     #[test]
-    fn test_search_returns_indexed_results() {
+    fn test_doc_export_html_inline_embeds_local_images() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
+        let export_dir = TempDir::new().unwrap();
+
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
         let location = store
-            .location_add("Search Location".to_string(), location_dir.path().to_path_buf())
+            .location_add("Export Location".to_string(), location_dir.path().to_path_buf())
             .unwrap();
 
-        let doc_id = DocId::new(location.id, PathBuf::from("chapter-1.md")).unwrap();
+        std::fs::create_dir_all(location_dir.path().join("notes/img")).unwrap();
+        std::fs::write(location_dir.path().join("notes/img/cat.png"), b"png").unwrap();
+        std::fs::create_dir_all(location_dir.path().join("notes/other")).unwrap();
+        std::fs::write(location_dir.path().join("notes/other/Cat.png"), b"other").unwrap();
+
+        let doc_id = DocId::new(location.id, PathBuf::from("notes/post.md")).unwrap();
         store
-            .doc_save(&doc_id, "# Chapter One\nThe stormlight archives begin here.", None)
+            .doc_save(
+                &doc_id,
+                "![Cat](img/cat.png)\n\n![Remote](https://example.com/x.png)\n\n![Other](other/Cat.png)",
+                None,
+            )
             .unwrap();
 
-        let results = store.search("stormlight", None, 10).unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].location_id, location.id);
-        assert_eq!(results[0].rel_path, "chapter-1.md");
-        assert!(!results[0].snippet.is_empty());
+        let output_path = export_dir.path().join("post.html");
+        let options = ExportOptions { asset_mode: AssetMode::Inline, ..ExportOptions::standalone() };
+        store.doc_export_html(&doc_id, &options, &output_path).unwrap();
+
+        let html = std::fs::read_to_string(&output_path).unwrap();
+        assert!(html.contains("src=\"data:image/png;base64,cG5n\""));
+        assert!(!html.contains("src=\"img/cat.png\""));
+        assert!(html.contains("src=\"https://example.com/x.png\""));
+
+        let options = ExportOptions { asset_mode: AssetMode::CopyFolder, ..ExportOptions::standalone() };
+        store.doc_export_html(&doc_id, &options, &output_path).unwrap();
+
+        let html = std::fs::read_to_string(&output_path).unwrap();
+        assert!(html.contains("src=\"assets/cat.png\""));
+        assert!(html.contains("src=\"assets/Cat-1.png\""));
+        assert_eq!(std::fs::read(export_dir.path().join("assets/cat.png")).unwrap(), b"png");
+        assert_eq!(
+            std::fs::read(export_dir.path().join("assets/Cat-1.png")).unwrap(),
+            b"other"
+        );
     }
 
     #[test]
-    fn test_reconcile_location_index_removes_deleted_docs_from_search() {
+    fn test_doc_save_reports_goal_progress_once_reached() {
         let (store, _temp) = create_test_store();
         let location_dir = TempDir::new().unwrap();
-        let location_path = location_dir.path().to_path_buf();
+        let location = store
+            .location_add("Goal Location".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
 
-        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
-        store.ui_layout_set(&settings).unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("draft.md")).unwrap();
+        let result = store.doc_save(&doc_id, "one two three", None).unwrap();
+        assert_eq!(result.goal_progress, None);
 
-        let location = store
-            .location_add("Reconcile Location".to_string(), location_path.clone())
+        store.doc_goal_set(&doc_id, 5).unwrap();
+        assert_eq!(store.doc_goal_get(&doc_id).unwrap(), Some(5));
+
+        let result = store.doc_save(&doc_id, "one two three four", None).unwrap();
+        assert_eq!(
+            result.goal_progress,
+            Some(GoalProgress { current_words: 4, target_words: 5 })
+        );
+
+        let result = store.doc_save(&doc_id, "one two three four five six", None).unwrap();
+        let progress = result.goal_progress.unwrap();
+        assert_eq!(progress, GoalProgress { current_words: 6, target_words: 5 });
+        assert!(progress.is_reached());
+
+        let result = store
+            .doc_save(&doc_id, "one two three four five six seven", None)
             .unwrap();
+        assert_eq!(result.goal_progress, None);
 
-        let full_path = location_path.join("notes.md");
-        std::fs::write(&full_path, "# Notes\nIndex me").unwrap();
+        store.doc_goal_set(&doc_id, 10).unwrap();
+        let result = store
+            .doc_save(&doc_id, "one two three four five six seven", None)
+            .unwrap();
+        assert_eq!(
+            result.goal_progress,
+            Some(GoalProgress { current_words: 7, target_words: 10 })
+        );
 
-        let indexed = store.reconcile_location_index(location.id).unwrap();
-        assert_eq!(indexed, 1);
-        assert_eq!(store.search("Index", None, 10).unwrap().len(), 1);
+        let renamed = store.doc_rename(&doc_id, "renamed.md").unwrap().id;
+        assert_eq!(store.doc_goal_get(&doc_id).unwrap(), None);
+        assert_eq!(store.doc_goal_get(&renamed).unwrap(), Some(10));
 
-        std::fs::remove_file(full_path).unwrap();
+        let moved = store.doc_move(&renamed, Path::new("drafts/renamed.md")).unwrap().id;
+        assert_eq!(store.doc_goal_get(&renamed).unwrap(), None);
+        assert_eq!(store.doc_goal_get(&moved).unwrap(), Some(10));
 
-        let indexed_after_delete = store.reconcile_location_index(location.id).unwrap();
-        assert_eq!(indexed_after_delete, 0);
-        assert!(store.search("Index", None, 10).unwrap().is_empty());
+        assert!(store.doc_delete(&moved).unwrap());
+        assert_eq!(store.doc_goal_get(&moved).unwrap(), None);
     }
 
     #[test]
@@ -2727,6 +10204,82 @@ mod tests {
         assert!(matches!(enc, Encoding::Utf8WithBom));
     }
 
+    #[test]
+    fn test_doc_open_with_encoding_overrides_detection() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        store
+            .ui_layout_set(&UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() })
+            .unwrap();
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        std::fs::write(location_path.join("latin.txt"), b"Caf\xe9 cr\xe8me \xa92024\n").unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("latin.txt")).unwrap();
+
+        assert!(store.doc_open(&doc_id).unwrap().text.contains('\u{fffd}'));
+        let error = store.doc_open_with_encoding(&doc_id, Encoding::Utf8).unwrap_err();
+        assert_eq!(error.code, ErrorCode::Parse);
+
+        let content = store.doc_open_with_encoding(&doc_id, Encoding::Latin1).unwrap();
+        assert_eq!(content.text, "Café crème ©2024\n");
+        assert_eq!(content.meta.encoding, Encoding::Latin1);
+        let listed = store.doc_list_catalog(location.id, None).unwrap();
+        assert_eq!(listed[0].encoding, Encoding::Latin1);
+
+        let utf16: Vec<u8> = "Grüße".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        std::fs::write(location_path.join("utf16.txt"), &utf16).unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("utf16.txt")).unwrap();
+        let content = store.doc_open_with_encoding(&doc_id, Encoding::Utf16Be).unwrap();
+        assert_eq!(content.text, "Grüße");
+
+        std::fs::write(location_path.join("odd.txt"), [0x00, 0x41, 0x00]).unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("odd.txt")).unwrap();
+        let error = store.doc_open_with_encoding(&doc_id, Encoding::Utf16Le).unwrap_err();
+        assert_eq!(error.code, ErrorCode::Parse);
+    }
+
+    #[test]
+    fn test_latin1_document_round_trips() {
+        let (store, _temp) = create_test_store();
+        let location_dir = TempDir::new().unwrap();
+        let location_path = location_dir.path().to_path_buf();
+        store
+            .ui_layout_set(&UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() })
+            .unwrap();
+        let location = store
+            .location_add("Test Location".to_string(), location_path.clone())
+            .unwrap();
+
+        std::fs::write(location_path.join("latin.txt"), b"Caf\xe9\n").unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        let doc_id = DocId::new(location.id, PathBuf::from("latin.txt")).unwrap();
+        store.doc_open_with_encoding(&doc_id, Encoding::Latin1).unwrap();
+
+        let reopened = store.doc_open(&doc_id).unwrap();
+        assert_eq!(reopened.text, "Café\n");
+        assert_eq!(reopened.meta.encoding, Encoding::Latin1);
+
+        let saved = store.doc_save(&doc_id, "Café crème ©2024\n", None).unwrap();
+        assert_eq!(saved.new_meta.unwrap().encoding, Encoding::Latin1);
+        let on_disk = std::fs::read(location_path.join("latin.txt")).unwrap();
+        assert_eq!(on_disk, b"Caf\xe9 cr\xe8me \xa92024\n");
+        assert_eq!(store.doc_open(&doc_id).unwrap().text, "Café crème ©2024\n");
+
+        let error = store.doc_save(&doc_id, "Prix: 5 €\n", None).unwrap_err();
+        assert_eq!(error.code, ErrorCode::Parse);
+        assert_eq!(std::fs::read(location_path.join("latin.txt")).unwrap(), on_disk);
+
+        std::fs::write(location_path.join("latin.txt"), b"Na\xefve\n").unwrap();
+        store.reconcile_location_index(location.id).unwrap();
+        let reopened = store.doc_open(&doc_id).unwrap();
+        assert_eq!(reopened.text, "Naïve\n");
+        assert_eq!(reopened.meta.encoding, Encoding::Latin1);
+    }
+
     #[test]
     /// TODO: move to core
     fn test_detect_line_ending_lf() {
@@ -2770,6 +10323,15 @@ mod tests {
             filename_visibility: false,
             create_readme_in_new_locations: false,
             markdown_preview_style: MarkdownPreviewStyle::Pdf,
+            max_open_bytes: 1024,
+            trim_trailing_whitespace_on_save: true,
+            preserve_code_whitespace: false,
+            stamp_updated_on_save: true,
+            write_utf8_bom: true,
+            default_extension: "txt".to_string(),
+            bulk_tags_inline: true,
+            home_document_names: vec!["Home.md".to_string()],
+            conflict_patterns: vec!["sync-conflict".to_string()],
         };
 
         store.ui_layout_set(&settings).unwrap();
@@ -2835,6 +10397,57 @@ mod tests {
         assert_eq!(loaded, state);
     }
 
+    #[test]
+    fn test_location_view_prefs_round_trip() {
+        let (store, _temp) = create_test_store();
+        let settings = UiLayoutSettings { create_readme_in_new_locations: false, ..UiLayoutSettings::default() };
+        store.ui_layout_set(&settings).unwrap();
+
+        let first_dir = TempDir::new().unwrap();
+        let second_dir = TempDir::new().unwrap();
+        let first = store
+            .location_add("First".to_string(), first_dir.path().to_path_buf())
+            .unwrap();
+        let second = store
+            .location_add("Second".to_string(), second_dir.path().to_path_buf())
+            .unwrap();
+        assert_eq!(
+            store.location_view_prefs_get(first.id).unwrap(),
+            LocationViewPrefs::default()
+        );
+
+        let view = LocationViewPrefs {
+            sort_by: Some(DocSortField::Name),
+            sort_order: SortOrder::Ascending,
+            recursive: true,
+            extensions: Some(vec!["md".to_string(), "txt".to_string()]),
+        };
+        store.location_view_prefs_set(first.id, &view).unwrap();
+        store
+            .location_view_prefs_set(
+                second.id,
+                &LocationViewPrefs { recursive: true, ..LocationViewPrefs::default() },
+            )
+            .unwrap();
+        assert_eq!(store.location_view_prefs_get(first.id).unwrap(), view);
+
+        let options = view.list_options();
+        assert_eq!(options.sort_by, Some(DocSortField::Name));
+        assert_eq!(options.extensions, view.extensions);
+        assert!(options.recursive);
+
+        assert!(store.location_remove(second.id).unwrap());
+        let stored: BTreeMap<i64, LocationViewPrefs> = store.kv_get_json(LOCATION_VIEW_PREFS_KEY).unwrap().unwrap();
+        assert_eq!(stored.keys().copied().collect::<Vec<_>>(), vec![first.id.0]);
+        assert_eq!(
+            store.location_view_prefs_get(second.id).unwrap(),
+            LocationViewPrefs::default()
+        );
+
+        let error = store.location_view_prefs_set(second.id, &view).unwrap_err();
+        assert!(matches!(error.code, ErrorCode::NotFound));
+    }
+
     #[test]
     fn test_sidebar_tree_state_backfills_directory_defaults() {
         let (store, _temp) = create_test_store();
@@ -2878,6 +10491,7 @@ mod tests {
                 text: "in this day and age".to_string(),
                 category: "cliche".to_string(),
                 replacement: Some("today".to_string()),
+                is_regex: false,
             }],
             marker_style: settings::StyleMarkerStyle::Underline,
         };
@@ -2944,6 +10558,10 @@ mod tests {
             close_after_save: false,
             show_tray_icon: false,
             last_capture_target: Some("Inbox/Daily".to_string()),
+            date_subfolder_format: Some("%Y/%m".to_string()),
+            date_subfolder_for_append: true,
+            append_timestamp_format: Some("%H:%M".to_string()),
+            on_missing_target: MissingCaptureTarget::Error,
         };
 
         store.global_capture_set(&settings).unwrap();
@@ -2978,6 +10596,7 @@ mod tests {
         assert_eq!(loaded.inbox_relative_dir, "inbox");
         assert!(loaded.close_after_save);
         assert!(loaded.show_tray_icon);
+        assert!(!loaded.date_subfolder_for_append);
     }
 
     #[test]
@@ -3143,6 +10762,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_readme_template_defaults_to_built_in() {
+        let (store, _temp) = create_test_store();
+
+        assert_eq!(store.readme_template_get().unwrap(), get_markdown_help());
+
+        store.readme_template_set(Some("# Custom")).unwrap();
+        store.readme_template_set(None).unwrap();
+        assert_eq!(store.readme_template_get().unwrap(), get_markdown_help());
+    }
+
+    #[test]
+    fn test_readme_uses_custom_template() {
+        let (store, _temp) = create_test_store();
+        let template = "# Welcome\n\nNotes live here.\n";
+        store.readme_template_set(Some(template)).unwrap();
+        assert_eq!(store.readme_template_get().unwrap(), template);
+
+        let location_dir = TempDir::new().unwrap();
+        store
+            .location_add("Custom Readme".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        let content = std::fs::read_to_string(location_dir.path().join("README.md")).unwrap();
+        assert_eq!(content, template);
+    }
+
+    #[test]
+    fn test_empty_readme_template_disables_creation() {
+        let (store, _temp) = create_test_store();
+        store.readme_template_set(Some("")).unwrap();
+        assert_eq!(store.readme_template_get().unwrap(), "");
+
+        let location_dir = TempDir::new().unwrap();
+        store
+            .location_add("No Readme".to_string(), location_dir.path().to_path_buf())
+            .unwrap();
+
+        assert!(!location_dir.path().join("README.md").exists());
+    }
+
     #[test]
     fn test_existing_readme_not_overwritten() {
         let (store, _temp) = create_test_store();
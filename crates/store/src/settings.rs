@@ -1,6 +1,7 @@
 use super::StyleCheckPattern;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use writer_core::{DocListOptions, DocSortField, SortOrder};
 
 fn default_true() -> bool {
     true
@@ -38,6 +39,26 @@ fn default_create_readme_in_new_locations() -> bool {
     true
 }
 
+fn default_max_open_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_preview_chars() -> usize {
+    240
+}
+
+fn default_max_search_results() -> usize {
+    200
+}
+
+fn default_extension() -> String {
+    "md".to_string()
+}
+
+fn default_home_document_names() -> Vec<String> {
+    vec!["README.md".to_string(), "index.md".to_string()]
+}
+
 fn default_markdown_preview_style() -> MarkdownPreviewStyle {
     MarkdownPreviewStyle::default()
 }
@@ -97,6 +118,41 @@ pub struct StyleCheckSettings {
     pub marker_style: StyleMarkerStyle,
 }
 
+/// Full-text search options; changing them rebuilds the `docs_fts` table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SearchSettings {
+    /// Fold accents when tokenizing so `cafe` matches `café` (uses `remove_diacritics 2`)
+    #[serde(default)]
+    pub fold_diacritics: bool,
+    /// Index at most this many bytes of each document's content (0 indexes everything)
+    ///
+    /// Catalog metadata, tags, and aliases still come from the full text, but matches and snippets
+    /// past the cap are not found, so search results for long documents may be partial.
+    #[serde(default)]
+    pub fts_max_content_bytes: usize,
+    /// Length, in characters, of the plain-text preview stored for each document and returned
+    /// with its search hits (0 stores none)
+    #[serde(default = "default_preview_chars")]
+    pub preview_chars: usize,
+    /// Most hits a single search may return (the floor is always 1)
+    ///
+    /// Every hit gets its own snippet and is sent to the UI in one response, so raising this makes
+    /// broad queries on large locations slower and heavier; keep the default for interactive search.
+    #[serde(default = "default_max_search_results")]
+    pub max_search_results: usize,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            fold_diacritics: false,
+            fts_max_content_bytes: 0,
+            preview_chars: default_preview_chars(),
+            max_search_results: default_max_search_results(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UiLayoutSettings {
     pub sidebar_collapsed: bool,
@@ -126,6 +182,35 @@ pub struct UiLayoutSettings {
     pub create_readme_in_new_locations: bool,
     #[serde(default = "default_markdown_preview_style")]
     pub markdown_preview_style: MarkdownPreviewStyle,
+    /// Files larger than this are opened truncated and left out of the search index
+    #[serde(default = "default_max_open_bytes")]
+    pub max_open_bytes: u64,
+    /// Strip trailing whitespace and normalize the final newline when saving
+    #[serde(default)]
+    pub trim_trailing_whitespace_on_save: bool,
+    /// With trim-on-save, leave lines inside fenced and indented code blocks untouched
+    #[serde(default = "default_true")]
+    pub preserve_code_whitespace: bool,
+    /// Set or refresh an `updated` timestamp in the front matter of markdown documents that have one
+    #[serde(default)]
+    pub stamp_updated_on_save: bool,
+    /// Start new documents with a UTF-8 byte order mark; existing documents keep whether they have one
+    #[serde(default)]
+    pub write_utf8_bom: bool,
+    /// Extension (without the dot) given to new documents saved with an extensionless path
+    #[serde(default = "default_extension")]
+    pub default_extension: String,
+    /// Add tags in bulk as inline `#tags` at the end of a document instead of to its front matter
+    #[serde(default)]
+    pub bulk_tags_inline: bool,
+    /// Filenames of entry-point documents (matched case-insensitively in any folder) that are never
+    /// reported as orphans
+    #[serde(default = "default_home_document_names")]
+    pub home_document_names: Vec<String>,
+    /// Extra filename substrings (matched case-insensitively) that mark a conflicted copy, on top of
+    /// the built-in cloud provider patterns
+    #[serde(default)]
+    pub conflict_patterns: Vec<String>,
 }
 
 impl Default for UiLayoutSettings {
@@ -146,6 +231,15 @@ impl Default for UiLayoutSettings {
             filename_visibility: false,
             create_readme_in_new_locations: default_create_readme_in_new_locations(),
             markdown_preview_style: default_markdown_preview_style(),
+            max_open_bytes: default_max_open_bytes(),
+            trim_trailing_whitespace_on_save: false,
+            preserve_code_whitespace: true,
+            stamp_updated_on_save: false,
+            write_utf8_bom: false,
+            default_extension: default_extension(),
+            bulk_tags_inline: false,
+            home_document_names: default_home_document_names(),
+            conflict_patterns: Vec::new(),
         }
     }
 }
@@ -158,6 +252,32 @@ pub struct SidebarTreeState {
     pub expanded_directories_by_location: BTreeMap<i64, Vec<String>>,
 }
 
+/// The document listing a location was last viewed with, restored when the location is opened again
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct LocationViewPrefs {
+    #[serde(default)]
+    pub sort_by: Option<DocSortField>,
+    #[serde(default)]
+    pub sort_order: SortOrder,
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default)]
+    pub extensions: Option<Vec<String>>,
+}
+
+impl LocationViewPrefs {
+    /// Listing options for [`crate::Store::doc_list`] that reproduce this view
+    pub fn list_options(&self) -> DocListOptions {
+        DocListOptions {
+            recursive: self.recursive,
+            extensions: self.extensions.clone(),
+            sort_by: self.sort_by,
+            sort_order: self.sort_order,
+            ..DocListOptions::default()
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum CaptureMode {
     #[default]
@@ -166,6 +286,16 @@ pub enum CaptureMode {
     Append,
 }
 
+/// What a capture does when its destination location has been removed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum MissingCaptureTarget {
+    /// Save to the first available location instead
+    #[default]
+    Fallback,
+    /// Reject the capture with a `NotFound` error
+    Error,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CaptureDocRef {
     pub location_id: i64,
@@ -218,6 +348,19 @@ pub struct GlobalCaptureSettings {
     pub show_tray_icon: bool,
     #[serde(default)]
     pub last_capture_target: Option<String>,
+    /// `strftime` pattern for dated quick-note subfolders of the inbox (e.g. `%Y/%m`); unset keeps the
+    /// default layout
+    #[serde(default)]
+    pub date_subfolder_format: Option<String>,
+    /// Also redirect append captures into the dated subfolder of the target's directory; off by
+    /// default, so an append target is written exactly where it was chosen
+    #[serde(default)]
+    pub date_subfolder_for_append: bool,
+    /// `strftime` pattern prefixed to each appended capture (e.g. `%H:%M`); unset appends the text as-is
+    #[serde(default)]
+    pub append_timestamp_format: Option<String>,
+    #[serde(default)]
+    pub on_missing_target: MissingCaptureTarget,
 }
 
 impl Default for GlobalCaptureSettings {
@@ -233,6 +376,10 @@ impl Default for GlobalCaptureSettings {
             close_after_save: true,
             show_tray_icon: true,
             last_capture_target: None,
+            date_subfolder_format: None,
+            date_subfolder_for_append: false,
+            append_timestamp_format: None,
+            on_missing_target: MissingCaptureTarget::default(),
         }
     }
 }
@@ -1,6 +1,7 @@
+use std::borrow::Cow;
 use std::hash::{Hash, Hasher};
 
-use writer_core::{AppError, Encoding, SearchMatch};
+use writer_core::{AppError, Encoding, ErrorCode, SearchMatch};
 
 pub fn hash_text(text: &str) -> String {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
@@ -8,24 +9,30 @@ pub fn hash_text(text: &str) -> String {
     format!("{:016x}", hasher.finish())
 }
 
-pub fn extract_highlight_matches(snippet: &str) -> (String, Vec<SearchMatch>) {
+pub fn extract_highlight_matches(snippet: &str, open: &str, close: &str) -> (String, Vec<SearchMatch>) {
     let mut plain = String::new();
     let mut matches = Vec::new();
     let mut start_index: Option<usize> = None;
 
     let mut i = 0usize;
     while i < snippet.len() {
-        if snippet[i..].starts_with("<<") {
+        // Inside a match the close marker wins, so markers sharing a prefix (`[` / `[/`) still pair up
+        if start_index.is_some() && snippet[i..].starts_with(close) {
+            if let Some(start) = start_index.take() {
+                matches.push(SearchMatch { start, end: plain.len() });
+            }
+            i += close.len();
+            continue;
+        }
+
+        if snippet[i..].starts_with(open) {
             start_index = Some(plain.len());
-            i += 2;
+            i += open.len();
             continue;
         }
 
-        if snippet[i..].starts_with(">>") {
-            if let Some(start) = start_index.take() {
-                matches.push(SearchMatch { start, end: plain.len() });
-            }
-            i += 2;
+        if snippet[i..].starts_with(close) {
+            i += close.len();
             continue;
         }
 
@@ -40,28 +47,126 @@ pub fn extract_highlight_matches(snippet: &str) -> (String, Vec<SearchMatch>) {
     (plain, matches)
 }
 
-/// Detects encoding from byte BOM and decodes to string
-pub fn detect_and_decode(bytes: &[u8]) -> Result<(String, Encoding), AppError> {
-    if bytes.starts_with(&[0xef, 0xbb, 0xbf]) {
-        let text = String::from_utf8_lossy(&bytes[3..]).into_owned();
-        Ok((text, Encoding::Utf8WithBom))
+/// Byte order mark that starts a UTF-8 file written as `Encoding::Utf8WithBom`
+pub const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+
+/// Encodes text as UTF-8 for writing, prefixed with [`UTF8_BOM`] for `Encoding::Utf8WithBom`
+///
+/// A U+FEFF already at the start of the text is dropped first, so the mark is never doubled.
+pub fn encode_utf8(text: &str, encoding: Encoding) -> Vec<u8> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut bytes = Vec::with_capacity(text.len() + UTF8_BOM.len());
+    if encoding == Encoding::Utf8WithBom {
+        bytes.extend_from_slice(&UTF8_BOM);
+    }
+    bytes.extend_from_slice(text.as_bytes());
+    bytes
+}
+
+/// Encodes text as ISO-8859-1 for writing
+///
+/// Characters above U+00FF have no Latin-1 byte, so they give a `Parse` error instead of being replaced.
+pub fn encode_latin1(text: &str) -> Result<Vec<u8>, AppError> {
+    text.chars()
+        .map(|ch| {
+            u8::try_from(u32::from(ch)).map_err(|_| {
+                AppError::new(
+                    ErrorCode::Parse,
+                    format!("Cannot encode {:?} (U+{:04X}) as Latin-1", ch, u32::from(ch)),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Encoding indicated by the byte order mark, or UTF-8 when there is none
+pub fn detect_encoding(bytes: &[u8]) -> Encoding {
+    if bytes.starts_with(&UTF8_BOM) {
+        Encoding::Utf8WithBom
     } else if bytes.starts_with(&[0xff, 0xfe]) {
-        let u16_vec: Vec<u16> = bytes[2..]
-            .chunks_exact(2)
-            .map(|c| u16::from_le_bytes([c[0], c[1]]))
-            .collect();
-        let text = String::from_utf16(&u16_vec).map_err(|e| AppError::io(format!("Invalid UTF-16 LE: {}", e)))?;
-        Ok((text, Encoding::Utf16Le))
+        Encoding::Utf16Le
     } else if bytes.starts_with(&[0xfe, 0xff]) {
-        let u16_vec: Vec<u16> = bytes[2..]
-            .chunks_exact(2)
-            .map(|c| u16::from_be_bytes([c[0], c[1]]))
-            .collect();
-        let text = String::from_utf16(&u16_vec).map_err(|e| AppError::io(format!("Invalid UTF-16 BE: {}", e)))?;
-        Ok((text, Encoding::Utf16Be))
+        Encoding::Utf16Be
     } else {
-        let text = String::from_utf8_lossy(bytes).into_owned();
-        Ok((text, Encoding::Utf8))
+        Encoding::Utf8
+    }
+}
+
+/// Detects encoding from byte BOM and decodes to string
+///
+/// UTF-8 is decoded lossily; UTF-16 must be valid, though an odd trailing byte is ignored.
+pub fn detect_and_decode(bytes: &[u8]) -> Result<(String, Encoding), AppError> {
+    let decode_utf16 = |to_unit: fn([u8; 2]) -> u16, name: &str| {
+        let units: Vec<u16> = bytes[2..].chunks_exact(2).map(|c| to_unit([c[0], c[1]])).collect();
+        String::from_utf16(&units).map_err(|e| AppError::io(format!("Invalid {}: {}", name, e)))
+    };
+
+    let encoding = detect_encoding(bytes);
+    let text = match encoding {
+        Encoding::Utf16Le => decode_utf16(u16::from_le_bytes, "UTF-16 LE")?,
+        Encoding::Utf16Be => decode_utf16(u16::from_be_bytes, "UTF-16 BE")?,
+        _ => String::from_utf8_lossy(bytes.strip_prefix(&UTF8_BOM).unwrap_or(bytes)).into_owned(),
+    };
+    Ok((text, encoding))
+}
+
+/// Decodes bytes as `encoding` instead of detecting it, failing on any invalid sequence
+///
+/// A BOM matching the encoding is skipped. Invalid UTF-8, odd-length or unpaired-surrogate UTF-16
+/// are `Parse` errors rather than being replaced; Latin-1 maps every byte and never fails.
+pub fn decode_with_encoding(bytes: &[u8], encoding: Encoding) -> Result<String, AppError> {
+    let decode_utf16 = |bytes: &[u8], bom: [u8; 2], to_unit: fn([u8; 2]) -> u16, name: &str| {
+        let bytes = bytes.strip_prefix(&bom).unwrap_or(bytes);
+        if !bytes.len().is_multiple_of(2) {
+            return Err(AppError::new(
+                ErrorCode::Parse,
+                format!("Invalid {}: odd number of bytes ({})", name, bytes.len()),
+            ));
+        }
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| to_unit([c[0], c[1]])).collect();
+        String::from_utf16(&units).map_err(|e| AppError::new(ErrorCode::Parse, format!("Invalid {}: {}", name, e)))
+    };
+
+    match encoding {
+        Encoding::Utf8 | Encoding::Utf8WithBom => {
+            let bytes = bytes.strip_prefix(&UTF8_BOM).unwrap_or(bytes);
+            String::from_utf8(bytes.to_vec()).map_err(|e| {
+                AppError::new(
+                    ErrorCode::Parse,
+                    format!("Invalid UTF-8 at byte {}", e.utf8_error().valid_up_to()),
+                )
+            })
+        }
+        Encoding::Utf16Le => decode_utf16(bytes, [0xff, 0xfe], u16::from_le_bytes, "UTF-16 LE"),
+        Encoding::Utf16Be => decode_utf16(bytes, [0xfe, 0xff], u16::from_be_bytes, "UTF-16 BE"),
+        Encoding::Latin1 => Ok(bytes.iter().map(|byte| char::from(*byte)).collect()),
+    }
+}
+
+/// Drops a multi-byte character left incomplete at the end of a truncated read
+///
+/// Honors the BOMs found by [`detect_encoding`] so the cut always lands on a character boundary.
+pub fn trim_incomplete_trailing_char(bytes: &mut Vec<u8>) {
+    let encoding = detect_encoding(bytes);
+    if matches!(encoding, Encoding::Utf16Le | Encoding::Utf16Be) {
+        let little_endian = encoding == Encoding::Utf16Le;
+        bytes.truncate(bytes.len() & !1);
+
+        if bytes.len() >= 4 {
+            let last = [bytes[bytes.len() - 2], bytes[bytes.len() - 1]];
+            let unit = if little_endian { u16::from_le_bytes(last) } else { u16::from_be_bytes(last) };
+            if (0xd800..=0xdbff).contains(&unit) {
+                bytes.truncate(bytes.len() - 2);
+            }
+        }
+        return;
+    }
+
+    let offset = if encoding == Encoding::Utf8WithBom { UTF8_BOM.len() } else { 0 };
+    if let Err(error) = std::str::from_utf8(&bytes[offset..])
+        && error.error_len().is_none()
+    {
+        bytes.truncate(offset + error.valid_up_to());
     }
 }
 
@@ -91,6 +196,41 @@ pub fn locate_query_position(content: &str, query: &str) -> (usize, usize) {
     }
 }
 
+/// Collapses plain text to a single line of at most `max_chars` characters for a preview
+///
+/// Longer text is cut at the last word boundary that fits and ends with `…`.
+pub fn prose_preview(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= max_chars {
+        return collapsed;
+    }
+
+    let cut: String = collapsed.chars().take(max_chars.saturating_sub(1)).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 => &cut[..space],
+        _ => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end())
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote, or line break, doubling embedded quotes
+///
+/// Fields starting with `=`, `+`, `-`, `@`, tab, or carriage return get a leading `'` so spreadsheets
+/// show them as text instead of evaluating them as formulas.
+pub fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +240,25 @@ mod tests {
         assert_eq!(hash_text("hello"), hash_text("hello"));
         assert_ne!(hash_text("hello"), hash_text("goodbye"));
     }
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_field_neutralizes_formulas() {
+        assert_eq!(csv_field("=SUM(A1:A2)"), "'=SUM(A1:A2)");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("@cmd"), "'@cmd");
+        assert_eq!(
+            csv_field("=HYPERLINK(\"x\",\"y\")"),
+            "\"'=HYPERLINK(\"\"x\"\",\"\"y\"\")\""
+        );
+        assert_eq!(csv_field("a=b"), "a=b");
+    }
 }
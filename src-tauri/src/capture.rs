@@ -1,5 +1,7 @@
 use super::AppState;
-use std::path::PathBuf;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use std::path::{Component, Path, PathBuf};
 use tauri::{
     menu::{Menu, MenuItem, PredefinedMenuItem},
     tray::TrayIconBuilder,
@@ -274,9 +276,134 @@ fn parse_shortcut(shortcut_str: &str) -> Result<Shortcut, AppError> {
 pub fn generate_quick_note_path(inbox_dir: &str) -> PathBuf {
     let now = chrono::Utc::now();
     let year = now.format("%Y").to_string();
-    let filename = format!("{}_{}.md", now.format("%Y_%m_%d"), now.timestamp_millis());
+    PathBuf::from(inbox_dir).join(year).join(quick_note_filename(now))
+}
+
+fn quick_note_filename(now: DateTime<Utc>) -> String {
+    format!("{}_{}.md", now.format("%Y_%m_%d"), now.timestamp_millis())
+}
+
+/// Computes the quick note path inside a dated subfolder of the inbox.
+pub fn dated_quick_note_path(
+    inbox_dir: &str, date_subfolder_format: &str, now: DateTime<Utc>,
+) -> Result<PathBuf, AppError> {
+    Ok(PathBuf::from(inbox_dir)
+        .join(format_date_subfolder(date_subfolder_format, now)?)
+        .join(quick_note_filename(now)))
+}
 
-    PathBuf::from(inbox_dir).join(year).join(filename)
+fn resolve_quick_note_path(inbox_dir: &str, date_subfolder_format: Option<&str>) -> Result<PathBuf, AppError> {
+    match date_subfolder_format {
+        Some(format) => dated_quick_note_path(inbox_dir, format, Utc::now()),
+        None => Ok(generate_quick_note_path(inbox_dir)),
+    }
+}
+
+/// Computes the append target path, moving the file into a dated subfolder of its directory when a format is set.
+pub fn append_path_at(
+    rel_path: &str, date_subfolder_format: Option<&str>, now: DateTime<Utc>,
+) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(rel_path);
+    let Some(format) = date_subfolder_format else {
+        return Ok(path);
+    };
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| AppError::new(ErrorCode::InvalidPath, "Append target must be a file"))?;
+    let parent = path.parent().map(PathBuf::from).unwrap_or_default();
+
+    Ok(parent.join(format_date_subfolder(format, now)?).join(file_name))
+}
+
+/// Returns a configured format string, treating blank values as unset.
+pub fn configured_format(format: &Option<String>) -> Option<&str> {
+    format.as_deref().filter(|format| !format.trim().is_empty())
+}
+
+/// Validates a date subfolder format: chrono `strftime` specifiers that render to a relative path.
+pub fn validate_date_subfolder_format(format: &str) -> Result<(), AppError> {
+    if format.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidPath,
+            "Date subfolder format cannot be empty",
+        ));
+    }
+
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(AppError::new(
+            ErrorCode::InvalidPath,
+            format!(
+                "Invalid date subfolder format '{}': unknown or incomplete % specifier",
+                format
+            ),
+        ));
+    }
+
+    let rendered = Utc::now().format(format).to_string();
+    let path = Path::new(&rendered);
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(AppError::new(
+            ErrorCode::InvalidPath,
+            format!(
+                "Date subfolder format '{}' must produce a relative path without '..'",
+                format
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates an append timestamp format made of chrono `strftime` specifiers.
+pub fn validate_append_timestamp_format(format: &str) -> Result<(), AppError> {
+    if format.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidPath,
+            "Append timestamp format cannot be empty",
+        ));
+    }
+
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(AppError::new(
+            ErrorCode::InvalidPath,
+            format!(
+                "Invalid append timestamp format '{}': unknown or incomplete % specifier",
+                format
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Appends a capture to existing text, separated by exactly one blank line and optionally timestamped.
+pub fn append_capture_text(
+    existing: &str, text: &str, timestamp_format: Option<&str>, now: DateTime<Utc>,
+) -> Result<String, AppError> {
+    let entry = text.trim_matches(['\r', '\n']);
+    let entry = match timestamp_format {
+        Some(format) => {
+            validate_append_timestamp_format(format)?;
+            format!("{} {}", now.format(format), entry)
+        }
+        None => entry.to_string(),
+    };
+
+    let existing = existing.trim_end_matches(['\r', '\n']);
+    if existing.trim().is_empty() {
+        return Ok(format!("{}\n", entry));
+    }
+
+    Ok(format!("{}\n\n{}\n", existing, entry))
+}
+
+fn format_date_subfolder(format: &str, now: DateTime<Utc>) -> Result<PathBuf, AppError> {
+    validate_date_subfolder_format(format)?;
+    Ok(PathBuf::from(now.format(format).to_string()))
 }
 
 /// Validates a shortcut string format.
@@ -293,11 +420,17 @@ pub fn validate_shortcut_format(shortcut: &str) -> Result<(), AppError> {
 }
 
 /// Handles capture submission based on mode.
+///
+/// Close behaviour, date and timestamp formats, and the missing-target policy come from `settings`.
 pub async fn handle_capture_submit(
     app: &AppHandle, mode: CaptureMode, text: String, target_location_id: Option<i64>, inbox_dir: &str,
-    append_target: &Option<writer_store::CaptureDocRef>, close_after_save: bool,
+    append_target: &Option<writer_store::CaptureDocRef>, settings: &GlobalCaptureSettings,
 ) -> Result<CaptureSubmitResult, AppError> {
     let state = app.state::<AppState>();
+    let close_after_save = settings.close_after_save;
+    let date_subfolder_format = configured_format(&settings.date_subfolder_format);
+    let append_subfolder_format = date_subfolder_format.filter(|_| settings.date_subfolder_for_append);
+    let append_timestamp_format = configured_format(&settings.append_timestamp_format);
 
     if text.trim().is_empty() {
         return Err(AppError::new(ErrorCode::InvalidPath, "Capture text cannot be empty"));
@@ -321,7 +454,7 @@ pub async fn handle_capture_submit(
 
     match mode {
         CaptureMode::QuickNote => {
-            let rel_path = generate_quick_note_path(inbox_dir);
+            let rel_path = resolve_quick_note_path(inbox_dir, date_subfolder_format)?;
             let doc_id = DocId::new(location_id, rel_path.clone())?;
             let result = state.store.doc_save(&doc_id, &text, None)?;
             if result.success {
@@ -350,11 +483,26 @@ pub async fn handle_capture_submit(
                 }
             };
 
-            let target_location = LocationId(target.location_id);
-            let rel_path = PathBuf::from(&target.rel_path);
+            let target_location = crate::locations::check_capture_destination(
+                &state.store,
+                LocationId(target.location_id),
+                settings.on_missing_target,
+            )?
+            .ok_or_else(|| {
+                AppError::new(
+                    ErrorCode::NotFound,
+                    "No location configured. Please add a location first.",
+                )
+            })?;
+            let rel_path = append_path_at(&target.rel_path, append_subfolder_format, Utc::now())?;
+            let rel_path_str = rel_path.to_string_lossy().to_string();
             let doc_id = DocId::new(target_location, rel_path)?;
-            let existing_text = state.store.doc_open(&doc_id).map(|c| c.text).unwrap_or_default();
-            let new_text = if existing_text.is_empty() { text } else { format!("{}\n\n{}", existing_text, text) };
+            let existing_text = match state.store.doc_open(&doc_id) {
+                Ok(content) => content.text,
+                Err(e) if e.code == ErrorCode::NotFound => String::new(),
+                Err(e) => return Err(e),
+            };
+            let new_text = append_capture_text(&existing_text, &text, append_timestamp_format, Utc::now())?;
             let result = state.store.doc_save(&doc_id, &new_text, None)?;
             if result.success {
                 let new_mtime = result
@@ -364,18 +512,18 @@ pub async fn handle_capture_submit(
                     .unwrap_or_else(chrono::Utc::now);
                 emit_capture_saved_event(app, &doc_id, new_mtime);
             }
-            let target_str = format!("{}/{}", target.location_id, target.rel_path);
+            let target_str = format!("{}/{}", target_location.0, rel_path_str);
 
             Ok(CaptureSubmitResult {
                 success: result.success,
-                saved_to: Some(target.rel_path.clone()),
-                location_id: target.location_id,
+                saved_to: Some(rel_path_str),
+                location_id: target_location.0,
                 should_close: close_after_save,
                 last_capture_target: Some(target_str),
             })
         }
         CaptureMode::WritingSession => {
-            let rel_path = generate_quick_note_path(inbox_dir);
+            let rel_path = resolve_quick_note_path(inbox_dir, date_subfolder_format)?;
             let doc_id = DocId::new(location_id, rel_path.clone())?;
             let result = state.store.doc_save(&doc_id, &text, None)?;
             if result.success {
@@ -421,7 +569,11 @@ pub fn update_last_capture_target(app: &AppHandle, target: Option<String>) -> Re
 
 #[cfg(test)]
 mod tests {
-    use super::{generate_quick_note_path, validate_shortcut_format};
+    use super::{
+        append_capture_text, append_path_at, dated_quick_note_path, generate_quick_note_path,
+        validate_date_subfolder_format, validate_shortcut_format,
+    };
+    use chrono::TimeZone;
 
     #[test]
     fn validate_shortcut_accepts_known_valid_shortcuts() {
@@ -447,4 +599,45 @@ mod tests {
         assert!(parts[2].ends_with(".md"));
         assert!(parts[2].starts_with(&format!("{}_", chrono::Utc::now().format("%Y_%m_%d"))));
     }
+
+    #[test]
+    fn date_subfolder_format_nests_quick_notes_and_append_targets() {
+        let now = chrono::Utc.with_ymd_and_hms(2024, 6, 15, 9, 30, 0).unwrap();
+
+        let quick_note = dated_quick_note_path("inbox", "%Y/%m", now).unwrap();
+        assert_eq!(
+            quick_note.to_string_lossy(),
+            format!("inbox/2024/06/2024_06_15_{}.md", now.timestamp_millis())
+        );
+
+        let append = append_path_at("journal/log.md", Some("%Y/%m"), now).unwrap();
+        assert_eq!(append.to_string_lossy(), "journal/2024/06/log.md");
+        assert_eq!(append_path_at("log.md", None, now).unwrap().to_string_lossy(), "log.md");
+    }
+
+    #[test]
+    fn validate_date_subfolder_format_rejects_invalid_formats() {
+        assert!(validate_date_subfolder_format("%Y/%m").is_ok());
+        assert!(validate_date_subfolder_format("   ").is_err());
+        assert!(validate_date_subfolder_format("%Y/%Q").is_err());
+        assert!(validate_date_subfolder_format("%Y/%").is_err());
+        assert!(validate_date_subfolder_format("/%Y").is_err());
+        assert!(validate_date_subfolder_format("../%Y").is_err());
+    }
+
+    #[test]
+    fn append_capture_text_prefixes_timestamp_and_keeps_prior_content() {
+        let now = chrono::Utc.with_ymd_and_hms(2024, 6, 15, 14, 5, 0).unwrap();
+
+        let first = append_capture_text("", "Started the draft\n", Some("%H:%M"), now).unwrap();
+        assert_eq!(first, "14:05 Started the draft\n");
+
+        let existing = "# Log\n\n09:00 Coffee\n\n\n";
+        let appended = append_capture_text(existing, "Finished chapter two", Some("%H:%M"), now).unwrap();
+        assert_eq!(appended, "# Log\n\n09:00 Coffee\n\n14:05 Finished chapter two\n");
+
+        let plain = append_capture_text("Note", "More", None, now).unwrap();
+        assert_eq!(plain, "Note\n\nMore\n");
+        assert!(append_capture_text("Note", "More", Some("%H:%Q"), now).is_err());
+    }
 }
@@ -3,17 +3,26 @@ use super::locations::*;
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, State};
 use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_fs::FsExt;
 use writer_core::atproto::AtProtoState;
-use writer_core::scan_style_matches;
+use writer_core::scan_style_report;
 use writer_core::{
-    AppError, BackendEvent, CommandResult, DocContent, DocId, DocListOptions, DocMeta, LocationDescriptor, LocationId,
-    SaveResult, SearchFilters, SearchHit, StyleCategorySettings, StyleMatch, StylePatternInput, StyleScanInput,
+    AppError, BackendEvent, CommandResult, DiffHunk, DocContent, DocId, DocListOptions, DocMeta, DocSortField,
+    Durability, Encoding, FindMatch, FindOptions, ImportOptions, ImportReport, IndexAudit, LocationDescriptor,
+    LocationId, RawDocContent, ReconcilePlan, ReplaceOptions, ReplaceResult, SaveResult, SearchFilters, SearchHit,
+    SearchOptions, StyleCategorySettings, StyleMatch, StylePatternInput, StyleScanInput, StyleScanReport, StyleSummary,
+    TagBulkReport,
+};
+use writer_md::{AssetMode, DocStats, ExportOptions, Heading, ImageRef};
+use writer_store::{
+    DirEntry, LocationViewPrefs, SearchSettings, SidebarTreeState, SplitNaming, Store, StyleCheckSettings,
+    UiLayoutSettings,
 };
-use writer_store::{SidebarTreeState, Store, StyleCheckSettings, UiLayoutSettings};
 
 mod atproto;
 mod md;
@@ -32,13 +41,20 @@ pub struct AppState {
     pub store: Arc<Store>,
     pub watchers: Mutex<HashMap<i64, RecommendedWatcher>>,
     pub atproto: Arc<AtProtoState>,
+    /// Cancellation flags for background reindexes, keyed by location id
+    pub reindex_jobs: Arc<Mutex<HashMap<i64, Arc<AtomicBool>>>>,
 }
 
 impl AppState {
     pub fn new(store: Store) -> Self {
         let app_dir = Store::default_app_dir().expect("store app dir should resolve");
         let atproto = AtProtoState::new(&app_dir).expect("AT Protocol state should initialize");
-        Self { store: Arc::new(store), watchers: Mutex::new(HashMap::new()), atproto: Arc::new(atproto) }
+        Self {
+            store: Arc::new(store),
+            watchers: Mutex::new(HashMap::new()),
+            atproto: Arc::new(atproto),
+            reindex_jobs: Arc::new(Mutex::new(HashMap::new())),
+        }
     }
 }
 
@@ -146,6 +162,212 @@ pub fn location_remove(state: State<'_, AppState>, location_id: i64) -> CommandR
     }
 }
 
+/// Sets which file extensions a location indexes, then reconciles its search index
+///
+/// Passing `None` restores the default extension set.
+#[tauri::command]
+pub fn location_set_indexable_extensions(
+    state: State<'_, AppState>, location_id: i64, extensions: Option<Vec<String>>,
+) -> CommandResponse<LocationDescriptor> {
+    let id = LocationId(location_id);
+    log::debug!(
+        "Setting indexable extensions: location={}, extensions={:?}",
+        location_id,
+        extensions
+    );
+
+    match state.store.location_set_indexable_extensions(id, extensions) {
+        Ok(descriptor) => {
+            if let Err(error) = state.store.reconcile_location_index(id) {
+                log::warn!(
+                    "Index rebuild failed after extension change for location {:?}: {}",
+                    id,
+                    error
+                );
+            }
+            Ok(CommandResult::ok(descriptor))
+        }
+        Err(e) => {
+            log::error!("Failed to set indexable extensions: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Sets the gitignore-style patterns a location excludes from indexing and prunes matching files
+///
+/// An empty list indexes everything, including hidden directories.
+#[tauri::command]
+pub fn location_set_ignore_globs(
+    state: State<'_, AppState>, location_id: i64, ignore_globs: Vec<String>,
+) -> CommandResponse<LocationDescriptor> {
+    let id = LocationId(location_id);
+    log::debug!(
+        "Setting ignore globs: location={}, globs={:?}",
+        location_id,
+        ignore_globs
+    );
+
+    match state.store.location_set_ignore_globs(id, ignore_globs) {
+        Ok(descriptor) => {
+            if let Err(error) = state.store.reconcile_location_index(id) {
+                log::warn!(
+                    "Index rebuild failed after ignore glob change for location {:?}: {}",
+                    id,
+                    error
+                );
+            }
+            Ok(CommandResult::ok(descriptor))
+        }
+        Err(e) => {
+            log::error!("Failed to set ignore globs: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Reports what reindexing a location would add, update, or remove without applying it
+#[tauri::command]
+pub fn location_reconcile_plan(state: State<'_, AppState>, location_id: i64) -> CommandResponse<ReconcilePlan> {
+    let id = LocationId(location_id);
+    log::debug!("Planning reconcile: location={}", location_id);
+
+    match state.store.reconcile_location_plan(id) {
+        Ok(plan) => Ok(CommandResult::ok(plan)),
+        Err(e) => {
+            log::error!("Failed to plan reconcile: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Reports where a location's files, catalog, and search index disagree, without fixing anything
+#[tauri::command]
+pub fn index_audit(state: State<'_, AppState>, location_id: i64) -> CommandResponse<IndexAudit> {
+    let id = LocationId(location_id);
+    log::debug!("Auditing index: location={}", location_id);
+
+    match state.store.index_audit(id) {
+        Ok(audit) => {
+            if !audit.is_consistent() {
+                log::warn!(
+                    "Index audit found inconsistencies in location {}: {:?}",
+                    location_id,
+                    audit
+                );
+            }
+            Ok(CommandResult::ok(audit))
+        }
+        Err(e) => {
+            log::error!("Failed to audit location index: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Drops a location's catalog and search index rows without touching its files, returning how many documents were removed
+#[tauri::command]
+pub fn location_clear_index(state: State<'_, AppState>, location_id: i64) -> CommandResponse<usize> {
+    let id = LocationId(location_id);
+    log::debug!("Clearing index: location={}", location_id);
+
+    match state.store.location_clear_index(id) {
+        Ok(removed) => Ok(CommandResult::ok(removed)),
+        Err(e) => {
+            log::error!("Failed to clear location index: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Minimum time between `ReconcileProgress` events from a background reindex
+const REINDEX_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Reindexes a location on a background thread, emitting progress and a final completion event
+///
+/// Only one background reindex runs per location; starting another while it runs is a conflict.
+/// `Store::reconcile_location_index` remains the blocking path used by the watcher and other commands.
+#[tauri::command]
+pub fn location_reindex_start(app: AppHandle, state: State<'_, AppState>, location_id: i64) -> CommandResponse<()> {
+    log::debug!("Starting background reindex: location={}", location_id);
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut jobs = match state.reindex_jobs.lock() {
+            Ok(jobs) => jobs,
+            Err(_) => return Ok(CommandResult::err(AppError::io("Failed to lock reindex jobs"))),
+        };
+        if jobs.contains_key(&location_id) {
+            return Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Conflict,
+                "A reindex is already running for this location",
+            )));
+        }
+        jobs.insert(location_id, cancel.clone());
+    }
+
+    let store = state.store.clone();
+    let jobs = state.reindex_jobs.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let id = LocationId(location_id);
+        let mut last_emit: Option<Instant> = None;
+        let result = store.reconcile_location_index_with_progress(id, &cancel, |processed, total| {
+            let due = last_emit.is_none_or(|emitted| emitted.elapsed() >= REINDEX_PROGRESS_INTERVAL);
+            if !due && processed < total {
+                return;
+            }
+
+            last_emit = Some(Instant::now());
+            let event = BackendEvent::ReconcileProgress { location_id: id, processed, total };
+            if let Err(e) = app.emit("backend-event", event) {
+                log::warn!("Failed to emit reindex progress: {}", e);
+            }
+        });
+
+        if let Ok(mut jobs) = jobs.lock() {
+            jobs.remove(&location_id);
+        }
+
+        let event = match result {
+            Ok(Some(indexed)) => {
+                BackendEvent::ReconcileFinished { location_id: id, indexed, cancelled: false, error: None }
+            }
+            Ok(None) => BackendEvent::ReconcileFinished { location_id: id, indexed: 0, cancelled: true, error: None },
+            Err(e) => {
+                log::error!("Background reindex failed for location {}: {}", location_id, e);
+                BackendEvent::ReconcileFinished {
+                    location_id: id,
+                    indexed: 0,
+                    cancelled: false,
+                    error: Some(e.message),
+                }
+            }
+        };
+        if let Err(e) = app.emit("backend-event", event) {
+            log::warn!("Failed to emit reindex completion: {}", e);
+        }
+    });
+
+    Ok(CommandResult::ok(()))
+}
+
+/// Asks a running background reindex to stop; returns whether one was running
+#[tauri::command]
+pub fn location_reindex_cancel(state: State<'_, AppState>, location_id: i64) -> CommandResponse<bool> {
+    log::debug!("Cancelling background reindex: location={}", location_id);
+
+    match state.reindex_jobs.lock() {
+        Ok(jobs) => match jobs.get(&location_id) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::Relaxed);
+                Ok(CommandResult::ok(true))
+            }
+            None => Ok(CommandResult::ok(false)),
+        },
+        Err(_) => Ok(CommandResult::err(AppError::io("Failed to lock reindex jobs"))),
+    }
+}
+
 /// Validates all locations and returns those that no longer exist
 #[tauri::command]
 pub fn location_validate(state: State<'_, AppState>) -> CommandResponse<Vec<(i64, String)>> {
@@ -199,6 +421,35 @@ pub fn ui_layout_set(state: State<'_, AppState>, settings: UiLayoutSettings) ->
     }
 }
 
+/// Returns the README content written to new locations (the custom template, or the built-in one)
+#[tauri::command]
+pub fn readme_template_get(state: State<'_, AppState>) -> CommandResponse<String> {
+    log::debug!("Loading README template");
+
+    match state.store.readme_template_get() {
+        Ok(template) => Ok(CommandResult::ok(template)),
+        Err(e) => {
+            log::error!("Failed to load README template: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Sets a custom README template for new locations; `None` restores the built-in one and an empty
+/// template turns README creation off
+#[tauri::command]
+pub fn readme_template_set(state: State<'_, AppState>, template: Option<String>) -> CommandResponse<bool> {
+    log::debug!("Persisting README template");
+
+    match state.store.readme_template_set(template.as_deref()) {
+        Ok(()) => Ok(CommandResult::ok(true)),
+        Err(e) => {
+            log::error!("Failed to persist README template: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
 #[tauri::command]
 pub fn sidebar_tree_get(state: State<'_, AppState>) -> CommandResponse<SidebarTreeState> {
     log::debug!("Loading persisted sidebar tree state");
@@ -225,6 +476,36 @@ pub fn sidebar_tree_set(state: State<'_, AppState>, state_value: SidebarTreeStat
     }
 }
 
+/// Loads the listing view last saved for a location, or the default view when there is none
+#[tauri::command]
+pub fn location_view_prefs_get(state: State<'_, AppState>, location_id: i64) -> CommandResponse<LocationViewPrefs> {
+    log::debug!("Loading view prefs: location={}", location_id);
+
+    match state.store.location_view_prefs_get(LocationId(location_id)) {
+        Ok(view) => Ok(CommandResult::ok(view)),
+        Err(e) => {
+            log::error!("Failed to load view prefs: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Saves the listing view (sort, recursion, extension filter) for a location
+#[tauri::command]
+pub fn location_view_prefs_set(
+    state: State<'_, AppState>, location_id: i64, view: LocationViewPrefs,
+) -> CommandResponse<bool> {
+    log::debug!("Persisting view prefs: location={}", location_id);
+
+    match state.store.location_view_prefs_set(LocationId(location_id), &view) {
+        Ok(()) => Ok(CommandResult::ok(true)),
+        Err(e) => {
+            log::error!("Failed to persist view prefs: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
 #[tauri::command]
 pub fn session_last_doc_get(state: State<'_, AppState>) -> CommandResponse<Option<writer_store::CaptureDocRef>> {
     log::debug!("Loading last opened document session state");
@@ -398,75 +679,1092 @@ pub fn session_prune_locations(
     match state.store.session_prune_locations(&valid_ids) {
         Ok(session) => Ok(CommandResult::ok(session)),
         Err(e) => {
-            log::error!("Failed to prune session tabs by locations: {}", e);
+            log::error!("Failed to prune session tabs by locations: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Lists documents in a location
+#[tauri::command]
+pub fn doc_list(
+    state: State<'_, AppState>, location_id: i64, options: Option<DocListOptions>,
+) -> CommandResponse<Vec<DocMeta>> {
+    let id = LocationId(location_id);
+    let list_options = Some(options.unwrap_or(DocListOptions { recursive: true, ..Default::default() }));
+    log::debug!("Listing documents for location: id={}", location_id);
+
+    match state.store.doc_list(id, list_options) {
+        Ok(docs) => {
+            log::debug!("Found {} documents in location {}", docs.len(), location_id);
+            Ok(CommandResult::ok(docs))
+        }
+        Err(e) => {
+            log::error!("Failed to list documents: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Lists the most recently edited documents across all locations for the home screen
+///
+/// Conflicted copies and trashed files are left out unless `include_hidden` is set.
+#[tauri::command]
+pub fn recently_edited(
+    state: State<'_, AppState>, limit: Option<usize>, include_hidden: Option<bool>,
+) -> CommandResponse<Vec<DocMeta>> {
+    let limit = limit.unwrap_or(20);
+    log::debug!("Listing recently edited documents: limit={}", limit);
+
+    match state.store.recently_edited(limit, !include_hidden.unwrap_or(false)) {
+        Ok(docs) => Ok(CommandResult::ok(docs)),
+        Err(e) => {
+            log::error!("Failed to list recently edited documents: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Lists a location's documents like `doc_list`, leaving out drafts (`draft: true` in front matter)
+#[tauri::command]
+pub fn doc_list_published(
+    state: State<'_, AppState>, location_id: i64, options: Option<DocListOptions>,
+) -> CommandResponse<Vec<DocMeta>> {
+    let id = LocationId(location_id);
+    let list_options = Some(options.unwrap_or(DocListOptions { recursive: true, ..Default::default() }));
+    log::debug!("Listing published documents for location: id={}", location_id);
+
+    match state.store.doc_list_published(id, list_options) {
+        Ok(docs) => Ok(CommandResult::ok(docs)),
+        Err(e) => {
+            log::error!("Failed to list published documents: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Returns the previous and next documents around a document, ordered like `doc_list` for `sort`
+///
+/// Siblings are limited to the document's directory unless `whole_location` is set.
+#[tauri::command]
+pub fn doc_neighbors(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, sort: DocSortField, whole_location: Option<bool>,
+) -> CommandResponse<(Option<DocMeta>, Option<DocMeta>)> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Finding document neighbors: location={:?}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state
+            .store
+            .doc_neighbors(&doc_id, sort, whole_location.unwrap_or(false))
+        {
+            Ok(neighbors) => Ok(CommandResult::ok(neighbors)),
+            Err(e) => {
+                log::error!("Failed to find document neighbors: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Lists documents in a location from the catalog, without touching the filesystem
+#[tauri::command]
+pub fn doc_list_catalog(
+    state: State<'_, AppState>, location_id: i64, options: Option<DocListOptions>,
+) -> CommandResponse<Vec<DocMeta>> {
+    let id = LocationId(location_id);
+    let list_options = Some(options.unwrap_or(DocListOptions { recursive: true, ..Default::default() }));
+    log::debug!("Listing catalogued documents for location: id={}", location_id);
+
+    match state.store.doc_list_catalog(id, list_options) {
+        Ok(docs) => Ok(CommandResult::ok(docs)),
+        Err(e) => {
+            log::error!("Failed to list catalogued documents: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Lists near-empty text documents (fewer than `min_words` words) in a location for cleanup
+#[tauri::command]
+pub fn find_stubs(state: State<'_, AppState>, location_id: i64, min_words: usize) -> CommandResponse<Vec<DocMeta>> {
+    let id = LocationId(location_id);
+    log::debug!(
+        "Finding stub documents: location={}, min_words={}",
+        location_id,
+        min_words
+    );
+
+    match state.store.find_stubs(id, min_words) {
+        Ok(docs) => Ok(CommandResult::ok(docs)),
+        Err(e) => {
+            log::error!("Failed to find stub documents: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Lists text documents in a location that no other document there links to
+#[tauri::command]
+pub fn find_orphans(state: State<'_, AppState>, location_id: i64) -> CommandResponse<Vec<DocMeta>> {
+    let id = LocationId(location_id);
+    log::debug!("Finding orphaned documents: location={}", location_id);
+
+    match state.store.find_orphans(id) {
+        Ok(docs) => Ok(CommandResult::ok(docs)),
+        Err(e) => {
+            log::error!("Failed to find orphaned documents: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Lists directories in a location
+#[tauri::command]
+pub fn dir_list(state: State<'_, AppState>, location_id: i64) -> CommandResponse<Vec<String>> {
+    let id = LocationId(location_id);
+    log::debug!("Listing directories for location: id={}", location_id);
+
+    match state.store.dir_list(id, None, true) {
+        Ok(directories) => {
+            let values = directories
+                .into_iter()
+                .map(|entry| entry.rel_path.to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            log::debug!("Found {} directories in location {}", values.len(), location_id);
+            Ok(CommandResult::ok(values))
+        }
+        Err(e) => {
+            log::error!("Failed to list directories: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Lists the subdirectories under `rel_path` (the location root when omitted) with their child counts
+///
+/// Only direct children are returned unless `recursive` is set. A missing `rel_path` gives an empty list.
+#[tauri::command]
+pub fn dir_entries(
+    state: State<'_, AppState>, location_id: i64, rel_path: Option<String>, recursive: Option<bool>,
+) -> CommandResponse<Vec<DirEntry>> {
+    let id = LocationId(location_id);
+    let rel_path = rel_path.map(PathBuf::from);
+    log::debug!(
+        "Listing directory entries: location={}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match state
+        .store
+        .dir_list(id, rel_path.as_deref(), recursive.unwrap_or(false))
+    {
+        Ok(entries) => Ok(CommandResult::ok(entries)),
+        Err(e) => {
+            log::error!("Failed to list directory entries: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Opens a document by location_id and relative path
+#[tauri::command]
+pub fn doc_open(state: State<'_, AppState>, location_id: i64, rel_path: String) -> CommandResponse<DocContent> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!("Opening document: location={:?}, path={:?}", location_id, rel_path);
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_open(&doc_id) {
+            Ok(content) => {
+                log::info!(
+                    "Document opened successfully: location={:?}, size={} bytes",
+                    doc_id.location_id,
+                    content.meta.size_bytes
+                );
+                Ok(CommandResult::ok(content))
+            }
+            Err(e) => {
+                log::error!("Failed to open document: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Returns a document's raw bytes with its detected encoding and line ending, for raw views
+///
+/// Undecodable or NUL-containing content is flagged `is_binary` rather than reported as an error.
+#[tauri::command]
+pub fn doc_open_raw(state: State<'_, AppState>, location_id: i64, rel_path: String) -> CommandResponse<RawDocContent> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!("Opening raw document: location={:?}, path={:?}", location_id, rel_path);
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_open_raw(&doc_id) {
+            Ok(raw) => Ok(CommandResult::ok(raw)),
+            Err(e) => {
+                log::error!("Failed to open raw document: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Returns the line hunks between the document on disk and the editor's unsaved text
+#[tauri::command]
+pub fn doc_pending_diff(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, text: String,
+) -> CommandResponse<Vec<DiffHunk>> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Diffing unsaved text against disk: location={:?}, path={:?}, text_len={}",
+        location_id,
+        rel_path,
+        text.len()
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_pending_diff(&doc_id, &text) {
+            Ok(hunks) => Ok(CommandResult::ok(hunks)),
+            Err(e) => {
+                log::error!("Failed to diff document against disk: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Opens a document, creating it with `initial_text` first when it does not exist yet
+#[tauri::command]
+pub fn doc_open_or_create(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, initial_text: Option<String>,
+) -> CommandResponse<DocContent> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Opening or creating document: location={:?}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match state
+        .store
+        .doc_open_or_create(location_id, &rel_path, initial_text.as_deref())
+    {
+        Ok(content) => Ok(CommandResult::ok(content)),
+        Err(e) => {
+            log::error!("Failed to open or create document: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Opens a document decoded with an explicit encoding, for files whose detected encoding is wrong
+#[tauri::command]
+pub fn doc_open_with_encoding(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, encoding: Encoding,
+) -> CommandResponse<DocContent> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Opening document with encoding: location={:?}, path={:?}, encoding={:?}",
+        location_id,
+        rel_path,
+        encoding
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_open_with_encoding(&doc_id, encoding) {
+            Ok(content) => Ok(CommandResult::ok(content)),
+            Err(e) => {
+                log::error!("Failed to open document with encoding {:?}: {}", encoding, e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Saves a document with atomic write semantics
+#[tauri::command]
+pub fn doc_save(
+    app: AppHandle, state: State<'_, AppState>, location_id: i64, rel_path: String, text: String,
+    durability: Option<Durability>,
+) -> CommandResponse<SaveResult> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Saving document: location={:?}, path={:?}, size={} bytes",
+        location_id,
+        rel_path,
+        text.len()
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state
+            .store
+            .doc_save_with_durability(&doc_id, &text, None, durability.unwrap_or_default())
+        {
+            Ok(result) => {
+                if result.conflict_detected {
+                    log::warn!(
+                        "Conflicted copy detected: location={:?}, path={:?}",
+                        doc_id.location_id,
+                        doc_id.rel_path
+                    );
+
+                    let event = BackendEvent::ConflictDetected {
+                        location_id: doc_id.location_id,
+                        rel_path: doc_id.rel_path.clone(),
+                        conflict_filename: doc_id
+                            .rel_path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_else(|| "unknown".to_string()),
+                    };
+
+                    if let Err(e) = app.emit("backend-event", event) {
+                        log::error!("Failed to emit conflict event: {}", e);
+                    }
+                }
+
+                log::info!(
+                    "Document saved successfully: location={:?}, size={} bytes",
+                    doc_id.location_id,
+                    text.len()
+                );
+
+                let new_mtime = result
+                    .new_meta
+                    .as_ref()
+                    .map(|meta| meta.mtime)
+                    .unwrap_or_else(chrono::Utc::now);
+                emit_doc_modified_event(&app, doc_id.clone(), new_mtime);
+
+                if let Some(progress) = result.goal_progress {
+                    let event = BackendEvent::GoalProgress {
+                        doc_id: doc_id.clone(),
+                        current_words: progress.current_words,
+                        target_words: progress.target_words,
+                    };
+
+                    if let Err(e) = app.emit("backend-event", event) {
+                        log::error!("Failed to emit goal progress event: {}", e);
+                    }
+                }
+
+                Ok(CommandResult::ok(result))
+            }
+            Err(e) => {
+                log::error!("Failed to save document: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Finds matches of a query within a single document
+#[tauri::command]
+pub fn find_in_document(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, query: String, options: Option<FindOptions>,
+) -> CommandResponse<Vec<FindMatch>> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+    let options = options.unwrap_or_default();
+
+    log::debug!(
+        "Finding in document: location={:?}, path={:?}, query_len={}",
+        location_id,
+        rel_path,
+        query.len()
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state
+            .store
+            .doc_open(&doc_id)
+            .and_then(|content| writer_core::find_in_text(&content.text, &query, &options))
+        {
+            Ok(matches) => Ok(CommandResult::ok(matches)),
+            Err(e) => {
+                log::error!("Failed to find in document: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Replaces matches of a query within a single document, or with `preview_only` just reports them
+#[tauri::command]
+pub fn replace_in_document(
+    app: AppHandle, state: State<'_, AppState>, location_id: i64, rel_path: String, query: String, replacement: String,
+    options: Option<ReplaceOptions>,
+) -> CommandResponse<ReplaceResult> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+    let options = options.unwrap_or_default();
+
+    log::debug!(
+        "Replacing in document: location={:?}, path={:?}, preview_only={}",
+        location_id,
+        rel_path,
+        options.preview_only
+    );
+
+    let doc_id = match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => doc_id,
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            return Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))));
+        }
+    };
+
+    let result = match state
+        .store
+        .doc_open(&doc_id)
+        .and_then(|content| writer_core::replace_in_text(&content.text, &query, &replacement, &options))
+    {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!("Failed to replace in document: {}", e);
+            return Ok(CommandResult::err(e));
+        }
+    };
+
+    if options.preview_only || result.replacements.is_empty() {
+        return Ok(CommandResult::ok(result));
+    }
+
+    match state.store.doc_save(&doc_id, &result.text, None) {
+        Ok(saved) => {
+            let new_mtime = saved
+                .new_meta
+                .as_ref()
+                .map(|meta| meta.mtime)
+                .unwrap_or_else(chrono::Utc::now);
+            emit_doc_modified_event(&app, doc_id, new_mtime);
+            Ok(CommandResult::ok(result))
+        }
+        Err(e) => {
+            log::error!("Failed to save replaced document: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Returns statistics for a document, computed from `text` when the editor has unsaved changes
+#[tauri::command]
+pub fn doc_stats(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, text: Option<String>,
+) -> CommandResponse<DocStats> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Computing document stats: location={:?}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_stats(&doc_id, text.as_deref()) {
+            Ok(stats) => Ok(CommandResult::ok(stats)),
+            Err(e) => {
+                log::error!("Failed to compute document stats: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Returns a document's heading outline, served from the catalog cache while the file is unchanged
+#[tauri::command]
+pub fn doc_outline(
+    state: State<'_, AppState>, location_id: i64, rel_path: String,
+) -> CommandResponse<Option<Vec<Heading>>> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Loading document outline: location={:?}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_outline_cached(&doc_id) {
+            Ok(outline) => Ok(CommandResult::ok(outline)),
+            Err(e) => {
+                log::error!("Failed to load document outline: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Returns a document's word count over time as `(timestamp, words)` points, oldest first
+#[tauri::command]
+pub fn word_count_history(
+    state: State<'_, AppState>, location_id: i64, rel_path: String,
+) -> CommandResponse<Vec<(chrono::DateTime<chrono::Utc>, usize)>> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Loading word count history: location={:?}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.word_count_history(&doc_id) {
+            Ok(history) => Ok(CommandResult::ok(history)),
+            Err(e) => {
+                log::error!("Failed to load word count history: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Returns the word-count goal set for a document
+#[tauri::command]
+pub fn doc_goal_get(state: State<'_, AppState>, location_id: i64, rel_path: String) -> CommandResponse<Option<usize>> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!("Loading document goal: location={:?}, path={:?}", location_id, rel_path);
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_goal_get(&doc_id) {
+            Ok(target_words) => Ok(CommandResult::ok(target_words)),
+            Err(e) => {
+                log::error!("Failed to load document goal: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Sets (or with `None`, clears) a document's word-count goal
+#[tauri::command]
+pub fn doc_goal_set(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, target_words: Option<usize>,
+) -> CommandResponse<bool> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Setting document goal: location={:?}, path={:?}, target_words={:?}",
+        location_id,
+        rel_path,
+        target_words
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => {
+            let result = match target_words {
+                Some(target_words) => state.store.doc_goal_set(&doc_id, target_words).map(|_| true),
+                None => state.store.doc_goal_clear(&doc_id),
+            };
+
+            match result {
+                Ok(changed) => Ok(CommandResult::ok(changed)),
+                Err(e) => {
+                    log::error!("Failed to set document goal: {}", e);
+                    Ok(CommandResult::err(e))
+                }
+            }
+        }
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Stores unsaved editor content for crash recovery; returns false when it is over the size limit
+#[tauri::command]
+pub fn autosave_put(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, text: String,
+) -> CommandResponse<bool> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Writing autosave entry: location={:?}, path={:?}, bytes={}",
+        location_id,
+        rel_path,
+        text.len()
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.autosave_put(&doc_id, &text) {
+            Ok(stored) => Ok(CommandResult::ok(stored)),
+            Err(e) => {
+                log::error!("Failed to write autosave entry: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Removes and returns a document's autosave entry, if it has one
+#[tauri::command]
+pub fn autosave_take(
+    state: State<'_, AppState>, location_id: i64, rel_path: String,
+) -> CommandResponse<Option<writer_store::AutosaveRecovery>> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!("Taking autosave entry: location={:?}, path={:?}", location_id, rel_path);
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.autosave_take(&doc_id) {
+            Ok(recovery) => Ok(CommandResult::ok(recovery)),
+            Err(e) => {
+                log::error!("Failed to take autosave entry: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Lists documents with unsaved content to offer for recovery on startup
+#[tauri::command]
+pub fn autosave_pending(state: State<'_, AppState>) -> CommandResponse<Vec<writer_store::AutosavePending>> {
+    log::debug!("Listing pending autosave entries");
+
+    match state.store.autosave_pending() {
+        Ok(pending) => Ok(CommandResult::ok(pending)),
+        Err(e) => {
+            log::error!("Failed to list autosave entries: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Returns the stable uid of a catalogued document
+#[tauri::command]
+pub fn doc_uid_get(state: State<'_, AppState>, location_id: i64, rel_path: String) -> CommandResponse<Option<String>> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!("Getting document uid: location={:?}, path={:?}", location_id, rel_path);
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_uid(&doc_id) {
+            Ok(uid) => Ok(CommandResult::ok(uid)),
+            Err(e) => {
+                log::error!("Failed to get document uid: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Finds the document carrying a stable uid, wherever it has been renamed or moved to
+#[tauri::command]
+pub fn doc_by_uid(state: State<'_, AppState>, uid: String) -> CommandResponse<Option<DocMeta>> {
+    log::debug!("Looking up document by uid: {}", uid);
+
+    match state.store.doc_by_uid(&uid) {
+        Ok(meta) => Ok(CommandResult::ok(meta)),
+        Err(e) => {
+            log::error!("Failed to look up document by uid: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Checks if a document exists in a location
+#[tauri::command]
+pub fn doc_exists(state: State<'_, AppState>, location_id: i64, rel_path: String) -> CommandResponse<bool> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Checking document existence: location={:?}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.location_get(doc_id.location_id) {
+            Ok(Some(location)) => {
+                let full_path = doc_id.resolve(&location.root_path);
+                let exists = full_path.exists();
+                Ok(CommandResult::ok(exists))
+            }
+            Ok(None) => {
+                log::warn!("Location not found: {:?}", doc_id.location_id);
+                Ok(CommandResult::err(AppError::not_found("Location not found")))
+            }
+            Err(e) => {
+                log::error!("Failed to check location: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Resolves a wikilink target to the document declaring it as a front matter alias
+#[tauri::command]
+pub fn doc_resolve_alias(
+    state: State<'_, AppState>, location_id: i64, alias: String,
+) -> CommandResponse<Option<DocMeta>> {
+    let location_id = LocationId(location_id);
+    log::debug!("Resolving alias: location={:?}, alias={}", location_id, alias);
+
+    match state.store.resolve_alias(location_id, &alias) {
+        Ok(meta) => Ok(CommandResult::ok(meta)),
+        Err(e) => {
+            log::error!("Failed to resolve alias: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Lists the local images a document references that are missing on disk
+#[tauri::command]
+pub fn doc_check_images(
+    state: State<'_, AppState>, location_id: i64, rel_path: String,
+) -> CommandResponse<Vec<ImageRef>> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Checking document images: location={:?}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.check_document_images(&doc_id) {
+            Ok(missing) => Ok(CommandResult::ok(missing)),
+            Err(e) => {
+                log::error!("Failed to check document images: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Exports a document as standalone HTML, optionally inlining or copying its local images
+#[tauri::command]
+pub fn doc_export_html(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, output_path: String, asset_mode: Option<AssetMode>,
+) -> CommandResponse<bool> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+    let output_path = PathBuf::from(&output_path);
+    let options = ExportOptions { asset_mode: asset_mode.unwrap_or_default(), ..ExportOptions::standalone() };
+
+    log::debug!(
+        "Exporting document to HTML: location={:?}, path={:?}, output={:?}, asset_mode={:?}",
+        location_id,
+        rel_path,
+        output_path,
+        options.asset_mode
+    );
+
+    match DocId::new(location_id, rel_path) {
+        Ok(doc_id) => match state.store.doc_export_html(&doc_id, &options, &output_path) {
+            Ok(()) => Ok(CommandResult::ok(true)),
+            Err(e) => {
+                log::error!("Failed to export document to HTML: {}", e);
+                Ok(CommandResult::err(e))
+            }
+        },
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))))
+        }
+    }
+}
+
+/// Exports every file in a location to a zip archive with a manifest, returning how many files were archived
+#[tauri::command]
+pub fn location_export_zip(
+    state: State<'_, AppState>, location_id: i64, output_path: String,
+) -> CommandResponse<usize> {
+    let location_id = LocationId(location_id);
+    let output_path = PathBuf::from(&output_path);
+    log::debug!(
+        "Exporting location to zip: location={:?}, output={:?}",
+        location_id,
+        output_path
+    );
+
+    match state.store.export_location_zip(location_id, &output_path) {
+        Ok(count) => Ok(CommandResult::ok(count)),
+        Err(e) => {
+            log::error!("Failed to export location to zip: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Writes a location's catalog metadata as a CSV file for spreadsheets, returning the number of bytes written
+#[tauri::command]
+pub fn location_export_csv(
+    state: State<'_, AppState>, location_id: i64, output_path: String,
+) -> CommandResponse<usize> {
+    let location_id = LocationId(location_id);
+    let output_path = PathBuf::from(&output_path);
+    log::debug!(
+        "Exporting catalog to CSV: location={:?}, output={:?}",
+        location_id,
+        output_path
+    );
+
+    let written = state.store.export_catalog_csv(location_id).and_then(|csv| {
+        std::fs::write(&output_path, &csv)
+            .map(|_| csv.len())
+            .map_err(|e| AppError::io(format!("Failed to write CSV file: {}", e)))
+    });
+
+    match written {
+        Ok(bytes) => Ok(CommandResult::ok(bytes)),
+        Err(e) => {
+            log::error!("Failed to export catalog to CSV: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Extracts a zip archive into a location, handling existing files per `options`
+#[tauri::command]
+pub fn location_import_zip(
+    state: State<'_, AppState>, location_id: i64, zip_path: String, options: Option<ImportOptions>,
+) -> CommandResponse<ImportReport> {
+    let location_id = LocationId(location_id);
+    let zip_path = PathBuf::from(&zip_path);
+    let options = options.unwrap_or_default();
+    log::debug!(
+        "Importing zip into location: location={:?}, zip={:?}, options={:?}",
+        location_id,
+        zip_path,
+        options
+    );
+
+    match state.store.import_location_zip(location_id, &zip_path, &options) {
+        Ok(report) => Ok(CommandResult::ok(report)),
+        Err(e) => {
+            log::error!("Failed to import zip into location: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Renames a tag across every document in a location, returning how many documents changed
+#[tauri::command]
+pub fn tag_rename(
+    state: State<'_, AppState>, location_id: i64, old_tag: String, new_tag: String,
+) -> CommandResponse<usize> {
+    let location_id = LocationId(location_id);
+    log::debug!("Renaming tag: location={:?}, {} -> {}", location_id, old_tag, new_tag);
+
+    match state.store.tag_rename(location_id, &old_tag, &new_tag) {
+        Ok(count) => Ok(CommandResult::ok(count)),
+        Err(e) => {
+            log::error!("Failed to rename tag: {}", e);
             Ok(CommandResult::err(e))
         }
     }
 }
 
-/// Lists documents in a location
+/// Empties old entries from every location's trash, or reports what would go with `dry_run`
 #[tauri::command]
-pub fn doc_list(
-    state: State<'_, AppState>, location_id: i64, options: Option<DocListOptions>,
-) -> CommandResponse<Vec<DocMeta>> {
-    let id = LocationId(location_id);
-    let list_options = Some(options.unwrap_or(DocListOptions { recursive: true, ..Default::default() }));
-    log::debug!("Listing documents for location: id={}", location_id);
+pub fn maintenance_purge(
+    state: State<'_, AppState>, policy: writer_store::PurgePolicy,
+) -> CommandResponse<writer_store::PurgeReport> {
+    log::debug!("Purging trash: {:?}", policy);
 
-    match state.store.doc_list(id, list_options) {
-        Ok(docs) => {
-            log::debug!("Found {} documents in location {}", docs.len(), location_id);
-            Ok(CommandResult::ok(docs))
-        }
+    match state.store.maintenance_purge(policy) {
+        Ok(report) => Ok(CommandResult::ok(report)),
         Err(e) => {
-            log::error!("Failed to list documents: {}", e);
+            log::error!("Failed to purge trash: {}", e);
             Ok(CommandResult::err(e))
         }
     }
 }
 
-/// Lists directories in a location
+/// Lists distinct tags with document counts, most used first, for one location or all of them
 #[tauri::command]
-pub fn dir_list(state: State<'_, AppState>, location_id: i64) -> CommandResponse<Vec<String>> {
-    let id = LocationId(location_id);
-    log::debug!("Listing directories for location: id={}", location_id);
+pub fn tag_cloud(state: State<'_, AppState>, location_id: Option<i64>) -> CommandResponse<Vec<(String, usize)>> {
+    let location_id = location_id.map(LocationId);
+    log::debug!("Listing tag cloud: location={:?}", location_id);
 
-    match state.store.dir_list(id) {
-        Ok(directories) => {
-            let values = directories
-                .into_iter()
-                .map(|path| path.to_string_lossy().to_string())
-                .collect::<Vec<_>>();
-            log::debug!("Found {} directories in location {}", values.len(), location_id);
-            Ok(CommandResult::ok(values))
-        }
+    match state.store.tag_cloud(location_id) {
+        Ok(tags) => Ok(CommandResult::ok(tags)),
         Err(e) => {
-            log::error!("Failed to list directories: {}", e);
+            log::error!("Failed to list tags: {}", e);
             Ok(CommandResult::err(e))
         }
     }
 }
 
-/// Opens a document by location_id and relative path
+/// Adds and removes tags across the given documents, reporting per-document failures
+///
+/// `docs` are `(location_id, rel_path)` pairs; the report lists them in the same order.
 #[tauri::command]
-pub fn doc_open(state: State<'_, AppState>, location_id: i64, rel_path: String) -> CommandResponse<DocContent> {
-    let location_id = LocationId(location_id);
-    let rel_path = PathBuf::from(&rel_path);
+pub fn tag_apply_bulk(
+    state: State<'_, AppState>, docs: Vec<(i64, String)>, add: Vec<String>, remove: Vec<String>,
+) -> CommandResponse<TagBulkReport> {
+    log::debug!(
+        "Applying bulk tags to {} documents: +{:?} -{:?}",
+        docs.len(),
+        add,
+        remove
+    );
 
-    log::debug!("Opening document: location={:?}, path={:?}", location_id, rel_path);
+    let doc_ids = docs
+        .into_iter()
+        .map(|(location_id, rel_path)| DocId::new(LocationId(location_id), PathBuf::from(rel_path)))
+        .collect::<Result<Vec<_>, _>>();
 
-    match DocId::new(location_id, rel_path) {
-        Ok(doc_id) => match state.store.doc_open(&doc_id) {
-            Ok(content) => {
-                log::info!(
-                    "Document opened successfully: location={:?}, size={} bytes",
-                    doc_id.location_id,
-                    content.meta.size_bytes
-                );
-                Ok(CommandResult::ok(content))
-            }
+    match doc_ids {
+        Ok(doc_ids) => match state.store.tag_apply_bulk(doc_ids, add, remove) {
+            Ok(report) => Ok(CommandResult::ok(report)),
             Err(e) => {
-                log::error!("Failed to open document: {}", e);
+                log::error!("Failed to apply bulk tags: {}", e);
                 Ok(CommandResult::err(e))
             }
         },
@@ -480,63 +1778,54 @@ pub fn doc_open(state: State<'_, AppState>, location_id: i64, rel_path: String)
     }
 }
 
-/// Saves a document with atomic write semantics
+/// Lists conflicted copies across all locations, most recently updated first
 #[tauri::command]
-pub fn doc_save(
-    app: AppHandle, state: State<'_, AppState>, location_id: i64, rel_path: String, text: String,
-) -> CommandResponse<SaveResult> {
+pub fn conflicts_list(state: State<'_, AppState>) -> CommandResponse<Vec<DocMeta>> {
+    log::debug!("Listing conflicted documents");
+
+    match state.store.list_conflicts() {
+        Ok(conflicts) => Ok(CommandResult::ok(conflicts)),
+        Err(e) => {
+            log::error!("Failed to list conflicts: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Checks a file or directory name with the same rules `doc_rename` and `dir_rename` apply
+#[tauri::command]
+pub fn validate_filename(name: String) -> CommandResponse<bool> {
+    log::debug!("Validating filename: {:?}", name);
+
+    match writer_core::validate_filename(&name) {
+        Ok(()) => Ok(CommandResult::ok(true)),
+        Err(e) => Ok(CommandResult::err(e.into())),
+    }
+}
+
+/// Renames a document to a new filename within the same directory
+#[tauri::command]
+pub fn doc_rename(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, new_name: String,
+) -> CommandResponse<DocMeta> {
     let location_id = LocationId(location_id);
     let rel_path = PathBuf::from(&rel_path);
 
     log::debug!(
-        "Saving document: location={:?}, path={:?}, size={} bytes",
+        "Renaming document: location={:?}, path={:?}, new_name={}",
         location_id,
         rel_path,
-        text.len()
+        new_name
     );
 
     match DocId::new(location_id, rel_path) {
-        Ok(doc_id) => match state.store.doc_save(&doc_id, &text, None) {
-            Ok(result) => {
-                if result.conflict_detected {
-                    log::warn!(
-                        "Conflicted copy detected: location={:?}, path={:?}",
-                        doc_id.location_id,
-                        doc_id.rel_path
-                    );
-
-                    let event = BackendEvent::ConflictDetected {
-                        location_id: doc_id.location_id,
-                        rel_path: doc_id.rel_path.clone(),
-                        conflict_filename: doc_id
-                            .rel_path
-                            .file_name()
-                            .map(|n| n.to_string_lossy().to_string())
-                            .unwrap_or_else(|| "unknown".to_string()),
-                    };
-
-                    if let Err(e) = app.emit("backend-event", event) {
-                        log::error!("Failed to emit conflict event: {}", e);
-                    }
-                }
-
-                log::info!(
-                    "Document saved successfully: location={:?}, size={} bytes",
-                    doc_id.location_id,
-                    text.len()
-                );
-
-                let new_mtime = result
-                    .new_meta
-                    .as_ref()
-                    .map(|meta| meta.mtime)
-                    .unwrap_or_else(chrono::Utc::now);
-                emit_doc_modified_event(&app, doc_id.clone(), new_mtime);
-
-                Ok(CommandResult::ok(result))
+        Ok(doc_id) => match state.store.doc_rename(&doc_id, &new_name) {
+            Ok(new_meta) => {
+                log::info!("Document renamed successfully: {:?}", doc_id.rel_path);
+                Ok(CommandResult::ok(new_meta))
             }
             Err(e) => {
-                log::error!("Failed to save document: {}", e);
+                log::error!("Failed to rename document: {}", e);
                 Ok(CommandResult::err(e))
             }
         },
@@ -550,31 +1839,62 @@ pub fn doc_save(
     }
 }
 
-/// Checks if a document exists in a location
+/// Normalizes a destination path and checks it stays inside the location, for previewing a move
+///
+/// Returns the normalized relative path, ready to pass to `doc_move`.
 #[tauri::command]
-pub fn doc_exists(state: State<'_, AppState>, location_id: i64, rel_path: String) -> CommandResponse<bool> {
+pub fn location_resolve_path(
+    state: State<'_, AppState>, location_id: i64, rel_path: String,
+) -> CommandResponse<PathBuf> {
+    let location_id = LocationId(location_id);
+    log::debug!(
+        "Resolving path within location: location={:?}, path={:?}",
+        location_id,
+        rel_path
+    );
+
+    match state
+        .store
+        .resolve_within_location(location_id, &PathBuf::from(rel_path))
+    {
+        Ok(resolved) => Ok(CommandResult::ok(resolved)),
+        Err(e) => {
+            log::error!("Failed to resolve path within location: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Moves a document to a new relative path within the same location
+#[tauri::command]
+pub fn doc_move(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, new_rel_path: String,
+    target_location_id: Option<i64>,
+) -> CommandResponse<DocMeta> {
     let location_id = LocationId(location_id);
     let rel_path = PathBuf::from(&rel_path);
+    let new_rel_path = PathBuf::from(&new_rel_path);
+    let target_location_id = target_location_id.map(LocationId).unwrap_or(location_id);
 
     log::debug!(
-        "Checking document existence: location={:?}, path={:?}",
+        "Moving document: source_location={:?}, path={:?}, new_path={:?}, target_location={:?}",
         location_id,
-        rel_path
+        rel_path,
+        new_rel_path,
+        target_location_id
     );
 
     match DocId::new(location_id, rel_path) {
-        Ok(doc_id) => match state.store.location_get(doc_id.location_id) {
-            Ok(Some(location)) => {
-                let full_path = doc_id.resolve(&location.root_path);
-                let exists = full_path.exists();
-                Ok(CommandResult::ok(exists))
-            }
-            Ok(None) => {
-                log::warn!("Location not found: {:?}", doc_id.location_id);
-                Ok(CommandResult::err(AppError::not_found("Location not found")))
+        Ok(doc_id) => match state
+            .store
+            .doc_move_to_location(&doc_id, target_location_id, &new_rel_path)
+        {
+            Ok(new_meta) => {
+                log::info!("Document moved successfully: {:?}", doc_id.rel_path);
+                Ok(CommandResult::ok(new_meta))
             }
             Err(e) => {
-                log::error!("Failed to check location: {}", e);
+                log::error!("Failed to move document: {}", e);
                 Ok(CommandResult::err(e))
             }
         },
@@ -588,29 +1908,39 @@ pub fn doc_exists(state: State<'_, AppState>, location_id: i64, rel_path: String
     }
 }
 
-/// Renames a document to a new filename within the same directory
+/// Splits a document into one file per section at headings of `level` or above
+///
+/// `naming` defaults to heading slugs. Front matter is copied into every file unless
+/// `keep_front_matter` is false, and the original is only trashed when `trash_original` is set.
 #[tauri::command]
-pub fn doc_rename(
-    state: State<'_, AppState>, location_id: i64, rel_path: String, new_name: String,
-) -> CommandResponse<DocMeta> {
+pub fn doc_split_by_heading(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, level: u8, naming: Option<SplitNaming>,
+    keep_front_matter: Option<bool>, trash_original: Option<bool>,
+) -> CommandResponse<Vec<DocMeta>> {
     let location_id = LocationId(location_id);
     let rel_path = PathBuf::from(&rel_path);
 
     log::debug!(
-        "Renaming document: location={:?}, path={:?}, new_name={}",
+        "Splitting document at headings: location={:?}, path={:?}, level={}",
         location_id,
         rel_path,
-        new_name
+        level
     );
 
     match DocId::new(location_id, rel_path) {
-        Ok(doc_id) => match state.store.doc_rename(&doc_id, &new_name) {
-            Ok(new_meta) => {
-                log::info!("Document renamed successfully: {:?}", doc_id.rel_path);
-                Ok(CommandResult::ok(new_meta))
+        Ok(doc_id) => match state.store.doc_split_by_heading(
+            &doc_id,
+            level,
+            naming.unwrap_or_default(),
+            keep_front_matter.unwrap_or(true),
+            trash_original.unwrap_or(false),
+        ) {
+            Ok(created) => {
+                log::info!("Split {:?} into {} documents", doc_id.rel_path, created.len());
+                Ok(CommandResult::ok(created))
             }
             Err(e) => {
-                log::error!("Failed to rename document: {}", e);
+                log::error!("Failed to split document: {}", e);
                 Ok(CommandResult::err(e))
             }
         },
@@ -624,36 +1954,45 @@ pub fn doc_rename(
     }
 }
 
-/// Moves a document to a new relative path within the same location
+/// Appends one document to another and deletes the source
+///
+/// `separator` defaults to a thematic break when omitted or blank.
 #[tauri::command]
-pub fn doc_move(
-    state: State<'_, AppState>, location_id: i64, rel_path: String, new_rel_path: String,
-    target_location_id: Option<i64>,
+pub fn doc_merge(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, source_location_id: Option<i64>,
+    source_rel_path: String, separator: Option<String>, strip_source_front_matter: Option<bool>,
 ) -> CommandResponse<DocMeta> {
     let location_id = LocationId(location_id);
-    let rel_path = PathBuf::from(&rel_path);
-    let new_rel_path = PathBuf::from(&new_rel_path);
-    let target_location_id = target_location_id.map(LocationId).unwrap_or(location_id);
+    let source_location_id = source_location_id.map(LocationId).unwrap_or(location_id);
 
     log::debug!(
-        "Moving document: source_location={:?}, path={:?}, new_path={:?}, target_location={:?}",
+        "Merging document: source_location={:?}, source={:?}, target_location={:?}, target={:?}",
+        source_location_id,
+        source_rel_path,
         location_id,
-        rel_path,
-        new_rel_path,
-        target_location_id
+        rel_path
     );
 
-    match DocId::new(location_id, rel_path) {
-        Ok(doc_id) => match state
-            .store
-            .doc_move_to_location(&doc_id, target_location_id, &new_rel_path)
-        {
+    let doc_ids = DocId::new(location_id, PathBuf::from(&rel_path))
+        .and_then(|target| Ok((target, DocId::new(source_location_id, PathBuf::from(&source_rel_path))?)));
+
+    match doc_ids {
+        Ok((target, source)) => match state.store.doc_merge(
+            &target,
+            &source,
+            separator.as_deref().unwrap_or_default(),
+            strip_source_front_matter.unwrap_or(true),
+        ) {
             Ok(new_meta) => {
-                log::info!("Document moved successfully: {:?}", doc_id.rel_path);
+                log::info!(
+                    "Document merged successfully: {:?} <- {:?}",
+                    target.rel_path,
+                    source.rel_path
+                );
                 Ok(CommandResult::ok(new_meta))
             }
             Err(e) => {
-                log::error!("Failed to move document: {}", e);
+                log::error!("Failed to merge documents: {}", e);
                 Ok(CommandResult::err(e))
             }
         },
@@ -888,18 +2227,39 @@ pub fn watch_disable(state: State<'_, AppState>, location_id: i64) -> CommandRes
 }
 
 /// Full-text search across indexed documents.
+///
+/// `options` tunes the snippet length, ellipsis, and highlight markers; omitted fields keep the defaults.
+/// `limit` defaults to 50 and may go up to the `max_search_results` search setting (200 unless raised).
 #[tauri::command]
 pub fn search(
     state: State<'_, AppState>, query: String, filters: Option<SearchFilters>, limit: Option<usize>,
+    options: Option<SearchOptions>,
 ) -> CommandResponse<Vec<SearchHit>> {
     let limit = limit.unwrap_or(50);
+    let options = options.unwrap_or_default();
 
-    match state.store.search(&query, filters, limit) {
+    match state.store.search_with_options(&query, filters, limit, &options) {
         Ok(results) => Ok(CommandResult::ok(results)),
         Err(error) => Ok(CommandResult::err(error)),
     }
 }
 
+/// Searches across locations, returning up to `per_location_limit` hits for each location that matches
+#[tauri::command]
+pub fn search_grouped(
+    state: State<'_, AppState>, query: String, filters: Option<SearchFilters>, per_location_limit: Option<usize>,
+) -> CommandResponse<Vec<(LocationId, Vec<SearchHit>)>> {
+    let per_location_limit = per_location_limit.unwrap_or(5);
+
+    match state.store.search_grouped(&query, filters, per_location_limit) {
+        Ok(groups) => Ok(CommandResult::ok(groups)),
+        Err(error) => {
+            log::error!("Grouped search failed: {}", error);
+            Ok(CommandResult::err(error))
+        }
+    }
+}
+
 #[tauri::command]
 pub fn style_check_get(state: State<'_, AppState>) -> CommandResponse<StyleCheckSettings> {
     log::debug!("Loading persisted style check settings");
@@ -927,12 +2287,38 @@ pub fn style_check_set(state: State<'_, AppState>, settings: StyleCheckSettings)
 }
 
 #[tauri::command]
-pub fn style_check_scan(
-    _: State<'_, AppState>, text: String, settings: StyleCheckSettings,
-) -> CommandResponse<Vec<StyleMatch>> {
-    log::debug!("Scanning style matches: text_len={}", text.len());
+pub fn search_settings_get(state: State<'_, AppState>) -> CommandResponse<SearchSettings> {
+    log::debug!("Loading persisted search settings");
+
+    match state.store.search_settings_get() {
+        Ok(settings) => Ok(CommandResult::ok(settings)),
+        Err(e) => {
+            log::error!("Failed to load search settings: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+#[tauri::command]
+pub fn search_settings_set(state: State<'_, AppState>, settings: SearchSettings) -> CommandResponse<bool> {
+    log::debug!(
+        "Persisting search settings: fold_diacritics={}, fts_max_content_bytes={}, max_search_results={}",
+        settings.fold_diacritics,
+        settings.fts_max_content_bytes,
+        settings.max_search_results
+    );
+
+    match state.store.search_settings_set(&settings) {
+        Ok(()) => Ok(CommandResult::ok(true)),
+        Err(e) => {
+            log::error!("Failed to persist search settings: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
 
-    let input = StyleScanInput {
+fn style_scan_input(text: String, settings: StyleCheckSettings) -> StyleScanInput {
+    StyleScanInput {
         text,
         categories: StyleCategorySettings {
             filler: settings.categories.filler,
@@ -946,11 +2332,46 @@ pub fn style_check_scan(
                 text: pattern.text,
                 category: pattern.category,
                 replacement: pattern.replacement,
+                is_regex: pattern.is_regex,
             })
             .collect(),
-    };
+    }
+}
+
+#[tauri::command]
+pub fn style_check_scan(
+    _: State<'_, AppState>, text: String, settings: StyleCheckSettings,
+) -> CommandResponse<Vec<StyleMatch>> {
+    log::debug!("Scanning style matches: text_len={}", text.len());
+
+    let report = scan_style_report(&style_scan_input(text, settings));
+    for error in &report.errors {
+        log::warn!("Skipping invalid style pattern {:?}: {}", error.pattern, error.message);
+    }
+
+    Ok(CommandResult::ok(report.matches))
+}
+
+/// Scans style matches and reports custom regex patterns that failed to compile
+#[tauri::command]
+pub fn style_check_scan_report(
+    _: State<'_, AppState>, text: String, settings: StyleCheckSettings,
+) -> CommandResponse<StyleScanReport> {
+    log::debug!("Scanning style matches with report: text_len={}", text.len());
+
+    Ok(CommandResult::ok(scan_style_report(&style_scan_input(text, settings))))
+}
+
+/// Counts style matches per category and in total, for match-count badges
+#[tauri::command]
+pub fn style_summary(
+    _: State<'_, AppState>, text: String, settings: StyleCheckSettings,
+) -> CommandResponse<StyleSummary> {
+    log::debug!("Summarizing style matches: text_len={}", text.len());
 
-    Ok(CommandResult::ok(scan_style_matches(&input)))
+    Ok(CommandResult::ok(writer_core::style_summary(&style_scan_input(
+        text, settings,
+    ))))
 }
 
 /// Gets global capture settings
@@ -978,6 +2399,18 @@ pub fn global_capture_set(
         return Ok(CommandResult::err(e));
     }
 
+    if let Some(format) = capture::configured_format(&settings.date_subfolder_format) {
+        if let Err(e) = capture::validate_date_subfolder_format(format) {
+            return Ok(CommandResult::err(e));
+        }
+    }
+
+    if let Some(format) = capture::configured_format(&settings.append_timestamp_format) {
+        if let Err(e) = capture::validate_append_timestamp_format(format) {
+            return Ok(CommandResult::err(e));
+        }
+    }
+
     match state.store.global_capture_set(&settings) {
         Ok(()) => match capture::reconcile_capture_runtime(&app, &settings) {
             Ok(_) => Ok(CommandResult::ok(true)),
@@ -1020,7 +2453,8 @@ pub async fn global_capture_submit(
         Err(e) => return Ok(CommandResult::err(e)),
     };
 
-    let target_location = match resolve_capture_target_location(&app, &state, &destination) {
+    let target_location = match resolve_capture_target_location(&app, &state, &destination, settings.on_missing_target)
+    {
         Ok(location_id) => Some(location_id.0),
         Err(e) => return Ok(CommandResult::err(e)),
     };
@@ -1056,7 +2490,7 @@ pub async fn global_capture_submit(
         target_location,
         quick_note_inbox_dir,
         &append_target,
-        settings.close_after_save,
+        &settings,
     )
     .await
     {
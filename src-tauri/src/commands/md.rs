@@ -1,8 +1,14 @@
 use super::{AppState, CommandResponse};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use tauri::State;
-use writer_core::{AppError, CommandResult, LocationId};
-use writer_md::{DocxExportResult, MarkdownEngine, MarkdownProfile, PdfRenderResult, RenderResult, TextExportResult};
+use writer_core::{AppError, CommandResult, DocId, FreqOptions, LocationId, TypographyIssue, WhitespaceIssue};
+use writer_md::{
+    CodeBlock, Diagnostics, DocxExportResult, DocxOptions, FrontMatter, FrontMatterFormat, MarkdownEngine,
+    MarkdownProfile, PageEstimateConfig, PdfRenderResult, RenderResult, SectionStat, SectionTaskStats,
+    TextExportResult,
+};
 
 /// Returns the markdown help guide content
 #[tauri::command]
@@ -15,30 +21,40 @@ pub fn markdown_help_get() -> CommandResponse<String> {
 ///
 /// This command takes document reference, text content, and a rendering profile,
 /// returning HTML with source position attributes for editor-preview sync.
+/// When `with_timings` is set, per-stage render timings are included for diagnosing slow previews.
 #[tauri::command]
 pub fn markdown_render(
     _: State<'_, AppState>, location_id: i64, rel_path: String, text: String, profile: Option<MarkdownProfile>,
+    with_timings: Option<bool>,
 ) -> CommandResponse<RenderResult> {
     let location_id = LocationId(location_id);
     let rel_path = PathBuf::from(&rel_path);
+    let with_timings = with_timings.unwrap_or(false);
 
     log::debug!(
-        "Rendering markdown: location={:?}, path={:?}, profile={:?}, text_len={}",
+        "Rendering markdown: location={:?}, path={:?}, profile={:?}, text_len={}, with_timings={}",
         location_id,
         rel_path,
         profile,
-        text.len()
+        text.len(),
+        with_timings
     );
 
     let engine = MarkdownEngine::new();
     let profile = profile.unwrap_or_default();
+    let rendered = if with_timings {
+        engine.render_with_timings(&text, profile)
+    } else {
+        engine.render(&text, profile)
+    };
 
-    match engine.render(&text, profile) {
+    match rendered {
         Ok(result) => {
             log::debug!(
-                "Markdown rendered successfully: html_len={}, outline_items={}",
+                "Markdown rendered successfully: html_len={}, outline_items={}, timings={:?}",
                 result.html.len(),
-                result.metadata.outline.len()
+                result.metadata.outline.len(),
+                result.timings
             );
             Ok(CommandResult::ok(result))
         }
@@ -52,6 +68,91 @@ pub fn markdown_render(
     }
 }
 
+/// Reads and renders several documents in one call, off the main thread
+///
+/// `docs` are `(location_id, rel_path, profile)` triples, with a missing profile meaning the default.
+/// Results come back in the same order, each its own success or error, so one unreadable document
+/// does not fail the batch.
+#[tauri::command]
+pub async fn markdown_render_many(
+    state: State<'_, AppState>, docs: Vec<(i64, String, Option<MarkdownProfile>)>,
+) -> CommandResponse<Vec<CommandResult<RenderResult>>> {
+    log::debug!("Rendering {} documents", docs.len());
+
+    let docs = docs
+        .into_iter()
+        .map(|(location_id, rel_path, profile)| {
+            DocId::new(LocationId(location_id), PathBuf::from(rel_path))
+                .map(|doc_id| (doc_id, profile.unwrap_or_default()))
+        })
+        .collect::<Result<Vec<_>, _>>();
+
+    let docs = match docs {
+        Ok(docs) => docs,
+        Err(e) => {
+            log::error!("Invalid document reference: {}", e);
+            return Ok(CommandResult::err(AppError::invalid_path(format!(
+                "Invalid path: {}",
+                e
+            ))));
+        }
+    };
+
+    let store = state.store.clone();
+    match tauri::async_runtime::spawn_blocking(move || store.render_many(docs)).await {
+        Ok(results) => Ok(CommandResult::ok(
+            results
+                .into_iter()
+                .map(|result| match result {
+                    Ok(rendered) => CommandResult::ok(rendered),
+                    Err(e) => {
+                        log::warn!("Failed to render document in batch: {}", e);
+                        CommandResult::err(e)
+                    }
+                })
+                .collect(),
+        )),
+        Err(e) => {
+            log::error!("Batch render task failed: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Batch render failed: {}", e),
+            )))
+        }
+    }
+}
+
+/// Lints markdown text, returning only diagnostics within a line range
+///
+/// Used for live linting so the editor can refresh markers near the edited lines
+/// (`start_line` and `end_line` are 1-indexed and inclusive).
+#[tauri::command]
+pub fn markdown_lint_range(
+    _: State<'_, AppState>, text: String, profile: Option<MarkdownProfile>, start_line: usize, end_line: usize,
+) -> CommandResponse<Diagnostics> {
+    log::debug!(
+        "Linting markdown range: lines={}..={}, profile={:?}, text_len={}",
+        start_line,
+        end_line,
+        profile,
+        text.len()
+    );
+
+    let engine = MarkdownEngine::new();
+    let profile = profile.unwrap_or_default();
+
+    match engine.lint_range(&text, profile, start_line..=end_line) {
+        Ok(diagnostics) => Ok(CommandResult::ok(diagnostics)),
+        Err(e) => {
+            log::error!("Failed to lint markdown range: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to lint markdown range: {}", e),
+            )))
+        }
+    }
+}
+
 /// Renders markdown text to a PDF-compatible AST
 ///
 /// This command takes document text and returns a structured AST
@@ -139,9 +240,11 @@ pub fn markdown_render_for_text(
 /// This command takes document text and returns DOCX bytes
 /// generated via docx-rs with support for headings, bold, italic,
 /// code font, ordered/unordered lists, blockquotes, and code blocks.
+/// `options` sets the page size, margins, and base font (A4 with the docx-rs defaults when omitted).
 #[tauri::command]
 pub fn markdown_render_for_docx(
     _: State<'_, AppState>, location_id: i64, rel_path: String, text: String, profile: Option<MarkdownProfile>,
+    options: Option<DocxOptions>,
 ) -> CommandResponse<DocxExportResult> {
     let location_id = LocationId(location_id);
     let rel_path = PathBuf::from(&rel_path);
@@ -157,7 +260,7 @@ pub fn markdown_render_for_docx(
     let engine = MarkdownEngine::new();
     let profile = profile.unwrap_or(MarkdownProfile::Extended);
 
-    match engine.render_for_docx(&text, profile) {
+    match engine.render_for_docx(&text, profile, &options.unwrap_or_default()) {
         Ok(result) => {
             log::debug!(
                 "Markdown rendered for DOCX successfully: data_len={}, word_count={}",
@@ -175,3 +278,479 @@ pub fn markdown_render_for_docx(
         }
     }
 }
+
+/// Builds an href from one document to another within the same location
+fn relative_doc_href(from_rel_path: &Path, target_rel_path: &Path, extension: Option<&str>) -> String {
+    let depth = from_rel_path.parent().map_or(0, |parent| parent.components().count());
+    let target = match extension {
+        Some(extension) => target_rel_path.with_extension(extension.trim_start_matches('.')),
+        None => target_rel_path.to_path_buf(),
+    };
+    let target_parts = target
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+
+    let mut parts = vec!["..".to_string(); depth];
+    parts.extend(target_parts);
+    parts.join("/")
+}
+
+/// Rewrites wikilinks to standard markdown links for export
+///
+/// Targets are resolved against the location catalog (paths, filenames, then front matter aliases)
+/// and linked relative to the exported document, optionally swapping the extension (e.g. `html`).
+#[tauri::command]
+pub fn markdown_resolve_wikilinks(
+    state: State<'_, AppState>, location_id: i64, rel_path: String, text: String, extension: Option<String>,
+) -> CommandResponse<String> {
+    let location_id = LocationId(location_id);
+    let rel_path = PathBuf::from(&rel_path);
+
+    log::debug!(
+        "Resolving wikilinks: location={:?}, path={:?}, extension={:?}, text_len={}",
+        location_id,
+        rel_path,
+        extension,
+        text.len()
+    );
+
+    let engine = MarkdownEngine::new();
+    let resolver = |target: &str| match state.store.resolve_wikilink(location_id, target) {
+        Ok(resolved) => {
+            resolved.map(|target_rel_path| relative_doc_href(&rel_path, &target_rel_path, extension.as_deref()))
+        }
+        Err(e) => {
+            log::warn!("Failed to resolve wikilink target {:?}: {}", target, e);
+            None
+        }
+    };
+
+    match engine.resolve_wikilinks(&text, resolver) {
+        Ok(result) => Ok(CommandResult::ok(result)),
+        Err(e) => {
+            log::error!("Failed to resolve wikilinks: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to resolve wikilinks: {}", e),
+            )))
+        }
+    }
+}
+
+/// Returns the raw Comrak parse tree as JSON
+///
+/// Intended for tooling and debugging; see [`writer_md::RawAstNode`] for the node shape.
+#[tauri::command]
+pub fn markdown_parse_ast(
+    _: State<'_, AppState>, text: String, profile: Option<MarkdownProfile>,
+) -> CommandResponse<String> {
+    log::debug!("Parsing markdown AST: profile={:?}, text_len={}", profile, text.len());
+
+    let engine = MarkdownEngine::new();
+    let profile = profile.unwrap_or_default();
+
+    match engine.parse_to_ast_json(&text, profile) {
+        Ok(json) => Ok(CommandResult::ok(json)),
+        Err(e) => {
+            log::error!("Failed to parse markdown AST: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to parse markdown AST: {}", e),
+            )))
+        }
+    }
+}
+
+/// Parses markdown into Writer's stable syntax tree, serialized as JSON
+///
+/// See [`writer_md::MarkdownAstNode`] for the node shape.
+#[tauri::command]
+pub fn markdown_ast(_: State<'_, AppState>, text: String, profile: Option<MarkdownProfile>) -> CommandResponse<String> {
+    log::debug!("Building markdown AST: profile={:?}, text_len={}", profile, text.len());
+
+    let engine = MarkdownEngine::new();
+    let profile = profile.unwrap_or_default();
+
+    match engine.render_ast_json(&text, profile) {
+        Ok(json) => Ok(CommandResult::ok(json)),
+        Err(e) => {
+            log::error!("Failed to build markdown AST: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to build markdown AST: {}", e),
+            )))
+        }
+    }
+}
+
+/// Lightly converts pasted plain text to markdown (autolinks, list bullets, blank-line runs)
+#[tauri::command]
+pub fn markdown_from_plaintext(text: String) -> CommandResponse<String> {
+    log::debug!("Converting plain text to markdown: text_len={}", text.len());
+    Ok(CommandResult::ok(writer_core::plaintext_to_markdown(&text)))
+}
+
+/// Converts pasted HTML (rich text from a browser) to GitHub Flavored Markdown
+#[tauri::command]
+pub fn html_to_markdown(html: String) -> CommandResponse<String> {
+    log::debug!("Converting HTML to markdown: html_len={}", html.len());
+    Ok(CommandResult::ok(writer_core::html_to_markdown(&html)))
+}
+
+/// Returns the `<title>` of a pasted link, from the cache when it was fetched recently
+///
+/// Only `http(s)` URLs are fetched, with a timeout. The request is awaited on the async runtime, so the UI
+/// stays responsive without tying up a blocking worker.
+#[cfg(feature = "link-titles")]
+#[tauri::command]
+pub async fn link_title_fetch(state: State<'_, AppState>, url: String) -> CommandResponse<String> {
+    log::debug!("Fetching link title: url={}", url);
+
+    let resolved = async {
+        if let Some(title) = state.store.link_title_cached(&url)? {
+            return Ok(title);
+        }
+
+        let title = writer_core::fetch_link_title(&url).await?;
+        state.store.link_title_remember(&url, &title)?;
+        Ok::<_, AppError>(title)
+    }
+    .await;
+
+    match resolved {
+        Ok(title) => Ok(CommandResult::ok(title)),
+        Err(e) => {
+            log::warn!("Failed to fetch link title: {}", e);
+            Ok(CommandResult::err(e))
+        }
+    }
+}
+
+/// Exports the heading outline of the text as an OPML 2.0 document
+#[tauri::command]
+pub fn markdown_export_opml(text: String, profile: Option<MarkdownProfile>) -> CommandResponse<String> {
+    log::debug!("Exporting outline as OPML: text_len={}", text.len());
+
+    match MarkdownEngine::new().export_opml(&text, profile.unwrap_or(MarkdownProfile::Extended)) {
+        Ok(opml) => Ok(CommandResult::ok(opml)),
+        Err(e) => {
+            log::error!("Failed to export OPML: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to export OPML: {}", e),
+            )))
+        }
+    }
+}
+
+/// Flips the checkbox of the task list item starting on `line` and returns the updated text
+#[tauri::command]
+pub fn markdown_toggle_task(text: String, line: usize) -> CommandResponse<String> {
+    log::debug!("Toggling task: line={}, text_len={}", line, text.len());
+
+    match MarkdownEngine::new().toggle_task(&text, line) {
+        Ok(toggled) => Ok(CommandResult::ok(toggled)),
+        Err(e) => {
+            log::error!("Failed to toggle task: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to toggle task: {}", e),
+            )))
+        }
+    }
+}
+
+/// Inserts content at the end of the section under the heading with the given anchor
+///
+/// When no heading matches, the content goes at the end of the document if `append_if_missing` is
+/// set; otherwise an error is returned.
+#[tauri::command]
+pub fn markdown_append_to_section(
+    text: String, heading_anchor: String, content: String, append_if_missing: Option<bool>,
+) -> CommandResponse<String> {
+    log::debug!(
+        "Appending to section: anchor={}, text_len={}",
+        heading_anchor,
+        text.len()
+    );
+
+    match MarkdownEngine::new().append_to_section(&text, &heading_anchor, &content, append_if_missing.unwrap_or(false))
+    {
+        Ok(updated) => Ok(CommandResult::ok(updated)),
+        Err(e) => {
+            log::error!("Failed to append to section: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to append to section: {}", e),
+            )))
+        }
+    }
+}
+
+/// Lists the fenced and indented code blocks in the text with their languages and source lines
+#[tauri::command]
+pub fn markdown_extract_code_blocks(text: String, profile: Option<MarkdownProfile>) -> CommandResponse<Vec<CodeBlock>> {
+    log::debug!("Extracting code blocks: text_len={}", text.len());
+
+    match MarkdownEngine::new().extract_code_blocks(&text, profile.unwrap_or(MarkdownProfile::Extended)) {
+        Ok(blocks) => Ok(CommandResult::ok(blocks)),
+        Err(e) => {
+            log::error!("Failed to extract code blocks: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to extract code blocks: {}", e),
+            )))
+        }
+    }
+}
+
+/// Estimates the printed page count of the text for a "~N pages" indicator
+#[tauri::command]
+pub fn markdown_estimate_pages(
+    text: String, profile: Option<MarkdownProfile>, config: Option<PageEstimateConfig>,
+) -> CommandResponse<usize> {
+    log::debug!("Estimating page count: text_len={}", text.len());
+
+    let pages = MarkdownEngine::new().estimate_pages(
+        &text,
+        profile.unwrap_or(MarkdownProfile::Extended),
+        config.unwrap_or_default(),
+    );
+    Ok(CommandResult::ok(pages))
+}
+
+/// Promotes (negative `delta`) or demotes (positive `delta`) every heading in the text
+#[tauri::command]
+pub fn markdown_shift_headings(text: String, delta: i8, profile: Option<MarkdownProfile>) -> CommandResponse<String> {
+    log::debug!("Shifting markdown headings: delta={}, text_len={}", delta, text.len());
+
+    let engine = MarkdownEngine::new();
+    let profile = profile.unwrap_or(MarkdownProfile::Extended);
+
+    match engine.shift_headings(&text, delta, profile) {
+        Ok(shifted) => Ok(CommandResult::ok(shifted)),
+        Err(e) => {
+            log::error!("Failed to shift markdown headings: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to shift markdown headings: {}", e),
+            )))
+        }
+    }
+}
+
+/// Splits the text into its body and parsed front matter; text without front matter is returned as-is
+#[tauri::command]
+pub fn markdown_strip_front_matter(text: String) -> CommandResponse<(String, FrontMatter)> {
+    log::debug!("Stripping front matter: text_len={}", text.len());
+    Ok(CommandResult::ok(MarkdownEngine::new().strip_front_matter(&text)))
+}
+
+/// Inserts or updates scalar front matter fields; a new block uses `format` (YAML by default)
+#[tauri::command]
+pub fn markdown_upsert_front_matter(
+    text: String, fields: HashMap<String, String>, format: Option<FrontMatterFormat>,
+) -> CommandResponse<String> {
+    log::debug!(
+        "Upserting front matter: fields={}, text_len={}",
+        fields.len(),
+        text.len()
+    );
+
+    match MarkdownEngine::new().upsert_front_matter(&text, &fields, format.unwrap_or_default()) {
+        Ok(updated) => Ok(CommandResult::ok(updated)),
+        Err(e) => {
+            log::error!("Failed to update front matter: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to update front matter: {}", e),
+            )))
+        }
+    }
+}
+
+/// Counts words in the text, or in the `start..end` byte range of it when given
+///
+/// Without a range, front matter is skipped so the count matches the document's `word_count`.
+#[tauri::command]
+pub fn count_words(text: String, start: Option<usize>, end: Option<usize>) -> CommandResponse<usize> {
+    log::debug!(
+        "Counting words: start={:?}, end={:?}, text_len={}",
+        start,
+        end,
+        text.len()
+    );
+
+    let count = match (start, end) {
+        (None, None) => writer_core::count_words(MarkdownEngine::new().body(&text)),
+        (start, end) => writer_core::count_words_in_range(&text, start.unwrap_or(0), end.unwrap_or(text.len())),
+    };
+    Ok(CommandResult::ok(count))
+}
+
+/// Reports trailing whitespace, hard tabs, non-breaking spaces, and runs of spaces in the text
+///
+/// With `markdown_aware`, fenced and indented code blocks are skipped.
+#[tauri::command]
+pub fn whitespace_scan(
+    text: String, markdown_aware: Option<bool>, profile: Option<MarkdownProfile>,
+) -> CommandResponse<Vec<WhitespaceIssue>> {
+    let markdown_aware = markdown_aware.unwrap_or(false);
+    log::debug!(
+        "Scanning whitespace: markdown_aware={}, text_len={}",
+        markdown_aware,
+        text.len()
+    );
+
+    if !markdown_aware {
+        return Ok(CommandResult::ok(writer_core::scan_whitespace_issues(&text)));
+    }
+
+    match MarkdownEngine::new().code_block_ranges(&text, profile.unwrap_or(MarkdownProfile::Extended)) {
+        Ok(code) => Ok(CommandResult::ok(writer_core::scan_whitespace_issues_outside(
+            &text, &code,
+        ))),
+        Err(e) => {
+            log::error!("Failed to find code blocks for whitespace scan: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to parse markdown: {}", e),
+            )))
+        }
+    }
+}
+
+/// Code blocks and front matter, which typography suggestions leave as typed
+fn typography_skip_ranges(text: &str, profile: MarkdownProfile) -> Result<Vec<Range<usize>>, AppError> {
+    let engine = MarkdownEngine::new();
+    let mut skip = engine.code_block_ranges(text, profile).map_err(|e| {
+        log::error!("Failed to find code blocks for typography scan: {}", e);
+        AppError::new(
+            writer_core::ErrorCode::Parse,
+            format!("Failed to parse markdown: {}", e),
+        )
+    })?;
+
+    if profile.supports_front_matter() {
+        skip.push(0..text.len() - engine.body(text).len());
+    }
+    Ok(skip)
+}
+
+/// Reports straight quotes, `--`, and `...` that have typographic replacements (curly quotes, em
+/// dashes, ellipses)
+///
+/// Inline code, URLs, and HTML tags are always skipped; with `markdown_aware` (the default), so are
+/// code blocks and front matter.
+#[tauri::command]
+pub fn typography_scan(
+    text: String, markdown_aware: Option<bool>, profile: Option<MarkdownProfile>,
+) -> CommandResponse<Vec<TypographyIssue>> {
+    let markdown_aware = markdown_aware.unwrap_or(true);
+    log::debug!(
+        "Scanning typography: markdown_aware={}, text_len={}",
+        markdown_aware,
+        text.len()
+    );
+
+    if !markdown_aware {
+        return Ok(CommandResult::ok(writer_core::typography_scan(&text)));
+    }
+
+    match typography_skip_ranges(&text, profile.unwrap_or(MarkdownProfile::Extended)) {
+        Ok(skip) => Ok(CommandResult::ok(writer_core::typography_scan_outside(&text, &skip))),
+        Err(e) => Ok(CommandResult::err(e)),
+    }
+}
+
+/// Applies every `typography_scan` suggestion, returning the updated text
+#[tauri::command]
+pub fn typography_fix(
+    text: String, markdown_aware: Option<bool>, profile: Option<MarkdownProfile>,
+) -> CommandResponse<String> {
+    let markdown_aware = markdown_aware.unwrap_or(true);
+    log::debug!(
+        "Fixing typography: markdown_aware={}, text_len={}",
+        markdown_aware,
+        text.len()
+    );
+
+    if !markdown_aware {
+        return Ok(CommandResult::ok(writer_core::typography_fix(&text, &[])));
+    }
+
+    match typography_skip_ranges(&text, profile.unwrap_or(MarkdownProfile::Extended)) {
+        Ok(skip) => Ok(CommandResult::ok(writer_core::typography_fix(&text, &skip))),
+        Err(e) => Ok(CommandResult::err(e)),
+    }
+}
+
+/// Counts how often each word occurs, most frequent first, to spot overused words
+///
+/// With `markdown_aware` (the default), fenced and indented code blocks are skipped.
+#[tauri::command]
+pub fn word_frequencies(
+    text: String, options: Option<FreqOptions>, markdown_aware: Option<bool>, profile: Option<MarkdownProfile>,
+) -> CommandResponse<Vec<(String, usize)>> {
+    let options = options.unwrap_or_default();
+    let markdown_aware = markdown_aware.unwrap_or(true);
+    log::debug!(
+        "Counting word frequencies: markdown_aware={}, text_len={}",
+        markdown_aware,
+        text.len()
+    );
+
+    if !markdown_aware {
+        return Ok(CommandResult::ok(writer_core::word_frequencies(&text, &options)));
+    }
+
+    match MarkdownEngine::new().code_block_ranges(&text, profile.unwrap_or(MarkdownProfile::Extended)) {
+        Ok(code) => Ok(CommandResult::ok(writer_core::word_frequencies_outside(
+            &text, &code, &options,
+        ))),
+        Err(e) => {
+            log::error!("Failed to find code blocks for word frequencies: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to parse markdown: {}", e),
+            )))
+        }
+    }
+}
+
+/// Counts completed and total task list items per heading section, for per-section progress bars
+#[tauri::command]
+pub fn markdown_task_rollup(text: String, profile: Option<MarkdownProfile>) -> CommandResponse<Vec<SectionTaskStats>> {
+    log::debug!("Rolling up tasks by section: text_len={}", text.len());
+
+    match MarkdownEngine::new().task_rollup(&text, profile.unwrap_or(MarkdownProfile::Extended)) {
+        Ok(sections) => Ok(CommandResult::ok(sections)),
+        Err(e) => {
+            log::error!("Failed to roll up tasks: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to roll up tasks: {}", e),
+            )))
+        }
+    }
+}
+
+/// Counts prose words per heading section, with a preamble section for prose before the first heading
+#[tauri::command]
+pub fn markdown_section_word_counts(
+    text: String, profile: Option<MarkdownProfile>,
+) -> CommandResponse<Vec<SectionStat>> {
+    log::debug!("Counting section words: text_len={}", text.len());
+
+    match MarkdownEngine::new().section_word_counts(&text, profile.unwrap_or(MarkdownProfile::Extended)) {
+        Ok(sections) => Ok(CommandResult::ok(sections)),
+        Err(e) => {
+            log::error!("Failed to count section words: {}", e);
+            Ok(CommandResult::err(AppError::new(
+                writer_core::ErrorCode::Parse,
+                format!("Failed to count section words: {}", e),
+            )))
+        }
+    }
+}
@@ -103,14 +103,54 @@ pub fn run() {
             cmd::location_add_via_dialog,
             cmd::location_list,
             cmd::location_remove,
+            cmd::location_set_indexable_extensions,
+            cmd::location_set_ignore_globs,
+            cmd::location_reconcile_plan,
+            cmd::index_audit,
+            cmd::location_clear_index,
+            cmd::location_reindex_start,
+            cmd::location_reindex_cancel,
             cmd::location_validate,
             cmd::doc_list,
+            cmd::doc_list_published,
+            cmd::recently_edited,
+            cmd::doc_neighbors,
+            cmd::doc_list_catalog,
+            cmd::find_stubs,
+            cmd::find_orphans,
             cmd::dir_list,
+            cmd::dir_entries,
             cmd::doc_open,
+            cmd::doc_open_raw,
+            cmd::doc_open_or_create,
+            cmd::doc_pending_diff,
+            cmd::doc_open_with_encoding,
             cmd::doc_save,
+            cmd::doc_goal_get,
+            cmd::doc_goal_set,
+            cmd::autosave_put,
+            cmd::autosave_take,
+            cmd::autosave_pending,
+            cmd::doc_uid_get,
+            cmd::doc_by_uid,
             cmd::doc_exists,
+            cmd::doc_resolve_alias,
+            cmd::doc_check_images,
+            cmd::doc_export_html,
+            cmd::location_export_zip,
+            cmd::location_export_csv,
+            cmd::location_import_zip,
+            cmd::tag_rename,
+            cmd::tag_apply_bulk,
+            cmd::tag_cloud,
+            cmd::maintenance_purge,
+            cmd::conflicts_list,
+            cmd::validate_filename,
             cmd::doc_rename,
+            cmd::location_resolve_path,
             cmd::doc_move,
+            cmd::doc_merge,
+            cmd::doc_split_by_heading,
             cmd::doc_delete,
             cmd::dir_create,
             cmd::dir_rename,
@@ -119,14 +159,48 @@ pub fn run() {
             cmd::watch_enable,
             cmd::watch_disable,
             cmd::search,
+            cmd::search_grouped,
+            cmd::find_in_document,
+            cmd::replace_in_document,
+            cmd::doc_stats,
+            cmd::doc_outline,
+            cmd::word_count_history,
             cmd::markdown_render,
+            cmd::markdown_render_many,
+            cmd::markdown_lint_range,
             cmd::markdown_render_for_pdf,
             cmd::markdown_render_for_text,
             cmd::markdown_render_for_docx,
+            cmd::markdown_resolve_wikilinks,
+            cmd::markdown_parse_ast,
+            cmd::markdown_ast,
+            cmd::markdown_from_plaintext,
+            cmd::html_to_markdown,
+            #[cfg(feature = "link-titles")]
+            cmd::link_title_fetch,
+            cmd::markdown_export_opml,
+            cmd::markdown_toggle_task,
+            cmd::markdown_append_to_section,
+            cmd::markdown_extract_code_blocks,
+            cmd::markdown_estimate_pages,
+            cmd::markdown_shift_headings,
+            cmd::markdown_strip_front_matter,
+            cmd::markdown_upsert_front_matter,
+            cmd::count_words,
+            cmd::whitespace_scan,
+            cmd::typography_scan,
+            cmd::typography_fix,
+            cmd::word_frequencies,
+            cmd::markdown_section_word_counts,
+            cmd::markdown_task_rollup,
             cmd::ui_layout_get,
             cmd::ui_layout_set,
+            cmd::readme_template_get,
+            cmd::readme_template_set,
             cmd::sidebar_tree_get,
             cmd::sidebar_tree_set,
+            cmd::location_view_prefs_get,
+            cmd::location_view_prefs_set,
             cmd::session_get,
             cmd::session_open_tab,
             cmd::session_select_tab,
@@ -140,7 +214,11 @@ pub fn run() {
             cmd::session_last_doc_set,
             cmd::style_check_get,
             cmd::style_check_set,
+            cmd::search_settings_get,
+            cmd::search_settings_set,
             cmd::style_check_scan,
+            cmd::style_check_scan_report,
+            cmd::style_summary,
             cmd::global_capture_get,
             cmd::global_capture_set,
             cmd::global_capture_open,
@@ -5,8 +5,10 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_fs::FsExt;
-use writer_core::{AppError, BackendEvent, DocId, FsChangeKind, FsEntryKind, LocationDescriptor, LocationId};
-use writer_store::Store;
+use writer_core::{
+    AppError, BackendEvent, DocId, ErrorCode, FsChangeKind, FsEntryKind, LocationDescriptor, LocationId,
+};
+use writer_store::{MissingCaptureTarget, Store};
 
 fn should_process_watcher_event(kind: &EventKind) -> bool {
     matches!(kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
@@ -378,18 +380,40 @@ pub(super) fn ensure_default_capture_location(app: &AppHandle, state: &AppState)
     }
 }
 
+/// Checks that a capture destination location still exists, applying `on_missing_target` when it does not.
+///
+/// Falling back yields the first available location, or `None` when there are no locations left.
+pub(crate) fn check_capture_destination(
+    store: &Store, location_id: LocationId, on_missing_target: MissingCaptureTarget,
+) -> Result<Option<LocationId>, AppError> {
+    if store.location_get(location_id)?.is_some() {
+        return Ok(Some(location_id));
+    }
+
+    match on_missing_target {
+        MissingCaptureTarget::Error => Err(AppError::new(
+            ErrorCode::NotFound,
+            format!("Capture target location {} no longer exists", location_id.0),
+        )),
+        MissingCaptureTarget::Fallback => {
+            log::warn!(
+                "Capture destination location no longer exists: {}. Falling back to the first available location.",
+                location_id.0
+            );
+            Ok(store.location_list()?.first().map(|location| location.id))
+        }
+    }
+}
+
 pub(super) fn resolve_capture_target_location(
     app: &AppHandle, state: &AppState, destination: &Option<writer_store::CaptureDocRef>,
+    on_missing_target: MissingCaptureTarget,
 ) -> Result<LocationId, AppError> {
     if let Some(destination_ref) = destination {
         let destination_id = LocationId(destination_ref.location_id);
-        if state.store.location_get(destination_id)?.is_some() {
-            return Ok(destination_id);
+        if let Some(location_id) = check_capture_destination(&state.store, destination_id, on_missing_target)? {
+            return Ok(location_id);
         }
-        log::warn!(
-            "Capture destination location no longer exists: {}. Falling back to current/open location resolution.",
-            destination_ref.location_id
-        );
     }
 
     if let Some(last_open_doc) = state.store.last_open_doc_get()? {
@@ -493,4 +517,33 @@ mod tests {
             FsChangeKind::Deleted
         );
     }
+
+    #[test]
+    fn missing_capture_destination_errors_or_falls_back() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store = Store::open(&temp_dir.path().join("test.db")).unwrap();
+        let missing = LocationId(404);
+
+        let error = check_capture_destination(&store, missing, MissingCaptureTarget::Error).unwrap_err();
+        assert_eq!(error.code, ErrorCode::NotFound);
+        assert!(error.message.contains("404"));
+
+        assert_eq!(
+            check_capture_destination(&store, missing, MissingCaptureTarget::Fallback).unwrap(),
+            None
+        );
+
+        let root = temp_dir.path().join("notes");
+        std::fs::create_dir_all(&root).unwrap();
+        let location = store.location_add("notes".to_string(), root).unwrap();
+
+        assert_eq!(
+            check_capture_destination(&store, missing, MissingCaptureTarget::Fallback).unwrap(),
+            Some(location.id)
+        );
+        assert_eq!(
+            check_capture_destination(&store, location.id, MissingCaptureTarget::Error).unwrap(),
+            Some(location.id)
+        );
+    }
 }
@@ -107,6 +107,7 @@ describe("commandResult", () => {
         "PARSE_ERROR",
         "INDEX_ERROR",
         "CONFLICT",
+        "INVALID_URL",
       ] as const;
 
       for (const code of codes) {
@@ -139,6 +139,37 @@ function handleBackendEvent(payload: unknown): void {
       logger.info(f("Save status changed", { docId: normalized.doc_id, status: normalized.status }));
       break;
     }
+    case "GoalProgress": {
+      logger.info(
+        f("Goal progress", {
+          docId: normalized.doc_id,
+          currentWords: normalized.current_words,
+          targetWords: normalized.target_words,
+        }),
+      );
+      break;
+    }
+    case "ReconcileProgress": {
+      logger.debug(
+        f("Reindex progress", {
+          locationId: normalized.location_id,
+          processed: normalized.processed,
+          total: normalized.total,
+        }),
+      );
+      break;
+    }
+    case "ReconcileFinished": {
+      logger.info(
+        f("Reindex finished", {
+          locationId: normalized.location_id,
+          indexed: normalized.indexed,
+          cancelled: normalized.cancelled,
+          error: normalized.error,
+        }),
+      );
+      break;
+    }
     case "FilesystemChanged": {
       logger.info(
         f("Filesystem changed", {
@@ -96,6 +96,10 @@ function toBackendGlobalCaptureSettings(settings: GlobalCaptureSettings): Backen
     close_after_save: settings.closeAfterSave,
     show_tray_icon: settings.showTrayIcon,
     last_capture_target: settings.lastCaptureTarget,
+    date_subfolder_format: settings.dateSubfolderFormat,
+    date_subfolder_for_append: settings.dateSubfolderForAppend,
+    append_timestamp_format: settings.appendTimestampFormat,
+    on_missing_target: settings.onMissingTarget,
   };
 }
 
@@ -111,7 +115,7 @@ function toBackendCaptureSubmitInput(input: CaptureSubmitInput): BackendCaptureS
 }
 
 function normalizeMarkdownProfile(profile: unknown): MarkdownProfile | undefined {
-  if (profile === "StrictCommonMark" || profile === "GfmSafe") {
+  if (profile === "StrictCommonMark" || profile === "GfmSafe" || profile === "GfmSanitized") {
     return profile;
   }
   return undefined;
@@ -77,6 +77,10 @@ function normalizeErrorCode(code: unknown): ErrorCode {
     case "Conflict": {
       return "CONFLICT";
     }
+    case "INVALID_URL":
+    case "InvalidUrl": {
+      return "INVALID_URL";
+    }
     default: {
       return "IO_ERROR";
     }
@@ -225,6 +229,16 @@ function normalizeGlobalCaptureSettings(value: unknown): GlobalCaptureSettings {
     closeAfterSave: typeof value.close_after_save === "boolean" ? value.close_after_save : true,
     showTrayIcon: typeof value.show_tray_icon === "boolean" ? value.show_tray_icon : true,
     lastCaptureTarget: typeof value.last_capture_target === "string" ? value.last_capture_target : null,
+    dateSubfolderFormat: typeof value.date_subfolder_format === "string" ? value.date_subfolder_format : undefined,
+    dateSubfolderForAppend: typeof value.date_subfolder_for_append === "boolean"
+      ? value.date_subfolder_for_append
+      : undefined,
+    appendTimestampFormat: typeof value.append_timestamp_format === "string"
+      ? value.append_timestamp_format
+      : undefined,
+    onMissingTarget: value.on_missing_target === "Fallback" || value.on_missing_target === "Error"
+      ? value.on_missing_target
+      : undefined,
   };
 }
 
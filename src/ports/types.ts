@@ -14,6 +14,7 @@ import type {
   LocationId,
   MarkdownPreviewStyle,
   MarkdownProfile,
+  MissingCaptureTarget,
   PostRecord,
   PublicationListResult,
   RenderResult,
@@ -35,7 +36,16 @@ export type EditorState = {
   selection_to: number | null;
 };
 
-export type SaveResult = { success: boolean; new_meta: DocMeta | null; conflict_detected: boolean };
+export type GoalProgress = { current_words: number; target_words: number };
+
+export type SaveResult = {
+  success: boolean;
+  new_meta: DocMeta | null;
+  conflict_detected: boolean;
+  goal_progress?: GoalProgress | null;
+  whitespace_trimmed?: boolean;
+  updated_stamped?: boolean;
+};
 
 export type UiLayoutSettings = {
   sidebar_collapsed: boolean;
@@ -53,6 +63,15 @@ export type UiLayoutSettings = {
   filename_visibility: boolean;
   create_readme_in_new_locations: boolean;
   markdown_preview_style: MarkdownPreviewStyle;
+  max_open_bytes?: number;
+  trim_trailing_whitespace_on_save?: boolean;
+  preserve_code_whitespace?: boolean;
+  stamp_updated_on_save?: boolean;
+  write_utf8_bom?: boolean;
+  default_extension?: string;
+  bulk_tags_inline?: boolean;
+  home_document_names?: string[];
+  conflict_patterns?: string[];
 };
 
 export type PersistedSidebarTreeState = {
@@ -83,7 +102,16 @@ export type BackendEvent =
     rel_path: string;
     old_rel_path?: string | null;
   }
-  | { type: "SaveStatusChanged"; doc_id: DocRef; status: SaveStatus };
+  | { type: "ReconcileProgress"; location_id: LocationId; processed: number; total: number }
+  | {
+    type: "ReconcileFinished";
+    location_id: LocationId;
+    indexed: number;
+    cancelled: boolean;
+    error?: string | null;
+  }
+  | { type: "SaveStatusChanged"; doc_id: DocRef; status: SaveStatus }
+  | { type: "GoalProgress"; doc_id: DocRef; current_words: number; target_words: number };
 
 export type ErrorCallback = (error: AppError) => void;
 export type SuccessCallback<T> = (value: T) => void;
@@ -250,6 +278,10 @@ export type BackendGlobalCaptureSettings = {
   close_after_save: boolean;
   show_tray_icon: boolean;
   last_capture_target: string | null;
+  date_subfolder_format?: string | null;
+  date_subfolder_for_append?: boolean;
+  append_timestamp_format?: string | null;
+  on_missing_target?: MissingCaptureTarget;
 };
 
 export type BackendCaptureSubmitInput = {
@@ -14,7 +14,7 @@ export type EditorFontFamily =
   | "Monaspace Radon"
   | "Monaspace Xenon";
 
-export type MarkdownProfile = "StrictCommonMark" | "GfmSafe";
+export type MarkdownProfile = "StrictCommonMark" | "GfmSafe" | "GfmSanitized";
 
 export type PanelMode = "editor" | "preview" | "split";
 
@@ -27,7 +27,8 @@ export type ErrorCode =
   | "IO_ERROR"
   | "PARSE_ERROR"
   | "INDEX_ERROR"
-  | "CONFLICT";
+  | "CONFLICT"
+  | "INVALID_URL";
 
 export type LocationId = number;
 
@@ -45,17 +46,26 @@ export type DocMeta = {
   word_count: number;
 };
 
-export type DocContent = { text: string; meta: DocMeta };
+export type DocContent = { text: string; meta: DocMeta; truncated?: boolean };
 
-export type LocationDescriptor = { id: LocationId; name: string; root_path: string; added_at: string };
+export type LocationDescriptor = {
+  id: LocationId;
+  name: string;
+  root_path: string;
+  added_at: string;
+  indexable_extensions?: string[] | null;
+  ignore_globs?: string[];
+};
 export type SidebarTreeState = {
   expandedLocationIds: number[];
   expandedDirectoriesByLocation: Record<number, string[]>;
 };
 
-export type Heading = { level: number; text: string; anchor: string | null };
+export type Heading = { level: number; text: string; anchor: string | null; line: number };
+
+export type LinkRef = { url: string; title: string | null; is_local: boolean; line: number };
 
-export type LinkRef = { url: string; title: string | null };
+export type ImageRef = { alt: string; url: string; is_local: boolean; line: number };
 
 export type TaskStats = { total: number; completed: number };
 
@@ -63,11 +73,17 @@ export type DocumentMetadata = {
   title: string | null;
   outline: Heading[];
   links: LinkRef[];
+  images?: ImageRef[];
   task_items: TaskStats;
   word_count: number;
+  sentence_count: number;
+  prose_word_count?: number;
+  paragraph_count: number;
 };
 
-export type RenderResult = { html: string; metadata: DocumentMetadata };
+export type RenderTimings = { parse_ms: number; metadata_ms: number; html_ms: number; diagnostics_ms: number };
+
+export type RenderResult = { html: string; metadata: DocumentMetadata; timings?: RenderTimings };
 
 export type SearchMatch = { start: number; end: number };
 
@@ -79,6 +95,8 @@ export type SearchHit = {
   line: number;
   column: number;
   matches: SearchMatch[];
+  nearest_heading_anchor?: string | null;
+  preview?: string | null;
 };
 
 export type AppError = { code: ErrorCode; message: string; context?: string };
@@ -146,6 +164,8 @@ export type StyleCheckSettings = {
 
 export type CaptureMode = "QuickNote" | "WritingSession" | "Append";
 
+export type MissingCaptureTarget = "Fallback" | "Error";
+
 export type CaptureDocRef = { locationId: number; relPath: string };
 
 export type GlobalCaptureSettings = {
@@ -159,6 +179,10 @@ export type GlobalCaptureSettings = {
   closeAfterSave: boolean;
   showTrayIcon: boolean;
   lastCaptureTarget: string | null;
+  dateSubfolderFormat?: string | null;
+  dateSubfolderForAppend?: boolean;
+  appendTimestampFormat?: string | null;
+  onMissingTarget?: MissingCaptureTarget;
 };
 
 export type CaptureSubmitResult = {